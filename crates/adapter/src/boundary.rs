@@ -1,5 +1,5 @@
-use ca_application::usecase::Usecase;
-use ca_domain::entity::auth_context::AuthError;
+use ca_application::usecase::{request_context::RequestContext, Usecase};
+use ca_domain::{entity::auth_context::AuthError, value_object::ValidationError};
 use serde::Serialize;
 use thiserror::Error;
 
@@ -21,6 +21,12 @@ impl<D, U: Usecase<D>> From<AuthError> for Error<D, U> {
     }
 }
 
+impl<D, U: Usecase<D>> From<ValidationError> for Error<D, U> {
+    fn from(err: ValidationError) -> Self {
+        Error::ParseInputError(err.to_string())
+    }
+}
+
 pub type UsecaseResponseResult<D, U> = Result<<U as Usecase<D>>::Response, Error<D, U>>;
 
 pub type UsecaseRequestResult<D, U> = Result<<U as Usecase<D>>::Request, Error<D, U>>;
@@ -30,6 +36,16 @@ pub trait Ingester<D, U: Usecase<D>> {
     type InputModel: Send + Sync + 'static;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, U>;
 }
+/// Extracts the per request context from the metadata an interface
+/// receives next to the usecase input (headers, environment, ...).
+///
+/// The raw auth token is returned separately, it is resolved into an
+/// `AuthContext` by the controller.
+#[async_trait::async_trait]
+pub trait ContextIngester {
+    type ContextModel: Send + Sync + 'static;
+    async fn ingest_context(input: Self::ContextModel) -> (Option<String>, RequestContext);
+}
 #[async_trait::async_trait]
 pub trait Presenter<D, U: Usecase<D>> {
     type ViewModel;
@@ -1,12 +1,18 @@
 use std::{marker::PhantomData, sync::Arc};
 
 use ca_application::{
-    gateway::{service::auth::AuthExtractor, AuthExtractorProvider},
-    usecase::Usecase,
+    gateway::{
+        database::{
+            audit_log::{Outcome, Record as AuditLogRecord, Repo as AuditLogRepo},
+            Database,
+        },
+        service::{auth::AuthExtractor, clock::Clock},
+        AuthExtractorProvider, ClockProvider, DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
 };
-use ca_domain::entity::auth_context::AuthError;
 
-use super::boundary::{Error, Ingester, Presenter};
+use super::boundary::{ContextIngester, Error, Ingester, Presenter};
 
 #[derive(Clone)]
 pub struct Controller<D, B> {
@@ -17,17 +23,17 @@ pub struct Controller<D, B> {
 #[async_trait::async_trait]
 pub trait ControllerTrait<D, B>
 where
-    D: AuthExtractorProvider,
+    D: AuthExtractorProvider + ClockProvider + DatabaseProvider,
 {
     fn dependency_provider(&self) -> Arc<D>;
     async fn handle_usecase<U>(
         &self,
         input: <B as Ingester<D, U>>::InputModel,
-        token: Option<String>,
+        context: <B as ContextIngester>::ContextModel,
     ) -> <B as Presenter<D, U>>::ViewModel
     where
         U: Usecase<D>,
-        B: Ingester<D, U> + Presenter<D, U>,
+        B: Ingester<D, U> + Presenter<D, U> + ContextIngester,
     {
         // process input
         let processed_req = match <B as Ingester<D, U>>::ingest(input).await {
@@ -36,6 +42,8 @@ where
             }
             Ok(r) => r,
         };
+        // process request context
+        let (token, ctx) = <B as ContextIngester>::ingest_context(context).await;
         // Extract auth context from token
         let auth_context = if let Some(token) = token {
             self.dependency_provider()
@@ -45,25 +53,65 @@ where
         } else {
             None
         };
+        let ctx = ctx.with_auth_context(auth_context);
         // Instantiate the usecase
         let usecase = U::new(self.dependency_provider());
+        let audit_targets = usecase
+            .is_audited()
+            .then(|| usecase.extract_target(&processed_req));
         // Authorize request
-        if usecase.authorize(&processed_req, auth_context).is_err() {
-            return <B as Presenter<D, U>>::present(Err(Error::AuthError(AuthError::Unauthorized)))
-                .await;
+        if let Err(err) = usecase.authorize(&processed_req, ctx.auth_context.clone()) {
+            if let Some(targets) = audit_targets {
+                self.audit::<U>(&ctx, targets, Outcome::Denied).await;
+            }
+            return <B as Presenter<D, U>>::present(Err(Error::AuthError(err))).await;
         }
         // Execute use case in transaction if it is transactional
         let req = usecase
-            .exec(processed_req)
+            .exec(processed_req, &ctx)
             .await
             .map_err(|err| Error::UsecaseError(err));
+        if let Some(targets) = audit_targets {
+            let outcome = match &req {
+                Ok(_) => Outcome::Success,
+                Err(Error::UsecaseError(err)) => Outcome::Failure(format!("{err:?}")),
+                Err(err) => Outcome::Failure(err.to_string()),
+            };
+            self.audit::<U>(&ctx, targets, outcome).await;
+        }
         <B as Presenter<D, U>>::present(req).await
     }
+    /// Records a privileged usecase execution, a failing audit log does not
+    /// fail the request itself.
+    async fn audit<U>(&self, ctx: &RequestContext, target_ids: Vec<String>, outcome: Outcome)
+    where
+        U: Usecase<D>,
+    {
+        let record = AuditLogRecord {
+            actor_id: ctx.auth_context().map(|auth| auth.user_id),
+            actor_role: ctx.auth_context().map(|auth| auth.role.clone()),
+            usecase: U::NAME.to_string(),
+            target_ids,
+            outcome,
+            request_id: ctx.request_id.clone(),
+            client_ip: ctx.client_ip.clone(),
+            occurred_at: self.dependency_provider().clock().now(),
+        };
+        if let Err(err) = self
+            .dependency_provider()
+            .database()
+            .audit_log_repo()
+            .append(None, record)
+            .await
+        {
+            log::error!("Unable to record audit log entry for {}: {err}", U::NAME);
+        }
+    }
 }
 
 impl<D, B> Controller<D, B>
 where
-    D: AuthExtractorProvider,
+    D: AuthExtractorProvider + ClockProvider + DatabaseProvider,
 {
     pub const fn new(dependency_provider: Arc<D>) -> Self {
         Self {
@@ -75,7 +123,7 @@ where
 
 impl<D, B> ControllerTrait<D, B> for Controller<D, B>
 where
-    D: AuthExtractorProvider,
+    D: AuthExtractorProvider + ClockProvider + DatabaseProvider,
 {
     fn dependency_provider(&self) -> Arc<D> {
         self.dependency_provider.clone()
@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use ca_domain::{entity::user::Id as UserId, value_object::Role};
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use mockall::automock;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum SaveError {
+    #[error("AuditLog repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum QueryError {
+    #[error("AuditLog repository connection problem")]
+    Connection,
+}
+
+/// How the audited usecase execution ended
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Outcome {
+    Success,
+    Failure(String),
+    Denied,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Record {
+    pub actor_id: Option<UserId>,
+    pub actor_role: Option<Role>,
+    pub usecase: String,
+    pub target_ids: Vec<String>,
+    pub outcome: Outcome,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Narrows down the audit log, unset fields match everything. Entries come
+/// oldest first, `offset` of them are skipped and at most `limit` returned.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub actor_id: Option<UserId>,
+    pub target_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+#[cfg_attr(test, automock(type Transaction = ();))]
+#[async_trait]
+pub trait Repo: Send + Sync {
+    type Transaction;
+    async fn append<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError>;
+    async fn query<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        filter: Filter,
+    ) -> Result<Vec<Record>, QueryError>;
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Repo for &MockRepo {
+    type Transaction = ();
+    async fn append<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        (**self).append(transaction, record).await
+    }
+    async fn query<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        filter: Filter,
+    ) -> Result<Vec<Record>, QueryError> {
+        (**self).query(transaction, filter).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    async fn test_mock() {
+        // Create a mock instance
+        let mut mock = MockRepo::new();
+
+        // Define a sample record
+        let record = Record {
+            actor_id: Some(UserId::new(uuid::Uuid::nil())),
+            actor_role: Some(Role::admin()),
+            usecase: "user.delete".to_string(),
+            target_ids: vec![uuid::Uuid::new_v4().to_string()],
+            outcome: Outcome::Success,
+            request_id: None,
+            client_ip: None,
+            occurred_at: Utc::now(),
+        };
+        let eq_record = record.clone();
+
+        // Set up expectations
+        mock.expect_append()
+            .withf(move |transaction, actual_record| {
+                transaction.is_none() && actual_record == &eq_record
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // Call the method
+        let result = mock.append(None, record).await;
+
+        // Verify the result
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use ca_domain::{entity::user::Id, value_object::SecretString};
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use mockall::automock;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum SaveError {
+    #[error("External login repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum TakeError {
+    #[error("External login not found")]
+    NotFound,
+    #[error("External login repository connection problem")]
+    Connection,
+}
+
+/// Login at a provider that was started but not completed yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Handed through the provider and back, it finds the login again
+    pub state: SecretString,
+    pub provider: String,
+    /// Expected in the ID token, it ties the token to this login
+    pub nonce: String,
+    /// PKCE verifier, proves to the provider that the code is redeemed by
+    /// whoever started the login
+    pub code_verifier: SecretString,
+    /// User the identity is linked to, logins of anyone otherwise
+    pub link_user_id: Option<Id>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Logins are kept by a hash of their state and can only be taken once
+#[cfg_attr(test, automock(type Transaction = ();))]
+#[async_trait]
+pub trait Repo: Send + Sync {
+    type Transaction;
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError>;
+    /// Removes the login started with `state` and returns it
+    async fn take<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        state: &str,
+    ) -> Result<Record, TakeError>;
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Repo for &MockRepo {
+    type Transaction = ();
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        (**self).save(transaction, record).await
+    }
+    async fn take<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        state: &str,
+    ) -> Result<Record, TakeError> {
+        (**self).take(transaction, state).await
+    }
+}
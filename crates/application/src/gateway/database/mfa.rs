@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use ca_domain::{entity::user::Id, value_object::SecretString};
+#[cfg(test)]
+use mockall::automock;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum GetError {
+    #[error("Second factor not found")]
+    NotFound,
+    #[error("Second factor repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum SaveError {
+    #[error("Second factor repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum DeleteError {
+    #[error("Second factor repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum RecoveryCodeError {
+    #[error("Recovery code not found")]
+    NotFound,
+    #[error("Second factor repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum StepError {
+    #[error("Time step was used before")]
+    Replayed,
+    #[error("Second factor repository connection problem")]
+    Connection,
+}
+
+/// TOTP second factor of a user, it only counts once it is confirmed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub user_id: Id,
+    pub secret: SecretString,
+    pub confirmed: bool,
+    /// Time step of the last accepted code
+    pub last_used_step: Option<u64>,
+}
+
+/// Secrets are kept encrypted and recovery codes as hashes only
+#[cfg_attr(test, automock(type Transaction = ();))]
+#[async_trait]
+pub trait Repo: Send + Sync {
+    type Transaction;
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+    ) -> Result<Record, GetError>;
+    /// Creates the second factor or replaces the one of the user, the step
+    /// of an existing one is only ever moved by [`Repo::use_step`]
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError>;
+    /// Removes the second factor of the user along with its recovery codes
+    async fn delete<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+    ) -> Result<(), DeleteError>;
+    /// Replaces the recovery codes of the user
+    async fn replace_recovery_codes<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+        codes: &[String],
+    ) -> Result<(), SaveError>;
+    /// Consumes one of the recovery codes of the user
+    async fn use_recovery_code<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+        code: &str,
+    ) -> Result<(), RecoveryCodeError>;
+    /// Moves the second factor of the user to `step`, only if no code of
+    /// that step or a later one was accepted yet
+    async fn use_step<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+        step: u64,
+    ) -> Result<(), StepError>;
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Repo for &MockRepo {
+    type Transaction = ();
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        user_id: Id,
+    ) -> Result<Record, GetError> {
+        (**self).get(transaction, user_id).await
+    }
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        (**self).save(transaction, record).await
+    }
+    async fn delete<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        user_id: Id,
+    ) -> Result<(), DeleteError> {
+        (**self).delete(transaction, user_id).await
+    }
+    async fn replace_recovery_codes<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        user_id: Id,
+        codes: &[String],
+    ) -> Result<(), SaveError> {
+        (**self)
+            .replace_recovery_codes(transaction, user_id, codes)
+            .await
+    }
+    async fn use_recovery_code<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        user_id: Id,
+        code: &str,
+    ) -> Result<(), RecoveryCodeError> {
+        (**self).use_recovery_code(transaction, user_id, code).await
+    }
+    async fn use_step<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        user_id: Id,
+        step: u64,
+    ) -> Result<(), StepError> {
+        (**self).use_step(transaction, user_id, step).await
+    }
+}
@@ -5,10 +5,15 @@ use identifier::NewId;
 #[cfg(test)]
 use identifier::NewIdError;
 
+pub mod audit_log;
+pub mod external_login;
 pub mod identifier;
+pub mod mfa;
+pub mod role;
 pub mod signup_process;
 pub mod token;
 pub mod user;
+pub mod user_identity;
 
 #[cfg(test)]
 use mockall::mock;
@@ -21,6 +26,11 @@ pub trait Database: Send + Sync {
     fn signuo_id_gen(&self) -> impl NewId<Id<SignupProcessValue>>;
     fn user_repo(&self) -> impl user::Repo<Transaction = Self::Transaction>;
     fn token_repo(&self) -> impl token::Repo<Transaction = Self::Transaction>;
+    fn audit_log_repo(&self) -> impl audit_log::Repo<Transaction = Self::Transaction>;
+    fn role_repo(&self) -> impl role::Repo<Transaction = Self::Transaction>;
+    fn mfa_repo(&self) -> impl mfa::Repo<Transaction = Self::Transaction>;
+    fn user_identity_repo(&self) -> impl user_identity::Repo<Transaction = Self::Transaction>;
+    fn external_login_repo(&self) -> impl external_login::Repo<Transaction = Self::Transaction>;
     async fn begin_transaction(&self) -> Self::Transaction;
     async fn commit_transaction(&self, transaction: Self::Transaction) -> Result<(), Self::Error>;
     async fn rollback_transaction(&self, transaction: Self::Transaction)
@@ -48,6 +58,11 @@ pub struct MockDatabase {
     pub signup_id_gen: MockSignupIdGen,
     pub token_repo: token::MockRepo,
     pub user_repo: user::MockRepo,
+    pub audit_log_repo: audit_log::MockRepo,
+    pub role_repo: role::MockRepo,
+    pub mfa_repo: mfa::MockRepo,
+    pub user_identity_repo: user_identity::MockRepo,
+    pub external_login_repo: external_login::MockRepo,
 }
 #[cfg(test)]
 impl Default for MockDatabase {
@@ -57,6 +72,11 @@ impl Default for MockDatabase {
             signup_id_gen: MockSignupIdGen::new(),
             token_repo: token::MockRepo::new(),
             user_repo: user::MockRepo::new(),
+            audit_log_repo: audit_log::MockRepo::new(),
+            role_repo: role::MockRepo::new(),
+            mfa_repo: mfa::MockRepo::new(),
+            user_identity_repo: user_identity::MockRepo::new(),
+            external_login_repo: external_login::MockRepo::new(),
         }
     }
 }
@@ -77,6 +97,21 @@ impl Database for &MockDatabase {
     fn token_repo(&self) -> impl token::Repo<Transaction = Self::Transaction> {
         &self.token_repo
     }
+    fn audit_log_repo(&self) -> impl audit_log::Repo<Transaction = Self::Transaction> {
+        &self.audit_log_repo
+    }
+    fn role_repo(&self) -> impl role::Repo<Transaction = Self::Transaction> {
+        &self.role_repo
+    }
+    fn mfa_repo(&self) -> impl mfa::Repo<Transaction = Self::Transaction> {
+        &self.mfa_repo
+    }
+    fn user_identity_repo(&self) -> impl user_identity::Repo<Transaction = Self::Transaction> {
+        &self.user_identity_repo
+    }
+    fn external_login_repo(&self) -> impl external_login::Repo<Transaction = Self::Transaction> {
+        &self.external_login_repo
+    }
     async fn begin_transaction(&self) -> Self::Transaction {}
     async fn commit_transaction(&self, _transaction: Self::Transaction) -> Result<(), Self::Error> {
         Ok(())
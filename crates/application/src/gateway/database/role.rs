@@ -0,0 +1,114 @@
+use std::collections::BTreeSet;
+
+use async_trait::async_trait;
+use ca_domain::value_object::{Permission, Role};
+#[cfg(test)]
+use mockall::automock;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GetError {
+    #[error("Role not found")]
+    NotFound,
+    #[error("Role repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Role repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error)]
+pub enum GetAllError {
+    #[error("Role repository connection problem")]
+    Connection,
+}
+
+/// A role together with the permissions it grants
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Record {
+    pub role: Role,
+    pub permissions: BTreeSet<Permission>,
+}
+
+#[cfg_attr(test, automock(type Transaction = ();))]
+#[async_trait]
+pub trait Repo: Send + Sync {
+    type Transaction;
+    /// Creates the role or replaces the permissions of an existing one
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError>;
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        role: Role,
+    ) -> Result<Record, GetError>;
+    async fn get_all<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+    ) -> Result<Vec<Record>, GetAllError>;
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Repo for &MockRepo {
+    type Transaction = ();
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        (*self).save(transaction, record).await
+    }
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        role: Role,
+    ) -> Result<Record, GetError> {
+        (*self).get(transaction, role).await
+    }
+    async fn get_all<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+    ) -> Result<Vec<Record>, GetAllError> {
+        (*self).get_all(transaction).await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    async fn test_mock() {
+        // Create a mock instance
+        let mut mock = MockRepo::new();
+
+        // Define a sample record
+        let record = Record {
+            role: Role::new("Support"),
+            permissions: BTreeSet::from([Permission::new(Permission::USERS_READ)]),
+        };
+        let eq_record = record.clone();
+
+        // Set up expectations
+        mock.expect_save()
+            .withf(move |transaction, actual_record| {
+                transaction.is_none() && actual_record == &eq_record
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        // Call the method
+        let result = mock.save(None, record).await;
+
+        // Verify the result
+        assert!(result.is_ok());
+    }
+}
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use ca_domain::entity::signup_process::*;
+use ca_domain::entity::{signup_process::*, user::Email};
 use chrono::{DateTime, Utc};
 #[cfg(test)]
 use mockall::automock;
@@ -14,12 +14,16 @@ pub enum GetError {
     Connection,
     #[error("SignupProcess in incorrect state")]
     IncorrectState,
+    #[error("SignupProcess state could not be decoded")]
+    Corrupted,
 }
 
 #[derive(Debug, Error, Serialize)]
 pub enum SaveError {
     #[error("SignupProcess repository connection problem")]
     Connection,
+    #[error("SignupProcess was changed concurrently")]
+    Conflict,
 }
 
 #[derive(Debug, Error, Serialize)]
@@ -33,10 +37,32 @@ pub enum DeleteError {
 #[derive(Debug, Clone, Serialize)]
 pub struct Record {
     pub id: Id,
+    /// Position in the state chain starting at 0, filled in when loading
+    pub seq: u64,
     pub state: SignupStateEnum,
     pub entered_at: DateTime<Utc>,
 }
 
+/// State of a signup process without its data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateKind {
+    Initialized,
+    VerificationEmailSent,
+    EmailVerified,
+    Completed,
+    ForDeletion,
+    Failed,
+}
+
+/// Selects processes whose latest state is `state` and was entered before
+/// `entered_before`, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateFilter {
+    pub state: StateKind,
+    pub entered_before: DateTime<Utc>,
+    pub limit: u32,
+}
+
 impl PartialEq for Record {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -49,6 +75,7 @@ impl<S: SignupStateTrait> From<SignupProcess<S>> for Record {
     fn from(process: SignupProcess<S>) -> Self {
         Record {
             id: process.id(),
+            seq: 0,
             state: process.state().clone().into(),
             entered_at: process.entered_at(),
         }
@@ -67,10 +94,13 @@ impl<S: SignupStateTrait + Clone> TryFrom<Record> for SignupProcess<S> {
 #[async_trait]
 pub trait Repo: Send + Sync {
     type Transaction;
+    /// Appends the state to the chain if its latest state still has
+    /// `expected_seq`, `None` expects the chain to be empty.
     async fn save_latest_state<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
         record: Record,
+        expected_seq: Option<u64>,
     ) -> Result<(), SaveError>;
     async fn get_latest_state<'a>(
         &self,
@@ -82,11 +112,30 @@ pub trait Repo: Send + Sync {
         transaction: Option<&'a mut Self::Transaction>,
         id: Id,
     ) -> Result<Vec<Record>, GetError>;
+    /// Removes the whole state chain and leaves a tombstone in its place
     async fn delete<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
         id: Id,
     ) -> Result<(), DeleteError>;
+    /// Number of processes removed so far
+    async fn count_tombstones<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+    ) -> Result<u64, GetError>;
+    /// Latest states of the processes matching the filter
+    async fn get_by_state<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        filter: StateFilter,
+    ) -> Result<Vec<Record>, GetError>;
+    /// Latest state of the in-flight process signing up `email`, if any,
+    /// matched by its canonical form
+    async fn get_active_by_email<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        email: &Email,
+    ) -> Result<Option<Record>, GetError>;
 }
 
 #[cfg(test)]
@@ -97,8 +146,11 @@ impl Repo for &MockRepo {
         &self,
         transaction: Option<&'a mut Self::Transaction>,
         record: Record,
+        expected_seq: Option<u64>,
     ) -> Result<(), SaveError> {
-        (**self).save_latest_state(transaction, record).await
+        (**self)
+            .save_latest_state(transaction, record, expected_seq)
+            .await
     }
     async fn get_latest_state<'a>(
         &self,
@@ -121,6 +173,26 @@ impl Repo for &MockRepo {
     ) -> Result<(), DeleteError> {
         (**self).delete(transaction, id).await
     }
+    async fn count_tombstones<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+    ) -> Result<u64, GetError> {
+        (**self).count_tombstones(transaction).await
+    }
+    async fn get_by_state<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        filter: StateFilter,
+    ) -> Result<Vec<Record>, GetError> {
+        (**self).get_by_state(transaction, filter).await
+    }
+    async fn get_active_by_email<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        email: &Email,
+    ) -> Result<Option<Record>, GetError> {
+        (**self).get_active_by_email(transaction, email).await
+    }
 }
 
 #[cfg(test)]
@@ -137,6 +209,7 @@ mod tests {
         // Define a sample record
         let record = Record {
             id: Id::new(uuid::Uuid::new_v4()), // Assuming Id::new() creates a new ID
+            seq: 0,
             state: SignupStateEnum::ForDeletion, // Adjust based on your SignupStateEnum
             entered_at: Utc::now(),
         };
@@ -144,14 +217,14 @@ mod tests {
 
         // Set up expectations
         mock.expect_save_latest_state()
-            .withf(move |transaction, actual_record| {
-                transaction.is_none() && actual_record == &eq_record
+            .withf(move |transaction, actual_record, expected_seq| {
+                transaction.is_none() && actual_record == &eq_record && expected_seq.is_none()
             })
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
 
         // Call the method
-        let result = mock.save_latest_state(None, record).await;
+        let result = mock.save_latest_state(None, record, None).await;
 
         // Verify the result
         assert!(result.is_ok());
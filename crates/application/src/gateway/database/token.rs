@@ -1,11 +1,17 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 #[cfg(test)]
 use mockall::automock;
 use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Debug, Error, Serialize, PartialEq, Clone)]
-pub enum GenError {
+pub enum IssueError {
     #[error("Token repository connection problem")]
     Connection,
 }
@@ -30,58 +36,207 @@ pub enum ExtendError {
     NotFound,
 }
 
-#[derive(Debug, Clone)]
-pub struct Record {
-    pub token: String,
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum InvalidateError {
+    #[error("Token repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum AttemptError {
+    #[error("Token repository connection problem")]
+    Connection,
+}
+
+/// What a token may be used for
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum Purpose {
+    Signup,
+    PasswordReset,
+    EmailChange,
+    Login,
+    Mfa,
+}
+
+impl Display for Purpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Purpose::Signup => write!(f, "Signup"),
+            Purpose::PasswordReset => write!(f, "PasswordReset"),
+            Purpose::EmailChange => write!(f, "EmailChange"),
+            Purpose::Login => write!(f, "Login"),
+            Purpose::Mfa => write!(f, "Mfa"),
+        }
+    }
+}
+
+impl FromStr for Purpose {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Signup" => Ok(Purpose::Signup),
+            "PasswordReset" => Ok(Purpose::PasswordReset),
+            "EmailChange" => Ok(Purpose::EmailChange),
+            "Login" => Ok(Purpose::Login),
+            "Mfa" => Ok(Purpose::Mfa),
+            purpose => Err(format!("Unknown token purpose {purpose}")),
+        }
+    }
+}
+
+/// Purpose and process a token was issued for, it only verifies within
+/// the same scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scope {
+    pub purpose: Purpose,
+    pub process_id: String,
+}
+
+impl Scope {
+    pub fn new(purpose: Purpose, process_id: impl ToString) -> Self {
+        Self {
+            purpose,
+            process_id: process_id.to_string(),
+        }
+    }
 }
 
+/// Tokens are single use and kept as hashes only. They are bound to the
+/// canonical form of the email, see
+/// [`Email::canonical`](ca_domain::value_object::Email::canonical)
 #[cfg_attr(test, automock(type Transaction = ();))]
 #[async_trait]
 pub trait Repo: Send + Sync {
     type Transaction;
-    async fn gen<'a>(
+    /// Stores `token` for the email within `scope`, created at `now`
+    async fn issue<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
         email: &str,
-    ) -> Result<Record, GenError>;
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), IssueError>;
+    /// Consumes the token if it was issued within `scope` for the email and
+    /// is at most `max_age` old at `now`, along with the failed attempts of
+    /// the scope. A failed check leaves both in place
     async fn verify<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
         email: &str,
         token: &str,
+        max_age: Duration,
+        now: DateTime<Utc>,
     ) -> Result<(), VerifyError>;
+    /// Restarts the lifetime of the tokens of `scope` at `now`
     async fn extend<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
-        email: &str,
+        scope: &Scope,
+        now: DateTime<Utc>,
     ) -> Result<(), ExtendError>;
+    /// Removes every token issued within `scope`
+    async fn invalidate<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+    ) -> Result<(), InvalidateError>;
+    /// Counts a failed verification within `scope` at `now`, returns the
+    /// failures counted so far
+    async fn record_failed_attempt<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+        now: DateTime<Utc>,
+    ) -> Result<u32, AttemptError>;
+    /// Forgets the failed verifications within `scope`
+    async fn clear_attempts<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+    ) -> Result<(), AttemptError>;
+    /// Counts a token request within `scope` at `now`, returns the requests
+    /// counted in the current window. A window starts with the first request
+    /// after the previous one is more than `window` old
+    async fn record_request<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<u32, AttemptError>;
 }
 
 #[cfg(test)]
 #[async_trait]
 impl Repo for &MockRepo {
     type Transaction = ();
-    async fn gen<'a>(
+    async fn issue<'a>(
         &self,
         transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        scope: &Scope,
         email: &str,
-    ) -> Result<Record, GenError> {
-        (**self).gen(transaction, email).await
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), IssueError> {
+        (**self).issue(transaction, scope, email, token, now).await
     }
     async fn verify<'a>(
         &self,
         transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        scope: &Scope,
         email: &str,
         token: &str,
+        max_age: Duration,
+        now: DateTime<Utc>,
     ) -> Result<(), VerifyError> {
-        (**self).verify(transaction, email, token).await
+        (**self)
+            .verify(transaction, scope, email, token, max_age, now)
+            .await
     }
     async fn extend<'a>(
         &self,
         transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
-        email: &str,
+        scope: &Scope,
+        now: DateTime<Utc>,
     ) -> Result<(), ExtendError> {
-        (**self).extend(transaction, email).await
+        (**self).extend(transaction, scope, now).await
+    }
+    async fn invalidate<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        scope: &Scope,
+    ) -> Result<(), InvalidateError> {
+        (**self).invalidate(transaction, scope).await
+    }
+    async fn record_failed_attempt<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        scope: &Scope,
+        now: DateTime<Utc>,
+    ) -> Result<u32, AttemptError> {
+        (**self)
+            .record_failed_attempt(transaction, scope, now)
+            .await
+    }
+    async fn clear_attempts<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        scope: &Scope,
+    ) -> Result<(), AttemptError> {
+        (**self).clear_attempts(transaction, scope).await
+    }
+    async fn record_request<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        scope: &Scope,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<u32, AttemptError> {
+        (**self)
+            .record_request(transaction, scope, window, now)
+            .await
     }
 }
 
@@ -98,24 +253,40 @@ mod tests {
 
         // email
         const EMAIL: &str = "test@email.com";
-        const RETURN_TOKEN: &str = "test_token";
+        const TOKEN: &str = "test_token";
+        let scope = Scope::new(Purpose::Signup, "process");
 
         // Set up expectations
-        mock.expect_gen()
-            .withf(move |transaction, actual_email| transaction.is_none() && actual_email == EMAIL)
+        let expected_scope = scope.clone();
+        mock.expect_issue()
+            .withf(
+                move |transaction, actual_scope, actual_email, actual_token, _| {
+                    transaction.is_none()
+                        && actual_scope == &expected_scope
+                        && actual_email == EMAIL
+                        && actual_token == TOKEN
+                },
+            )
             .times(1)
-            .returning(|_, _| {
-                Ok(Record {
-                    token: RETURN_TOKEN.to_string(),
-                })
-            });
+            .returning(|_, _, _, _, _| Ok(()));
 
         // Call the method
-        let result = mock.gen(None, EMAIL).await;
+        let result = mock.issue(None, &scope, EMAIL, TOKEN, Utc::now()).await;
 
         // Verify the result
         assert!(result.is_ok());
-        let record = result.unwrap();
-        assert_eq!(record.token, RETURN_TOKEN);
+    }
+
+    #[test]
+    fn test_purpose_round_trips() {
+        for purpose in [
+            Purpose::Signup,
+            Purpose::PasswordReset,
+            Purpose::EmailChange,
+            Purpose::Login,
+            Purpose::Mfa,
+        ] {
+            assert_eq!(purpose.to_string().parse(), Ok(purpose));
+        }
     }
 }
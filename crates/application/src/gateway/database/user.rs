@@ -17,6 +17,10 @@ pub enum GetError {
 pub enum SaveError {
     #[error("User repository connection problem")]
     Connection,
+    #[error("Email already registered")]
+    EmailTaken,
+    #[error("Username already taken")]
+    UsernameTaken,
 }
 
 #[derive(Debug, Error)]
@@ -56,6 +60,8 @@ impl From<Record> for User {
     }
 }
 
+/// Emails and usernames are unique and looked up by their canonical form,
+/// see [`Email::canonical`] and [`UserName::canonical`]
 #[cfg_attr(test, automock(type Transaction = ();))]
 #[async_trait]
 pub trait Repo: Send + Sync {
@@ -75,6 +81,16 @@ pub trait Repo: Send + Sync {
         transaction: Option<&'a mut Self::Transaction>,
         username: UserName,
     ) -> Result<Record, GetError>;
+    async fn exists_by_email<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        email: &Email,
+    ) -> Result<bool, GetError>;
+    async fn exists_by_username<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        username: &UserName,
+    ) -> Result<bool, GetError>;
     async fn get_all<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
@@ -111,6 +127,20 @@ impl Repo for &MockRepo {
     ) -> Result<Record, GetError> {
         (*self).get_by_username(transaction, username).await
     }
+    async fn exists_by_email<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        email: &Email,
+    ) -> Result<bool, GetError> {
+        (*self).exists_by_email(transaction, email).await
+    }
+    async fn exists_by_username<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        username: &UserName,
+    ) -> Result<bool, GetError> {
+        (*self).exists_by_username(transaction, username).await
+    }
     async fn get_all<'a>(
         &self,
         transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
@@ -143,10 +173,10 @@ mod tests {
         let record = Record {
             user: User::new(
                 Id::from(uuid::Uuid::new_v4()),
-                Role::User,
-                Email::new("test@email.com"),
-                UserName::new("test_user"),
-                Password::new("password"),
+                Role::user(),
+                Email::new_unchecked("test@email.com"),
+                UserName::new_unchecked("test_user"),
+                Password::new_unchecked("password"),
             ),
         };
         let eq_record = record.clone();
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use ca_domain::entity::user_identity::UserIdentity;
+#[cfg(test)]
+use mockall::automock;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum GetError {
+    #[error("Identity not found")]
+    NotFound,
+    #[error("Identity repository connection problem")]
+    Connection,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum SaveError {
+    #[error("Identity repository connection problem")]
+    Connection,
+    #[error("Identity already linked to a user")]
+    AlreadyLinked,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Record {
+    pub identity: UserIdentity,
+}
+
+impl From<UserIdentity> for Record {
+    fn from(identity: UserIdentity) -> Self {
+        Self { identity }
+    }
+}
+
+/// An identity of a provider belongs to one user at most
+#[cfg_attr(test, automock(type Transaction = ();))]
+#[async_trait]
+pub trait Repo: Send + Sync {
+    type Transaction;
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Record, GetError>;
+    /// Links the identity, fails with [`SaveError::AlreadyLinked`] when it
+    /// is linked already
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError>;
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Repo for &MockRepo {
+    type Transaction = ();
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Record, GetError> {
+        (**self).get(transaction, provider, subject).await
+    }
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut <MockRepo as Repo>::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        (**self).save(transaction, record).await
+    }
+}
@@ -1,4 +1,6 @@
+use ca_domain::value_object::{LoginLinkPolicy, PasswordPolicy, SignupPolicy};
 use database::Database;
+use service::job_metrics::MetricsHandle;
 
 pub mod database;
 pub mod service;
@@ -11,6 +13,10 @@ pub trait EmailVerificationServiceProvider: Send {
     fn email_verification_service(&self) -> impl service::email::EmailVerificationService;
 }
 
+pub trait EmailServiceProvider: Send + Sync {
+    fn email_service(&self) -> impl service::email::EmailService;
+}
+
 pub trait AuthPackerProvider: Send + Sync {
     fn auth_packer(&self) -> impl service::auth::AuthPacker;
 }
@@ -19,22 +25,75 @@ pub trait AuthExtractorProvider: Send + Sync {
     fn auth_extractor(&self) -> impl service::auth::AuthExtractor;
 }
 
+pub trait ClockProvider: Send + Sync {
+    fn clock(&self) -> impl service::clock::Clock;
+}
+
+pub trait TokenGeneratorProvider: Send + Sync {
+    fn token_generator(&self) -> impl service::token::TokenGenerator;
+}
+
+pub trait TotpServiceProvider: Send + Sync {
+    fn totp_service(&self) -> impl service::totp::TotpService;
+}
+
+pub trait OidcServiceProvider: Send + Sync {
+    fn oidc_service(&self) -> impl service::oidc::OidcService;
+}
+
+pub trait SignupPolicyProvider: Send + Sync {
+    fn signup_policy(&self) -> SignupPolicy;
+}
+
+pub trait PasswordPolicyProvider: Send + Sync {
+    fn password_policy(&self) -> PasswordPolicy;
+}
+
+pub trait LoginLinkPolicyProvider: Send + Sync {
+    fn login_link_policy(&self) -> LoginLinkPolicy;
+}
+
+pub trait JobMetricsProvider: Send + Sync {
+    fn expire_signup_metrics(&self) -> MetricsHandle;
+}
+
 #[cfg(test)]
 pub mod mock {
     use super::{
         database::{Database, MockDatabase},
         service::{
             auth::{AuthPacker, MockAuthPacker},
-            email::{EmailVerificationService, MockEmailVerificationService},
+            clock::{Clock, ManualClock},
+            email::{
+                EmailService, EmailVerificationService, MockEmailService,
+                MockEmailVerificationService,
+            },
+            oidc::{MockOidcService, OidcService},
+            token::{MockTokenGenerator, TokenGenerator},
+            totp::{MockTotpService, TotpService},
         },
-        AuthPackerProvider, DatabaseProvider, EmailVerificationServiceProvider,
+        AuthPackerProvider, ClockProvider, DatabaseProvider, EmailServiceProvider,
+        EmailVerificationServiceProvider, JobMetricsProvider, LoginLinkPolicyProvider,
+        OidcServiceProvider, PasswordPolicyProvider, SignupPolicyProvider, TokenGeneratorProvider,
+        TotpServiceProvider,
     };
+    use crate::gateway::service::job_metrics::MetricsHandle;
+    use ca_domain::value_object::{LoginLinkPolicy, PasswordPolicy, SignupPolicy};
 
     #[derive(Default)]
     pub struct MockDependencyProvider {
         pub db: MockDatabase,
         pub email_verification_service: MockEmailVerificationService,
+        pub email_service: MockEmailService,
         pub auth_packer: MockAuthPacker,
+        pub token_generator: MockTokenGenerator,
+        pub totp_service: MockTotpService,
+        pub oidc_service: MockOidcService,
+        pub signup_policy: SignupPolicy,
+        pub password_policy: PasswordPolicy,
+        pub login_link_policy: LoginLinkPolicy,
+        pub clock: ManualClock,
+        pub expire_signup_metrics: MetricsHandle,
     }
     impl DatabaseProvider for MockDependencyProvider {
         fn database(&self) -> impl Database {
@@ -46,9 +105,54 @@ pub mod mock {
             &self.email_verification_service
         }
     }
+    impl EmailServiceProvider for MockDependencyProvider {
+        fn email_service(&self) -> impl EmailService {
+            &self.email_service
+        }
+    }
     impl AuthPackerProvider for MockDependencyProvider {
         fn auth_packer(&self) -> impl AuthPacker {
             &self.auth_packer
         }
     }
+    impl TokenGeneratorProvider for MockDependencyProvider {
+        fn token_generator(&self) -> impl TokenGenerator {
+            &self.token_generator
+        }
+    }
+    impl TotpServiceProvider for MockDependencyProvider {
+        fn totp_service(&self) -> impl TotpService {
+            &self.totp_service
+        }
+    }
+    impl OidcServiceProvider for MockDependencyProvider {
+        fn oidc_service(&self) -> impl OidcService {
+            &self.oidc_service
+        }
+    }
+    impl SignupPolicyProvider for MockDependencyProvider {
+        fn signup_policy(&self) -> SignupPolicy {
+            self.signup_policy.clone()
+        }
+    }
+    impl PasswordPolicyProvider for MockDependencyProvider {
+        fn password_policy(&self) -> PasswordPolicy {
+            self.password_policy.clone()
+        }
+    }
+    impl LoginLinkPolicyProvider for MockDependencyProvider {
+        fn login_link_policy(&self) -> LoginLinkPolicy {
+            self.login_link_policy.clone()
+        }
+    }
+    impl ClockProvider for MockDependencyProvider {
+        fn clock(&self) -> impl Clock {
+            &self.clock
+        }
+    }
+    impl JobMetricsProvider for MockDependencyProvider {
+        fn expire_signup_metrics(&self) -> MetricsHandle {
+            self.expire_signup_metrics.clone()
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use ca_domain::entity::signup_process::Id;
 
 #[cfg(test)]
 use mockall::automock;
@@ -13,6 +14,20 @@ impl EmailAddress {
         &self.0
     }
 }
+/// Percent-encodes everything except the unreserved characters of RFC 3986,
+/// for values put into links sent by email
+pub fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
 #[derive(Debug, Error, Serialize, PartialEq)]
 pub enum EmailServiceError {
     #[error("Invalid email address: {0}")]
@@ -32,12 +47,28 @@ pub trait EmailService: Send + Sync {
     ) -> Result<(), EmailServiceError>;
 }
 
+#[cfg(test)]
+#[async_trait]
+impl EmailService for &MockEmailService {
+    async fn send_email(
+        &self,
+        to: EmailAddress,
+        subject: &str,
+        body: &str,
+    ) -> Result<(), EmailServiceError> {
+        (*self).send_email(to, subject, body).await
+    }
+}
+
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait EmailVerificationService: Send + Sync {
+    /// Sends `token` for the signup process `id`, the service may put both
+    /// into a link to click instead of a code to type in
     async fn send_verification_email(
         &self,
         to: EmailAddress,
+        id: Id,
         token: &str,
     ) -> Result<(), EmailServiceError>;
 }
@@ -48,8 +79,9 @@ impl EmailVerificationService for &MockEmailVerificationService {
     async fn send_verification_email(
         &self,
         to: EmailAddress,
+        id: Id,
         token: &str,
     ) -> Result<(), EmailServiceError> {
-        (*self).send_verification_email(to, token).await
+        (*self).send_verification_email(to, id, token).await
     }
 }
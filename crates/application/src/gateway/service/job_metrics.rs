@@ -0,0 +1,45 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Outcome of a single sweep
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Report {
+    pub verification_timed_out: u64,
+    pub completion_timed_out: u64,
+    /// Processes that moved on while being expired
+    pub conflicts: u64,
+}
+
+/// Totals over every sweep since the process started
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Metrics {
+    pub runs: u64,
+    pub failed_runs: u64,
+    pub verification_timed_out: u64,
+    pub completion_timed_out: u64,
+    pub conflicts: u64,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+/// Metrics shared between the background job and the usecases reading or
+/// triggering it, clones point at the same totals
+#[derive(Debug, Clone, Default)]
+pub struct MetricsHandle(Arc<Mutex<Metrics>>);
+
+impl MetricsHandle {
+    pub fn get(&self) -> Metrics {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record(&self, now: DateTime<Utc>, report: &Report, failed: bool) {
+        let mut metrics = self.0.lock().unwrap();
+        metrics.runs += 1;
+        metrics.failed_runs += u64::from(failed);
+        metrics.verification_timed_out += report.verification_timed_out;
+        metrics.completion_timed_out += report.completion_timed_out;
+        metrics.conflicts += report.conflicts;
+        metrics.last_run = Some(now);
+    }
+}
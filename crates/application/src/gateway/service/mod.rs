@@ -1,2 +1,7 @@
 pub mod auth;
+pub mod clock;
 pub mod email;
+pub mod job_metrics;
+pub mod oidc;
+pub mod token;
+pub mod totp;
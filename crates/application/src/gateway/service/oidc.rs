@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use ca_domain::value_object::SecretString;
+#[cfg(test)]
+use mockall::automock;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum OidcError {
+    #[error("Identity provider {0} is not configured")]
+    UnknownProvider(String),
+    #[error("Identity provider can not be reached or answered unexpectedly")]
+    Provider,
+    #[error("Authorization code was rejected by the identity provider")]
+    InvalidCode,
+    #[error("ID token is invalid: {0}")]
+    InvalidIdToken(String),
+}
+
+/// Login started at a provider, the user is sent to `url` and comes back
+/// with a code and the `state`
+#[derive(Debug, Clone)]
+pub struct Authorization {
+    pub url: String,
+    pub state: SecretString,
+    pub nonce: String,
+    pub code_verifier: SecretString,
+}
+
+/// Claims of a validated ID token
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalClaims {
+    pub subject: String,
+    pub email: Option<String>,
+    /// Whether the provider checked the user owns `email`
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+}
+
+/// OpenID Connect authorization code flow with PKCE
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait OidcService: Send + Sync {
+    /// Starts a login at `provider` with a fresh state, nonce and verifier
+    async fn authorize(&self, provider: &str) -> Result<Authorization, OidcError>;
+    /// Redeems `code` at `provider` and validates the returned ID token
+    /// against the keys of the provider and `nonce`
+    async fn exchange_code(
+        &self,
+        provider: &str,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<ExternalClaims, OidcError>;
+}
+
+#[cfg(test)]
+#[async_trait]
+impl OidcService for &MockOidcService {
+    async fn authorize(&self, provider: &str) -> Result<Authorization, OidcError> {
+        (*self).authorize(provider).await
+    }
+    async fn exchange_code(
+        &self,
+        provider: &str,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<ExternalClaims, OidcError> {
+        (*self)
+            .exchange_code(provider, code, code_verifier, nonce)
+            .await
+    }
+}
@@ -0,0 +1,15 @@
+#[cfg(test)]
+use mockall::automock;
+
+/// Produces the secrets sent to users to prove they own an address, e.g. a
+/// UUID for links or a short numeric code to type in on a phone
+#[cfg_attr(test, automock)]
+pub trait TokenGenerator: Send + Sync {
+    fn generate(&self) -> String;
+}
+
+impl<G: TokenGenerator + ?Sized> TokenGenerator for &G {
+    fn generate(&self) -> String {
+        (**self).generate()
+    }
+}
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+#[cfg(test)]
+use mockall::automock;
+
+/// Time-based one-time passwords (RFC 6238) for the second login factor
+#[cfg_attr(test, automock)]
+pub trait TotpService: Send + Sync {
+    /// New random shared secret, base32 encoded
+    fn generate_secret(&self) -> String;
+    /// `otpauth://` URI authenticator apps import the secret of `account` from
+    fn provisioning_uri(&self, secret: &str, account: &str) -> String;
+    /// Time step `code` belongs to if it is valid at `now`, comparing the
+    /// step against the last used one keeps a code from being replayed
+    fn verify(&self, secret: &str, code: &str, now: DateTime<Utc>) -> Option<u64>;
+    /// New random single use code to log in with when the device is lost
+    fn generate_recovery_code(&self) -> String;
+}
+
+impl<T: TotpService + ?Sized> TotpService for &T {
+    fn generate_secret(&self) -> String {
+        (**self).generate_secret()
+    }
+    fn provisioning_uri(&self, secret: &str, account: &str) -> String {
+        (**self).provisioning_uri(secret, account)
+    }
+    fn verify(&self, secret: &str, code: &str, now: DateTime<Utc>) -> Option<u64> {
+        (**self).verify(secret, code, now)
+    }
+    fn generate_recovery_code(&self) -> String {
+        (**self).generate_recovery_code()
+    }
+}
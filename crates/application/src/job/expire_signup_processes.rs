@@ -0,0 +1,413 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    gateway::{
+        database::{
+            signup_process::{GetError, Record, Repo, SaveError, StateFilter, StateKind},
+            Database,
+        },
+        service::job_metrics::{Metrics, Report},
+        DatabaseProvider, JobMetricsProvider, SignupPolicyProvider,
+    },
+    job::{Job, JobError},
+};
+use ca_domain::entity::signup_process::{
+    EmailVerified, Error as SignupError, SignupProcess, VerificationEmailSent,
+};
+
+const DEFAULT_BATCH_SIZE: u32 = 100;
+const DEFAULT_INTERVAL_MINUTES: i64 = 5;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Stale signup processes could not be decoded")]
+    Corrupted,
+}
+
+impl From<GetError> for Error {
+    fn from(err: GetError) -> Self {
+        match err {
+            GetError::Corrupted => Self::Corrupted,
+            GetError::NotFound | GetError::IncorrectState | GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+/// Fails signup processes that have been waiting for verification or
+/// completion past the windows of the signup policy
+pub struct ExpireSignupProcesses<D> {
+    dependency_provider: Arc<D>,
+    batch_size: u32,
+    interval: Duration,
+}
+
+impl<D> ExpireSignupProcesses<D>
+where
+    D: DatabaseProvider + SignupPolicyProvider + JobMetricsProvider,
+{
+    pub fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+            batch_size: DEFAULT_BATCH_SIZE,
+            interval: Duration::minutes(DEFAULT_INTERVAL_MINUTES),
+        }
+    }
+
+    /// Number of processes expired per transaction
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Totals of every sweep recorded through the dependency provider
+    pub fn metrics(&self) -> Metrics {
+        self.dependency_provider.expire_signup_metrics().get()
+    }
+
+    /// Expires every stale process as of `now`
+    pub async fn sweep(&self, now: DateTime<Utc>) -> Result<Report, Error> {
+        let mut report = Report::default();
+        let result = self.sweep_inner(now, &mut report).await;
+        self.dependency_provider
+            .expire_signup_metrics()
+            .record(now, &report, result.is_err());
+        result.map(|_| report)
+    }
+
+    async fn sweep_inner(&self, now: DateTime<Utc>, report: &mut Report) -> Result<(), Error> {
+        let policy = self.dependency_provider.signup_policy();
+        let deadlines = [
+            (
+                StateKind::VerificationEmailSent,
+                now - policy.verification_ttl,
+            ),
+            (StateKind::EmailVerified, now - policy.completion_ttl),
+        ];
+        for (state, entered_before) in deadlines {
+            let filter = StateFilter {
+                state,
+                entered_before,
+                limit: self.batch_size,
+            };
+            // expired processes drop out of the filter, stop on the last
+            // batch or once a batch makes no progress
+            while self.expire_batch(filter.clone(), now, report).await? == self.batch_size as usize
+            {
+            }
+        }
+        Ok(())
+    }
+
+    /// Expires one batch in a transaction, returns how many were expired
+    async fn expire_batch(
+        &self,
+        filter: StateFilter,
+        now: DateTime<Utc>,
+        report: &mut Report,
+    ) -> Result<usize, Error> {
+        let state = filter.state;
+        let database = self.dependency_provider.database();
+        let mut transaction = database.begin_transaction().await;
+        let records = database
+            .signup_process_repo()
+            .get_by_state(Some(&mut transaction), filter)
+            .await?;
+        let mut expired = 0;
+        for record in records {
+            let id = record.id;
+            let seq = record.seq;
+            let (failed, counter): (Record, _) = match state {
+                StateKind::VerificationEmailSent => (
+                    SignupProcess::<VerificationEmailSent>::try_from(record)
+                        .map_err(|_| Error::Corrupted)?
+                        .fail(SignupError::VerificationTimedOut, now)
+                        .into(),
+                    &mut report.verification_timed_out,
+                ),
+                StateKind::EmailVerified => (
+                    SignupProcess::<EmailVerified>::try_from(record)
+                        .map_err(|_| Error::Corrupted)?
+                        .fail(SignupError::CompletionTimedOut, now)
+                        .into(),
+                    &mut report.completion_timed_out,
+                ),
+                _ => return Err(Error::Corrupted),
+            };
+            match database
+                .signup_process_repo()
+                .save_latest_state(Some(&mut transaction), failed, Some(seq))
+                .await
+            {
+                Ok(()) => {
+                    *counter += 1;
+                    expired += 1;
+                }
+                Err(SaveError::Conflict) => {
+                    log::debug!("SignupProcess {} changed while expiring it", id);
+                    report.conflicts += 1;
+                }
+                Err(SaveError::Connection) => return Err(Error::Repo),
+            }
+        }
+        database
+            .commit_transaction(transaction)
+            .await
+            .map_err(|_| Error::Repo)?;
+        Ok(expired)
+    }
+}
+
+#[async_trait]
+impl<D> Job for ExpireSignupProcesses<D>
+where
+    D: DatabaseProvider + SignupPolicyProvider + JobMetricsProvider,
+{
+    fn name(&self) -> &'static str {
+        "signup_process.expire"
+    }
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+    async fn run(&self, now: DateTime<Utc>) -> Result<(), JobError> {
+        let report = self.sweep(now).await.map_err(|err| JobError {
+            job: self.name(),
+            message: err.to_string(),
+        })?;
+        log::info!(
+            "Expired signup processes: {:?}, totals: {:?}",
+            report,
+            self.metrics()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gateway::mock::MockDependencyProvider, usecase::tests::fixtures::*};
+    use ca_domain::{entity::signup_process::SignupStateEnum, value_object::SignupPolicy};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_sweep_expires_stale_processes(
+        mut dependency_provider: MockDependencyProvider,
+        verification_email_sent_record: Record,
+        email_verified_record: Record,
+    ) {
+        // fixtures
+        let now = Utc::now();
+        let sent_seq = verification_email_sent_record.seq;
+        // Mock setup -- one stale process per state
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(move |_, filter| {
+                filter.state == StateKind::VerificationEmailSent
+                    && filter.entered_before == now - Duration::days(1)
+            })
+            .times(1)
+            .returning(move |_, _| Ok(vec![verification_email_sent_record.clone()]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(|_, filter| filter.state == StateKind::EmailVerified)
+            .times(1)
+            .returning(move |_, _| Ok(vec![email_verified_record.clone()]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, record, expected_seq| {
+                matches!(
+                    &record.state,
+                    SignupStateEnum::Failed {
+                        error: SignupError::VerificationTimedOut,
+                        ..
+                    }
+                ) && expected_seq == &Some(sent_seq)
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(|_, record, _| {
+                matches!(
+                    &record.state,
+                    SignupStateEnum::Failed {
+                        error: SignupError::CompletionTimedOut,
+                        ..
+                    }
+                )
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Job Initialization
+        let job = ExpireSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution -- mock predicates will fail during execution
+        let result = job.sweep(now).await;
+        // Assert execution success
+        assert_eq!(
+            result,
+            Ok(Report {
+                verification_timed_out: 1,
+                completion_timed_out: 1,
+                conflicts: 0,
+            })
+        );
+        let metrics = job.metrics();
+        assert_eq!(metrics.runs, 1);
+        assert_eq!(metrics.verification_timed_out, 1);
+        assert_eq!(metrics.last_run, Some(now));
+    }
+    #[rstest]
+    async fn test_sweep_uses_policy_windows(mut dependency_provider: MockDependencyProvider) {
+        // fixtures
+        let now = Utc::now();
+        dependency_provider.signup_policy = SignupPolicy::default()
+            .with_verification_ttl(Duration::minutes(15))
+            .with_completion_ttl(Duration::days(7));
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(move |_, filter| {
+                filter.state == StateKind::VerificationEmailSent
+                    && filter.entered_before == now - Duration::minutes(15)
+            })
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(move |_, filter| {
+                filter.state == StateKind::EmailVerified
+                    && filter.entered_before == now - Duration::days(7)
+            })
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        // Job Initialization
+        let job = ExpireSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution -- mock predicates will fail during execution
+        let result = job.sweep(now).await;
+        // Assert execution success
+        assert_eq!(result, Ok(Report::default()));
+    }
+    #[rstest]
+    async fn test_sweep_in_batches(
+        mut dependency_provider: MockDependencyProvider,
+        verification_email_sent_record: Record,
+    ) {
+        // Mock setup -- a full batch is followed by another query
+        let mut calls = 0;
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(|_, filter| {
+                filter.state == StateKind::VerificationEmailSent && filter.limit == 1
+            })
+            .times(2)
+            .returning(move |_, _| {
+                calls += 1;
+                Ok(if calls == 1 {
+                    vec![verification_email_sent_record.clone()]
+                } else {
+                    vec![]
+                })
+            });
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(|_, filter| filter.state == StateKind::EmailVerified)
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Job Initialization
+        let job = ExpireSignupProcesses::new(Arc::new(dependency_provider)).with_batch_size(1);
+        // Job Execution
+        let result = job.sweep(Utc::now()).await;
+        // Assert execution success
+        assert_eq!(result.unwrap().verification_timed_out, 1);
+    }
+    #[rstest]
+    async fn test_sweep_counts_conflicts(
+        mut dependency_provider: MockDependencyProvider,
+        email_verified_record: Record,
+    ) {
+        // Mock setup -- the process moved on before it was expired
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(|_, filter| filter.state == StateKind::VerificationEmailSent)
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(|_, filter| filter.state == StateKind::EmailVerified)
+            .times(1)
+            .returning(move |_, _| Ok(vec![email_verified_record.clone()]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .times(1)
+            .returning(|_, _, _| Err(SaveError::Conflict));
+        // Job Initialization
+        let job = ExpireSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution
+        let result = job.sweep(Utc::now()).await;
+        // Assert execution success
+        assert_eq!(
+            result,
+            Ok(Report {
+                conflicts: 1,
+                ..Default::default()
+            })
+        );
+    }
+    #[rstest]
+    async fn test_sweep_fail_connection(mut dependency_provider: MockDependencyProvider) {
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(1)
+            .returning(|_, _| Err(GetError::Connection));
+        // Job Initialization
+        let job = ExpireSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution
+        let result = job.run(Utc::now()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(job.metrics().failed_runs, 1);
+    }
+}
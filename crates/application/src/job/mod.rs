@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+
+pub mod expire_signup_processes;
+pub mod purge_signup_processes;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("Job {job} failed: {message}")]
+pub struct JobError {
+    pub job: &'static str,
+    pub message: String,
+}
+
+/// Work the application does on its own on a fixed interval
+#[async_trait]
+pub trait Job: Send + Sync {
+    /// Name used in logs and errors
+    fn name(&self) -> &'static str;
+    /// Time between two runs
+    fn interval(&self) -> Duration;
+    async fn run(&self, now: DateTime<Utc>) -> Result<(), JobError>;
+}
+
+struct Scheduled {
+    job: Arc<dyn Job>,
+    next_run: DateTime<Utc>,
+}
+
+/// Runs registered jobs once they are due.
+///
+/// The runner does not own a timer, the interface driving it decides how to
+/// wait between calls to [`JobRunner::run_due`].
+#[derive(Default)]
+pub struct JobRunner {
+    jobs: Vec<Scheduled>,
+}
+
+impl JobRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a job, it is due right away
+    pub fn with_job(mut self, job: Arc<dyn Job>) -> Self {
+        self.jobs.push(Scheduled {
+            job,
+            next_run: DateTime::<Utc>::MIN_UTC,
+        });
+        self
+    }
+
+    /// Runs every job due at `now` and returns the errors of failed runs,
+    /// a failed job is retried after its regular interval
+    pub async fn run_due(&mut self, now: DateTime<Utc>) -> Vec<JobError> {
+        let mut errors = vec![];
+        for scheduled in self.jobs.iter_mut().filter(|s| s.next_run <= now) {
+            log::debug!("Running job {}", scheduled.job.name());
+            if let Err(err) = scheduled.job.run(now).await {
+                log::error!("{}", err);
+                errors.push(err);
+            }
+            scheduled.next_run = now + scheduled.job.interval();
+        }
+        errors
+    }
+
+    /// Earliest time a registered job becomes due
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.jobs.iter().map(|s| s.next_run).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counting {
+        runs: AtomicUsize,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Job for Counting {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+        fn interval(&self) -> Duration {
+            Duration::minutes(5)
+        }
+        async fn run(&self, _now: DateTime<Utc>) -> Result<(), JobError> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(JobError {
+                    job: self.name(),
+                    message: "boom".to_string(),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    #[rstest]
+    async fn test_run_due() {
+        let job = Arc::new(Counting {
+            runs: AtomicUsize::new(0),
+            fail: false,
+        });
+        let mut runner = JobRunner::new().with_job(job.clone());
+        let now = Utc::now();
+        // due right away, then only after the interval
+        assert!(runner.run_due(now).await.is_empty());
+        runner.run_due(now + Duration::minutes(1)).await;
+        assert_eq!(job.runs.load(Ordering::SeqCst), 1);
+        assert_eq!(runner.next_due(), Some(now + Duration::minutes(5)));
+        runner.run_due(now + Duration::minutes(5)).await;
+        assert_eq!(job.runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[rstest]
+    async fn test_run_due_failure_is_rescheduled() {
+        let job = Arc::new(Counting {
+            runs: AtomicUsize::new(0),
+            fail: true,
+        });
+        let mut runner = JobRunner::new().with_job(job.clone());
+        let now = Utc::now();
+        let errors = runner.run_due(now).await;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].job, "counting");
+        assert_eq!(runner.next_due(), Some(now + Duration::minutes(5)));
+    }
+}
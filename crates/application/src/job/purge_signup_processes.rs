@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    gateway::{
+        database::{
+            signup_process::{DeleteError, GetError, Repo, StateFilter, StateKind},
+            token::{AttemptError, InvalidateError, Purpose, Repo as TokenRepo, Scope},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    job::{Job, JobError},
+};
+
+/// Time a deleted process is kept around before it is purged
+pub const DEFAULT_GRACE_PERIOD_DAYS: u32 = 7;
+const DEFAULT_BATCH_SIZE: u32 = 100;
+const DEFAULT_INTERVAL_HOURS: i64 = 1;
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Deleted signup processes could not be decoded")]
+    Corrupted,
+    #[error("Token Repo error: {0}")]
+    TokenInvalidate(#[from] InvalidateError),
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+}
+
+impl From<GetError> for Error {
+    fn from(err: GetError) -> Self {
+        match err {
+            GetError::Corrupted => Self::Corrupted,
+            GetError::NotFound | GetError::IncorrectState | GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+/// Outcome of a single purge
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct Report {
+    pub purged: u64,
+    /// Processes purged over the lifetime of the database
+    pub tombstones: u64,
+}
+
+/// Hard-deletes signup processes that stayed `ForDeletion` past the grace
+/// period, together with the tokens issued for them and their failed
+/// attempts
+pub struct PurgeSignupProcesses<D> {
+    dependency_provider: Arc<D>,
+    grace_period: Duration,
+    batch_size: u32,
+    interval: Duration,
+}
+
+impl<D> PurgeSignupProcesses<D>
+where
+    D: DatabaseProvider,
+{
+    pub fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+            grace_period: Duration::days(DEFAULT_GRACE_PERIOD_DAYS.into()),
+            batch_size: DEFAULT_BATCH_SIZE,
+            interval: Duration::hours(DEFAULT_INTERVAL_HOURS),
+        }
+    }
+
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    /// Number of processes purged per transaction
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Purges every process deleted before `now` minus the grace period
+    pub async fn purge(&self, now: DateTime<Utc>) -> Result<Report, Error> {
+        let filter = StateFilter {
+            state: StateKind::ForDeletion,
+            entered_before: now - self.grace_period,
+            limit: self.batch_size,
+        };
+        let mut report = Report::default();
+        // purged processes drop out of the filter, stop on the last batch
+        // or once a batch makes no progress
+        while self.purge_batch(filter.clone(), &mut report).await? == self.batch_size as usize {}
+        report.tombstones = self
+            .dependency_provider
+            .database()
+            .signup_process_repo()
+            .count_tombstones(None)
+            .await?;
+        Ok(report)
+    }
+
+    /// Purges one batch in a transaction, returns how many were purged
+    async fn purge_batch(&self, filter: StateFilter, report: &mut Report) -> Result<usize, Error> {
+        let database = self.dependency_provider.database();
+        let mut transaction = database.begin_transaction().await;
+        let records = database
+            .signup_process_repo()
+            .get_by_state(Some(&mut transaction), filter)
+            .await?;
+        let mut purged = 0;
+        for record in records {
+            let scope = Scope::new(Purpose::Signup, record.id);
+            database
+                .token_repo()
+                .invalidate(Some(&mut transaction), &scope)
+                .await?;
+            database
+                .token_repo()
+                .clear_attempts(Some(&mut transaction), &scope)
+                .await?;
+            match database
+                .signup_process_repo()
+                .delete(Some(&mut transaction), record.id)
+                .await
+            {
+                Ok(()) => {
+                    report.purged += 1;
+                    purged += 1;
+                }
+                Err(DeleteError::NotFound) => {
+                    log::debug!("SignupProcess {} was already purged", record.id);
+                }
+                Err(DeleteError::Connection) => return Err(Error::Repo),
+            }
+        }
+        database
+            .commit_transaction(transaction)
+            .await
+            .map_err(|_| Error::Repo)?;
+        Ok(purged)
+    }
+}
+
+#[async_trait]
+impl<D> Job for PurgeSignupProcesses<D>
+where
+    D: DatabaseProvider,
+{
+    fn name(&self) -> &'static str {
+        "signup_process.purge"
+    }
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+    async fn run(&self, now: DateTime<Utc>) -> Result<(), JobError> {
+        let report = self.purge(now).await.map_err(|err| JobError {
+            job: self.name(),
+            message: err.to_string(),
+        })?;
+        log::info!("Purged signup processes: {:?}", report);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::signup_process::Record, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::signup_process::SignupStateEnum;
+    use rstest::*;
+
+    #[fixture]
+    fn for_deletion_record(verification_email_sent_record: Record) -> Record {
+        Record {
+            seq: verification_email_sent_record.seq + 1,
+            state: SignupStateEnum::ForDeletion,
+            ..verification_email_sent_record
+        }
+    }
+
+    #[rstest]
+    async fn test_purge(
+        mut dependency_provider: MockDependencyProvider,
+        for_deletion_record: Record,
+    ) {
+        // fixtures
+        let now = Utc::now();
+        let id = for_deletion_record.id;
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(move |_, filter| {
+                filter.state == StateKind::ForDeletion
+                    && filter.entered_before
+                        == now - Duration::days(DEFAULT_GRACE_PERIOD_DAYS.into())
+            })
+            .times(1)
+            .returning(move |_, _| Ok(vec![for_deletion_record.clone()]));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .withf(move |_, scope| scope == &Scope::new(Purpose::Signup, id))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_delete()
+            .withf(move |_, actual_id| actual_id == &id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_count_tombstones()
+            .times(1)
+            .returning(|_| Ok(5));
+        // Job Initialization
+        let job = PurgeSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution -- mock predicates will fail during execution
+        let result = job.purge(now).await;
+        // Assert execution success
+        assert_eq!(
+            result,
+            Ok(Report {
+                purged: 1,
+                tombstones: 5,
+            })
+        );
+    }
+    #[rstest]
+    async fn test_purge_grace_period(mut dependency_provider: MockDependencyProvider) {
+        // fixtures
+        let now = Utc::now();
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .withf(move |_, filter| filter.entered_before == now - Duration::hours(1))
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_count_tombstones()
+            .times(1)
+            .returning(|_| Ok(0));
+        // Job Initialization
+        let job = PurgeSignupProcesses::new(Arc::new(dependency_provider))
+            .with_grace_period(Duration::hours(1));
+        // Job Execution
+        let result = job.purge(now).await;
+        // Assert execution success
+        assert_eq!(result, Ok(Report::default()));
+    }
+    #[rstest]
+    async fn test_purge_skips_already_purged(
+        mut dependency_provider: MockDependencyProvider,
+        for_deletion_record: Record,
+    ) {
+        // fixtures
+        // Mock setup -- another purge removed the process in the meantime
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(1)
+            .returning(move |_, _| Ok(vec![for_deletion_record.clone()]));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_delete()
+            .times(1)
+            .returning(|_, _| Err(DeleteError::NotFound));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_count_tombstones()
+            .times(1)
+            .returning(|_| Ok(1));
+        // Job Initialization
+        let job = PurgeSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution
+        let result = job.purge(Utc::now()).await;
+        // Assert execution success
+        assert_eq!(
+            result,
+            Ok(Report {
+                purged: 0,
+                tombstones: 1,
+            })
+        );
+    }
+    #[rstest]
+    async fn test_purge_fail_token_repo(
+        mut dependency_provider: MockDependencyProvider,
+        for_deletion_record: Record,
+    ) {
+        // fixtures
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(1)
+            .returning(move |_, _| Ok(vec![for_deletion_record.clone()]));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Err(InvalidateError::Connection));
+        // Job Initialization
+        let job = PurgeSignupProcesses::new(Arc::new(dependency_provider));
+        // Job Execution
+        let result = job.run(Utc::now()).await;
+        // Assert execution error
+        assert!(result.is_err());
+    }
+}
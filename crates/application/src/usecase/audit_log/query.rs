@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            audit_log::{Filter, QueryError, Record, Repo},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{auth_strategy::AuthStrategy, user::Id as UserId},
+    value_object::Permission,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Entries returned when the request does not ask for a number
+pub const DEFAULT_LIMIT: u32 = 100;
+/// Most entries returned at once, larger limits are cut down to it
+pub const MAX_LIMIT: u32 = 1000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Request {
+    pub actor_id: Option<UserId>,
+    pub target_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Defaults to [`DEFAULT_LIMIT`], capped at [`MAX_LIMIT`]
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub entries: Vec<Record>,
+}
+
+/// Query the audit log of privileged usecase executions
+pub struct QueryAuditLog<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Time range start {0} is after its end {1}")]
+    InvalidTimeRange(DateTime<Utc>, DateTime<Utc>),
+    #[error("{}", QueryError::Connection)]
+    Repo,
+}
+
+impl From<QueryError> for Error {
+    fn from(e: QueryError) -> Self {
+        match e {
+            QueryError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for QueryAuditLog<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "audit_log.query";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Query audit log: {:?}", req);
+        if let (Some(from), Some(to)) = (req.from, req.to) {
+            if from > to {
+                return Err(Error::InvalidTimeRange(from, to));
+            }
+        }
+        let filter = Filter {
+            actor_id: req.actor_id,
+            target_id: req.target_id,
+            from: req.from,
+            to: req.to,
+            limit: req.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+            offset: req.offset.unwrap_or_default(),
+        };
+        let entries = self
+            .dependency_provider
+            .database()
+            .audit_log_repo()
+            .query(None, filter)
+            .await?;
+        Ok(Self::Response { entries })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::AdminOnly
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::AUDIT_LOG_READ)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::audit_log::Outcome, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::{
+        entity::auth_context::{AuthContext, AuthError},
+        value_object::Role,
+    };
+    use chrono::Duration;
+    use rstest::*;
+
+    #[fixture]
+    fn audit_log_record(user_id_zero: UserId, user_id: UserId) -> Record {
+        Record {
+            actor_id: Some(user_id_zero),
+            actor_role: Some(Role::admin()),
+            usecase: "user.delete".to_string(),
+            target_ids: vec![user_id.to_string()],
+            outcome: Outcome::Success,
+            request_id: None,
+            client_ip: None,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    #[rstest]
+    async fn test_query_audit_log_success(
+        mut dependency_provider: MockDependencyProvider,
+        user_id_zero: UserId,
+        user_id: UserId,
+        audit_log_record: Record,
+    ) {
+        // fixtures
+        let to = Utc::now();
+        let from = to - Duration::days(1);
+        let req = Request {
+            actor_id: Some(user_id_zero),
+            target_id: Some(user_id.to_string()),
+            from: Some(from),
+            to: Some(to),
+            limit: Some(10),
+            offset: Some(20),
+        };
+        let expected_filter = Filter {
+            actor_id: Some(user_id_zero),
+            target_id: Some(user_id.to_string()),
+            from: Some(from),
+            to: Some(to),
+            limit: 10,
+            offset: 20,
+        };
+        let expected_record = audit_log_record.clone();
+        // mock setup
+        dependency_provider
+            .db
+            .audit_log_repo
+            .expect_query()
+            .withf(move |_, actual_filter| actual_filter == &expected_filter)
+            .times(1)
+            .returning(move |_, _| Ok(vec![audit_log_record.clone()]));
+        // Usecase Initialization
+        let usecase =
+            <QueryAuditLog<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().entries, vec![expected_record]);
+    }
+    #[rstest]
+    #[case(None, DEFAULT_LIMIT)]
+    #[case(Some(MAX_LIMIT + 1), MAX_LIMIT)]
+    async fn test_query_audit_log_limit(
+        mut dependency_provider: MockDependencyProvider,
+        #[case] limit: Option<u32>,
+        #[case] expected_limit: u32,
+    ) {
+        // fixtures
+        let req = Request {
+            limit,
+            ..Default::default()
+        };
+        // mock setup
+        dependency_provider
+            .db
+            .audit_log_repo
+            .expect_query()
+            .withf(move |_, filter| filter.limit == expected_limit && filter.offset == 0)
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        // Usecase Initialization
+        let usecase =
+            <QueryAuditLog<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    async fn test_query_audit_log_fail_invalid_time_range(
+        dependency_provider: MockDependencyProvider,
+    ) {
+        // fixtures
+        let from = Utc::now();
+        let to = from - Duration::days(1);
+        let req = Request {
+            from: Some(from),
+            to: Some(to),
+            ..Default::default()
+        };
+        // Usecase Initialization
+        let usecase =
+            <QueryAuditLog<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- no repo calls expected
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::InvalidTimeRange(from, to));
+    }
+    #[rstest]
+    async fn test_query_audit_log_fail_query_connection(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .audit_log_repo
+            .expect_query()
+            .times(1)
+            .returning(move |_, _| Err(QueryError::Connection));
+        // Usecase Initialization
+        let usecase =
+            <QueryAuditLog<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(Request::default(), &RequestContext::default())
+            .await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Repo);
+    }
+    #[rstest]
+    fn test_authorize_admin_zero(auth_context_admin: AuthContext) {
+        let result = QueryAuditLog::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request::default(), Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_user_zero(auth_context_user: AuthContext) {
+        let result = QueryAuditLog::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request::default(), Some(auth_context_user));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+    #[rstest]
+    fn test_authorize_none() {
+        let result = QueryAuditLog::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request::default(), None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
+    }
+}
@@ -1,16 +1,24 @@
 use std::sync::Arc;
 
-use ca_domain::entity::{
-    auth_context::{AuthContext, AuthError},
-    auth_strategy::AuthStrategy,
-    user::Id as UserId,
+use ca_domain::{
+    entity::{
+        auth_context::{AuthContext, AuthError, PendingStep},
+        auth_policy::Policy,
+        auth_strategy::AuthStrategy,
+        user::Id as UserId,
+    },
+    value_object::Permission,
 };
 
+use request_context::RequestContext;
 use serde::{de::DeserializeOwned, Serialize};
 
+pub mod audit_log;
+pub mod request_context;
+pub mod role;
 pub mod signup_process;
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 pub mod user;
 
 /// Usecase trait
@@ -19,59 +27,66 @@ pub trait Usecase<D>: Send + Sync {
     type Request: DeserializeOwned + Send;
     type Response: Serialize + Send + 'static;
     type Error: std::fmt::Debug + Serialize + Send;
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error>;
+    /// Stable name the usecase is recorded under in the audit log
+    const NAME: &'static str;
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error>;
     fn new(db: Arc<D>) -> Self;
     fn extract_owner(&self, _req: &Self::Request) -> Option<UserId> {
         None
     }
+    /// Ids of the objects the request acts on, recorded in the audit log
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        self.extract_owner(req)
+            .map(|owner| vec![owner.to_string()])
+            .unwrap_or_default()
+    }
     fn auth_strategy(&self) -> AuthStrategy {
         AuthStrategy::AdminOnly
     }
-    #[allow(unused_variables)]
+    /// Permissions that grant access regardless of the `auth_strategy`,
+    /// all of them are required
+    fn required_permissions(&self) -> Vec<Permission> {
+        Vec::new()
+    }
+    /// Policy the request is authorized against, derived from the
+    /// `auth_strategy` and `required_permissions` unless overridden
+    fn auth_policy(&self) -> Policy {
+        let policy = self.auth_strategy().into();
+        let permissions = self.required_permissions();
+        if permissions.is_empty() {
+            policy
+        } else {
+            Policy::any([
+                policy,
+                Policy::all(permissions.into_iter().map(Policy::HasPermission)),
+            ])
+        }
+    }
+    /// Login step a context may still be pending on to get through, contexts
+    /// pending on any other step are treated as unauthenticated
+    fn pending_step(&self) -> Option<PendingStep> {
+        None
+    }
+    /// Privileged executions are recorded in the audit log
+    fn is_audited(&self) -> bool {
+        self.auth_policy().is_privileged()
+    }
     fn authorize(
         &self,
         req: &Self::Request,
         auth_context: Option<AuthContext>,
     ) -> Result<(), AuthError> {
-        match self.auth_strategy() {
-            AuthStrategy::AdminOnly => {
-                if let Some(auth_context) = auth_context {
-                    if auth_context.is_admin() {
-                        Ok(())
-                    } else {
-                        Err(AuthError::Unauthorized)
-                    }
-                } else {
-                    Err(AuthError::Unauthorized)
-                }
-            }
-            AuthStrategy::AdminAndOwnerOnly => {
-                if let Some(auth_context) = auth_context {
-                    // check if user is admin
-                    if auth_context.is_admin() {
-                        Ok(())
-                    // check if user is owner
-                    } else if let Some(owner) = self.extract_owner(req) {
-                        if owner == auth_context.user_id {
-                            Ok(())
-                        } else {
-                            Err(AuthError::Unauthorized)
-                        }
-                    } else {
-                        // extract owner returned None
-                        // for strategy AdminAndOwnerOnly
-                        // this should not happen
-                        unimplemented!("extract_owner returned None")
-                    }
-                // if no auth context is provided
-                } else {
-                    Err(AuthError::Unauthorized)
-                }
-            }
-            AuthStrategy::Public => {
-                // no auth required
-                Ok(())
-            }
+        if auth_context
+            .as_ref()
+            .is_some_and(|auth| auth.pending.is_some() && auth.pending != self.pending_step())
+        {
+            return Err(AuthError::Unauthenticated);
         }
+        self.auth_policy()
+            .evaluate(auth_context.as_ref(), self.extract_owner(req).as_ref())
     }
 }
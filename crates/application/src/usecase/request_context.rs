@@ -0,0 +1,41 @@
+use ca_domain::entity::auth_context::AuthContext;
+
+/// Per request data handed to a usecase alongside its request.
+///
+/// Filled in by the interface the request came through (http headers,
+/// cli environment, ...) and by the controller once the caller has been
+/// authenticated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestContext {
+    pub auth_context: Option<AuthContext>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl RequestContext {
+    pub fn with_auth_context(mut self, auth_context: Option<AuthContext>) -> Self {
+        self.auth_context = auth_context;
+        self
+    }
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+    pub fn with_client_ip(mut self, client_ip: impl Into<String>) -> Self {
+        self.client_ip = Some(client_ip.into());
+        self
+    }
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+    pub fn auth_context(&self) -> Option<&AuthContext> {
+        self.auth_context.as_ref()
+    }
+}
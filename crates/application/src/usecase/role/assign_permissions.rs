@@ -0,0 +1,234 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use crate::{
+    gateway::{
+        database::{
+            role::{GetError, Repo, SaveError},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::auth_strategy::AuthStrategy,
+    value_object::{Permission, Role},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub name: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub role: Role,
+    pub permissions: BTreeSet<Permission>,
+}
+
+/// Grant additional permissions to an existing role
+pub struct AssignPermissions<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Role {0} not found")]
+    NotFound(Role),
+    #[error("{}", SaveError::Connection)]
+    Repo,
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<(GetError, Role)> for Error {
+    fn from((err, role): (GetError, Role)) -> Self {
+        match err {
+            GetError::NotFound => Self::NotFound(role),
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for AssignPermissions<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "role.assign_permissions";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Assign permissions to role: {:?}", req);
+        let role = Role::new(req.name);
+        let mut record = self
+            .dependency_provider
+            .database()
+            .role_repo()
+            .get(None, role.clone())
+            .await
+            .map_err(|err| (err, role))?;
+        record
+            .permissions
+            .extend(req.permissions.into_iter().map(Permission::new));
+        self.dependency_provider
+            .database()
+            .role_repo()
+            .save(None, record.clone())
+            .await?;
+        Ok(Self::Response {
+            role: record.role,
+            permissions: record.permissions,
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.name.clone()]
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::AdminOnly
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::ROLES_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::role::Record, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[fixture]
+    fn req() -> Request {
+        Request {
+            name: TEST_ROLE.to_string(),
+            permissions: vec![Permission::AUDIT_LOG_READ.to_string()],
+        }
+    }
+
+    #[rstest]
+    async fn test_assign_permissions_success(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+        role_record: Record,
+    ) {
+        // fixtures
+        let mut expected_record = role_record.clone();
+        expected_record
+            .permissions
+            .insert(Permission::new(Permission::AUDIT_LOG_READ));
+        let saved_record = expected_record.clone();
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .withf(move |_, actual_role| actual_role == &Role::new(TEST_ROLE))
+            .times(1)
+            .returning(move |_, _| Ok(role_record.clone()));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_save()
+            .withf(move |_, actual_record| actual_record == &saved_record)
+            .times(1)
+            .returning(move |_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <AssignPermissions<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().permissions, expected_record.permissions);
+    }
+    #[rstest]
+    async fn test_assign_permissions_fail_get_not_found(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        // Usecase Initialization
+        let usecase = <AssignPermissions<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::NotFound(Role::new(TEST_ROLE)));
+    }
+    #[rstest]
+    async fn test_assign_permissions_fail_save_connection(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+        role_record: Record,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(role_record.clone()));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_save()
+            .times(1)
+            .returning(move |_, _| Err(SaveError::Connection));
+        // Usecase Initialization
+        let usecase = <AssignPermissions<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Repo);
+    }
+    #[rstest]
+    fn test_authorize_admin_zero(req: Request, auth_context_admin: AuthContext) {
+        let result = AssignPermissions::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_user_zero(req: Request, auth_context_user: AuthContext) {
+        let result = AssignPermissions::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_user));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+}
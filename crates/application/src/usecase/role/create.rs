@@ -0,0 +1,259 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use crate::{
+    gateway::{
+        database::{
+            role::{GetError, Record, Repo, SaveError},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::auth_strategy::AuthStrategy,
+    value_object::{Permission, Role},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct Request {
+    #[validate(length(min = 1, max = 30))]
+    pub name: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub role: Role,
+    pub permissions: BTreeSet<Permission>,
+}
+
+/// Create a new role granting the given permissions
+pub struct Create<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Role {0} already exists")]
+    AlreadyExists(Role),
+    #[error(transparent)]
+    Invalidity(#[from] validator::ValidationErrors),
+    #[error("{}", SaveError::Connection)]
+    Repo,
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for Create<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "role.create";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Create role: {:?}", req);
+        req.validate()?;
+        let role = Role::new(req.name);
+        match self
+            .dependency_provider
+            .database()
+            .role_repo()
+            .get(None, role.clone())
+            .await
+        {
+            Ok(_) => return Err(Error::AlreadyExists(role)),
+            Err(GetError::NotFound) => {}
+            Err(GetError::Connection) => return Err(Error::Repo),
+        }
+        let record = Record {
+            role,
+            permissions: req.permissions.into_iter().map(Permission::new).collect(),
+        };
+        self.dependency_provider
+            .database()
+            .role_repo()
+            .save(None, record.clone())
+            .await?;
+        Ok(Self::Response {
+            role: record.role,
+            permissions: record.permissions,
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.name.clone()]
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::AdminOnly
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::ROLES_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gateway::mock::MockDependencyProvider, usecase::tests::fixtures::*};
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[fixture]
+    fn req() -> Request {
+        Request {
+            name: TEST_ROLE.to_string(),
+            permissions: vec![Permission::USERS_READ.to_string()],
+        }
+    }
+
+    #[rstest]
+    async fn test_create_success(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+        role_record: Record,
+    ) {
+        // fixtures
+        let expected_record = role_record.clone();
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .withf(move |_, actual_role| actual_role == &Role::new(TEST_ROLE))
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_save()
+            .withf(move |_, actual_record| actual_record == &expected_record)
+            .times(1)
+            .returning(move |_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <Create<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.role, role_record.role);
+        assert_eq!(result.permissions, role_record.permissions);
+    }
+    #[rstest]
+    async fn test_create_fail_already_exists(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+        role_record: Record,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(role_record.clone()));
+        // Usecase Initialization
+        let usecase = <Create<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            Error::AlreadyExists(Role::new(TEST_ROLE))
+        );
+    }
+    #[rstest]
+    async fn test_create_fail_req_validation(dependency_provider: MockDependencyProvider) {
+        // fixtures
+        let req = Request {
+            name: "".to_string(),
+            permissions: vec![],
+        };
+        // Usecase Initialization
+        let usecase = <Create<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- no repo calls expected
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("name: Validation error: length"));
+    }
+    #[rstest]
+    async fn test_create_fail_save_connection(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_save()
+            .times(1)
+            .returning(move |_, _| Err(SaveError::Connection));
+        // Usecase Initialization
+        let usecase = <Create<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Repo);
+    }
+    #[rstest]
+    fn test_authorize_admin_zero(req: Request, auth_context_admin: AuthContext) {
+        let result = Create::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_support(req: Request, auth_context_support: AuthContext) {
+        let result = Create::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_support));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+    #[rstest]
+    fn test_authorize_none(req: Request) {
+        let result = Create::new(Arc::new(MockDependencyProvider::default())).authorize(&req, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            role::{GetAllError, Record, Repo},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{entity::auth_strategy::AuthStrategy, value_object::Permission};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request;
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub roles: Vec<Record>,
+}
+
+/// List all roles with the permissions they grant
+pub struct GetAll<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("{}", GetAllError::Connection)]
+    Repo,
+}
+
+impl From<GetAllError> for Error {
+    fn from(e: GetAllError) -> Self {
+        match e {
+            GetAllError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for GetAll<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "role.get_all";
+
+    async fn exec(
+        &self,
+        _req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Get all roles");
+        let roles = self
+            .dependency_provider
+            .database()
+            .role_repo()
+            .get_all(None)
+            .await?;
+        Ok(Self::Response { roles })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::AdminOnly
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::ROLES_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gateway::mock::MockDependencyProvider, usecase::tests::fixtures::*};
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_get_all_success(
+        mut dependency_provider: MockDependencyProvider,
+        role_record: Record,
+    ) {
+        // fixtures
+        let expected_record = role_record.clone();
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get_all()
+            .times(1)
+            .returning(move |_| Ok(vec![role_record.clone()]));
+        // Usecase Initialization
+        let usecase = <GetAll<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(Request, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().roles, vec![expected_record]);
+    }
+    #[rstest]
+    async fn test_get_all_fail_get_all_connection(mut dependency_provider: MockDependencyProvider) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get_all()
+            .times(1)
+            .returning(move |_| Err(GetAllError::Connection));
+        // Usecase Initialization
+        let usecase = <GetAll<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(Request, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Repo);
+    }
+    #[rstest]
+    fn test_authorize_admin_zero(auth_context_admin: AuthContext) {
+        let result = GetAll::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_user_zero(auth_context_user: AuthContext) {
+        let result = GetAll::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request, Some(auth_context_user));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod assign_permissions;
+pub mod create;
+pub mod get_all;
@@ -7,9 +7,10 @@ use crate::{
             user::{self, Repo as UserRepo, SaveError as UserSaveError},
             Database,
         },
-        DatabaseProvider,
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider, PasswordPolicyProvider, SignupPolicyProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
 use ca_domain::{
@@ -18,20 +19,16 @@ use ca_domain::{
         signup_process::{EmailVerified, Id, SignupProcess},
         user::{Password, User, UserName},
     },
-    value_object::Role,
+    value_object::{PasswordRejected, Role, SecretString, ValidationError},
 };
-use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize)]
 pub struct Request {
     pub id: Id,
-    #[validate(length(min = 1, max = 30))]
     pub username: String,
-    #[validate(length(min = 5, max = 60))]
-    pub password: String,
+    pub password: SecretString,
 }
 
 #[derive(Debug, Serialize)]
@@ -46,14 +43,24 @@ pub struct Complete<D> {
 pub enum Error {
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
     #[error("SignupProcess {0} not found")]
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
     #[error("SignupProcess completion timed out")]
     CompletionTimedOut,
+    #[error("{}", UserSaveError::EmailTaken)]
+    EmailAlreadyRegistered,
+    #[error("{}", UserSaveError::UsernameTaken)]
+    UsernameTaken,
     #[error(transparent)]
-    Validation(#[from] validator::ValidationErrors),
+    Validation(#[from] ValidationError),
+    #[error(transparent)]
+    WeakPassword(#[from] PasswordRejected),
 }
 
 impl From<(GetError, Id)> for Error {
@@ -61,6 +68,7 @@ impl From<(GetError, Id)> for Error {
         match err {
             GetError::NotFound => Self::NotFound(id),
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
         }
     }
@@ -70,6 +78,7 @@ impl From<SaveError> for Error {
     fn from(e: SaveError) -> Self {
         match e {
             SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
         }
     }
 }
@@ -78,22 +87,33 @@ impl From<UserSaveError> for Error {
     fn from(e: UserSaveError) -> Self {
         match e {
             UserSaveError::Connection => Self::Repo,
+            UserSaveError::EmailTaken => Self::EmailAlreadyRegistered,
+            UserSaveError::UsernameTaken => Self::UsernameTaken,
         }
     }
 }
 #[async_trait::async_trait]
 impl<D> Usecase<D> for Complete<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + SignupPolicyProvider + PasswordPolicyProvider + ClockProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "signup_process.complete";
 
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("SignupProcess Completed: {:?}", req);
-        // Validate the request
-        req.validate()?;
+        let now = self.dependency_provider.clock().now();
+        let username = UserName::parse(req.username)?;
+        let password = Password::parse(req.password.expose_secret())?;
+        if username.is_reserved() {
+            return Err(Self::Error::UsernameTaken);
+        }
         let transaction = self
             .dependency_provider
             .database()
@@ -106,28 +126,33 @@ where
             .get_latest_state(None, req.id)
             .await
             .map_err(|e| (e, req.id))?;
+        let seq = record.seq;
         let process: SignupProcess<EmailVerified> = record.try_into().map_err(|e| (e, req.id))?;
-        if Utc::now() - Duration::days(1) > process.entered_at() {
-            let process =
-                process.fail(ca_domain::entity::signup_process::Error::CompletionTimedOut);
-            self.dependency_provider
-                .database()
-                .signup_process_repo()
-                .save_latest_state(None, process.into())
-                .await?;
-            self.dependency_provider
-                .database()
-                .commit_transaction(transaction)
-                .await
-                .map_err(|_| SaveError::Connection)?;
-            return Err(Self::Error::CompletionTimedOut);
-        }
-        let username = UserName::new(req.username);
-        let password = Password::new(req.password);
-        let process = process.complete(username, password);
+        self.dependency_provider.password_policy().check(
+            &password,
+            &username,
+            &process.state().email,
+        )?;
+        let policy = self.dependency_provider.signup_policy();
+        let process = match process.complete(username, password, &policy, now) {
+            Ok(process) => process,
+            Err(failed) => {
+                self.dependency_provider
+                    .database()
+                    .signup_process_repo()
+                    .save_latest_state(None, failed.into(), Some(seq))
+                    .await?;
+                self.dependency_provider
+                    .database()
+                    .commit_transaction(transaction)
+                    .await
+                    .map_err(|_| SaveError::Connection)?;
+                return Err(Self::Error::CompletionTimedOut);
+            }
+        };
         let user: User = User::new(
             ca_domain::entity::user::Id::new(req.id),
-            Role::User,
+            Role::user(),
             process.email(),
             process.username(),
             process.password(),
@@ -142,7 +167,7 @@ where
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(None, process.clone().into())
+            .save_latest_state(None, process.clone().into(), Some(seq))
             .await?;
         self.dependency_provider
             .database()
@@ -159,6 +184,9 @@ where
             dependency_provider: db,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
     fn auth_strategy(&self) -> AuthStrategy {
         AuthStrategy::Public
     }
@@ -169,7 +197,9 @@ mod tests {
     use super::*;
     use crate::{
         gateway::{
-            database::signup_process::Record as SignupProcessRepoRecord,
+            database::{
+                signup_process::Record as SignupProcessRepoRecord, user::Record as UserRecord,
+            },
             mock::MockDependencyProvider,
         },
         usecase::tests::fixtures::*,
@@ -179,8 +209,10 @@ mod tests {
             auth_context::AuthContext,
             signup_process::{Error as SignupError, Id as SignupId},
         },
-        value_object::Email,
+        value_object::{Email, Field, PasswordPolicy, PasswordWeakness, SignupPolicy},
     };
+    use chrono::Duration;
+    use chrono::Utc;
     use rstest::*;
 
     #[rstest]
@@ -193,20 +225,26 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let process: SignupProcess<EmailVerified> =
             email_verified_record.clone().try_into().unwrap();
         // record to be passed to the save latest state method
         let record_to_save = process
-            .complete(UserName::new(TEST_USERNAME), Password::new(TEST_PASSWORD))
+            .complete(
+                UserName::new_unchecked(TEST_USERNAME),
+                Password::new_unchecked(TEST_PASSWORD),
+                &SignupPolicy::default(),
+                Utc::now(),
+            )
+            .unwrap()
             .into();
         let user: User = User::new(
             ca_domain::entity::user::Id::new(signup_id),
-            Role::User,
-            Email::new(TEST_EMAIL),
-            UserName::new(TEST_USERNAME),
-            Password::new(TEST_PASSWORD),
+            Role::user(),
+            Email::new_unchecked(TEST_EMAIL),
+            UserName::new_unchecked(TEST_USERNAME),
+            Password::new_unchecked(TEST_PASSWORD),
         );
         // Mock setup -- predicates and return values
         dependency_provider
@@ -234,15 +272,15 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -257,7 +295,7 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: "".to_string(),
-            password: "".to_string(),
+            password: "".into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -271,12 +309,88 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
-        assert!(result.is_err());
-        let error_string = result.unwrap_err().to_string();
-        assert!(error_string.contains("password: Validation error: length"));
-        assert!(error_string.contains("username: Validation error: length"));
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Validation(ValidationError::TooShort {
+                field: Field::UserName,
+                min: 5
+            })
+        );
+    }
+    #[rstest]
+    async fn test_complete_fail_password_too_long(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            username: TEST_USERNAME.to_string(),
+            password: "p".repeat(61).into(),
+        };
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Validation(ValidationError::TooLong {
+                field: Field::Password,
+                max: 60
+            })
+        );
+    }
+    #[rstest]
+    async fn test_complete_fail_breached_password(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        dependency_provider.password_policy =
+            PasswordPolicy::default().with_breached([TEST_PASSWORD]);
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(email_verified_record.clone()));
+        dependency_provider.db.user_repo.expect_save().never();
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::WeakPassword(PasswordRejected {
+                reasons: vec![PasswordWeakness::Breached]
+            })
+        );
     }
     #[rstest]
     async fn test_complete_fail_get_latest_state_connection(
@@ -287,7 +401,7 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -304,7 +418,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -318,7 +432,7 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -335,7 +449,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound(signup_id));
@@ -350,7 +464,7 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -367,7 +481,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution errpr
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::IncorrectState(signup_id));
@@ -382,13 +496,15 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         email_verified_record.entered_at = Utc::now() - Duration::days(2);
         let process: SignupProcess<EmailVerified> =
             email_verified_record.clone().try_into().unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.fail(SignupError::CompletionTimedOut).into();
+        let record_to_save = process
+            .fail(SignupError::CompletionTimedOut, chrono::Utc::now())
+            .into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -409,20 +525,70 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution errpr
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::CompletionTimedOut);
     }
     #[rstest]
+    async fn test_complete_success_longer_policy_window(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        mut email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures -- the policy allows a week, the clock is moved two days on
+        let req = Request {
+            id: signup_id,
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        dependency_provider.signup_policy =
+            SignupPolicy::default().with_completion_ttl(Duration::days(7));
+        email_verified_record.entered_at = dependency_provider.clock.now();
+        dependency_provider.clock.advance(Duration::days(2));
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(email_verified_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(|_, actual_record, _| {
+                matches!(
+                    actual_record.state,
+                    ca_domain::entity::signup_process::SignupStateEnum::Completed { .. }
+                )
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
     async fn test_complete_fail_user_repo_connection(
         mut dependency_provider: MockDependencyProvider,
         signup_id: SignupId,
@@ -432,14 +598,14 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let user: User = User::new(
             ca_domain::entity::user::Id::new(signup_id),
-            Role::User,
-            Email::new(TEST_EMAIL),
-            UserName::new(TEST_USERNAME),
-            Password::new(TEST_PASSWORD),
+            Role::user(),
+            Email::new_unchecked(TEST_EMAIL),
+            UserName::new_unchecked(TEST_USERNAME),
+            Password::new_unchecked(TEST_PASSWORD),
         );
         // Mock setup -- predicates and return values
         dependency_provider
@@ -474,12 +640,116 @@ mod tests {
         );
 
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution errpr
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
     }
     #[rstest]
+    async fn test_complete_fail_email_taken(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        // Mock setup -- the email was registered by another process meanwhile
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(email_verified_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .times(1)
+            .returning(|_, _| Err(UserSaveError::EmailTaken));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::EmailAlreadyRegistered);
+    }
+    #[rstest]
+    async fn test_complete_fail_username_taken(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        // Mock setup -- the process stays verified so another name can be tried
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(email_verified_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .times(1)
+            .returning(|_, _| Err(UserSaveError::UsernameTaken));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::UsernameTaken);
+    }
+    #[rstest]
+    async fn test_complete_fail_username_reserved(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            username: "Administrator".to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::UsernameTaken);
+    }
+    #[rstest]
     async fn test_complete_fail_save_latest_state_connection(
         mut dependency_provider: MockDependencyProvider,
         signup_id: SignupId,
@@ -489,20 +759,26 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let process: SignupProcess<EmailVerified> =
             email_verified_record.clone().try_into().unwrap();
         // record to be passed to the save latest state method
         let record_to_save = process
-            .complete(UserName::new(TEST_USERNAME), Password::new(TEST_PASSWORD))
+            .complete(
+                UserName::new_unchecked(TEST_USERNAME),
+                Password::new_unchecked(TEST_PASSWORD),
+                &SignupPolicy::default(),
+                Utc::now(),
+            )
+            .unwrap()
             .into();
         let user: User = User::new(
             ca_domain::entity::user::Id::new(signup_id),
-            Role::User,
-            Email::new(TEST_EMAIL),
-            UserName::new(TEST_USERNAME),
-            Password::new(TEST_PASSWORD),
+            Role::user(),
+            Email::new_unchecked(TEST_EMAIL),
+            UserName::new_unchecked(TEST_USERNAME),
+            Password::new_unchecked(TEST_PASSWORD),
         );
         // Mock setup -- predicates and return values
         dependency_provider
@@ -530,16 +806,16 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Err(SaveError::Connection));
+            .returning(move |_, _, _| Err(SaveError::Connection));
         // Usecase Initialization
         let usecase = <Complete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
 
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution errpr
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -549,7 +825,7 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result = Complete::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_admin));
@@ -561,7 +837,7 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result = Complete::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
@@ -572,10 +848,20 @@ mod tests {
         let req = Request {
             id: signup_id,
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result =
             Complete::new(Arc::new(MockDependencyProvider::default())).authorize(&req, None);
         assert!(result.is_ok());
     }
+    #[rstest]
+    fn test_request_and_record_redact_password(signup_id: SignupId, user_record: UserRecord) {
+        let req = Request {
+            id: signup_id,
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        assert!(!format!("{req:?}").contains(TEST_PASSWORD));
+        assert!(!format!("{user_record:?}").contains(TEST_PASSWORD));
+    }
 }
@@ -6,13 +6,17 @@ use crate::{
             signup_process::{GetError, Repo, SaveError},
             Database,
         },
-        DatabaseProvider,
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::signup_process::{
-    EmailVerified, Failed, Id, SignupProcess, SignupStateEnum, VerificationEmailSent,
+use ca_domain::{
+    entity::signup_process::{
+        EmailVerified, Failed, Id, SignupProcess, SignupStateEnum, VerificationEmailSent,
+    },
+    value_object::Permission,
 };
 
 use serde::{Deserialize, Serialize};
@@ -37,14 +41,19 @@ pub enum Error {
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
 }
 
 impl From<SaveError> for Error {
     fn from(err: SaveError) -> Self {
         match err {
             SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
         }
     }
 }
@@ -54,6 +63,7 @@ impl From<(GetError, Id)> for Error {
         match err {
             GetError::NotFound => Self::NotFound(id),
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
         }
     }
@@ -61,13 +71,19 @@ impl From<(GetError, Id)> for Error {
 #[async_trait::async_trait]
 impl<D> Usecase<D> for Delete<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    const NAME: &'static str = "signup_process.delete";
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("SignupProcess scheduled for deletion: {:?}", req);
+        let now = self.dependency_provider.clock().now();
         let record = self
             .dependency_provider
             .database()
@@ -75,6 +91,7 @@ where
             .get_latest_state(None, req.id)
             .await
             .map_err(|err| (err, req.id))?;
+        let seq = record.seq;
         let process = match &record.state {
             SignupStateEnum::Failed {
                 previous_state,
@@ -83,12 +100,12 @@ where
                 SignupStateEnum::VerificationEmailSent { .. } => {
                     SignupProcess::<Failed<VerificationEmailSent>>::try_from(record)
                         .map_err(|_| (GetError::IncorrectState, req.id))?
-                        .delete()
+                        .delete(now)
                 }
                 SignupStateEnum::EmailVerified { .. } => {
                     SignupProcess::<Failed<EmailVerified>>::try_from(record)
                         .map_err(|_| (GetError::IncorrectState, req.id))?
-                        .delete()
+                        .delete(now)
                 }
                 _ => return Err((GetError::IncorrectState, req.id).into()),
             },
@@ -98,7 +115,7 @@ where
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(None, process.into())
+            .save_latest_state(None, process.into(), Some(seq))
             .await?;
         Ok(Self::Response { id: req.id })
     }
@@ -107,6 +124,12 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
 }
 
 #[cfg(test)]
@@ -139,7 +162,7 @@ mod tests {
                 .try_into()
                 .unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.delete().into();
+        let record_to_save = process.delete(chrono::Utc::now()).into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -154,15 +177,15 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <Delete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -190,7 +213,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert GetError::Connection is converted to Error::Repo
@@ -218,7 +241,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert GetError::NotFound is converted to Error::NotFound wiht the correct id
@@ -247,7 +270,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         // Assert GetError::IncorrectState is converted to Error::IncorrectState with the correct id
@@ -276,7 +299,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         // Assert GetError::IncorrectState is converted to Error::IncorrectState with the correct id
@@ -296,7 +319,7 @@ mod tests {
                 .try_into()
                 .unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.delete().into();
+        let record_to_save = process.delete(chrono::Utc::now()).into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -311,15 +334,15 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Err(SaveError::Connection));
+            .returning(move |_, _, _| Err(SaveError::Connection));
         // Usecase Initialization
         let usecase = <Delete<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert SaveError::Connection is converted to Error::Repo
@@ -339,13 +362,13 @@ mod tests {
         let result = Delete::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_none_fail(signup_id: SignupId) {
         let req = super::Request { id: signup_id };
         let result = Delete::new(Arc::new(MockDependencyProvider::default())).authorize(&req, None);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
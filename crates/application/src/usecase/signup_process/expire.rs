@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        service::{clock::Clock, job_metrics::Metrics},
+        ClockProvider, DatabaseProvider, JobMetricsProvider, SignupPolicyProvider,
+    },
+    job::expire_signup_processes::{Error as ExpireError, ExpireSignupProcesses},
+    usecase::{request_context::RequestContext, Usecase},
+};
+
+use ca_domain::value_object::Permission;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request;
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub verification_timed_out: u64,
+    pub completion_timed_out: u64,
+    pub conflicts: u64,
+    /// Totals including the sweeps of the background job
+    pub totals: Metrics,
+}
+
+/// Fail signup processes past their deadline right away
+pub struct Expire<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("{0}")]
+    Expire(#[from] ExpireError),
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for Expire<D>
+where
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + JobMetricsProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "signup_process.expire";
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Expire stale signup processes: {:?}", req);
+        let now = self.dependency_provider.clock().now();
+        let job = ExpireSignupProcesses::new(self.dependency_provider.clone());
+        let report = job.sweep(now).await?;
+        Ok(Self::Response {
+            verification_timed_out: report.verification_timed_out,
+            completion_timed_out: report.completion_timed_out,
+            conflicts: report.conflicts,
+            totals: job.metrics(),
+        })
+    }
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::signup_process::GetError, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_expire_success(mut dependency_provider: MockDependencyProvider) {
+        // fixtures
+        let metrics = dependency_provider.expire_signup_metrics.clone();
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(2)
+            .returning(|_, _| Ok(vec![]));
+        // Usecase Initialization
+        let usecase = <Expire<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(Request, &RequestContext::default()).await;
+        // Assert execution success -- the run shows up in the shared totals
+        let response = result.unwrap();
+        assert_eq!(response.verification_timed_out, 0);
+        assert_eq!(response.totals.runs, 1);
+        assert_eq!(metrics.get(), response.totals);
+    }
+    #[rstest]
+    async fn test_expire_fail_repo(mut dependency_provider: MockDependencyProvider) {
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(1)
+            .returning(|_, _| Err(GetError::Connection));
+        // Usecase Initialization
+        let usecase = <Expire<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(Request, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Expire(ExpireError::Repo));
+    }
+    #[rstest]
+    fn test_authorize_admin_zero_success(auth_context_admin: AuthContext) {
+        let result = Expire::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_user_fail(auth_context_user: AuthContext) {
+        let result = Expire::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request, Some(auth_context_user));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+}
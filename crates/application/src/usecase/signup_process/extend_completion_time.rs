@@ -6,12 +6,16 @@ use crate::{
             signup_process::{GetError, Repo, SaveError},
             Database,
         },
-        DatabaseProvider,
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider, SignupPolicyProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::signup_process::{EmailVerified, Failed, Id, SignupProcess};
+use ca_domain::{
+    entity::signup_process::{EmailVerified, Failed, Id, SignupProcess},
+    value_object::Permission,
+};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -35,14 +39,21 @@ pub enum Error {
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
+    #[error("SignupProcess {0} was extended too often")]
+    TooManyExtensions(Id),
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
 }
 
 impl From<SaveError> for Error {
     fn from(err: SaveError) -> Self {
         match err {
             SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
         }
     }
 }
@@ -52,6 +63,7 @@ impl From<(GetError, Id)> for Error {
         match err {
             GetError::NotFound => Self::NotFound(id),
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
         }
     }
@@ -59,14 +71,20 @@ impl From<(GetError, Id)> for Error {
 #[async_trait::async_trait]
 impl<D> Usecase<D> for ExtendCompletionTime<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + SignupPolicyProvider + ClockProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "signup_process.extend_completion_time";
 
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("SignupProcess Completion extended: {:?}", req);
+        let now = self.dependency_provider.clock().now();
         let record = self
             .dependency_provider
             .database()
@@ -74,13 +92,23 @@ where
             .get_latest_state(None, req.id)
             .await
             .map_err(|err| (err, req.id))?;
+        let seq = record.seq;
+        // check if the process is in the right state
         let process: SignupProcess<Failed<EmailVerified>> =
             record.try_into().map_err(|err| (err, req.id))?;
-        let process = process.recover();
+        // only a timed out window is extended
+        if !process.state().error.is_timeout() {
+            return Err(Error::IncorrectState(req.id));
+        }
+        let policy = self.dependency_provider.signup_policy();
+        if process.state().previous_state.extensions >= policy.max_extensions {
+            return Err(Error::TooManyExtensions(req.id));
+        }
+        let process = process.extend(now);
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(None, process.into())
+            .save_latest_state(None, process.into(), Some(seq))
             .await?;
         Ok(Self::Response { id: req.id })
     }
@@ -89,6 +117,12 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
 }
 
 #[cfg(test)]
@@ -101,12 +135,36 @@ mod tests {
         },
         usecase::tests::fixtures::*,
     };
-    use ca_domain::entity::{
-        auth_context::{AuthContext, AuthError},
-        signup_process::Id as SignupId,
+    use ca_domain::{
+        entity::{
+            auth_context::{AuthContext, AuthError},
+            signup_process::{Error as SignupError, Id as SignupId, SignupStateEnum},
+            user::Email,
+        },
+        value_object::SignupPolicy,
     };
     use rstest::*;
 
+    fn record(
+        signup_id: SignupId,
+        email: Email,
+        error: SignupError,
+        extensions: u32,
+    ) -> SignupProcessRepoRecord {
+        SignupProcessRepoRecord {
+            id: signup_id,
+            seq: 3,
+            state: SignupStateEnum::Failed {
+                previous_state: std::sync::Arc::new(SignupStateEnum::EmailVerified {
+                    email,
+                    extensions,
+                }),
+                error,
+            },
+            entered_at: chrono::Utc::now(),
+        }
+    }
+
     #[rstest]
     async fn test_extend_completion_time_success(
         mut dependency_provider: MockDependencyProvider,
@@ -121,7 +179,7 @@ mod tests {
                 .try_into()
                 .unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.recover().into();
+        let record_to_save = process.extend(chrono::Utc::now()).into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -136,15 +194,15 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <ExtendCompletionTime<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -172,7 +230,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert error GetError::Connection is converted to Error::Repo
@@ -200,7 +258,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert error GetError::NotFound is converted to Error::NotFound
@@ -230,7 +288,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert error IncorrectState is returned
@@ -249,7 +307,7 @@ mod tests {
                 .clone()
                 .try_into()
                 .unwrap();
-        let record_to_save = process.recover().into();
+        let record_to_save = process.extend(chrono::Utc::now()).into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -264,22 +322,87 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
             // returns a connection error
-            .returning(move |_, _| Err(SaveError::Connection));
+            .returning(move |_, _, _| Err(SaveError::Connection));
         // Usecase Initialization
         let usecase = <ExtendCompletionTime<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         // Assert error SaveError::Connection is converted to Error::Repo
         assert_eq!(result.unwrap_err(), Error::Repo);
     }
     #[rstest]
+    async fn test_extend_completion_time_fail_too_many_extensions(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- the process timed out before and was extended once
+        let req = Request { id: signup_id };
+        dependency_provider.signup_policy = SignupPolicy::default().with_max_extensions(1);
+        let record = record(signup_id, email, SignupError::CompletionTimedOut, 1);
+        // Mock setup -- nothing is saved
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        // Usecase Initialization
+        let usecase = <ExtendCompletionTime<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::TooManyExtensions(signup_id));
+    }
+    #[rstest]
+    async fn test_extend_completion_time_counts_extension(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- one extension is left
+        let req = Request { id: signup_id };
+        dependency_provider.signup_policy = SignupPolicy::default().with_max_extensions(2);
+        let record = record(signup_id, email, SignupError::CompletionTimedOut, 1);
+        // Mock setup -- the saved state carries the second extension
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(|_, actual_record, expected_seq| {
+                *expected_seq == Some(3)
+                    && matches!(
+                        actual_record.state,
+                        SignupStateEnum::EmailVerified { extensions: 2, .. }
+                    )
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <ExtendCompletionTime<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
     fn test_authorize_admin_zero_success(signup_id: SignupId, auth_context_admin: AuthContext) {
         let req = super::Request { id: signup_id };
         let result = ExtendCompletionTime::new(Arc::new(MockDependencyProvider::default()))
@@ -293,7 +416,7 @@ mod tests {
         let result = ExtendCompletionTime::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_none_fail(signup_id: SignupId) {
@@ -301,6 +424,6 @@ mod tests {
         let result = ExtendCompletionTime::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, None);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
@@ -4,15 +4,19 @@ use crate::{
     gateway::{
         database::{
             signup_process::{GetError, Repo, SaveError},
-            token::{ExtendError, Repo as TokenRepo},
+            token::{ExtendError, Purpose, Repo as TokenRepo, Scope},
             Database,
         },
-        DatabaseProvider,
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider, SignupPolicyProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::signup_process::{Failed, Id, SignupProcess, VerificationEmailSent};
+use ca_domain::{
+    entity::signup_process::{Failed, Id, SignupProcess, VerificationEmailSent},
+    value_object::Permission,
+};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -36,8 +40,14 @@ pub enum Error {
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
+    #[error("SignupProcess {0} was extended too often")]
+    TooManyExtensions(Id),
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
     #[error("Token Extension Error {0}")]
     TokenRepoError(#[from] ExtendError),
 }
@@ -46,6 +56,7 @@ impl From<SaveError> for Error {
     fn from(err: SaveError) -> Self {
         match err {
             SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
         }
     }
 }
@@ -54,6 +65,7 @@ impl From<(GetError, Id)> for Error {
     fn from((err, id): (GetError, Id)) -> Self {
         match err {
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
             GetError::NotFound => Self::NotFound(id),
         }
@@ -62,13 +74,15 @@ impl From<(GetError, Id)> for Error {
 #[async_trait::async_trait]
 impl<D> Usecase<D> for ExtendVerificationTime<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + SignupPolicyProvider + ClockProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
-    async fn exec(&self, req: Request) -> Result<Response, Error> {
+    const NAME: &'static str = "signup_process.extend_verification_time";
+    async fn exec(&self, req: Request, _ctx: &RequestContext) -> Result<Response, Error> {
         log::debug!("SignupProcess Verification extended: {:?}", req);
+        let now = self.dependency_provider.clock().now();
         let record = self
             .dependency_provider
             .database()
@@ -76,20 +90,28 @@ where
             .get_latest_state(None, req.id)
             .await
             .map_err(|err| (err, req.id))?;
+        let seq = record.seq;
         // check if the process is in the right state
         let process: SignupProcess<Failed<VerificationEmailSent>> =
             record.try_into().map_err(|err| (err, req.id))?;
-        // update token
-        let process = process.recover();
+        // a lockout is lifted by waiting, only a timed out window is extended
+        if !process.state().error.is_timeout() {
+            return Err(Error::IncorrectState(req.id));
+        }
+        let policy = self.dependency_provider.signup_policy();
+        if process.state().previous_state.extensions >= policy.max_extensions {
+            return Err(Error::TooManyExtensions(req.id));
+        }
+        let process = process.extend(now);
         self.dependency_provider
             .database()
             .token_repo()
-            .extend(None, process.state().email.as_ref())
+            .extend(None, &Scope::new(Purpose::Signup, req.id), now)
             .await?;
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(None, process.into())
+            .save_latest_state(None, process.into(), Some(seq))
             .await?;
         Ok(Self::Response { id: req.id })
     }
@@ -98,6 +120,12 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
 }
 
 #[cfg(test)]
@@ -110,12 +138,38 @@ mod tests {
         },
         usecase::tests::fixtures::*,
     };
-    use ca_domain::entity::{
-        auth_context::{AuthContext, AuthError},
-        signup_process::Id as SignupId,
+    use ca_domain::{
+        entity::{
+            auth_context::{AuthContext, AuthError},
+            signup_process::{Error as SignupError, Id as SignupId, SignupStateEnum},
+            user::Email,
+        },
+        value_object::SignupPolicy,
     };
     use rstest::*;
 
+    fn record(
+        signup_id: SignupId,
+        email: Email,
+        error: SignupError,
+        extensions: u32,
+    ) -> SignupProcessRepoRecord {
+        SignupProcessRepoRecord {
+            id: signup_id,
+            seq: 2,
+            state: SignupStateEnum::Failed {
+                previous_state: std::sync::Arc::new(SignupStateEnum::VerificationEmailSent {
+                    email,
+                    sent_at: chrono::Utc::now(),
+                    resends: 0,
+                    extensions,
+                }),
+                error,
+            },
+            entered_at: chrono::Utc::now(),
+        }
+    }
+
     #[rstest]
     async fn test_extend_verification_time_success(
         mut dependency_provider: MockDependencyProvider,
@@ -129,7 +183,7 @@ mod tests {
                 .clone()
                 .try_into()
                 .unwrap();
-        let record_to_save = process.recover().into();
+        let record_to_save = process.extend(chrono::Utc::now()).into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -144,22 +198,22 @@ mod tests {
             .db
             .token_repo
             .expect_extend()
-            .withf(move |_, actual_email| actual_email == TEST_EMAIL)
+            .withf(move |_, scope, _| scope == &Scope::new(Purpose::Signup, signup_id))
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         dependency_provider
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <ExtendVerificationTime<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -187,7 +241,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution has failed
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -214,7 +268,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution has failed
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound(signup_id));
@@ -242,7 +296,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::IncorrectState(signup_id));
@@ -269,15 +323,15 @@ mod tests {
             .db
             .token_repo
             .expect_extend()
-            .withf(move |_, actual_email| actual_email == TEST_EMAIL)
+            .withf(move |_, scope, _| scope == &Scope::new(Purpose::Signup, signup_id))
             .times(1)
-            .returning(move |_, _| Err(ExtendError::Connection));
+            .returning(move |_, _, _| Err(ExtendError::Connection));
         // Usecase Initialization
         let usecase = <ExtendVerificationTime<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(
@@ -298,7 +352,7 @@ mod tests {
                 .clone()
                 .try_into()
                 .unwrap();
-        let record_to_save = process.recover().into();
+        let record_to_save = process.extend(chrono::Utc::now()).into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -313,27 +367,123 @@ mod tests {
             .db
             .token_repo
             .expect_extend()
-            .withf(move |_, actual_email| actual_email == TEST_EMAIL)
+            .withf(move |_, scope, _| scope == &Scope::new(Purpose::Signup, signup_id))
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         dependency_provider
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Err(SaveError::Connection));
+            .returning(move |_, _, _| Err(SaveError::Connection));
         // Usecase Initialization
         let usecase = <ExtendVerificationTime<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
     }
     #[rstest]
+    async fn test_extend_verification_time_fail_too_many_extensions(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- the process timed out before and was extended once
+        let req = Request { id: signup_id };
+        dependency_provider.signup_policy = SignupPolicy::default().with_max_extensions(1);
+        let record = record(signup_id, email, SignupError::VerificationTimedOut, 1);
+        // Mock setup -- nothing is saved
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        // Usecase Initialization
+        let usecase = <ExtendVerificationTime<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::TooManyExtensions(signup_id));
+    }
+    #[rstest]
+    async fn test_extend_verification_time_fail_locked(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- a lockout is not a timeout and is no extension
+        let req = Request { id: signup_id };
+        let record = record(signup_id, email, SignupError::TooManyAttempts, 0);
+        // Mock setup -- nothing is extended or saved
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        // Usecase Initialization
+        let usecase = <ExtendVerificationTime<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::IncorrectState(signup_id));
+    }
+    #[rstest]
+    async fn test_extend_verification_time_counts_extension(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- one extension is left
+        let req = Request { id: signup_id };
+        dependency_provider.signup_policy = SignupPolicy::default().with_max_extensions(2);
+        let record = record(signup_id, email, SignupError::TokenExpired, 1);
+        // Mock setup -- the saved state carries the second extension
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_extend()
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(|_, actual_record, expected_seq| {
+                *expected_seq == Some(2)
+                    && matches!(
+                        actual_record.state,
+                        SignupStateEnum::VerificationEmailSent { extensions: 2, .. }
+                    )
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <ExtendVerificationTime<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
     fn test_authorize_admin_zero_success(signup_id: SignupId, auth_context_admin: AuthContext) {
         let req = super::Request { id: signup_id };
         let result = ExtendVerificationTime::new(Arc::new(MockDependencyProvider::default()))
@@ -347,7 +497,7 @@ mod tests {
         let result = ExtendVerificationTime::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_none_fail(signup_id: SignupId) {
@@ -355,6 +505,6 @@ mod tests {
         let result = ExtendVerificationTime::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, None);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
@@ -8,10 +8,10 @@ use crate::{
         },
         DatabaseProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::signup_process::Id;
+use ca_domain::{entity::signup_process::Id, value_object::Permission};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -36,6 +36,8 @@ pub enum Error {
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
     #[error("{}", GetError::Connection)]
     Repo,
 }
@@ -45,6 +47,7 @@ impl From<(GetError, Id)> for Error {
         match err {
             GetError::NotFound => Self::NotFound(id),
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
         }
     }
@@ -57,7 +60,8 @@ where
     type Request = Request;
     type Response = Response;
     type Error = Error;
-    async fn exec(&self, req: Request) -> Result<Response, Error> {
+    const NAME: &'static str = "signup_process.get_state_chain";
+    async fn exec(&self, req: Request, _ctx: &RequestContext) -> Result<Response, Error> {
         log::debug!("Get signup process state chain");
         let state_chain = self
             .dependency_provider
@@ -73,6 +77,12 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_READ)]
+    }
 }
 
 #[cfg(test)]
@@ -116,7 +126,7 @@ mod tests {
                 Arc::new(dependency_provider),
             );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let state_chain = result.unwrap().state_chain;
@@ -145,12 +155,40 @@ mod tests {
                 Arc::new(dependency_provider),
             );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo,);
     }
     #[rstest]
+    async fn test_get_state_chain_fail_corrupted(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_state_chain()
+            // makes sure the correct id is used
+            .withf(move |_, actual_id| actual_id == &signup_id)
+            .times(1)
+            // returns the record with the correct state
+            .returning(move |_, _| Err(GetError::Corrupted));
+        // Usecase Initialization
+        let usecase =
+            <GetStateChain<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Corrupted(signup_id));
+    }
+    #[rstest]
     async fn test_get_state_chain_fail_not_found(
         mut dependency_provider: MockDependencyProvider,
         signup_id: SignupId,
@@ -173,7 +211,7 @@ mod tests {
                 Arc::new(dependency_provider),
             );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound(signup_id),);
@@ -191,7 +229,7 @@ mod tests {
         let result = GetStateChain::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_none_fail(signup_id: SignupId) {
@@ -199,6 +237,6 @@ mod tests {
         let result =
             GetStateChain::new(Arc::new(MockDependencyProvider::default())).authorize(&req, None);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
@@ -4,25 +4,27 @@ use crate::{
     gateway::{
         database::{
             identifier::{NewId, NewIdError},
-            signup_process::{Repo, SaveError},
+            signup_process::{GetError, Repo, SaveError},
+            user::{GetError as UserGetError, Repo as UserRepo, SaveError as UserSaveError},
             Database,
         },
-        DatabaseProvider,
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider, SignupPolicyProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
+
 use ca_domain::entity::{
     auth_strategy::AuthStrategy,
     signup_process::{Id, SignupProcess},
     user::Email,
 };
+use ca_domain::value_object::ValidationError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize)]
 pub struct Request {
-    #[validate(email)]
     pub email: String,
 }
 
@@ -38,35 +40,74 @@ pub struct Initialize<D> {
 pub enum Error {
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
     #[error("{}", NewIdError)]
     NewId,
+    #[error("{}", UserSaveError::EmailTaken)]
+    EmailAlreadyRegistered,
     #[error(transparent)]
-    EmailInvalidity(#[from] validator::ValidationErrors),
+    EmailInvalidity(#[from] ValidationError),
 }
 
 impl From<SaveError> for Error {
     fn from(e: SaveError) -> Self {
         match e {
             SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
         }
     }
 }
+
+impl From<GetError> for Error {
+    fn from(_: GetError) -> Self {
+        Self::Repo
+    }
+}
+
+impl From<UserGetError> for Error {
+    fn from(_: UserGetError) -> Self {
+        Self::Repo
+    }
+}
 #[async_trait::async_trait]
 impl<D> Usecase<D> for Initialize<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + SignupPolicyProvider + ClockProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
-    /// Create a new user with the given name.
+    const NAME: &'static str = "signup_process.initialize";
+    /// Starts signing up the given email.
+    ///
+    /// An email that already belongs to a user is rejected. An email that
+    /// is already being signed up gets the id of that process back instead
+    /// of a second process.
     /// TODO: add transaction, outbox pattern to send email.
-    /// when the user is created, send an email to the user.
-    /// with generated token.
-    async fn exec(&self, req: Request) -> Result<Response, Error> {
+    async fn exec(&self, req: Request, _ctx: &RequestContext) -> Result<Response, Error> {
         log::debug!("SignupProcess Initialized: {:?}", req);
-        // validate email
-        req.validate()?;
+        let now = self.dependency_provider.clock().now();
+        let email = Email::parse(&req.email)?;
+        if let Some(record) = self
+            .dependency_provider
+            .database()
+            .signup_process_repo()
+            .get_active_by_email(None, &email)
+            .await?
+        {
+            log::debug!("SignupProcess {} already in flight", record.id);
+            return Ok(Response { id: record.id });
+        }
+        if self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .exists_by_email(None, &email)
+            .await?
+        {
+            return Err(Error::EmailAlreadyRegistered);
+        }
         let id = self
             .dependency_provider
             .database()
@@ -74,12 +115,11 @@ where
             .new_id()
             .await
             .map_err(|_| Error::NewId)?;
-        let email = Email::new(&req.email);
-        let signup_process = SignupProcess::new(id, email);
+        let signup_process = SignupProcess::new(id, email, now);
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(None, signup_process.into())
+            .save_latest_state(None, signup_process.into(), None)
             .await?;
         Ok(Response { id })
     }
@@ -109,8 +149,9 @@ mod tests {
         let id = Id::new(uuid::Uuid::new_v4());
         let record = SignupProcessRepoRecord {
             id,
+            seq: 0,
             state: SignupStateEnum::Initialized {
-                email: Email::new(TEST_EMAIL),
+                email: Email::new_unchecked(TEST_EMAIL),
             },
             entered_at: chrono::Utc::now(),
         };
@@ -118,7 +159,20 @@ mod tests {
             email: TEST_EMAIL.to_string(),
         };
         // Mock setup -- predicates and return values
-
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .withf(|_, email| email.as_ref() == TEST_EMAIL)
+            .times(1)
+            .returning(|_, _| Ok(None));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .withf(|_, email| email.as_ref() == TEST_EMAIL)
+            .times(1)
+            .returning(|_, _| Ok(false));
         dependency_provider
             .db
             .signup_id_gen
@@ -128,15 +182,17 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record)
+            .withf(move |_, actual_record, expected_seq| {
+                actual_record == &record && expected_seq.is_none()
+            })
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <Initialize<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution is successful
         assert!(result.is_ok());
         // Assert return id equals the mock returned id.
@@ -153,16 +209,26 @@ mod tests {
         let req = super::Request {
             email: "ttt".to_string(),
         };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err().to_string(),
-            "email: Validation error: email [{\"value\": String(\"ttt\")}]"
+            "email is malformed: missing @"
         );
     }
 
     #[rstest]
     async fn test_initialize_fails_signup_id_gen(mut dependency_provider: MockDependencyProvider) {
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .returning(|_, _| Ok(None));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .returning(|_, _| Ok(false));
         dependency_provider
             .db
             .signup_id_gen
@@ -174,7 +240,7 @@ mod tests {
         let req = super::Request {
             email: TEST_EMAIL.to_string(),
         };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), super::Error::NewId);
     }
@@ -184,6 +250,16 @@ mod tests {
         mut dependency_provider: MockDependencyProvider,
         signup_id: Id,
     ) {
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .returning(|_, _| Ok(None));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .returning(|_, _| Ok(false));
         dependency_provider
             .db
             .signup_id_gen
@@ -193,18 +269,144 @@ mod tests {
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .returning(|_, _| Err(signup_process::SaveError::Connection));
+            .returning(|_, _, _| Err(signup_process::SaveError::Connection));
         let usecase = <Initialize<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         let req = super::Request {
             email: TEST_EMAIL.to_string(),
         };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), super::Error::Repo,);
     }
 
+    #[rstest]
+    async fn test_initialize_in_flight(
+        mut dependency_provider: MockDependencyProvider,
+        verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // Fixtures
+        let in_flight_id = verification_email_sent_record.id;
+        let req = super::Request {
+            email: TEST_EMAIL.to_string(),
+        };
+        // Mock setup -- no second process is started
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .times(1)
+            .returning(move |_, _| Ok(Some(verification_email_sent_record.clone())));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .never();
+        dependency_provider.db.signup_id_gen.expect_new_id().never();
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Initialize<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert the existing process is returned
+        assert_eq!(result.unwrap().id, in_flight_id);
+    }
+
+    #[rstest]
+    async fn test_initialize_registered_authenticated(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_admin: AuthContext,
+    ) {
+        let req = super::Request {
+            email: TEST_EMAIL.to_string(),
+        };
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .returning(|_, _| Ok(None));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .times(1)
+            .returning(|_, _| Ok(true));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Initialize<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let ctx = RequestContext::default().with_auth_context(Some(auth_context_admin));
+        let result = usecase.exec(req, &ctx).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), super::Error::EmailAlreadyRegistered);
+    }
+
+    #[rstest]
+    async fn test_initialize_registered_anonymous(mut dependency_provider: MockDependencyProvider) {
+        let req = super::Request {
+            email: TEST_EMAIL.to_string(),
+        };
+        // Mock setup -- no id is handed out
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .returning(|_, _| Ok(None));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .times(1)
+            .returning(|_, _| Ok(true));
+        dependency_provider.db.signup_id_gen.expect_new_id().never();
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <Initialize<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), super::Error::EmailAlreadyRegistered);
+    }
+
+    #[rstest]
+    async fn test_initialize_fails_get_active_by_email(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_active_by_email()
+            .returning(|_, _| Err(signup_process::GetError::Connection));
+        let usecase = <Initialize<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        let req = super::Request {
+            email: TEST_EMAIL.to_string(),
+        };
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        assert_eq!(result.unwrap_err(), super::Error::Repo);
+    }
+
     #[rstest]
     fn test_authorize_admin_zero(auth_context_admin: AuthContext) {
         let req = super::Request {
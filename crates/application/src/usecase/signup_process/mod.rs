@@ -1,8 +1,12 @@
 pub mod complete;
 pub mod delete;
+pub mod expire;
 pub mod extend_completion_time;
 pub mod extend_verification_time;
 pub mod get_state_chain;
 pub mod initialize;
+pub mod purge;
+pub mod recover;
+pub mod resend_verification_email;
 pub mod send_verification_email;
 pub mod verify_email;
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use chrono::Duration;
+
+use crate::{
+    gateway::{service::clock::Clock, ClockProvider, DatabaseProvider},
+    job::purge_signup_processes::{Error as PurgeError, PurgeSignupProcesses},
+    usecase::{request_context::RequestContext, Usecase},
+};
+
+use ca_domain::value_object::Permission;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// Days a process stays deleted before it is purged, defaults to
+    /// [`DEFAULT_GRACE_PERIOD_DAYS`](crate::job::purge_signup_processes::DEFAULT_GRACE_PERIOD_DAYS)
+    pub grace_period_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub purged: u64,
+    pub tombstones: u64,
+}
+
+/// Hard-delete signup processes scheduled for deletion
+pub struct Purge<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("{0}")]
+    Purge(#[from] PurgeError),
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for Purge<D>
+where
+    D: DatabaseProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "signup_process.purge";
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Purge deleted signup processes: {:?}", req);
+        let now = self.dependency_provider.clock().now();
+        let mut job = PurgeSignupProcesses::new(self.dependency_provider.clone());
+        if let Some(days) = req.grace_period_days {
+            job = job.with_grace_period(Duration::days(days.into()));
+        }
+        let report = job.purge(now).await?;
+        Ok(Self::Response {
+            purged: report.purged,
+            tombstones: report.tombstones,
+        })
+    }
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::signup_process::GetError, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_purge_success(mut dependency_provider: MockDependencyProvider) {
+        // fixtures
+        let req = Request {
+            grace_period_days: Some(0),
+        };
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_count_tombstones()
+            .times(1)
+            .returning(|_| Ok(3));
+        // Usecase Initialization
+        let usecase = <Purge<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        let response = result.unwrap();
+        assert_eq!(response.purged, 0);
+        assert_eq!(response.tombstones, 3);
+    }
+    #[rstest]
+    async fn test_purge_fail_repo(mut dependency_provider: MockDependencyProvider) {
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_by_state()
+            .times(1)
+            .returning(|_, _| Err(GetError::Connection));
+        // Usecase Initialization
+        let usecase = <Purge<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase
+            .exec(
+                Request {
+                    grace_period_days: None,
+                },
+                &RequestContext::default(),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Purge(PurgeError::Repo));
+    }
+    #[rstest]
+    fn test_authorize_admin_zero_success(auth_context_admin: AuthContext) {
+        let req = super::Request {
+            grace_period_days: None,
+        };
+        let result = Purge::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_user_fail(auth_context_user: AuthContext) {
+        let req = super::Request {
+            grace_period_days: None,
+        };
+        let result = Purge::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_user));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+}
@@ -0,0 +1,558 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            signup_process::{GetError, Record, Repo, SaveError},
+            token::{AttemptError, InvalidateError, IssueError, Purpose, Repo as TokenRepo, Scope},
+            Database,
+        },
+        service::clock::Clock,
+        service::email::{EmailAddress, EmailServiceError, EmailVerificationService},
+        service::token::TokenGenerator,
+        ClockProvider, DatabaseProvider, EmailVerificationServiceProvider, TokenGeneratorProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+
+use ca_domain::{
+    entity::signup_process::{
+        EmailVerified, Failed, Id, Initialized, SignupProcess, SignupStateEnum,
+        VerificationEmailSent,
+    },
+    value_object::Permission,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub id: Id,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub id: Id,
+}
+
+/// Return a failed signup process to the state it failed in
+pub struct RecoverSignupProcess<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("SignupProcess {0} not found")]
+    NotFound(Id),
+    #[error("SignupProcess {0} in incorrect state")]
+    IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
+    #[error("{}", SaveError::Connection)]
+    Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
+    #[error("Token Repo error: {0}")]
+    TokenGen(#[from] IssueError),
+    #[error("Token Repo error: {0}")]
+    TokenInvalidate(#[from] InvalidateError),
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+    #[error("Email Service error: {0}")]
+    EmailServiceError(#[from] EmailServiceError),
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
+        }
+    }
+}
+
+impl From<(GetError, Id)> for Error {
+    fn from((err, id): (GetError, Id)) -> Self {
+        match err {
+            GetError::NotFound => Self::NotFound(id),
+            GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for RecoverSignupProcess<D>
+where
+    D: DatabaseProvider + EmailVerificationServiceProvider + ClockProvider + TokenGeneratorProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "signup_process.recover";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("SignupProcess recovery: {:?}", req);
+        let now = self.dependency_provider.clock().now();
+        let mut transaction = self
+            .dependency_provider
+            .database()
+            .begin_transaction()
+            .await;
+        let record = self
+            .dependency_provider
+            .database()
+            .signup_process_repo()
+            .get_latest_state(Some(&mut transaction), req.id)
+            .await
+            .map_err(|err| (err, req.id))?;
+        let seq = record.seq;
+        // a fresh token to email once the recovery is committed
+        let mut verification = None;
+        let record: Record = match &record.state {
+            SignupStateEnum::Failed {
+                previous_state,
+                error: _,
+            } => match **previous_state {
+                SignupStateEnum::Initialized { .. } => {
+                    SignupProcess::<Failed<Initialized>>::try_from(record)
+                        .map_err(|_| (GetError::IncorrectState, req.id))?
+                        .recover(now)
+                        .into()
+                }
+                SignupStateEnum::VerificationEmailSent { .. } => {
+                    let process = SignupProcess::<Failed<VerificationEmailSent>>::try_from(record)
+                        .map_err(|_| (GetError::IncorrectState, req.id))?
+                        .recover(now);
+                    // the old token may have expired or bounced, send a fresh
+                    // one and lift a lock for too many attempts
+                    let scope = Scope::new(Purpose::Signup, req.id);
+                    self.dependency_provider
+                        .database()
+                        .token_repo()
+                        .invalidate(Some(&mut transaction), &scope)
+                        .await?;
+                    self.dependency_provider
+                        .database()
+                        .token_repo()
+                        .clear_attempts(Some(&mut transaction), &scope)
+                        .await?;
+                    let token = self.dependency_provider.token_generator().generate();
+                    self.dependency_provider
+                        .database()
+                        .token_repo()
+                        .issue(
+                            Some(&mut transaction),
+                            &scope,
+                            &process.state().email.canonical(),
+                            &token,
+                            now,
+                        )
+                        .await?;
+                    verification = Some((process.state().email.clone(), token));
+                    process.into()
+                }
+                SignupStateEnum::EmailVerified { .. } => {
+                    SignupProcess::<Failed<EmailVerified>>::try_from(record)
+                        .map_err(|_| (GetError::IncorrectState, req.id))?
+                        .recover(now)
+                        .into()
+                }
+                _ => return Err((GetError::IncorrectState, req.id).into()),
+            },
+            _ => return Err((GetError::IncorrectState, req.id).into()),
+        };
+        self.dependency_provider
+            .database()
+            .signup_process_repo()
+            .save_latest_state(Some(&mut transaction), record, Some(seq))
+            .await?;
+        self.dependency_provider
+            .database()
+            .commit_transaction(transaction)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        // only a token that was stored is worth sending
+        if let Some((email, token)) = verification {
+            self.dependency_provider
+                .email_verification_service()
+                .send_verification_email(EmailAddress::new(email.as_ref()), req.id, token.as_str())
+                .await?;
+        }
+        Ok(Self::Response { id: req.id })
+    }
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::signup_process::Record as SignupProcessRepoRecord,
+            mock::MockDependencyProvider,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::{
+        auth_context::{AuthContext, AuthError},
+        signup_process::Id as SignupId,
+    };
+    use rstest::*;
+
+    #[rstest]
+    async fn test_recover_email_verified_success(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        failed_verification_email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        let seq = failed_verification_email_verified_record.seq;
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            // makes sure the correct id is used
+            .withf(move |_, actual_id| actual_id == &signup_id)
+            .times(1)
+            .returning(move |_, _| Ok(failed_verification_email_verified_record.clone()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            // back in the state it failed in, no token is needed
+            .withf(move |_, actual_record, expected_seq| {
+                matches!(actual_record.state, SignupStateEnum::EmailVerified { .. })
+                    && expected_seq == &Some(seq)
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().id, signup_id);
+    }
+    #[rstest]
+    async fn test_recover_initialized_success(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        failed_initialized_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(failed_initialized_record.clone()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            // the verification email can be sent again from here
+            .withf(move |_, actual_record, _| {
+                matches!(actual_record.state, SignupStateEnum::Initialized { .. })
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    async fn test_recover_verification_email_sent_reissues_token(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        failed_verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(failed_verification_email_sent_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .withf(move |_, scope| scope == &Scope::new(Purpose::Signup, signup_id))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .withf(move |_, scope, actual_email, actual_token, _| {
+                scope == &Scope::new(Purpose::Signup, signup_id)
+                    && actual_email == TEST_EMAIL
+                    && actual_token == TEST_TOKEN
+            })
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .email_verification_service
+            .expect_send_verification_email()
+            .withf(move |actual_email, actual_id, actual_token| {
+                actual_email.as_str() == TEST_EMAIL
+                    && actual_id == &signup_id
+                    && actual_token == TEST_TOKEN
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, _| {
+                matches!(
+                    actual_record.state,
+                    SignupStateEnum::VerificationEmailSent { .. }
+                )
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    async fn test_recover_fail_email_service(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        failed_verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- the recovery is saved before the email goes out
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(failed_verification_email_sent_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .email_verification_service
+            .expect_send_verification_email()
+            .times(1)
+            .returning(|_, _, _| Err(EmailServiceError::SendEmailFailed));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            Error::EmailServiceError(EmailServiceError::SendEmailFailed)
+        );
+    }
+    #[rstest]
+    async fn test_recover_fail_conflict_sends_nothing(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        failed_verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- the token of a recovery that was not saved is not sent
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(failed_verification_email_sent_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .times(1)
+            .returning(|_, _, _| Err(SaveError::Conflict));
+        dependency_provider
+            .email_verification_service
+            .expect_send_verification_email()
+            .never();
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Conflict);
+    }
+    #[rstest]
+    async fn test_recover_fail_not_failed(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(email_verified_record.clone()));
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- no save expected
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::IncorrectState(signup_id));
+    }
+    #[rstest]
+    async fn test_recover_fail_get_latest_state_not_found(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        // Usecase Initialization
+        let usecase = <RecoverSignupProcess<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::NotFound(signup_id));
+    }
+    #[rstest]
+    fn test_authorize_admin_zero_success(signup_id: SignupId, auth_context_admin: AuthContext) {
+        let req = super::Request { id: signup_id };
+        let result = RecoverSignupProcess::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_user_fail(signup_id: SignupId, auth_context_user: AuthContext) {
+        let req = super::Request { id: signup_id };
+        let result = RecoverSignupProcess::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_user));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+    #[rstest]
+    fn test_authorize_none_fail(signup_id: SignupId) {
+        let req = super::Request { id: signup_id };
+        let result = RecoverSignupProcess::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
+    }
+}
@@ -0,0 +1,449 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            signup_process::{GetError, Repo, SaveError},
+            token::{InvalidateError, IssueError, Purpose, Repo as TokenRepo, Scope},
+            Database,
+        },
+        service::clock::Clock,
+        service::email::{EmailAddress, EmailServiceError, EmailVerificationService},
+        service::token::TokenGenerator,
+        ClockProvider, DatabaseProvider, EmailVerificationServiceProvider, SignupPolicyProvider,
+        TokenGeneratorProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+
+use ca_domain::entity::{
+    auth_strategy::AuthStrategy,
+    signup_process::{Id, SignupProcess, VerificationEmailSent},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub id: Id,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub id: Id,
+}
+
+/// Send the verification email again with a fresh token
+pub struct ResendVerificationEmail<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("SignupProcess {0} not found")]
+    NotFound(Id),
+    #[error("SignupProcess {0} in incorrect state")]
+    IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
+    #[error("Verification email for SignupProcess {0} was sent too recently")]
+    Cooldown(Id),
+    #[error("Verification email for SignupProcess {0} was resent too often")]
+    TooManyResends(Id),
+    #[error("{}", SaveError::Connection)]
+    Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
+    #[error("Token Repo error: {0}")]
+    TokenGen(#[from] IssueError),
+    #[error("Token Repo error: {0}")]
+    TokenInvalidate(#[from] InvalidateError),
+    #[error("Email Service error: {0}")]
+    EmailServiceError(#[from] EmailServiceError),
+}
+
+impl From<(GetError, Id)> for Error {
+    fn from((err, id): (GetError, Id)) -> Self {
+        match err {
+            GetError::NotFound => Self::NotFound(id),
+            GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for ResendVerificationEmail<D>
+where
+    D: DatabaseProvider
+        + EmailVerificationServiceProvider
+        + SignupPolicyProvider
+        + ClockProvider
+        + TokenGeneratorProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "signup_process.resend_verification_email";
+
+    async fn exec(&self, req: Request, _ctx: &RequestContext) -> Result<Response, Error> {
+        log::debug!("SignupProcess ResendVerificationEmail ID: {:?}", req);
+        let now = self.dependency_provider.clock().now();
+        let mut transaction = self
+            .dependency_provider
+            .database()
+            .begin_transaction()
+            .await;
+        let record = self
+            .dependency_provider
+            .database()
+            .signup_process_repo()
+            .get_latest_state(Some(&mut transaction), req.id)
+            .await
+            .map_err(|err| (err, req.id))?;
+        let seq = record.seq;
+        let process: SignupProcess<VerificationEmailSent> =
+            record.try_into().map_err(|err| (err, req.id))?;
+        let policy = self.dependency_provider.signup_policy();
+        if process.state().resends >= policy.max_resends {
+            return Err(Error::TooManyResends(req.id));
+        }
+        if now - process.state().sent_at < policy.resend_cooldown {
+            return Err(Error::Cooldown(req.id));
+        }
+        // the old token must not verify the process anymore
+        let scope = Scope::new(Purpose::Signup, req.id);
+        self.dependency_provider
+            .database()
+            .token_repo()
+            .invalidate(Some(&mut transaction), &scope)
+            .await?;
+        let token = self.dependency_provider.token_generator().generate();
+        self.dependency_provider
+            .database()
+            .token_repo()
+            .issue(
+                Some(&mut transaction),
+                &scope,
+                &process.state().email.canonical(),
+                &token,
+                now,
+            )
+            .await?;
+        let process = process.resend_verification_email(now);
+        let email = process.state().email.clone();
+        self.dependency_provider
+            .database()
+            .signup_process_repo()
+            .save_latest_state(Some(&mut transaction), process.into(), Some(seq))
+            .await?;
+        self.dependency_provider
+            .database()
+            .commit_transaction(transaction)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        // only a token that was stored is worth sending
+        self.dependency_provider
+            .email_verification_service()
+            .send_verification_email(EmailAddress::new(email.as_ref()), req.id, token.as_str())
+            .await?;
+        Ok(Response { id: req.id })
+    }
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::database::signup_process::Record as SignupProcessRepoRecord;
+    use crate::gateway::mock::MockDependencyProvider;
+    use crate::usecase::tests::fixtures::*;
+    use ca_domain::{
+        entity::{
+            signup_process::{Id as SignupId, SignupStateEnum},
+            user::Email,
+        },
+        value_object::SignupPolicy,
+    };
+    use chrono::Duration;
+    use chrono::Utc;
+    use rstest::*;
+
+    /// Latest state with `resends` emails sent after the first one, the
+    /// last one `sent_ago` before now. The state was entered just now, as
+    /// after a lifted lockout, which must not restart the cooldown.
+    fn record(
+        signup_id: SignupId,
+        email: Email,
+        resends: u32,
+        sent_ago: Duration,
+    ) -> SignupProcessRepoRecord {
+        SignupProcessRepoRecord {
+            id: signup_id,
+            seq: 1,
+            state: SignupStateEnum::VerificationEmailSent {
+                email,
+                sent_at: Utc::now() - sent_ago,
+                resends,
+                extensions: 0,
+            },
+            entered_at: Utc::now(),
+        }
+    }
+
+    #[rstest]
+    async fn test_resend_verification_email_success(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        let record = record(signup_id, email, 0, Duration::minutes(5));
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .withf(move |_, actual_id| actual_id == &signup_id)
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            // makes sure the old token of the process is dropped
+            .withf(move |_, scope| scope == &Scope::new(Purpose::Signup, signup_id))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .withf(move |_, scope, actual_email, actual_token, _| {
+                scope == &Scope::new(Purpose::Signup, signup_id)
+                    && actual_email == TEST_EMAIL
+                    && actual_token == TEST_TOKEN
+            })
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .email_verification_service
+            .expect_send_verification_email()
+            // makes sure the fresh token is sent
+            .withf(move |actual_email, actual_id, actual_token| {
+                actual_email.as_str() == TEST_EMAIL
+                    && actual_id == &signup_id
+                    && actual_token == TEST_TOKEN
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            // stays in VerificationEmailSent, appended onto the loaded state
+            .withf(move |_, actual_record, expected_seq| {
+                matches!(
+                    actual_record.state,
+                    SignupStateEnum::VerificationEmailSent { resends: 1, .. }
+                ) && expected_seq == &Some(1)
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <ResendVerificationEmail<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().id, signup_id);
+    }
+    #[rstest]
+    async fn test_resend_verification_email_fail_save_sends_nothing(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        let record = record(signup_id, email, 0, Duration::minutes(5));
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .times(1)
+            .returning(|_, _, _| Err(SaveError::Conflict));
+        dependency_provider
+            .email_verification_service
+            .expect_send_verification_email()
+            // the token was rolled back, it must not be sent
+            .never();
+        // Usecase Initialization
+        let usecase = <ResendVerificationEmail<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Conflict);
+    }
+    #[rstest]
+    async fn test_resend_verification_email_fail_cooldown(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        let record = record(signup_id, email, 0, Duration::seconds(10));
+        // Mock setup -- no token or email calls expected
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        // Usecase Initialization
+        let usecase = <ResendVerificationEmail<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Cooldown(signup_id));
+    }
+    #[rstest]
+    async fn test_resend_verification_email_fail_too_many_resends(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- the policy allows a single resend
+        let req = Request { id: signup_id };
+        dependency_provider.signup_policy = SignupPolicy::default().with_max_resends(1);
+        let record = record(signup_id, email, 1, Duration::minutes(5));
+        // Mock setup -- no token or email calls expected
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        // Usecase Initialization
+        let usecase = <ResendVerificationEmail<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::TooManyResends(signup_id));
+    }
+    #[rstest]
+    async fn test_resend_verification_email_fail_incorrect_state(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        initialized_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup -- no token or email calls expected
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(initialized_record.clone()));
+        // Usecase Initialization
+        let usecase = <ResendVerificationEmail<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::IncorrectState(signup_id));
+    }
+    #[rstest]
+    async fn test_resend_verification_email_fail_not_found(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+    ) {
+        // fixtures
+        let req = Request { id: signup_id };
+        // Mock setup
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        // Usecase Initialization
+        let usecase = <ResendVerificationEmail<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::NotFound(signup_id));
+    }
+    #[rstest]
+    fn test_authorize_none(signup_id: SignupId) {
+        let result = ResendVerificationEmail::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request { id: signup_id }, None);
+        assert!(result.is_ok());
+    }
+}
@@ -4,17 +4,20 @@ use crate::{
     gateway::{
         database::{
             signup_process::{GetError, Repo, SaveError},
-            token::{GenError as TokenRepoError, Repo as TokenRepo},
+            token::{IssueError as TokenRepoError, Purpose, Repo as TokenRepo, Scope},
             Database,
         },
+        service::clock::Clock,
         service::email::{EmailAddress, EmailServiceError, EmailVerificationService},
-        DatabaseProvider, EmailVerificationServiceProvider,
+        service::token::TokenGenerator,
+        ClockProvider, DatabaseProvider, EmailVerificationServiceProvider, TokenGeneratorProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::signup_process::{
-    Error as SignupProcessError, Id, Initialized, SignupProcess,
+use ca_domain::{
+    entity::signup_process::{Error as SignupProcessError, Id, Initialized, SignupProcess},
+    value_object::Permission,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -38,8 +41,12 @@ pub enum Error {
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
     #[error("SignupProcess Repo error")]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
     #[error("Token Repo error: {0}")]
     TokenRepoError(#[from] TokenRepoError),
     #[error("Email Service error: {0}")]
@@ -51,27 +58,33 @@ impl From<(GetError, Id)> for Error {
         match err {
             GetError::NotFound => Self::NotFound(id),
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
         }
     }
 }
 
 impl From<SaveError> for Error {
-    fn from(_: SaveError) -> Self {
-        Self::Repo
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
+        }
     }
 }
 #[async_trait::async_trait]
 impl<D> Usecase<D> for SendVerificationEmail<D>
 where
-    D: DatabaseProvider + EmailVerificationServiceProvider,
+    D: DatabaseProvider + EmailVerificationServiceProvider + ClockProvider + TokenGeneratorProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "signup_process.send_verification_email";
 
-    async fn exec(&self, req: Request) -> Result<Response, Error> {
+    async fn exec(&self, req: Request, _ctx: &RequestContext) -> Result<Response, Error> {
         log::debug!("SignupProcess SendVerificationEmail ID: {:?}", req);
+        let now = self.dependency_provider.clock().now();
         let record = self
             .dependency_provider
             .database()
@@ -79,49 +92,55 @@ where
             .get_latest_state(None, req.id)
             .await
             .map_err(|err| (err, req.id))?;
+        let seq = record.seq;
         let process: SignupProcess<Initialized> = record.try_into().map_err(|err| (err, req.id))?;
-        let token = match self
+        let token = self.dependency_provider.token_generator().generate();
+        if let Err(err) = self
             .dependency_provider
             .database()
             .token_repo()
-            .gen(None, process.state().email.as_ref())
+            .issue(
+                None,
+                &Scope::new(Purpose::Signup, req.id),
+                &process.state().email.canonical(),
+                &token,
+                now,
+            )
             .await
         {
-            Ok(record) => record.token,
-            Err(err) => {
-                log::error!("Token Repo error: {:?}", err);
-                let process = process.fail(SignupProcessError::TokenGenrationFailed);
-                self.dependency_provider
-                    .database()
-                    .signup_process_repo()
-                    .save_latest_state(None, process.into())
-                    .await?;
-                return Err(err.into());
-            }
-        };
+            log::error!("Token Repo error: {:?}", err);
+            let process = process.fail(SignupProcessError::TokenGenrationFailed, now);
+            self.dependency_provider
+                .database()
+                .signup_process_repo()
+                .save_latest_state(None, process.into(), Some(seq))
+                .await?;
+            return Err(err.into());
+        }
         if let Err(err) = self
             .dependency_provider
             .email_verification_service()
             .send_verification_email(
                 EmailAddress::new(process.state().email.as_ref()),
+                req.id,
                 token.as_str(),
             )
             .await
         {
             log::error!("Email Service error: {:?}", err);
-            let process = process.fail(SignupProcessError::VerificationEmailSendError);
+            let process = process.fail(SignupProcessError::VerificationEmailSendError, now);
             self.dependency_provider
                 .database()
                 .signup_process_repo()
-                .save_latest_state(None, process.into())
+                .save_latest_state(None, process.into(), Some(seq))
                 .await?;
             return Err(err.into());
         }
-        let process = process.send_verification_email();
+        let process = process.send_verification_email(now);
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(None, process.into())
+            .save_latest_state(None, process.into(), Some(seq))
             .await?;
         Ok(Response { id: req.id })
     }
@@ -130,13 +149,19 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::SIGNUP_MANAGE)]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::gateway::database::signup_process::{self, Record as SignupProcessRepoRecord};
-    use crate::gateway::database::token::{GenError as TokenRepoError, Record as TokenRepoRecord};
+    use crate::gateway::database::token::IssueError as TokenRepoError;
     use crate::gateway::mock::MockDependencyProvider;
     use crate::usecase::tests::fixtures::*;
     use ca_domain::entity::auth_context::{AuthContext, AuthError};
@@ -149,12 +174,10 @@ mod tests {
     async fn test_send_verification_email_success(
         mut dependency_provider: MockDependencyProvider,
         initialized_record: SignupProcessRepoRecord,
-        token_repo_record: TokenRepoRecord,
     ) {
         // fixtures
         let process = SignupProcess::<Initialized>::try_from(initialized_record.clone()).unwrap();
-        let process = process.send_verification_email();
-        let token = token_repo_record.token.clone();
+        let process = process.send_verification_email(chrono::Utc::now());
         let record_to_save: SignupProcessRepoRecord = process.clone().into();
         let id = initialized_record.id;
         let req = super::Request {
@@ -170,40 +193,51 @@ mod tests {
             .times(1)
             // returns the record with the correct state
             .returning(move |_, _| Ok(initialized_record.clone()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
         dependency_provider
             .db
             .token_repo
-            .expect_gen()
-            // makes sure the correct email is used
-            .withf(move |_, actual_email| actual_email == TEST_EMAIL)
+            .expect_issue()
+            // makes sure the token is issued for this process and email
+            .withf(move |_, scope, actual_email, actual_token, _| {
+                scope == &Scope::new(Purpose::Signup, id)
+                    && actual_email == TEST_EMAIL
+                    && actual_token == TEST_TOKEN
+            })
             .times(1)
-            // returns test_token to simulate token generation success
-            .returning(move |_, _| Ok(token_repo_record.clone()));
+            // returns ok to simulate token storage success
+            .returning(|_, _, _, _, _| Ok(()));
         dependency_provider
             .email_verification_service
             .expect_send_verification_email()
-            // makes sure the correct email and token are used
-            .withf(move |actual_email, actual_token| {
-                actual_email.as_str() == TEST_EMAIL && actual_token == token.as_str()
+            // makes sure the correct email, process and token are used
+            .withf(move |actual_email, actual_id, actual_token| {
+                actual_email.as_str() == TEST_EMAIL
+                    && actual_id == &id
+                    && actual_token == TEST_TOKEN
             })
             .times(1)
             // returns ok to simulate email send success
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
         dependency_provider
             .db
             .signup_process_repo
             // all steps successful, so we save the process in the new state
             .expect_save_latest_state()
             // makes sure the correct process in failed state is saved
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <SendVerificationEmail<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -225,7 +259,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         let req = super::Request { id: signup_id };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), super::Error::Repo,);
     }
@@ -245,7 +279,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         let req = super::Request { id: signup_id };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), super::Error::NotFound(signup_id),);
     }
@@ -263,6 +297,7 @@ mod tests {
             .returning(move |_, _| {
                 Ok(SignupProcessRepoRecord {
                     id: signup_id,
+                    seq: 0,
                     state: SignupStateEnum::ForDeletion, // should be Initialized
                     entered_at: chrono::Utc::now(),
                 })
@@ -271,7 +306,7 @@ mod tests {
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         let req = super::Request { id: signup_id };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), super::Error::IncorrectState(signup_id),);
     }
@@ -283,7 +318,7 @@ mod tests {
         // fixtures
         let process = SignupProcess::<Initialized>::try_from(initialized_record.clone())
             .unwrap()
-            .fail(SignupProcessError::TokenGenrationFailed);
+            .fail(SignupProcessError::TokenGenrationFailed, chrono::Utc::now());
         let record_to_save: SignupProcessRepoRecord = process.clone().into();
         let req = super::Request {
             id: initialized_record.id,
@@ -298,30 +333,35 @@ mod tests {
             .times(1)
             // returns the record with the correct state
             .returning(move |_, _| Ok(initialized_record.clone()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
         dependency_provider
             .db
             .token_repo
-            .expect_gen()
+            .expect_issue()
             // makes sure the correct email is used
-            .withf(move |_, actual_email| actual_email == TEST_EMAIL)
+            .withf(move |_, _, actual_email, _, _| actual_email == TEST_EMAIL)
             .times(1)
-            // returns an error to simulate token generation failure
-            .returning(|_, _| Err(TokenRepoError::Connection));
+            // returns an error to simulate token storage failure
+            .returning(|_, _, _, _, _| Err(TokenRepoError::Connection));
         dependency_provider
             .db
             .signup_process_repo
             // token generation failed, so we save the process with the error
             .expect_save_latest_state()
             // makes sure the correct process in failed state is saved
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <SendVerificationEmail<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution failed with TokenRepoError
         assert!(result.is_err());
         assert_eq!(
@@ -333,17 +373,17 @@ mod tests {
     async fn test_send_verification_email_fails_email_send(
         mut dependency_provider: MockDependencyProvider,
         initialized_record: SignupProcessRepoRecord,
-        token_repo_record: TokenRepoRecord,
     ) {
         // fixtures
         let process = SignupProcess::<Initialized>::try_from(initialized_record.clone())
             .unwrap()
-            .fail(SignupProcessError::VerificationEmailSendError);
-        let token = token_repo_record.token.clone();
+            .fail(
+                SignupProcessError::VerificationEmailSendError,
+                chrono::Utc::now(),
+            );
         let record_to_save: SignupProcessRepoRecord = process.clone().into();
-        let req = super::Request {
-            id: initialized_record.id,
-        };
+        let id = initialized_record.id;
+        let req = super::Request { id };
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -354,40 +394,51 @@ mod tests {
             .times(1)
             // returns the record with the correct state
             .returning(move |_, _| Ok(initialized_record.clone()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
         dependency_provider
             .db
             .token_repo
-            .expect_gen()
-            // makes sure the correct email is used
-            .withf(move |_, actual_email| actual_email == TEST_EMAIL)
+            .expect_issue()
+            // makes sure the token is issued for this process and email
+            .withf(move |_, scope, actual_email, actual_token, _| {
+                scope == &Scope::new(Purpose::Signup, id)
+                    && actual_email == TEST_EMAIL
+                    && actual_token == TEST_TOKEN
+            })
             .times(1)
-            // returns test_token to simulate token generation success
-            .returning(move |_, _| Ok(token_repo_record.clone()));
+            // returns ok to simulate token storage success
+            .returning(|_, _, _, _, _| Ok(()));
         dependency_provider
             .email_verification_service
             .expect_send_verification_email()
-            // makes sure the correct email and token are used
-            .withf(move |actual_email, actual_token| {
-                actual_email.as_str() == TEST_EMAIL && actual_token == token.as_str()
+            // makes sure the correct email, process and token are used
+            .withf(move |actual_email, actual_id, actual_token| {
+                actual_email.as_str() == TEST_EMAIL
+                    && actual_id == &id
+                    && actual_token == TEST_TOKEN
             })
             .times(1)
             // returns an error to simulate email send failure
-            .returning(|_, _| Err(EmailServiceError::SendEmailFailed));
+            .returning(|_, _, _| Err(EmailServiceError::SendEmailFailed));
         dependency_provider
             .db
             .signup_process_repo
             // token generation failed, so we save the process with the error
             .expect_save_latest_state()
             // makes sure the correct process in failed state is saved
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <SendVerificationEmail<MockDependencyProvider> as Usecase<
             MockDependencyProvider,
         >>::new(Arc::new(dependency_provider));
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution failed with TokenRepoError
         assert!(result.is_err());
         assert_eq!(
@@ -409,7 +460,7 @@ mod tests {
         let result = SendVerificationEmail::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_none(signup_id: SignupId) {
@@ -417,6 +468,6 @@ mod tests {
         let result = SendVerificationEmail::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, None);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
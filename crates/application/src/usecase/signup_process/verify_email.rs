@@ -4,19 +4,29 @@ use crate::{
     gateway::{
         database::{
             signup_process::{GetError, Repo, SaveError},
-            token::{Repo as TokenRepo, VerifyError as TokenRepoError},
+            token::{
+                AttemptError, Purpose, Repo as TokenRepo, Scope, VerifyError as TokenRepoError,
+            },
             Database,
         },
-        DatabaseProvider,
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider, SignupPolicyProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::{
-    auth_strategy::AuthStrategy,
-    signup_process::{Id, SignupProcess, VerificationEmailSent},
+use ca_domain::{
+    entity::{
+        auth_strategy::AuthStrategy,
+        signup_process::{
+            Error as SignupProcessError, Failed, Id, SignupProcess, SignupStateEnum,
+            VerificationEmailSent,
+        },
+    },
+    value_object::SecretString,
 };
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use validator::Validate;
@@ -24,8 +34,16 @@ use validator::Validate;
 #[derive(Debug, Deserialize, Validate)]
 pub struct Request {
     pub id: Id,
-    #[validate(length(min = 1, max = 255))]
-    pub token: String,
+    #[validate(custom(function = "validate_token_length"))]
+    pub token: SecretString,
+}
+
+/// `length` check for a token, the built in one can not see into a secret
+fn validate_token_length(token: &SecretString) -> Result<(), validator::ValidationError> {
+    match token.expose_secret().len() {
+        1..=255 => Ok(()),
+        _ => Err(validator::ValidationError::new("length")),
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -42,10 +60,18 @@ pub enum Error {
     NotFound(Id),
     #[error("SignupProcess {0} in incorrect state")]
     IncorrectState(Id),
+    #[error("SignupProcess {0} state is corrupted")]
+    Corrupted(Id),
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::Conflict)]
+    Conflict,
     #[error("Token Repo error: {0}")]
     TokenRepoError(#[from] TokenRepoError),
+    #[error("Too many verification attempts for SignupProcess {id}, retry after {retry_after}")]
+    TooManyAttempts { id: Id, retry_after: DateTime<Utc> },
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
     #[error(transparent)]
     TokenInvalidity(#[from] validator::ValidationErrors),
 }
@@ -54,6 +80,7 @@ impl From<SaveError> for Error {
     fn from(err: SaveError) -> Self {
         match err {
             SaveError::Connection => Self::Repo,
+            SaveError::Conflict => Self::Conflict,
         }
     }
 }
@@ -63,6 +90,7 @@ impl From<(GetError, Id)> for Error {
         match err {
             GetError::NotFound => Self::NotFound(id),
             GetError::IncorrectState => Self::IncorrectState(id),
+            GetError::Corrupted => Self::Corrupted(id),
             GetError::Connection => Self::Repo,
         }
     }
@@ -70,16 +98,19 @@ impl From<(GetError, Id)> for Error {
 #[async_trait::async_trait]
 impl<D> Usecase<D> for VerifyEmail<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + SignupPolicyProvider + ClockProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "signup_process.verify_email";
     /// Create a new user with the given name.
-    async fn exec(&self, req: Request) -> Result<Response, Error> {
+    async fn exec(&self, req: Request, _ctx: &RequestContext) -> Result<Response, Error> {
         log::debug!("SignupProcess Email Verification: {:?}", req);
+        let now = self.dependency_provider.clock().now();
         // Validate the request
         req.validate()?;
+        let policy = self.dependency_provider.signup_policy();
         // Begin transaction
         let mut transaction = self
             .dependency_provider
@@ -94,29 +125,101 @@ where
             .get_latest_state(Some(&mut transaction), req.id)
             .await
             .map_err(|err| (err, req.id))?;
-        let process: SignupProcess<VerificationEmailSent> =
-            record.try_into().map_err(|err| (err, req.id))?;
-        // Verify the token
+        let seq = record.seq;
+        let scope = Scope::new(Purpose::Signup, req.id);
+        let process: SignupProcess<VerificationEmailSent> = match &record.state {
+            SignupStateEnum::Failed {
+                error: SignupProcessError::TooManyAttempts,
+                ..
+            } => {
+                let retry_after = record.entered_at + policy.attempt_lockout;
+                if now < retry_after {
+                    return Err(Error::TooManyAttempts {
+                        id: req.id,
+                        retry_after,
+                    });
+                }
+                // the lockout is over, verify on top of the locked state
+                // without saving anything for it. The failed attempts are
+                // kept, so every wrong token from here on locks it again.
+                // The verification window stays the one of the locked state.
+                let chain = self
+                    .dependency_provider
+                    .database()
+                    .signup_process_repo()
+                    .get_state_chain(Some(&mut transaction), req.id)
+                    .await
+                    .map_err(|err| (err, req.id))?;
+                let locked_entered_at = chain
+                    .iter()
+                    .rev()
+                    .nth(1)
+                    .map(|locked| locked.entered_at)
+                    .ok_or((GetError::Corrupted, req.id))?;
+                SignupProcess::<Failed<VerificationEmailSent>>::try_from(record)
+                    .map_err(|err| (err, req.id))?
+                    .unlock(locked_entered_at)
+            }
+            // the email was verified before, e.g. the link was opened twice
+            SignupStateEnum::EmailVerified { .. } | SignupStateEnum::Completed { .. } => {
+                return Ok(Self::Response { id: req.id });
+            }
+            _ => record.try_into().map_err(|err| (err, req.id))?,
+        };
+        // Verify the token, which consumes it along with the transaction
         if let Err(err) = self
             .dependency_provider
             .database()
             .token_repo()
             .verify(
                 Some(&mut transaction),
-                process.state().email.as_ref(),
-                &req.token,
+                &scope,
+                &process.state().email.canonical(),
+                req.token.expose_secret(),
+                policy.verification_ttl,
+                now,
             )
             .await
         {
             log::error!("Token Repo error: {:?}", err);
-            if let TokenRepoError::TokenExpired = err {
-                let process =
-                    process.fail(ca_domain::entity::signup_process::Error::VerificationTimedOut);
-                self.dependency_provider
-                    .database()
-                    .signup_process_repo()
-                    .save_latest_state(Some(&mut transaction), process.into())
-                    .await?;
+            match err {
+                TokenRepoError::TokenExpired => {
+                    let process = process.fail(SignupProcessError::VerificationTimedOut, now);
+                    self.dependency_provider
+                        .database()
+                        .signup_process_repo()
+                        .save_latest_state(Some(&mut transaction), process.into(), Some(seq))
+                        .await?;
+                }
+                // a wrong token counts towards locking the process, so short
+                // codes can not be guessed
+                TokenRepoError::Mismatch | TokenRepoError::NotFound => {
+                    let failures = self
+                        .dependency_provider
+                        .database()
+                        .token_repo()
+                        .record_failed_attempt(Some(&mut transaction), &scope, now)
+                        .await?;
+                    if failures >= policy.max_verification_attempts {
+                        log::warn!("SignupProcess {} locked after {failures} attempts", req.id);
+                        let process = process.fail(SignupProcessError::TooManyAttempts, now);
+                        self.dependency_provider
+                            .database()
+                            .signup_process_repo()
+                            .save_latest_state(Some(&mut transaction), process.into(), Some(seq))
+                            .await?;
+                        self.dependency_provider
+                            .database()
+                            .commit_transaction(transaction)
+                            .await
+                            .map_err(|_| SaveError::Connection)?;
+                        return Err(Error::TooManyAttempts {
+                            id: req.id,
+                            retry_after: now + policy.attempt_lockout,
+                        });
+                    }
+                }
+                TokenRepoError::Connection => {}
             }
             self.dependency_provider
                 .database()
@@ -126,11 +229,27 @@ where
             return Err(err.into());
         };
         // Update the process state
-        let process = process.verify_email();
+        let process = match process.verify_email(&policy, now) {
+            Ok(process) => process,
+            Err(failed) => {
+                log::error!("SignupProcess {} verification timed out", req.id);
+                self.dependency_provider
+                    .database()
+                    .signup_process_repo()
+                    .save_latest_state(Some(&mut transaction), failed.into(), Some(seq))
+                    .await?;
+                self.dependency_provider
+                    .database()
+                    .commit_transaction(transaction)
+                    .await
+                    .map_err(|_| SaveError::Connection)?;
+                return Err(TokenRepoError::TokenExpired.into());
+            }
+        };
         self.dependency_provider
             .database()
             .signup_process_repo()
-            .save_latest_state(Some(&mut transaction), process.into())
+            .save_latest_state(Some(&mut transaction), process.into(), Some(seq))
             .await?;
         self.dependency_provider
             .database()
@@ -144,6 +263,9 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
     fn auth_strategy(&self) -> AuthStrategy {
         AuthStrategy::Public
     }
@@ -160,12 +282,18 @@ mod tests {
         },
         usecase::tests::fixtures::*,
     };
-    use ca_domain::entity::{
-        auth_context::AuthContext,
-        signup_process::{Error as SignupError, Id as SignupId},
+    use ca_domain::{
+        entity::{
+            auth_context::AuthContext,
+            signup_process::{Error as SignupError, Id as SignupId},
+            user::Email,
+        },
+        value_object::SignupPolicy,
     };
 
     use super::*;
+    use crate::gateway::service::clock::Clock;
+    use chrono::Utc;
 
     #[rstest]
     async fn test_verify_email_success(
@@ -176,12 +304,16 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         let process: SignupProcess<VerificationEmailSent> =
             verification_email_sent_record.clone().try_into().unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.verify_email().into();
+        let record_to_save = process
+            .verify_email(&SignupPolicy::default(), Utc::now())
+            .unwrap()
+            .into();
+        let loaded_seq = verification_email_sent_record.seq;
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -196,26 +328,31 @@ mod tests {
             .db
             .token_repo
             .expect_verify()
-            // makes sure the correct token is used
-            .withf(move |_, actual_email, actual_token| {
-                actual_token == TEST_TOKEN && actual_email == TEST_EMAIL
+            // makes sure the token of this signup process is used
+            .withf(move |_, scope, actual_email, actual_token, _, _| {
+                scope == &Scope::new(Purpose::Signup, signup_id)
+                    && actual_token == TEST_TOKEN
+                    && actual_email == TEST_EMAIL
             })
             .times(1)
             // returns Ok
-            .returning(move |_, _, _| Ok(()));
+            .returning(move |_, _, _, _, _, _| Ok(()));
         dependency_provider
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            // makes sure the state is only appended onto the loaded one
+            .withf(move |_, actual_record, expected_seq| {
+                actual_record == &record_to_save && expected_seq == &Some(loaded_seq)
+            })
             .times(1)
-            .returning(|_, _| Ok(()));
+            .returning(|_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let response = result.unwrap();
@@ -231,9 +368,9 @@ mod tests {
         let id = Id::from(uuid::Uuid::new_v4());
         let req = super::Request {
             id,
-            token: "".to_string(),
+            token: "".into(),
         };
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -248,7 +385,7 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -265,7 +402,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -278,7 +415,7 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -295,7 +432,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound(signup_id));
@@ -309,7 +446,7 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -326,12 +463,47 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::IncorrectState(signup_id));
     }
     #[rstest]
+    async fn test_verify_email_already_verified(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email_verified_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            token: TEST_TOKEN.into(),
+        };
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .withf(move |_, actual_id| actual_id == &signup_id)
+            .times(1)
+            .returning(move |_, _| Ok(email_verified_record.clone()));
+        // the token was consumed by the first verification and nothing changes
+        dependency_provider.db.token_repo.expect_verify().never();
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert_eq!(result.unwrap().id, signup_id);
+    }
+    #[rstest]
     async fn test_verify_fail_token_verification_connection(
         mut dependency_provider: MockDependencyProvider,
         signup_id: SignupId,
@@ -340,7 +512,7 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -357,12 +529,12 @@ mod tests {
             .token_repo
             .expect_verify()
             // makes sure the correct token is used
-            .withf(move |_, actual_email, actual_token| {
+            .withf(move |_, _, actual_email, actual_token, _, _| {
                 actual_token == TEST_TOKEN && actual_email == TEST_EMAIL
             })
             .times(1)
             // returns connection error
-            .returning(move |_, _, _| Err(VerifyError::Connection));
+            .returning(move |_, _, _, _, _, _| Err(VerifyError::Connection));
         // save latest state should not be called on token verification error
         dependency_provider
             .db
@@ -374,7 +546,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(
@@ -391,7 +563,7 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -408,12 +580,20 @@ mod tests {
             .token_repo
             .expect_verify()
             // makes sure the correct token is used
-            .withf(move |_, actual_email, actual_token| {
+            .withf(move |_, _, actual_email, actual_token, _, _| {
                 actual_token == TEST_TOKEN && actual_email == TEST_EMAIL
             })
             .times(1)
             // returns connection error
-            .returning(move |_, _, _| Err(VerifyError::NotFound));
+            .returning(move |_, _, _, _, _, _| Err(VerifyError::NotFound));
+        // the wrong token is counted, but stays below the limit
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .withf(move |_, scope, _| scope == &Scope::new(Purpose::Signup, signup_id))
+            .times(1)
+            .returning(|_, _, _| Ok(1));
         // save latest state should not be called on token verification error
         dependency_provider
             .db
@@ -425,7 +605,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(
@@ -443,7 +623,7 @@ mod tests {
         let wrong_token = "wrong_token".to_string();
         let req = Request {
             id: signup_id,
-            token: wrong_token.clone(),
+            token: wrong_token.clone().into(),
         };
         // Mock setup -- predicates and return values
         dependency_provider
@@ -460,12 +640,20 @@ mod tests {
             .token_repo
             .expect_verify()
             // makes sure the correct token is used
-            .withf(move |_, actual_email, actual_token| {
+            .withf(move |_, _, actual_email, actual_token, _, _| {
                 actual_token == wrong_token.clone() && actual_email == TEST_EMAIL
             })
             .times(1)
             // returns connection error
-            .returning(move |_, _, _| Err(VerifyError::Mismatch));
+            .returning(move |_, _, _, _, _, _| Err(VerifyError::Mismatch));
+        // the wrong token is counted, but stays below the limit
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .withf(move |_, scope, _| scope == &Scope::new(Purpose::Signup, signup_id))
+            .times(1)
+            .returning(|_, _, _| Ok(1));
         // save latest state should not be called on token verification error
         dependency_provider
             .db
@@ -477,7 +665,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(
@@ -494,12 +682,14 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         let process: SignupProcess<VerificationEmailSent> =
             verification_email_sent_record.clone().try_into().unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.fail(SignupError::VerificationTimedOut).into();
+        let record_to_save = process
+            .fail(SignupError::VerificationTimedOut, chrono::Utc::now())
+            .into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -515,26 +705,26 @@ mod tests {
             .token_repo
             .expect_verify()
             // makes sure the correct token is used
-            .withf(move |_, actual_email, actual_token| {
+            .withf(move |_, _, actual_email, actual_token, _, _| {
                 actual_token == TEST_TOKEN && actual_email == TEST_EMAIL
             })
             .times(1)
             // returns connection error
-            .returning(move |_, _, _| Err(VerifyError::TokenExpired));
+            .returning(move |_, _, _, _, _, _| Err(VerifyError::TokenExpired));
         // save latest state should be called for the failed verification
         dependency_provider
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Ok(()));
+            .returning(move |_, _, _| Ok(()));
         // Usecase Initialization
         let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(
@@ -543,6 +733,422 @@ mod tests {
         );
     }
     #[rstest]
+    async fn test_verify_email_fail_policy_timeout(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures -- the policy allows 15 minutes, the clock is moved past them
+        let req = Request {
+            id: signup_id,
+            token: TEST_TOKEN.into(),
+        };
+        let ttl = chrono::Duration::minutes(15);
+        dependency_provider.signup_policy = SignupPolicy::default().with_verification_ttl(ttl);
+        let sent_record = SignupProcessRepoRecord {
+            entered_at: dependency_provider.clock.now(),
+            ..verification_email_sent_record
+        };
+        dependency_provider
+            .clock
+            .advance(ttl + chrono::Duration::seconds(1));
+        let now = dependency_provider.clock.now();
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(sent_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            // makes sure the policy ttl and the clock time reach the token repo
+            .withf(move |_, _, _, _, max_age, actual_now| max_age == &ttl && actual_now == &now)
+            .times(1)
+            .returning(move |_, _, _, _, _, _| Ok(()));
+        // the process is failed by the domain check at the clock time
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, _| {
+                matches!(
+                    &actual_record.state,
+                    ca_domain::entity::signup_process::SignupStateEnum::Failed {
+                        error: SignupError::VerificationTimedOut,
+                        ..
+                    }
+                ) && actual_record.entered_at == now
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::TokenRepoError(VerifyError::TokenExpired)
+        );
+    }
+    #[rstest]
+    async fn test_verify_email_fail_too_many_attempts(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures -- the last allowed attempt is wrong as well
+        let req = Request {
+            id: signup_id,
+            token: "wrong_token".into(),
+        };
+        let policy = SignupPolicy::default();
+        let now = dependency_provider.clock.now();
+        let retry_after = now + policy.attempt_lockout;
+        let max_attempts = policy.max_verification_attempts;
+        let loaded_seq = verification_email_sent_record.seq;
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(verification_email_sent_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(move |_, _, _, _, _, _| Err(VerifyError::Mismatch));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .withf(move |_, scope, actual_now| {
+                scope == &Scope::new(Purpose::Signup, signup_id) && actual_now == &now
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(max_attempts));
+        // the process is locked
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, expected_seq| {
+                matches!(
+                    &actual_record.state,
+                    SignupStateEnum::Failed {
+                        error: SignupError::TooManyAttempts,
+                        ..
+                    }
+                ) && expected_seq == &Some(loaded_seq)
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::TooManyAttempts {
+                id: signup_id,
+                retry_after
+            }
+        );
+    }
+    #[rstest]
+    async fn test_verify_email_fail_locked(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- locked a minute ago
+        let req = Request {
+            id: signup_id,
+            token: TEST_TOKEN.into(),
+        };
+        let locked_at = dependency_provider.clock.now() - chrono::Duration::minutes(1);
+        let locked_record = locked_record(signup_id, email, locked_at);
+        // Mock setup -- the token is not even looked at
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(locked_record.clone()));
+        dependency_provider.db.token_repo.expect_verify().never();
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .never();
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::TooManyAttempts {
+                id: signup_id,
+                retry_after: locked_at + SignupPolicy::default().attempt_lockout
+            }
+        );
+    }
+    #[rstest]
+    async fn test_verify_email_success_after_lockout(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- locked longer ago than the lockout
+        let req = Request {
+            id: signup_id,
+            token: TEST_TOKEN.into(),
+        };
+        let now = dependency_provider.clock.now();
+        let locked_at = now - chrono::Duration::minutes(20);
+        let chain = locked_chain(signup_id, email.clone(), locked_at, locked_at);
+        let locked_record = locked_record(signup_id, email, locked_at);
+        let locked_seq = locked_record.seq;
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(locked_record.clone()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_state_chain()
+            .times(1)
+            .returning(move |_, _| Ok(chain.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(move |_, _, _, _, _, _| Ok(()));
+        // the email is verified right on top of the lock, lifting it saves
+        // nothing on its own
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, expected_seq| {
+                matches!(actual_record.state, SignupStateEnum::EmailVerified { .. })
+                    && expected_seq == &Some(locked_seq)
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert_eq!(result.unwrap().id, signup_id);
+    }
+    #[rstest]
+    async fn test_verify_email_fail_wrong_token_after_lockout_locks_again(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- locked longer ago than the lockout
+        let req = Request {
+            id: signup_id,
+            token: "wrong_token".into(),
+        };
+        let policy = SignupPolicy::default();
+        let now = dependency_provider.clock.now();
+        let locked_at = now - chrono::Duration::minutes(20);
+        let chain = locked_chain(signup_id, email.clone(), locked_at, locked_at);
+        let locked_record = locked_record(signup_id, email, locked_at);
+        let locked_seq = locked_record.seq;
+        let max_attempts = policy.max_verification_attempts;
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(locked_record.clone()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_state_chain()
+            .times(1)
+            .returning(move |_, _| Ok(chain.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(move |_, _, _, _, _, _| Err(VerifyError::Mismatch));
+        // the attempts of the first lock were kept
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .times(1)
+            .returning(move |_, _, _| Ok(max_attempts + 1));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .never();
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, expected_seq| {
+                matches!(
+                    &actual_record.state,
+                    SignupStateEnum::Failed {
+                        error: SignupError::TooManyAttempts,
+                        ..
+                    }
+                ) && expected_seq == &Some(locked_seq)
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::TooManyAttempts {
+                id: signup_id,
+                retry_after: now + policy.attempt_lockout
+            }
+        );
+    }
+    #[rstest]
+    async fn test_verify_email_fail_policy_timeout_after_lockout(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        email: Email,
+    ) {
+        // fixtures -- locked just before the verification window closed, the
+        // lockout ended after it
+        let req = Request {
+            id: signup_id,
+            token: TEST_TOKEN.into(),
+        };
+        let policy = SignupPolicy::default();
+        let now = dependency_provider.clock.now();
+        let locked_at = now - policy.attempt_lockout - chrono::Duration::minutes(1);
+        let sent_at = locked_at - policy.verification_ttl + chrono::Duration::minutes(1);
+        let chain = locked_chain(signup_id, email.clone(), sent_at, locked_at);
+        let locked_record = locked_record(signup_id, email, locked_at);
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            .times(1)
+            .returning(move |_, _| Ok(locked_record.clone()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_state_chain()
+            .times(1)
+            .returning(move |_, _| Ok(chain.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(move |_, _, _, _, _, _| Ok(()));
+        // the window is measured from the locked state, not from the lock
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, _| {
+                matches!(
+                    &actual_record.state,
+                    SignupStateEnum::Failed {
+                        error: SignupError::VerificationTimedOut,
+                        ..
+                    }
+                )
+            })
+            .times(1)
+            .returning(move |_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::TokenRepoError(VerifyError::TokenExpired)
+        );
+    }
+    /// Process locked for too many attempts at `locked_at`
+    fn locked_record(
+        signup_id: SignupId,
+        email: Email,
+        locked_at: DateTime<Utc>,
+    ) -> SignupProcessRepoRecord {
+        SignupProcessRepoRecord {
+            id: signup_id,
+            seq: 2,
+            state: SignupStateEnum::Failed {
+                previous_state: std::sync::Arc::new(SignupStateEnum::VerificationEmailSent {
+                    email,
+                    sent_at: locked_at,
+                    resends: 0,
+                    extensions: 0,
+                }),
+                error: SignupError::TooManyAttempts,
+            },
+            entered_at: locked_at,
+        }
+    }
+    /// State chain of a process that sent its email at `sent_at` and was
+    /// locked at `locked_at`
+    fn locked_chain(
+        signup_id: SignupId,
+        email: Email,
+        sent_at: DateTime<Utc>,
+        locked_at: DateTime<Utc>,
+    ) -> Vec<SignupProcessRepoRecord> {
+        let sent_record = SignupProcessRepoRecord {
+            id: signup_id,
+            seq: 1,
+            state: SignupStateEnum::VerificationEmailSent {
+                email: email.clone(),
+                sent_at,
+                resends: 0,
+                extensions: 0,
+            },
+            entered_at: sent_at,
+        };
+        vec![sent_record, locked_record(signup_id, email, locked_at)]
+    }
+    #[rstest]
     async fn test_verify_email_fail_save_latest_state_connection(
         mut dependency_provider: MockDependencyProvider,
         signup_id: SignupId,
@@ -551,12 +1157,15 @@ mod tests {
         // fixtures
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         let process: SignupProcess<VerificationEmailSent> =
             verification_email_sent_record.clone().try_into().unwrap();
         // record to be passed to the save latest state method
-        let record_to_save = process.verify_email().into();
+        let record_to_save = process
+            .verify_email(&SignupPolicy::default(), Utc::now())
+            .unwrap()
+            .into();
         // Mock setup -- predicates and return values
         dependency_provider
             .db
@@ -572,34 +1181,90 @@ mod tests {
             .token_repo
             .expect_verify()
             // makes sure the correct token is used
-            .withf(move |_, actual_email, actual_token| {
+            .withf(move |_, _, actual_email, actual_token, _, _| {
                 actual_token == TEST_TOKEN && actual_email == TEST_EMAIL
             })
             .times(1)
             // returns Ok
-            .returning(move |_, _, _| Ok(()));
+            .returning(move |_, _, _, _, _, _| Ok(()));
         dependency_provider
             .db
             .signup_process_repo
             .expect_save_latest_state()
-            .withf(move |_, actual_record| actual_record == &record_to_save)
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
             .times(1)
-            .returning(move |_, _| Err(SaveError::Connection));
+            .returning(move |_, _, _| Err(SaveError::Connection));
         // Usecase Initialization
         let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
     }
     #[rstest]
+    async fn test_verify_email_fail_save_latest_state_conflict(
+        mut dependency_provider: MockDependencyProvider,
+        signup_id: SignupId,
+        verification_email_sent_record: SignupProcessRepoRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: signup_id,
+            token: TEST_TOKEN.into(),
+        };
+        let process: SignupProcess<VerificationEmailSent> =
+            verification_email_sent_record.clone().try_into().unwrap();
+        // record to be passed to the save latest state method
+        let record_to_save = process
+            .verify_email(&SignupPolicy::default(), Utc::now())
+            .unwrap()
+            .into();
+        // Mock setup -- predicates and return values
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_get_latest_state()
+            // makes sure the correct id is used
+            .withf(move |_, actual_id| actual_id == &signup_id)
+            .times(1)
+            // returns the record with the correct state
+            .returning(move |_, _| Ok(verification_email_sent_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            // makes sure the correct token is used
+            .withf(move |_, _, actual_email, actual_token, _, _| {
+                actual_token == TEST_TOKEN && actual_email == TEST_EMAIL
+            })
+            .times(1)
+            // returns Ok
+            .returning(move |_, _, _, _, _, _| Ok(()));
+        dependency_provider
+            .db
+            .signup_process_repo
+            .expect_save_latest_state()
+            .withf(move |_, actual_record, _| actual_record == &record_to_save)
+            .times(1)
+            .returning(move |_, _, _| Err(SaveError::Conflict));
+        // Usecase Initialization
+        let usecase = <VerifyEmail<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Conflict);
+    }
+    #[rstest]
     fn test_authorize_admin_zero(signup_id: SignupId, auth_context_admin: AuthContext) {
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         let result = VerifyEmail::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_admin));
@@ -610,7 +1275,7 @@ mod tests {
     fn test_authorize_user_zero(signup_id: SignupId, auth_context_user: AuthContext) {
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         let result = VerifyEmail::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
@@ -620,7 +1285,7 @@ mod tests {
     fn test_authorize_none(signup_id: SignupId) {
         let req = Request {
             id: signup_id,
-            token: TEST_TOKEN.to_string(),
+            token: TEST_TOKEN.into(),
         };
         let auth_context = None;
         let result = VerifyEmail::new(Arc::new(MockDependencyProvider::default()))
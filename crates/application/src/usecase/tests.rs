@@ -6,17 +6,20 @@ pub mod fixtures {
             auth_context::AuthContext,
             signup_process::{Error as SignupError, Id as SignupId, SignupStateEnum},
             user::{Email, Id as UserId, User},
+            user_identity::UserIdentity,
         },
-        value_object::{Password, Role, UserName},
+        value_object::{Password, Permission, Role, SecretString, UserName},
     };
     use rstest::*;
 
     use crate::gateway::{
         database::{
-            signup_process::Record as SignupProcessRepoRecord, token::Record as TokenRepoRecord,
-            user::Record as UserRecord,
+            external_login::Record as ExternalLoginRecord, mfa::Record as MfaRecord,
+            role::Record as RoleRecord, signup_process::Record as SignupProcessRepoRecord,
+            user::Record as UserRecord, user_identity::Record as UserIdentityRecord,
         },
         mock::MockDependencyProvider,
+        service::oidc::ExternalClaims,
     };
 
     pub static TEST_EMAIL: &str = "test@email.com";
@@ -25,6 +28,17 @@ pub mod fixtures {
     pub static TEST_UUID2: &str = "03b85a20-e4cb-4e34-b6a5-a8cd86ba4a98";
     pub static TEST_USERNAME: &str = "test_username";
     pub static TEST_PASSWORD: &str = "test_password";
+    pub static TEST_ROLE: &str = "Support";
+    pub static TEST_TOTP_SECRET: &str = "JBSWY3DPEHPK3PXP";
+    pub static TEST_TOTP_CODE: &str = "123456";
+    pub static TEST_TOTP_STEP: u64 = 58_000_000;
+    pub static TEST_PROVIDER: &str = "example";
+    pub static TEST_SUBJECT: &str = "248289761001";
+    pub static TEST_STATE: &str = "test_state";
+    pub static TEST_NONCE: &str = "test_nonce";
+    pub static TEST_CODE: &str = "test_code";
+    pub static TEST_CODE_VERIFIER: &str = "test_code_verifier";
+    pub static TEST_AUTHORIZATION_URL: &str = "https://id.example.com/authorize?state=test_state";
 
     #[fixture]
     pub fn signup_id() -> SignupId {
@@ -36,23 +50,62 @@ pub mod fixtures {
     }
     #[fixture]
     pub fn auth_context_admin(user_id_zero: UserId) -> AuthContext {
-        AuthContext::new(user_id_zero, Role::Admin)
+        AuthContext::new(user_id_zero, Role::admin())
     }
     #[fixture]
     pub fn auth_context_user(user_id_zero: UserId) -> AuthContext {
-        AuthContext::new(user_id_zero, Role::User)
+        AuthContext::new(user_id_zero, Role::user())
     }
     #[fixture]
-    pub fn email() -> Email {
-        Email::new(TEST_EMAIL)
+    pub fn auth_context_support(user_id_zero: UserId) -> AuthContext {
+        AuthContext::new(user_id_zero, Role::new(TEST_ROLE))
+            .with_permissions([Permission::new(Permission::USERS_READ)])
+    }
+    #[fixture]
+    pub fn mfa_record(user_id_zero: UserId) -> MfaRecord {
+        MfaRecord {
+            user_id: user_id_zero,
+            secret: SecretString::from(TEST_TOTP_SECRET),
+            confirmed: true,
+            last_used_step: Some(TEST_TOTP_STEP - 1),
+        }
+    }
+    #[fixture]
+    pub fn external_login_record() -> ExternalLoginRecord {
+        ExternalLoginRecord {
+            state: SecretString::from(TEST_STATE),
+            provider: TEST_PROVIDER.to_string(),
+            nonce: TEST_NONCE.to_string(),
+            code_verifier: SecretString::from(TEST_CODE_VERIFIER),
+            link_user_id: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+    #[fixture]
+    pub fn external_claims() -> ExternalClaims {
+        ExternalClaims {
+            subject: TEST_SUBJECT.to_string(),
+            email: Some(TEST_EMAIL.to_string()),
+            email_verified: true,
+            preferred_username: Some(TEST_USERNAME.to_string()),
+        }
+    }
+    #[fixture]
+    pub fn user_identity_record(user_id: UserId) -> UserIdentityRecord {
+        UserIdentity::new(TEST_PROVIDER, TEST_SUBJECT, user_id).into()
     }
     #[fixture]
-    pub fn token_repo_record() -> TokenRepoRecord {
-        TokenRepoRecord {
-            token: TEST_TOKEN.to_string(),
+    pub fn role_record() -> RoleRecord {
+        RoleRecord {
+            role: Role::new(TEST_ROLE),
+            permissions: [Permission::new(Permission::USERS_READ)].into(),
         }
     }
     #[fixture]
+    pub fn email() -> Email {
+        Email::new_unchecked(TEST_EMAIL)
+    }
+    #[fixture]
     pub fn initialized_state(email: Email) -> SignupStateEnum {
         SignupStateEnum::Initialized { email }
     }
@@ -64,6 +117,7 @@ pub mod fixtures {
     pub fn initialized_record(signup_id: SignupId, email: Email) -> SignupProcessRepoRecord {
         SignupProcessRepoRecord {
             id: signup_id,
+            seq: 0,
             state: SignupStateEnum::Initialized { email },
             entered_at: chrono::Utc::now(),
         }
@@ -75,7 +129,13 @@ pub mod fixtures {
     ) -> SignupProcessRepoRecord {
         SignupProcessRepoRecord {
             id: signup_id,
-            state: SignupStateEnum::VerificationEmailSent { email },
+            seq: 1,
+            state: SignupStateEnum::VerificationEmailSent {
+                email,
+                sent_at: chrono::Utc::now(),
+                resends: 0,
+                extensions: 0,
+            },
             entered_at: chrono::Utc::now(),
         }
     }
@@ -83,7 +143,11 @@ pub mod fixtures {
     pub fn email_verified_record(signup_id: SignupId, email: Email) -> SignupProcessRepoRecord {
         SignupProcessRepoRecord {
             id: signup_id,
-            state: SignupStateEnum::EmailVerified { email },
+            seq: 2,
+            state: SignupStateEnum::EmailVerified {
+                email,
+                extensions: 0,
+            },
             entered_at: chrono::Utc::now(),
         }
     }
@@ -94,8 +158,14 @@ pub mod fixtures {
     ) -> SignupProcessRepoRecord {
         SignupProcessRepoRecord {
             id: signup_id,
+            seq: 2,
             state: SignupStateEnum::Failed {
-                previous_state: Arc::new(SignupStateEnum::VerificationEmailSent { email }),
+                previous_state: Arc::new(SignupStateEnum::VerificationEmailSent {
+                    email,
+                    sent_at: chrono::Utc::now(),
+                    resends: 0,
+                    extensions: 0,
+                }),
                 error: SignupError::VerificationTimedOut,
             },
             entered_at: chrono::Utc::now(),
@@ -108,8 +178,12 @@ pub mod fixtures {
     ) -> SignupProcessRepoRecord {
         SignupProcessRepoRecord {
             id: signup_id,
+            seq: 3,
             state: SignupStateEnum::Failed {
-                previous_state: Arc::new(SignupStateEnum::EmailVerified { email }),
+                previous_state: Arc::new(SignupStateEnum::EmailVerified {
+                    email,
+                    extensions: 0,
+                }),
                 error: SignupError::VerificationTimedOut,
             },
             entered_at: chrono::Utc::now(),
@@ -119,6 +193,7 @@ pub mod fixtures {
     pub fn failed_initialized_record(signup_id: SignupId, email: Email) -> SignupProcessRepoRecord {
         SignupProcessRepoRecord {
             id: signup_id,
+            seq: 1,
             state: SignupStateEnum::Failed {
                 previous_state: Arc::new(SignupStateEnum::Initialized { email }),
                 error: SignupError::TokenGenrationFailed,
@@ -135,6 +210,7 @@ pub mod fixtures {
         vec![
             SignupProcessRepoRecord {
                 id: signup_id,
+                seq: 0,
                 state: SignupStateEnum::Initialized {
                     email: email.clone(),
                 },
@@ -142,14 +218,22 @@ pub mod fixtures {
             },
             SignupProcessRepoRecord {
                 id: signup_id,
+                seq: 1,
                 state: SignupStateEnum::VerificationEmailSent {
                     email: email.clone(),
+                    sent_at: chrono::Utc::now(),
+                    resends: 0,
+                    extensions: 0,
                 },
                 entered_at: chrono::Utc::now(),
             },
             SignupProcessRepoRecord {
                 id: signup_id,
-                state: SignupStateEnum::EmailVerified { email },
+                seq: 2,
+                state: SignupStateEnum::EmailVerified {
+                    email,
+                    extensions: 0,
+                },
                 entered_at: chrono::Utc::now(),
             },
         ]
@@ -159,10 +243,10 @@ pub mod fixtures {
         UserRecord {
             user: User::new(
                 UserId::new(uuid::Uuid::parse_str(TEST_UUID).unwrap()),
-                Role::User,
-                Email::new(TEST_EMAIL),
-                UserName::new(TEST_USERNAME),
-                Password::new(TEST_PASSWORD),
+                Role::user(),
+                Email::new_unchecked(TEST_EMAIL),
+                UserName::new_unchecked(TEST_USERNAME),
+                Password::new_unchecked(TEST_PASSWORD),
             ),
         }
     }
@@ -172,19 +256,19 @@ pub mod fixtures {
             UserRecord {
                 user: User::new(
                     UserId::new(uuid::Uuid::parse_str(TEST_UUID).unwrap()),
-                    Role::User,
-                    Email::new(TEST_EMAIL),
-                    UserName::new(TEST_USERNAME),
-                    Password::new(TEST_PASSWORD),
+                    Role::user(),
+                    Email::new_unchecked(TEST_EMAIL),
+                    UserName::new_unchecked(TEST_USERNAME),
+                    Password::new_unchecked(TEST_PASSWORD),
                 ),
             },
             UserRecord {
                 user: User::new(
                     UserId::new(uuid::Uuid::parse_str(TEST_UUID2).unwrap()),
-                    Role::User,
-                    Email::new(TEST_EMAIL),
-                    UserName::new(TEST_USERNAME),
-                    Password::new(TEST_PASSWORD),
+                    Role::user(),
+                    Email::new_unchecked(TEST_EMAIL),
+                    UserName::new_unchecked(TEST_USERNAME),
+                    Password::new_unchecked(TEST_PASSWORD),
                 ),
             },
         ]
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            external_login::{Record, Repo as ExternalLoginRepo, SaveError},
+            Database,
+        },
+        service::{
+            clock::Clock,
+            oidc::{OidcError, OidcService},
+        },
+        ClockProvider, DatabaseProvider, OidcServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::auth_strategy::AuthStrategy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Minutes a started login may take at the provider
+pub(super) const EXTERNAL_LOGIN_TTL_MINUTES: i64 = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub provider: String,
+    /// Link the identity to the current user instead of logging in with
+    /// it, see [`LinkIdentity`](super::link_identity::LinkIdentity)
+    #[serde(default)]
+    pub link: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    /// Where the user logs in at the provider
+    pub authorization_url: String,
+}
+
+/// Start a login at an OpenID Connect provider, it is finished with
+/// [`CompleteExternalLogin`](super::complete_external_login::CompleteExternalLogin)
+pub struct BeginExternalLogin<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error(transparent)]
+    Oidc(#[from] OidcError),
+    #[error("{}", SaveError::Connection)]
+    Repo,
+}
+
+impl From<SaveError> for Error {
+    fn from(_: SaveError) -> Self {
+        Self::Repo
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for BeginExternalLogin<D>
+where
+    D: DatabaseProvider + OidcServiceProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.begin_external_login";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Begin external login: {:?}", req.provider);
+        let link_user_id = if req.link {
+            let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+            Some(auth_context.user_id)
+        } else {
+            None
+        };
+        let now = self.dependency_provider.clock().now();
+        let authorization = self
+            .dependency_provider
+            .oidc_service()
+            .authorize(&req.provider)
+            .await?;
+        self.dependency_provider
+            .database()
+            .external_login_repo()
+            .save(
+                None,
+                Record {
+                    state: authorization.state,
+                    provider: req.provider,
+                    nonce: authorization.nonce,
+                    code_verifier: authorization.code_verifier,
+                    link_user_id,
+                    created_at: now,
+                },
+            )
+            .await?;
+        Ok(Response {
+            authorization_url: authorization.url,
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{mock::MockDependencyProvider, service::oidc::Authorization},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::{auth_context::AuthContext, user::Id};
+    use rstest::*;
+
+    fn authorization() -> Authorization {
+        Authorization {
+            url: TEST_AUTHORIZATION_URL.to_string(),
+            state: TEST_STATE.into(),
+            nonce: TEST_NONCE.to_string(),
+            code_verifier: TEST_CODE_VERIFIER.into(),
+        }
+    }
+
+    #[rstest]
+    async fn test_begin_external_login_success(mut dependency_provider: MockDependencyProvider) {
+        // Mock setup
+        dependency_provider
+            .oidc_service
+            .expect_authorize()
+            .withf(|provider| provider == TEST_PROVIDER)
+            .times(1)
+            .returning(|_| Ok(authorization()));
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_save()
+            .withf(|_, record| {
+                record.state.expose_secret() == TEST_STATE
+                    && record.provider == TEST_PROVIDER
+                    && record.code_verifier.expose_secret() == TEST_CODE_VERIFIER
+                    && record.link_user_id.is_none()
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <BeginExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request {
+                    provider: TEST_PROVIDER.to_string(),
+                    link: false,
+                },
+                &RequestContext::default(),
+            )
+            .await;
+        // Assert execution success
+        assert_eq!(result.unwrap().authorization_url, TEST_AUTHORIZATION_URL);
+    }
+
+    #[rstest]
+    async fn test_begin_external_login_link(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        user_id_zero: Id,
+    ) {
+        // Mock setup
+        dependency_provider
+            .oidc_service
+            .expect_authorize()
+            .times(1)
+            .returning(|_| Ok(authorization()));
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_save()
+            .withf(move |_, record| record.link_user_id == Some(user_id_zero))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <BeginExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request {
+                    provider: TEST_PROVIDER.to_string(),
+                    link: true,
+                },
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+
+    #[rstest]
+    async fn test_begin_external_login_link_unauthenticated(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup
+        dependency_provider.oidc_service.expect_authorize().never();
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_save()
+            .never();
+        // Usecase Initialization
+        let usecase = <BeginExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request {
+                    provider: TEST_PROVIDER.to_string(),
+                    link: true,
+                },
+                &RequestContext::default(),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Unauthenticated);
+    }
+
+    #[rstest]
+    async fn test_begin_external_login_unknown_provider(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup
+        dependency_provider
+            .oidc_service
+            .expect_authorize()
+            .times(1)
+            .returning(|provider| Err(OidcError::UnknownProvider(provider.to_string())));
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_save()
+            .never();
+        // Usecase Initialization
+        let usecase = <BeginExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request {
+                    provider: "unknown".to_string(),
+                    link: false,
+                },
+                &RequestContext::default(),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Oidc(OidcError::UnknownProvider("unknown".to_string()))
+        );
+    }
+}
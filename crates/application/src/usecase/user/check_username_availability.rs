@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            user::{GetError, Repo},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{auth_strategy::AuthStrategy, user::UserName},
+    value_object::ValidationError,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub available: bool,
+}
+
+/// Tell whether a username can still be picked, for live form validation
+pub struct CheckUsernameAvailability<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error(transparent)]
+    Invalidity(#[from] ValidationError),
+    #[error("{}", GetError::Connection)]
+    Repo,
+}
+
+impl From<GetError> for Error {
+    fn from(_: GetError) -> Self {
+        Self::Repo
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for CheckUsernameAvailability<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.check_username_availability";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Check username availability: {:?}", req.username);
+        let username = UserName::parse(&req.username)?;
+        if username.is_reserved() {
+            return Ok(Response { available: false });
+        }
+        let taken = self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .exists_by_username(None, &username)
+            .await?;
+        Ok(Response { available: !taken })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gateway::mock::MockDependencyProvider, usecase::tests::fixtures::*};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_check_username_availability_available(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_username()
+            .withf(|_, username| username.as_ref() == TEST_USERNAME)
+            .times(1)
+            .returning(|_, _| Ok(false));
+        // Usecase Initialization
+        let usecase = <CheckUsernameAvailability<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+        };
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.unwrap().available);
+    }
+    #[rstest]
+    async fn test_check_username_availability_taken(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_username()
+            .withf(|_, username| username.as_ref() == TEST_USERNAME)
+            .times(1)
+            .returning(|_, _| Ok(true));
+        // Usecase Initialization
+        let usecase = <CheckUsernameAvailability<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+        };
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(!result.unwrap().available);
+    }
+    #[rstest]
+    async fn test_check_username_availability_reserved(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup -- reserved names never reach the repo
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_username()
+            .never();
+        // Usecase Initialization
+        let usecase = <CheckUsernameAvailability<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let req = Request {
+            username: "Admin".to_string(),
+        };
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert the name is unavailable
+        assert!(!result.unwrap().available);
+    }
+    #[rstest]
+    async fn test_check_username_availability_reserved_lookalike(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup -- a Cyrillic "А" does not get around the reservation
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_username()
+            .never();
+        // Usecase Initialization
+        let usecase = <CheckUsernameAvailability<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let req = Request {
+            username: "\u{410}dmin".to_string(),
+        };
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert the name is unavailable
+        assert!(!result.unwrap().available);
+    }
+    #[rstest]
+    async fn test_check_username_availability_fail_repo(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_username()
+            .times(1)
+            .returning(|_, _| Err(GetError::Connection));
+        // Usecase Initialization
+        let usecase = <CheckUsernameAvailability<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+        };
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Repo);
+    }
+    #[rstest]
+    fn test_authorize_none() {
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+        };
+        let result = CheckUsernameAvailability::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, None);
+        assert!(result.is_ok());
+    }
+}
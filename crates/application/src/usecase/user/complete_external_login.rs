@@ -0,0 +1,664 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            external_login::{Record as ExternalLoginRecord, Repo as ExternalLoginRepo, TakeError},
+            identifier::NewId,
+            user::{GetError, Repo as UserRepo, SaveError as UserSaveError},
+            user_identity::{
+                GetError as IdentityGetError, Repo as IdentityRepo, SaveError as IdentitySaveError,
+            },
+            Database,
+        },
+        service::{
+            clock::Clock,
+            oidc::{ExternalClaims, OidcError, OidcService},
+        },
+        AuthPackerProvider, ClockProvider, DatabaseProvider, OidcServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{
+        auth_context::PendingStep,
+        auth_strategy::AuthStrategy,
+        user::{Email, Id, Password, User, UserName},
+        user_identity::UserIdentity,
+    },
+    value_object::{Role, SecretString, ValidationError},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    begin_external_login::EXTERNAL_LOGIN_TTL_MINUTES,
+    login::{pack_auth, pending_step},
+};
+
+/// Numbered variants of a taken name tried for a new user
+const USERNAME_ATTEMPTS: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// Both handed back by the provider
+    pub state: SecretString,
+    pub code: SecretString,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub user_id: Id,
+    pub token: String,
+    /// Step the token still has to get through before it grants anything
+    pub pending: Option<PendingStep>,
+    /// Whether the user signed up with this login
+    pub created: bool,
+}
+
+/// Finish a login at an OpenID Connect provider, users logging in for the
+/// first time are signed up with the verified email of the provider
+pub struct CompleteExternalLogin<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("External login is unknown or was used already")]
+    InvalidState,
+    #[error("External login expired")]
+    Expired,
+    #[error(transparent)]
+    Oidc(#[from] OidcError),
+    #[error("Identity provider did not share a verified email")]
+    EmailMissing,
+    #[error("Email already registered, log in and link the identity instead")]
+    EmailRegistered,
+    #[error("{}", UserSaveError::UsernameTaken)]
+    UsernameTaken,
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error("{}", GetError::Connection)]
+    Repo,
+}
+
+/// Failures shared by the usecases redeeming an external login
+#[derive(Debug)]
+pub(super) enum RedeemError {
+    InvalidState,
+    Expired,
+    Oidc(OidcError),
+    Repo,
+}
+
+impl From<RedeemError> for Error {
+    fn from(err: RedeemError) -> Self {
+        match err {
+            RedeemError::InvalidState => Self::InvalidState,
+            RedeemError::Expired => Self::Expired,
+            RedeemError::Oidc(err) => Self::Oidc(err),
+            RedeemError::Repo => Self::Repo,
+        }
+    }
+}
+
+impl From<GetError> for Error {
+    fn from(_: GetError) -> Self {
+        Self::Repo
+    }
+}
+
+impl From<UserSaveError> for Error {
+    fn from(err: UserSaveError) -> Self {
+        match err {
+            UserSaveError::Connection => Self::Repo,
+            UserSaveError::EmailTaken => Self::EmailRegistered,
+            UserSaveError::UsernameTaken => Self::UsernameTaken,
+        }
+    }
+}
+
+impl From<IdentitySaveError> for Error {
+    fn from(_: IdentitySaveError) -> Self {
+        Self::Repo
+    }
+}
+
+/// Takes the login started with `state` and redeems `code` of it, the login
+/// has to be started for `link_user_id`. The login is gone afterwards,
+/// whatever the outcome
+pub(super) async fn redeem<D>(
+    dependency_provider: &D,
+    state: &str,
+    code: &str,
+    link_user_id: Option<Id>,
+    now: DateTime<Utc>,
+) -> Result<(ExternalLoginRecord, ExternalClaims), RedeemError>
+where
+    D: DatabaseProvider + OidcServiceProvider,
+{
+    let record = dependency_provider
+        .database()
+        .external_login_repo()
+        .take(None, state)
+        .await
+        .map_err(|err| match err {
+            TakeError::NotFound => RedeemError::InvalidState,
+            TakeError::Connection => RedeemError::Repo,
+        })?;
+    if record.created_at + Duration::minutes(EXTERNAL_LOGIN_TTL_MINUTES) < now {
+        return Err(RedeemError::Expired);
+    }
+    // a login started for someone else must not end up in their account
+    if record.link_user_id != link_user_id {
+        log::warn!("External login redeemed for another purpose than started");
+        return Err(RedeemError::InvalidState);
+    }
+    let claims = dependency_provider
+        .oidc_service()
+        .exchange_code(
+            &record.provider,
+            code,
+            record.code_verifier.expose_secret(),
+            &record.nonce,
+        )
+        .await
+        .map_err(RedeemError::Oidc)?;
+    Ok((record, claims))
+}
+
+/// Names tried for a new user, derived from what the provider tells about
+/// them and made to pass [`UserName::parse`]
+fn username_candidates(claims: &ExternalClaims) -> Vec<UserName> {
+    let hint = claims
+        .preferred_username
+        .as_deref()
+        .or_else(|| claims.email.as_deref()?.split('@').next())
+        .unwrap_or_default();
+    let mut base: String = hint
+        .chars()
+        .filter(|c| c.is_alphanumeric() || "_-.".contains(*c))
+        .skip_while(|c| !c.is_alphanumeric())
+        // leaves room for the number of a variant
+        .take(UserName::max_len() - 3)
+        .collect();
+    if base.is_empty() {
+        base = "member".to_string();
+    }
+    if base.chars().count() < UserName::min_len() {
+        base.push_str("_user");
+    }
+    std::iter::once(base.clone())
+        .chain((2..=USERNAME_ATTEMPTS + 1).map(|n| format!("{base}-{n}")))
+        .filter_map(|name| UserName::parse(name).ok())
+        .filter(|name| !name.is_reserved())
+        .collect()
+}
+
+impl<D> CompleteExternalLogin<D>
+where
+    D: DatabaseProvider,
+{
+    /// Signs up a user with the identity of `claims` at `provider` linked
+    async fn sign_up(&self, provider: &str, claims: &ExternalClaims) -> Result<User, Error> {
+        let email = match &claims.email {
+            Some(email) if claims.email_verified => Email::parse(email)?,
+            _ => return Err(Error::EmailMissing),
+        };
+        let database = self.dependency_provider.database();
+        // taking over an account by an email the provider vouches for is
+        // left to its owner, who can link the identity after logging in
+        if database.user_repo().exists_by_email(None, &email).await? {
+            return Err(Error::EmailRegistered);
+        }
+        let mut username = None;
+        for candidate in username_candidates(claims) {
+            if !database
+                .user_repo()
+                .exists_by_username(None, &candidate)
+                .await?
+            {
+                username = Some(candidate);
+                break;
+            }
+        }
+        let username = username.ok_or(Error::UsernameTaken)?;
+        let id_gen = database.signuo_id_gen();
+        let id = id_gen.new_id().await.map_err(|_| Error::Repo)?;
+        // nobody knows it, the user can set a password by updating it
+        let password = id_gen.new_id().await.map_err(|_| Error::Repo)?;
+        let user = User::new(
+            Id::new(id),
+            Role::user(),
+            email,
+            username,
+            Password::new_unchecked(password.to_string()),
+        );
+        let mut transaction = database.begin_transaction().await;
+        database
+            .user_repo()
+            .save(Some(&mut transaction), user.clone().into())
+            .await?;
+        database
+            .user_identity_repo()
+            .save(
+                Some(&mut transaction),
+                UserIdentity::new(provider, &claims.subject, user.id()).into(),
+            )
+            .await?;
+        database
+            .commit_transaction(transaction)
+            .await
+            .map_err(|_| Error::Repo)?;
+        log::info!("User {} signed up through {provider}", user.id());
+        Ok(user)
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for CompleteExternalLogin<D>
+where
+    D: DatabaseProvider + OidcServiceProvider + AuthPackerProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.complete_external_login";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Complete external login");
+        let now = self.dependency_provider.clock().now();
+        let (record, claims) = redeem(
+            &*self.dependency_provider,
+            req.state.expose_secret(),
+            req.code.expose_secret(),
+            None,
+            now,
+        )
+        .await?;
+        let (user, created) = match self
+            .dependency_provider
+            .database()
+            .user_identity_repo()
+            .get(None, &record.provider, &claims.subject)
+            .await
+        {
+            Ok(identity) => {
+                let user = self
+                    .dependency_provider
+                    .database()
+                    .user_repo()
+                    .get(None, identity.identity.user_id())
+                    .await?
+                    .user;
+                (user, false)
+            }
+            Err(IdentityGetError::NotFound) => {
+                (self.sign_up(&record.provider, &claims).await?, true)
+            }
+            Err(IdentityGetError::Connection) => return Err(Error::Repo),
+        };
+        // the provider stands in for the password, not the second factor
+        let pending = pending_step(&*self.dependency_provider, &user)
+            .await
+            .map_err(|_| Error::Repo)?;
+        let token = pack_auth(&*self.dependency_provider, &user, pending)
+            .await
+            .map_err(|_| Error::Repo)?;
+        Ok(Response {
+            user_id: user.id(),
+            token,
+            pending,
+            created,
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::{
+                mfa::GetError as MfaGetError, role::GetError as RoleGetError,
+                user::Record as UserRecord, user_identity::Record as UserIdentityRecord,
+            },
+            mock::MockDependencyProvider,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::signup_process::Id as SignupId;
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            state: TEST_STATE.into(),
+            code: TEST_CODE.into(),
+        }
+    }
+
+    fn mock_redeem(
+        dependency_provider: &mut MockDependencyProvider,
+        record: ExternalLoginRecord,
+        claims: ExternalClaims,
+    ) {
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_take()
+            .withf(|_, actual_state| actual_state == TEST_STATE)
+            .times(1)
+            .returning(move |_, _| Ok(record.clone()));
+        dependency_provider
+            .oidc_service
+            .expect_exchange_code()
+            .withf(|provider, code, code_verifier, nonce| {
+                provider == TEST_PROVIDER
+                    && code == TEST_CODE
+                    && code_verifier == TEST_CODE_VERIFIER
+                    && nonce == TEST_NONCE
+            })
+            .times(1)
+            .returning(move |_, _, _, _| Ok(claims.clone()));
+    }
+
+    fn mock_pack_auth(dependency_provider: &mut MockDependencyProvider) {
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(MfaGetError::NotFound));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(RoleGetError::NotFound));
+        dependency_provider
+            .auth_packer
+            .expect_pack_auth()
+            .times(1)
+            .returning(|_| TEST_TOKEN.to_string());
+    }
+
+    #[rstest]
+    async fn test_complete_external_login_linked(
+        mut dependency_provider: MockDependencyProvider,
+        external_login_record: ExternalLoginRecord,
+        external_claims: ExternalClaims,
+        user_identity_record: UserIdentityRecord,
+        user_record: UserRecord,
+        user_id: Id,
+    ) {
+        // Mock setup
+        mock_redeem(
+            &mut dependency_provider,
+            external_login_record,
+            external_claims,
+        );
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_get()
+            .withf(|_, provider, subject| provider == TEST_PROVIDER && subject == TEST_SUBJECT)
+            .times(1)
+            .returning(move |_, _, _| Ok(user_identity_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .withf(move |_, actual_id| actual_id == &user_id)
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider.db.user_repo.expect_save().never();
+        mock_pack_auth(&mut dependency_provider);
+        // Usecase Initialization
+        let usecase = <CompleteExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution success
+        let result = result.unwrap();
+        assert_eq!(result.user_id, user_id);
+        assert_eq!(result.token, TEST_TOKEN);
+        assert!(!result.created);
+    }
+
+    #[rstest]
+    async fn test_complete_external_login_sign_up(
+        mut dependency_provider: MockDependencyProvider,
+        external_login_record: ExternalLoginRecord,
+        external_claims: ExternalClaims,
+    ) {
+        // Mock setup
+        mock_redeem(
+            &mut dependency_provider,
+            external_login_record,
+            external_claims,
+        );
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _, _| Err(IdentityGetError::NotFound));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .times(1)
+            .returning(|_, _| Ok(false));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_username()
+            .times(1)
+            .returning(|_, _| Ok(false));
+        dependency_provider
+            .db
+            .signup_id_gen
+            .expect_new_id()
+            .times(2)
+            .returning(|| Ok(SignupId::new(uuid::Uuid::new_v4())));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .withf(|transaction, record| {
+                transaction.is_some()
+                    && record.user.username() == &UserName::new_unchecked(TEST_USERNAME)
+                    && record.user.email() == &Email::new_unchecked(TEST_EMAIL)
+                    && record.user.role() == &Role::user()
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_save()
+            .withf(|transaction, record| {
+                transaction.is_some()
+                    && record.identity.provider() == TEST_PROVIDER
+                    && record.identity.subject() == TEST_SUBJECT
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_pack_auth(&mut dependency_provider);
+        // Usecase Initialization
+        let usecase = <CompleteExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.unwrap().created);
+    }
+
+    #[rstest]
+    async fn test_complete_external_login_email_registered(
+        mut dependency_provider: MockDependencyProvider,
+        external_login_record: ExternalLoginRecord,
+        external_claims: ExternalClaims,
+    ) {
+        // Mock setup
+        mock_redeem(
+            &mut dependency_provider,
+            external_login_record,
+            external_claims,
+        );
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _, _| Err(IdentityGetError::NotFound));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_exists_by_email()
+            .times(1)
+            .returning(|_, _| Ok(true));
+        dependency_provider.db.user_repo.expect_save().never();
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_save()
+            .never();
+        // Usecase Initialization
+        let usecase = <CompleteExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::EmailRegistered);
+    }
+
+    #[rstest]
+    async fn test_complete_external_login_unverified_email(
+        mut dependency_provider: MockDependencyProvider,
+        external_login_record: ExternalLoginRecord,
+        mut external_claims: ExternalClaims,
+    ) {
+        // fixtures
+        external_claims.email_verified = false;
+        // Mock setup
+        mock_redeem(
+            &mut dependency_provider,
+            external_login_record,
+            external_claims,
+        );
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _, _| Err(IdentityGetError::NotFound));
+        dependency_provider.db.user_repo.expect_save().never();
+        // Usecase Initialization
+        let usecase = <CompleteExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::EmailMissing);
+    }
+
+    #[rstest]
+    async fn test_complete_external_login_expired(
+        mut dependency_provider: MockDependencyProvider,
+        external_login_record: ExternalLoginRecord,
+    ) {
+        // fixtures
+        dependency_provider.clock.set(
+            external_login_record.created_at + Duration::minutes(EXTERNAL_LOGIN_TTL_MINUTES + 1),
+        );
+        // Mock setup
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_take()
+            .times(1)
+            .returning(move |_, _| Ok(external_login_record.clone()));
+        dependency_provider
+            .oidc_service
+            .expect_exchange_code()
+            .never();
+        // Usecase Initialization
+        let usecase = <CompleteExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Expired);
+    }
+
+    #[rstest]
+    async fn test_complete_external_login_started_for_linking(
+        mut dependency_provider: MockDependencyProvider,
+        mut external_login_record: ExternalLoginRecord,
+        user_id: Id,
+    ) {
+        // fixtures
+        external_login_record.link_user_id = Some(user_id);
+        // Mock setup
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_take()
+            .times(1)
+            .returning(move |_, _| Ok(external_login_record.clone()));
+        dependency_provider
+            .oidc_service
+            .expect_exchange_code()
+            .never();
+        // Usecase Initialization
+        let usecase = <CompleteExternalLogin<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidState);
+    }
+
+    #[rstest]
+    #[case(Some("jane.doe"), None, "jane.doe")]
+    #[case(None, Some("jo@example.com"), "jo_user")]
+    #[case(Some("__Zoë Smith!"), None, "ZoëSmith")]
+    #[case(Some("admin"), None, "admin-2")]
+    #[case(None, None, "member")]
+    fn test_username_candidates(
+        #[case] preferred_username: Option<&str>,
+        #[case] email: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let claims = ExternalClaims {
+            subject: TEST_SUBJECT.to_string(),
+            email: email.map(String::from),
+            email_verified: true,
+            preferred_username: preferred_username.map(String::from),
+        };
+        let candidates = username_candidates(&claims);
+        assert_eq!(candidates[0], UserName::new_unchecked(expected));
+        assert!(candidates.len() >= USERNAME_ATTEMPTS);
+    }
+}
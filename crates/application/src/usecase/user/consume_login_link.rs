@@ -0,0 +1,401 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            token::{
+                AttemptError, InvalidateError, Purpose, Repo as TokenRepo, Scope,
+                VerifyError as TokenRepoError,
+            },
+            user::{GetError, Repo},
+            Database,
+        },
+        service::clock::Clock,
+        AuthPackerProvider, ClockProvider, DatabaseProvider, LoginLinkPolicyProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::{
+    auth_context::PendingStep,
+    auth_strategy::AuthStrategy,
+    user::{Id, UserName},
+};
+use ca_domain::value_object::SecretString;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::login::{pack_auth, pending_step};
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub username: String,
+    pub token: SecretString,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub user_id: Id,
+    pub token: String,
+    /// Step the token still has to get through before it grants anything
+    pub pending: Option<PendingStep>,
+}
+
+pub struct ConsumeLoginLink<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Login link is invalid")]
+    InvalidLogin,
+    #[error("Login link expired")]
+    Expired,
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Token Repo error: {0}")]
+    TokenRepoError(#[from] TokenRepoError),
+    #[error("Token Repo error: {0}")]
+    Invalidate(#[from] InvalidateError),
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+}
+
+// an unknown user looks like a wrong token, so links can not be used to
+// find out who has an account
+impl From<GetError> for Error {
+    fn from(err: GetError) -> Self {
+        match err {
+            GetError::NotFound => Self::InvalidLogin,
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for ConsumeLoginLink<D>
+where
+    D: DatabaseProvider + AuthPackerProvider + LoginLinkPolicyProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.consume_login_link";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Consume login link: {:?}", req.username);
+        let now = self.dependency_provider.clock().now();
+        let policy = self.dependency_provider.login_link_policy();
+        let user_name = UserName::new_unchecked(&req.username);
+        let record = self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .get_by_username(None, user_name)
+            .await?;
+        let user = record.user;
+        let scope = Scope::new(Purpose::Login, user.id());
+        // consuming the token is the check, so a link logs in only once
+        if let Err(err) = self
+            .dependency_provider
+            .database()
+            .token_repo()
+            .verify(
+                None,
+                &scope,
+                &user.email().canonical(),
+                req.token.expose_secret(),
+                policy.ttl,
+                now,
+            )
+            .await
+        {
+            log::error!("Token Repo error: {:?}", err);
+            return match err {
+                TokenRepoError::TokenExpired => Err(Error::Expired),
+                // a wrong token counts towards throwing the link away, so
+                // short codes can not be guessed
+                TokenRepoError::Mismatch | TokenRepoError::NotFound => {
+                    let failures = self
+                        .dependency_provider
+                        .database()
+                        .token_repo()
+                        .record_failed_attempt(None, &scope, now)
+                        .await?;
+                    if failures >= policy.max_attempts {
+                        log::warn!(
+                            "Login link of user {} dropped after {failures} attempts",
+                            user.id()
+                        );
+                        self.dependency_provider
+                            .database()
+                            .token_repo()
+                            .invalidate(None, &scope)
+                            .await?;
+                        self.dependency_provider
+                            .database()
+                            .token_repo()
+                            .clear_attempts(None, &scope)
+                            .await?;
+                    }
+                    Err(Error::InvalidLogin)
+                }
+                err => Err(err.into()),
+            };
+        }
+        // a login link replaces the password, not the second factor
+        let pending = pending_step(&*self.dependency_provider, &user)
+            .await
+            .map_err(|_| Error::Repo)?;
+        let token = pack_auth(&*self.dependency_provider, &user, pending)
+            .await
+            .map_err(|_| Error::Repo)?;
+        Ok(Response {
+            user_id: user.id(),
+            token,
+            pending,
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::{
+                mfa::GetError as MfaGetError, role::GetError as RoleGetError,
+                user::Record as UserRecord,
+            },
+            mock::MockDependencyProvider,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::{entity::auth_context::AuthContext, value_object::Role};
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            username: TEST_USERNAME.to_string(),
+            token: TEST_TOKEN.into(),
+        }
+    }
+
+    #[rstest]
+    async fn test_consume_login_link_success(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let user_id = user_record.user.id();
+        let scope = Scope::new(Purpose::Login, user_id);
+        let auth_context = AuthContext::new(user_id, Role::user());
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .withf(move |_, actual_username| {
+                actual_username == &UserName::new_unchecked(TEST_USERNAME)
+            })
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            // makes sure the token is checked within the login scope of the user
+            .withf(move |_, actual_scope, actual_email, actual_token, _, _| {
+                actual_scope == &scope && actual_email == TEST_EMAIL && actual_token == TEST_TOKEN
+            })
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(MfaGetError::NotFound));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(RoleGetError::NotFound));
+        dependency_provider
+            .auth_packer
+            .expect_pack_auth()
+            // the same token a password login issues
+            .withf(move |actual_auth_context| actual_auth_context == &auth_context)
+            .times(1)
+            .returning(|_| "jwt".to_string());
+        // Usecase Initialization
+        let usecase = <ConsumeLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution success
+        let result = result.unwrap();
+        assert_eq!(result.user_id, user_id);
+        assert_eq!(result.token, "jwt");
+    }
+
+    #[rstest]
+    async fn test_consume_login_link_mismatch(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _, _, _, _| Err(TokenRepoError::NotFound));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .times(1)
+            .returning(|_, _, _| Ok(1));
+        // the link stays usable below the limit
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .never();
+        dependency_provider.auth_packer.expect_pack_auth().never();
+        // Usecase Initialization
+        let usecase = <ConsumeLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidLogin);
+    }
+
+    #[rstest]
+    async fn test_consume_login_link_unknown_user(mut dependency_provider: MockDependencyProvider) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(|_, _| Err(GetError::NotFound));
+        dependency_provider.auth_packer.expect_pack_auth().never();
+        // Usecase Initialization
+        let usecase = <ConsumeLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert the same error as for a wrong token
+        assert_eq!(result.unwrap_err(), Error::InvalidLogin);
+    }
+
+    #[rstest]
+    async fn test_consume_login_link_too_many_attempts(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let scope = Scope::new(Purpose::Login, user_record.user.id());
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _, _, _, _| Err(TokenRepoError::Mismatch));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .times(1)
+            .returning(|_, _, _| Ok(5));
+        let expected_scope = scope.clone();
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            // makes sure the guessed link is thrown away
+            .withf(move |_, actual_scope| actual_scope == &expected_scope)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_clear_attempts()
+            .withf(move |_, actual_scope| actual_scope == &scope)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <ConsumeLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidLogin);
+    }
+
+    #[rstest]
+    async fn test_consume_login_link_expired(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _, _, _, _| Err(TokenRepoError::TokenExpired));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_failed_attempt()
+            .never();
+        // Usecase Initialization
+        let usecase = <ConsumeLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::Expired);
+    }
+}
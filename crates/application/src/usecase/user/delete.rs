@@ -8,10 +8,13 @@ use crate::{
         },
         DatabaseProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 
-use ca_domain::entity::{auth_strategy::AuthStrategy, user::Id};
+use ca_domain::{
+    entity::{auth_strategy::AuthStrategy, user::Id},
+    value_object::Permission,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -52,8 +55,13 @@ where
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "user.delete";
 
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("Delete User by ID: {:?}", req);
         self.dependency_provider
             .database()
@@ -68,9 +76,15 @@ where
             dependency_provider,
         }
     }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
     fn auth_strategy(&self) -> AuthStrategy {
         AuthStrategy::AdminOnly
     }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::USERS_DELETE)]
+    }
 }
 
 #[cfg(test)]
@@ -100,7 +114,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
     }
@@ -121,7 +135,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -143,7 +157,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound);
@@ -167,7 +181,7 @@ mod tests {
         let result = Delete::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_none(user_record: UserRecord) {
@@ -178,6 +192,6 @@ mod tests {
         let result =
             Delete::new(Arc::new(MockDependencyProvider::default())).authorize(&req, auth_context);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
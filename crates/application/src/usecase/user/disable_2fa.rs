@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            mfa::{DeleteError, GetError, RecoveryCodeError, Repo as MfaRepo, StepError},
+            token::AttemptError,
+            Database,
+        },
+        service::clock::Clock,
+        ClockProvider, DatabaseProvider, TotpServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::auth_strategy::AuthStrategy;
+use ca_domain::value_object::SecretString;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::verify_mfa::{check_second_factor, Check};
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// TOTP or recovery code, a stolen token alone can not turn it off
+    pub code: SecretString,
+}
+
+/// Turn off the second factor of the current user
+pub struct Disable2fa<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error("Two factor authentication is required for admins")]
+    RequiredForRole,
+    #[error("Two factor authentication was not enabled")]
+    NotEnabled,
+    #[error("Second factor code is invalid")]
+    InvalidCode,
+    #[error("Too many second factor codes tried, retry later")]
+    TooManyAttempts,
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+    #[error("Second factor repo error: {0}")]
+    RecoveryCode(#[from] RecoveryCodeError),
+    #[error("Second factor repo error: {0}")]
+    Step(#[from] StepError),
+}
+
+impl From<GetError> for Error {
+    fn from(err: GetError) -> Self {
+        match err {
+            GetError::NotFound => Self::NotEnabled,
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<DeleteError> for Error {
+    fn from(_: DeleteError) -> Self {
+        Self::Repo
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for Disable2fa<D>
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = ();
+    type Error = Error;
+    const NAME: &'static str = "user.disable_2fa";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Disable two factor authentication");
+        let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+        if auth_context.is_admin() {
+            return Err(Error::RequiredForRole);
+        }
+        let user_id = auth_context.user_id;
+        let now = self.dependency_provider.clock().now();
+        let record = self
+            .dependency_provider
+            .database()
+            .mfa_repo()
+            .get(None, user_id)
+            .await?;
+        if record.confirmed {
+            match check_second_factor::<_, Error>(
+                &*self.dependency_provider,
+                &record,
+                req.code.expose_secret(),
+                now,
+            )
+            .await?
+            {
+                Check::Accepted => {}
+                Check::Rejected => return Err(Error::InvalidCode),
+                Check::Locked => return Err(Error::TooManyAttempts),
+            }
+        }
+        // an unconfirmed enrollment is dropped without a code
+        self.dependency_provider
+            .database()
+            .mfa_repo()
+            .delete(None, user_id)
+            .await?;
+        Ok(())
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Authenticated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::mfa::Record as MfaRecord, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::{auth_context::AuthContext, user::Id};
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            code: TEST_TOTP_CODE.into(),
+        }
+    }
+
+    #[rstest]
+    async fn test_disable_2fa_with_recovery_code(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+        user_id_zero: Id,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .totp_service
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _| None);
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_recovery_code()
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_delete()
+            .withf(move |_, actual_user_id| actual_user_id == &user_id_zero)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <Disable2fa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+
+    #[rstest]
+    async fn test_disable_2fa_invalid_code(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .totp_service
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _| None);
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_recovery_code()
+            .times(1)
+            .returning(|_, _, _| Err(RecoveryCodeError::NotFound));
+        dependency_provider.db.mfa_repo.expect_delete().never();
+        // Usecase Initialization
+        let usecase = <Disable2fa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidCode);
+    }
+
+    #[rstest]
+    async fn test_disable_2fa_admin(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_admin: AuthContext,
+    ) {
+        // Mock setup
+        dependency_provider.db.mfa_repo.expect_delete().never();
+        // Usecase Initialization
+        let usecase = <Disable2fa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_admin)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::RequiredForRole);
+    }
+}
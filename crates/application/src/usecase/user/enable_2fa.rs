@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            mfa::{GetError as MfaGetError, Record, Repo as MfaRepo, SaveError},
+            user::{GetError, Repo},
+            Database,
+        },
+        service::totp::TotpService,
+        DatabaseProvider, TotpServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::{
+    auth_context::PendingStep,
+    auth_strategy::AuthStrategy,
+    user::{Id, User},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request;
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    /// Base32 secret for authenticator apps that can not scan the URI
+    pub secret: String,
+    pub uri: String,
+}
+
+/// Start enrolling a TOTP second factor for the current user, it only takes
+/// effect once a code of it is confirmed with
+/// [`Confirm2fa`](super::confirm_2fa::Confirm2fa)
+pub struct Enable2fa<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error("Two factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error("User {0} not found")]
+    NotFound(Id),
+    #[error("{}", GetError::Connection)]
+    Repo,
+}
+
+impl From<(GetError, Id)> for Error {
+    fn from((err, id): (GetError, Id)) -> Self {
+        match err {
+            GetError::NotFound => Self::NotFound(id),
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(_: SaveError) -> Self {
+        Self::Repo
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for Enable2fa<D>
+where
+    D: DatabaseProvider + TotpServiceProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.enable_2fa";
+
+    async fn exec(
+        &self,
+        _req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Enable two factor authentication");
+        let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+        let user_id = auth_context.user_id;
+        match self
+            .dependency_provider
+            .database()
+            .mfa_repo()
+            .get(None, user_id)
+            .await
+        {
+            Ok(record) if record.confirmed => return Err(Error::AlreadyEnabled),
+            // an unconfirmed secret is simply replaced
+            Ok(_) | Err(MfaGetError::NotFound) => {}
+            Err(MfaGetError::Connection) => return Err(Error::Repo),
+        }
+        let user: User = self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .get(None, user_id)
+            .await
+            .map_err(|err| (err, user_id))?
+            .into();
+        let totp_service = self.dependency_provider.totp_service();
+        let secret = totp_service.generate_secret();
+        let uri = totp_service.provisioning_uri(&secret, user.username().as_ref());
+        self.dependency_provider
+            .database()
+            .mfa_repo()
+            .save(
+                None,
+                Record {
+                    user_id,
+                    secret: secret.as_str().into(),
+                    confirmed: false,
+                    last_used_step: None,
+                },
+            )
+            .await?;
+        Ok(Response { secret, uri })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Authenticated
+    }
+    fn pending_step(&self) -> Option<PendingStep> {
+        Some(PendingStep::MfaEnrollment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::{mfa::Record as MfaRecord, user::Record as UserRecord},
+            mock::MockDependencyProvider,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_enable_2fa_success(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        user_record: UserRecord,
+        user_id_zero: Id,
+    ) {
+        // fixtures
+        let uri = format!("otpauth://totp/{TEST_USERNAME}?secret={TEST_TOTP_SECRET}");
+        let expected_uri = uri.clone();
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(MfaGetError::NotFound));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .withf(move |_, actual_id| actual_id == &user_id_zero)
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .totp_service
+            .expect_generate_secret()
+            .times(1)
+            .returning(|| TEST_TOTP_SECRET.to_string());
+        dependency_provider
+            .totp_service
+            .expect_provisioning_uri()
+            .withf(|actual_secret, actual_account| {
+                actual_secret == TEST_TOTP_SECRET && actual_account == TEST_USERNAME
+            })
+            .times(1)
+            .returning(move |_, _| uri.clone());
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_save()
+            // makes sure the secret does not count before it is confirmed
+            .withf(move |_, actual_record| {
+                actual_record.user_id == user_id_zero
+                    && actual_record.secret.expose_secret() == TEST_TOTP_SECRET
+                    && !actual_record.confirmed
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <Enable2fa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request,
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution success
+        let result = result.unwrap();
+        assert_eq!(result.secret, TEST_TOTP_SECRET);
+        assert_eq!(result.uri, expected_uri);
+    }
+
+    #[rstest]
+    async fn test_enable_2fa_already_enabled(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        // the confirmed secret is kept
+        dependency_provider.db.mfa_repo.expect_save().never();
+        // Usecase Initialization
+        let usecase = <Enable2fa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request,
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::AlreadyEnabled);
+    }
+
+    #[rstest]
+    fn test_authorize_enrollment_pending(auth_context_admin: AuthContext) {
+        let usecase = Enable2fa::new(Arc::new(MockDependencyProvider::default()));
+        let result = usecase.authorize(
+            &Request,
+            Some(
+                auth_context_admin
+                    .clone()
+                    .with_pending(PendingStep::MfaEnrollment),
+            ),
+        );
+        assert!(result.is_ok());
+        // a login waiting for its second factor can not enroll another one
+        let result = usecase.authorize(
+            &Request,
+            Some(auth_context_admin.with_pending(PendingStep::Mfa)),
+        );
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
+    }
+}
@@ -8,9 +8,12 @@ use crate::{
         },
         DatabaseProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{auth_strategy::AuthStrategy, user::User},
+    value_object::Permission,
 };
-use ca_domain::entity::{auth_strategy::AuthStrategy, user::User};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -48,8 +51,13 @@ where
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "user.get_all";
 
-    async fn exec(&self, _req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        _req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("Get all users");
         let users = self
             .dependency_provider
@@ -71,6 +79,9 @@ where
     fn auth_strategy(&self) -> AuthStrategy {
         AuthStrategy::AdminOnly
     }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::USERS_READ)]
+    }
 }
 
 #[cfg(test)]
@@ -102,7 +113,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -124,7 +135,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -146,7 +157,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -165,7 +176,14 @@ mod tests {
         let result = GetAll::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+    #[rstest]
+    fn test_authorize_support(auth_context_support: AuthContext) {
+        let req = Request;
+        let result = GetAll::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_support));
+        assert!(result.is_ok());
     }
     #[rstest]
     fn test_authorize_none() {
@@ -174,6 +192,6 @@ mod tests {
         let result =
             GetAll::new(Arc::new(MockDependencyProvider::default())).authorize(&req, auth_context);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            user::{GetError, Repo},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{
+        auth_context::Session,
+        auth_strategy::AuthStrategy,
+        user::{Id, User},
+    },
+    value_object::Role,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request;
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub user: User,
+    pub role: Role,
+    pub session: Option<Session>,
+}
+
+/// Get the user the request was authenticated as
+pub struct GetMe<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error("User {0} not found")]
+    NotFound(Id),
+    #[error("{}", GetError::Connection)]
+    Repo,
+}
+
+impl From<(GetError, Id)> for Error {
+    fn from((err, id): (GetError, Id)) -> Self {
+        match err {
+            GetError::NotFound => Self::NotFound(id),
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for GetMe<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.get_me";
+
+    async fn exec(
+        &self,
+        _req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Get current user");
+        let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+        let user_id = auth_context.user_id;
+        let user: User = self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .get(None, user_id)
+            .await
+            .map_err(|err| (err, user_id))?
+            .into();
+        Ok(Self::Response {
+            role: user.role().clone(),
+            user,
+            session: auth_context.session.clone(),
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Authenticated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::user::Record as UserRecord, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use chrono::{Duration, Utc};
+    use rstest::*;
+
+    #[rstest]
+    async fn test_get_me_success(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let user_id = user_record.user.id();
+        let issued_at = Utc::now();
+        let expires_at = issued_at + Duration::minutes(10);
+        let auth_context =
+            AuthContext::new(user_id, Role::user()).with_session(issued_at, expires_at);
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .withf(move |_, actual_id| actual_id == &user_id)
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        // Usecase Initialization
+        let usecase = <GetMe<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request,
+                &RequestContext::default().with_auth_context(Some(auth_context)),
+            )
+            .await;
+        // Assert execution success
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.user.id(), user_id);
+        assert_eq!(result.user.username().to_string(), TEST_USERNAME);
+        assert_eq!(result.role, Role::user());
+        assert_eq!(
+            result.session,
+            Some(Session {
+                issued_at,
+                expires_at
+            })
+        );
+    }
+    #[rstest]
+    async fn test_get_me_fail_get_not_found(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+    ) {
+        // fixtures
+        let user_id = auth_context_user.user_id;
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .withf(move |_, actual_id| actual_id == &user_id)
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        // Usecase Initialization
+        let usecase = <GetMe<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                Request,
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::NotFound(user_id));
+    }
+    #[rstest]
+    async fn test_get_me_fail_unauthenticated(dependency_provider: MockDependencyProvider) {
+        // Usecase Initialization
+        let usecase = <GetMe<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- no repo calls expected
+        let result = usecase.exec(Request, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::Unauthenticated);
+    }
+    #[rstest]
+    fn test_authorize_user(auth_context_user: AuthContext) {
+        let result = GetMe::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&Request, Some(auth_context_user));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_none() {
+        let result =
+            GetMe::new(Arc::new(MockDependencyProvider::default())).authorize(&Request, None);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
+    }
+}
@@ -8,11 +8,14 @@ use crate::{
         },
         DatabaseProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
-use ca_domain::entity::{
-    auth_strategy::AuthStrategy,
-    user::{Id, User},
+use ca_domain::{
+    entity::{
+        auth_strategy::AuthStrategy,
+        user::{Id, User},
+    },
+    value_object::Permission,
 };
 
 use serde::{Deserialize, Serialize};
@@ -57,8 +60,13 @@ where
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "user.get_one";
 
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("Get user by ID");
         let user = self
             .dependency_provider
@@ -82,6 +90,9 @@ where
     fn extract_owner(&self, req: &Self::Request) -> Option<Id> {
         Some(req.id)
     }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::USERS_READ)]
+    }
 }
 
 #[cfg(test)]
@@ -120,15 +131,15 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let result = result.unwrap();
         assert_eq!(result.user.id(), user_id);
         assert_eq!(result.user.username().to_string(), TEST_USERNAME);
         assert_eq!(result.user.email().to_string(), TEST_EMAIL);
-        assert_eq!(result.user.role(), &Role::User);
-        assert_eq!(result.user.password().to_string(), TEST_PASSWORD);
+        assert_eq!(result.user.role(), &Role::user());
+        assert_eq!(result.user.password().expose_secret(), TEST_PASSWORD);
     }
     #[rstest]
     async fn test_get_one_fail_get_connection(
@@ -153,7 +164,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -181,7 +192,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound);
@@ -204,7 +215,7 @@ mod tests {
         let result = GetOne::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_user_owner(user_record: UserRecord, mut auth_context_user: AuthContext) {
@@ -225,6 +236,6 @@ mod tests {
         let result =
             GetOne::new(Arc::new(MockDependencyProvider::default())).authorize(&req, auth_context);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
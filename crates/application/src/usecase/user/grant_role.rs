@@ -0,0 +1,268 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            role::{GetError as RoleGetError, Repo as RoleRepo},
+            user::{GetError, Repo, SaveError},
+            Database,
+        },
+        DatabaseProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{auth_strategy::AuthStrategy, user::Id},
+    value_object::{Permission, Role},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub id: Id,
+    pub role: String,
+}
+
+pub type Response = ();
+
+/// Grant a role to a user, replacing the role the user had before
+pub struct GrantRole<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("User {0} not found")]
+    NotFound(Id),
+    #[error("Role {0} not found")]
+    RoleNotFound(Role),
+    #[error("{}", SaveError::Connection)]
+    Repo,
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::Connection | SaveError::EmailTaken | SaveError::UsernameTaken => Self::Repo,
+        }
+    }
+}
+
+impl From<(GetError, Id)> for Error {
+    fn from((err, id): (GetError, Id)) -> Self {
+        match err {
+            GetError::NotFound => Self::NotFound(id),
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<(RoleGetError, Role)> for Error {
+    fn from((err, role): (RoleGetError, Role)) -> Self {
+        match err {
+            RoleGetError::NotFound => Self::RoleNotFound(role),
+            RoleGetError::Connection => Self::Repo,
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Usecase<D> for GrantRole<D>
+where
+    D: DatabaseProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.grant_role";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Grant role to user: {:?}", req);
+        let role = Role::new(req.role);
+        let role = self
+            .dependency_provider
+            .database()
+            .role_repo()
+            .get(None, role.clone())
+            .await
+            .map_err(|err| (err, role))?
+            .role;
+        let mut record = self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .get(None, req.id)
+            .await
+            .map_err(|err| (err, req.id))?;
+        record.user.set_role(role);
+        self.dependency_provider
+            .database()
+            .user_repo()
+            .save(None, record)
+            .await?;
+        Ok(())
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn extract_target(&self, req: &Self::Request) -> Vec<String> {
+        vec![req.id.to_string()]
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::AdminOnly
+    }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::ROLES_MANAGE)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::{role::Record as RoleRecord, user::Record as UserRecord},
+            mock::MockDependencyProvider,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use rstest::*;
+
+    #[fixture]
+    fn req(user_id: Id) -> Request {
+        Request {
+            id: user_id,
+            role: TEST_ROLE.to_string(),
+        }
+    }
+
+    #[rstest]
+    async fn test_grant_role_success(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+        user_id: Id,
+        user_record: UserRecord,
+        role_record: RoleRecord,
+    ) {
+        // fixtures
+        let mut expected_user_record = user_record.clone();
+        expected_user_record.user.set_role(Role::new(TEST_ROLE));
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .withf(move |_, actual_role| actual_role == &Role::new(TEST_ROLE))
+            .times(1)
+            .returning(move |_, _| Ok(role_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .withf(move |_, actual_id| actual_id == &user_id)
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .withf(move |_, actual_record| {
+                actual_record == &expected_user_record
+                    && actual_record.user.role() == &Role::new(TEST_ROLE)
+            })
+            .times(1)
+            .returning(move |_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <GrantRole<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    async fn test_grant_role_fail_role_not_found(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Err(RoleGetError::NotFound));
+        // Usecase Initialization
+        let usecase = <GrantRole<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err(),
+            Error::RoleNotFound(Role::new(TEST_ROLE))
+        );
+    }
+    #[rstest]
+    async fn test_grant_role_fail_user_not_found(
+        mut dependency_provider: MockDependencyProvider,
+        req: Request,
+        user_id: Id,
+        role_record: RoleRecord,
+    ) {
+        // mock setup
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(role_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Err(GetError::NotFound));
+        // Usecase Initialization
+        let usecase = <GrantRole<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), Error::NotFound(user_id));
+    }
+    #[rstest]
+    fn test_authorize_admin_zero(req: Request, auth_context_admin: AuthContext) {
+        let result = GrantRole::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_admin));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_roles_manage(req: Request, auth_context_user: AuthContext) {
+        let auth_context =
+            auth_context_user.with_permissions([Permission::new(Permission::ROLES_MANAGE)]);
+        let result = GrantRole::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context));
+        assert!(result.is_ok());
+    }
+    #[rstest]
+    fn test_authorize_support(req: Request, auth_context_support: AuthContext) {
+        let result = GrantRole::new(Arc::new(MockDependencyProvider::default()))
+            .authorize(&req, Some(auth_context_support));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
+    }
+}
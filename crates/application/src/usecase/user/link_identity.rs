@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            user_identity::{GetError, Repo as IdentityRepo, SaveError},
+            Database,
+        },
+        service::{clock::Clock, oidc::OidcError},
+        ClockProvider, DatabaseProvider, OidcServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::{
+    entity::{auth_strategy::AuthStrategy, user_identity::UserIdentity},
+    value_object::SecretString,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::complete_external_login::{redeem, RedeemError};
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// Both handed back by the provider
+    pub state: SecretString,
+    pub code: SecretString,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub provider: String,
+    pub subject: String,
+}
+
+/// Link the identity of a login started with
+/// [`BeginExternalLogin`](super::begin_external_login::BeginExternalLogin)
+/// for linking to the current user, who can log in with it afterwards
+pub struct LinkIdentity<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error("External login is unknown or was used already")]
+    InvalidState,
+    #[error("External login expired")]
+    Expired,
+    #[error(transparent)]
+    Oidc(#[from] OidcError),
+    #[error("{}", SaveError::AlreadyLinked)]
+    AlreadyLinked,
+    #[error("{}", GetError::Connection)]
+    Repo,
+}
+
+impl From<RedeemError> for Error {
+    fn from(err: RedeemError) -> Self {
+        match err {
+            RedeemError::InvalidState => Self::InvalidState,
+            RedeemError::Expired => Self::Expired,
+            RedeemError::Oidc(err) => Self::Oidc(err),
+            RedeemError::Repo => Self::Repo,
+        }
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(err: SaveError) -> Self {
+        match err {
+            SaveError::AlreadyLinked => Self::AlreadyLinked,
+            SaveError::Connection => Self::Repo,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for LinkIdentity<D>
+where
+    D: DatabaseProvider + OidcServiceProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.link_identity";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Link external identity");
+        let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+        let user_id = auth_context.user_id;
+        let now = self.dependency_provider.clock().now();
+        let (record, claims) = redeem(
+            &*self.dependency_provider,
+            req.state.expose_secret(),
+            req.code.expose_secret(),
+            Some(user_id),
+            now,
+        )
+        .await?;
+        match self
+            .dependency_provider
+            .database()
+            .user_identity_repo()
+            .get(None, &record.provider, &claims.subject)
+            .await
+        {
+            // linking twice changes nothing
+            Ok(linked) if linked.identity.user_id() == user_id => {}
+            Ok(_) => return Err(Error::AlreadyLinked),
+            Err(GetError::NotFound) => {
+                self.dependency_provider
+                    .database()
+                    .user_identity_repo()
+                    .save(
+                        None,
+                        UserIdentity::new(&record.provider, &claims.subject, user_id).into(),
+                    )
+                    .await?;
+                log::info!("User {user_id} linked an identity of {}", record.provider);
+            }
+            Err(GetError::Connection) => return Err(Error::Repo),
+        }
+        Ok(Response {
+            provider: record.provider,
+            subject: claims.subject,
+        })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Authenticated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::{
+                external_login::Record as ExternalLoginRecord,
+                user_identity::Record as UserIdentityRecord,
+            },
+            mock::MockDependencyProvider,
+            service::oidc::ExternalClaims,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::{auth_context::AuthContext, user::Id};
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            state: TEST_STATE.into(),
+            code: TEST_CODE.into(),
+        }
+    }
+
+    #[rstest]
+    async fn test_link_identity_success(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mut external_login_record: ExternalLoginRecord,
+        external_claims: ExternalClaims,
+        user_id_zero: Id,
+    ) {
+        // fixtures
+        external_login_record.link_user_id = Some(user_id_zero);
+        // Mock setup
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_take()
+            .times(1)
+            .returning(move |_, _| Ok(external_login_record.clone()));
+        dependency_provider
+            .oidc_service
+            .expect_exchange_code()
+            .times(1)
+            .returning(move |_, _, _, _| Ok(external_claims.clone()));
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _, _| Err(GetError::NotFound));
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_save()
+            .withf(move |_, record| {
+                record.identity == UserIdentity::new(TEST_PROVIDER, TEST_SUBJECT, user_id_zero)
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase =
+            <LinkIdentity<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution success
+        let result = result.unwrap();
+        assert_eq!(result.provider, TEST_PROVIDER);
+        assert_eq!(result.subject, TEST_SUBJECT);
+    }
+
+    #[rstest]
+    async fn test_link_identity_of_other_user(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mut external_login_record: ExternalLoginRecord,
+        external_claims: ExternalClaims,
+        user_identity_record: UserIdentityRecord,
+        user_id_zero: Id,
+    ) {
+        // fixtures
+        external_login_record.link_user_id = Some(user_id_zero);
+        // Mock setup
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_take()
+            .times(1)
+            .returning(move |_, _| Ok(external_login_record.clone()));
+        dependency_provider
+            .oidc_service
+            .expect_exchange_code()
+            .times(1)
+            .returning(move |_, _, _, _| Ok(external_claims.clone()));
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _, _| Ok(user_identity_record.clone()));
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_save()
+            .never();
+        // Usecase Initialization
+        let usecase =
+            <LinkIdentity<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::AlreadyLinked);
+    }
+
+    #[rstest]
+    async fn test_link_identity_started_for_login(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        external_login_record: ExternalLoginRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .external_login_repo
+            .expect_take()
+            .times(1)
+            .returning(move |_, _| Ok(external_login_record.clone()));
+        dependency_provider
+            .oidc_service
+            .expect_exchange_code()
+            .never();
+        dependency_provider
+            .db
+            .user_identity_repo
+            .expect_save()
+            .never();
+        // Usecase Initialization
+        let usecase =
+            <LinkIdentity<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+                Arc::new(dependency_provider),
+            );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidState);
+    }
+}
@@ -3,32 +3,37 @@ use std::sync::Arc;
 use crate::{
     gateway::{
         database::{
+            mfa::{GetError as MfaGetError, Repo as MfaRepo},
+            role::{GetError as RoleGetError, Repo as RoleRepo},
             user::{GetError, Repo, SaveError},
             Database,
         },
         service::auth::AuthPacker,
         AuthPackerProvider, DatabaseProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 use ca_domain::entity::{
-    auth_context::AuthContext,
+    auth_context::{AuthContext, PendingStep},
     auth_strategy::AuthStrategy,
-    user::{Id, Password, UserName},
+    user::{Id, Password, User, UserName},
 };
+use ca_domain::value_object::SecretString;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Debug, Deserialize)]
 pub struct Request {
     pub username: String,
-    pub password: String,
+    pub password: SecretString,
 }
 
 #[derive(Debug, Serialize)]
 pub struct Response {
     pub user_id: Id,
     pub token: String,
+    /// Step the token still has to get through before it grants anything
+    pub pending: Option<PendingStep>,
 }
 
 pub struct Login<D> {
@@ -48,7 +53,7 @@ pub enum Error {
 impl From<SaveError> for Error {
     fn from(err: SaveError) -> Self {
         match err {
-            SaveError::Connection => Self::Repo,
+            SaveError::Connection | SaveError::EmailTaken | SaveError::UsernameTaken => Self::Repo,
         }
     }
 }
@@ -61,6 +66,63 @@ impl From<(GetError, UserName)> for Error {
         }
     }
 }
+/// Login step `user` still has to get through after the first factor,
+/// admins without a second factor have to enroll one
+pub(super) async fn pending_step<D>(
+    dependency_provider: &D,
+    user: &User,
+) -> Result<Option<PendingStep>, MfaGetError>
+where
+    D: DatabaseProvider,
+{
+    match dependency_provider
+        .database()
+        .mfa_repo()
+        .get(None, user.id())
+        .await
+    {
+        Ok(record) if record.confirmed => Ok(Some(PendingStep::Mfa)),
+        Ok(_) | Err(MfaGetError::NotFound) if user.role().is_admin() => {
+            Ok(Some(PendingStep::MfaEnrollment))
+        }
+        Ok(_) | Err(MfaGetError::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Token for `user` carrying the permissions of its role, or none while it
+/// is `pending` on a step, it only fails with [`RoleGetError::Connection`]
+pub(super) async fn pack_auth<D>(
+    dependency_provider: &D,
+    user: &User,
+    pending: Option<PendingStep>,
+) -> Result<String, RoleGetError>
+where
+    D: DatabaseProvider + AuthPackerProvider,
+{
+    let auth_context = match pending {
+        Some(step) => AuthContext::new(user.id(), user.role().clone()).with_pending(step),
+        None => {
+            // roles without a stored definition grant no permissions
+            let permissions = match dependency_provider
+                .database()
+                .role_repo()
+                .get(None, user.role().clone())
+                .await
+            {
+                Ok(role) => role.permissions,
+                Err(RoleGetError::NotFound) => Default::default(),
+                Err(err) => return Err(err),
+            };
+            AuthContext::new(user.id(), user.role().clone()).with_permissions(permissions)
+        }
+    };
+    Ok(dependency_provider
+        .auth_packer()
+        .pack_auth(auth_context)
+        .await)
+}
+
 #[async_trait::async_trait]
 impl<D> Usecase<D> for Login<D>
 where
@@ -69,11 +131,18 @@ where
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "user.login";
 
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("Login User: {:?}", req.username);
-        let user_name = UserName::new(&req.username);
-        let password = Password::new(&req.password);
+        // only compared against stored values, which may predate the
+        // current validation rules
+        let user_name = UserName::new_unchecked(&req.username);
+        let password = Password::new_unchecked(req.password.expose_secret());
         let record = self
             .dependency_provider
             .database()
@@ -85,15 +154,16 @@ where
         if password.ne(record.user.password()) {
             return Err(Error::InvalidLogin);
         }
-        let auth_context = AuthContext::new(record.user.id(), record.user.role().clone());
-        let token = self
-            .dependency_provider
-            .auth_packer()
-            .pack_auth(auth_context)
-            .await;
+        let pending = pending_step(&*self.dependency_provider, &record.user)
+            .await
+            .map_err(|_| Error::Repo)?;
+        let token = pack_auth(&*self.dependency_provider, &record.user, pending)
+            .await
+            .map_err(|_| Error::Repo)?;
         Ok(Response {
             user_id: record.user.id(),
             token,
+            pending,
         })
     }
 
@@ -111,32 +181,55 @@ where
 mod tests {
     use super::*;
     use crate::{
-        gateway::{database::user::Record as UserRecord, mock::MockDependencyProvider},
+        gateway::{
+            database::{
+                mfa::Record as MfaRecord, role::Record as RoleRecord, user::Record as UserRecord,
+            },
+            mock::MockDependencyProvider,
+        },
         usecase::tests::fixtures::*,
     };
-    use ca_domain::entity::auth_context::AuthContext;
+    use ca_domain::value_object::Role;
     use rstest::*;
 
     #[rstest]
     async fn test_login_success(
         mut dependency_provider: MockDependencyProvider,
-        user_record: UserRecord,
+        mut user_record: UserRecord,
+        role_record: RoleRecord,
     ) {
         // fixtures
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
+        user_record.user.set_role(role_record.role.clone());
         let user_id = user_record.user.id();
-        let auth_context = AuthContext::new(user_id, user_record.user.role().clone());
+        let auth_context = AuthContext::new(user_id, role_record.role.clone())
+            .with_permissions(role_record.permissions.clone());
         // mock setup
         dependency_provider
             .db
             .user_repo
             .expect_get_by_username()
-            .withf(move |_, actual_username| actual_username == &UserName::new(TEST_USERNAME))
+            .withf(move |_, actual_username| {
+                actual_username == &UserName::new_unchecked(TEST_USERNAME)
+            })
             .times(1)
             .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(MfaGetError::NotFound));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .withf(move |_, actual_role| actual_role == &Role::new(TEST_ROLE))
+            .times(1)
+            .returning(move |_, _| Ok(role_record.clone()));
         dependency_provider
             .auth_packer
             .expect_pack_auth()
@@ -148,12 +241,146 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
         let result = result.unwrap();
         assert_eq!(result.user_id, user_id);
         assert_eq!(result.token, TEST_TOKEN);
+        assert_eq!(result.pending, None);
+    }
+    #[rstest]
+    async fn test_login_mfa_pending(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+        mfa_record: MfaRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        let user_id = user_record.user.id();
+        let auth_context = AuthContext::new(user_id, Role::user()).with_pending(PendingStep::Mfa);
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .withf(move |_, actual_user_id| actual_user_id == &user_id)
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        // the partial token grants no permissions
+        dependency_provider.db.role_repo.expect_get().never();
+        dependency_provider
+            .auth_packer
+            .expect_pack_auth()
+            .withf(move |actual_auth_context| actual_auth_context == &auth_context)
+            .times(1)
+            .returning(move |_| TEST_TOKEN.to_string());
+        // Usecase Initialization
+        let usecase = <Login<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert_eq!(result.unwrap().pending, Some(PendingStep::Mfa));
+    }
+    #[rstest]
+    async fn test_login_admin_enrollment_pending(
+        mut dependency_provider: MockDependencyProvider,
+        mut user_record: UserRecord,
+        mut mfa_record: MfaRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        user_record.user.set_role(Role::admin());
+        // an unconfirmed second factor does not count
+        mfa_record.confirmed = false;
+        let auth_context = AuthContext::new(user_record.user.id(), Role::admin())
+            .with_pending(PendingStep::MfaEnrollment);
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .auth_packer
+            .expect_pack_auth()
+            .withf(move |actual_auth_context| actual_auth_context == &auth_context)
+            .times(1)
+            .returning(move |_| TEST_TOKEN.to_string());
+        // Usecase Initialization
+        let usecase = <Login<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert_eq!(result.unwrap().pending, Some(PendingStep::MfaEnrollment));
+    }
+    #[rstest]
+    async fn test_login_success_role_without_definition(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            username: TEST_USERNAME.to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        let auth_context = AuthContext::new(user_record.user.id(), Role::user());
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(MfaGetError::NotFound));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Err(RoleGetError::NotFound));
+        dependency_provider
+            .auth_packer
+            .expect_pack_auth()
+            .withf(move |actual_auth_context| actual_auth_context == &auth_context)
+            .times(1)
+            .returning(move |_| TEST_TOKEN.to_string());
+        // Usecase Initialization
+        let usecase = <Login<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
     }
     #[rstest]
     async fn test_login_fail_get_by_username_connection(
@@ -162,14 +389,16 @@ mod tests {
         // fixtures
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // mock setup
         dependency_provider
             .db
             .user_repo
             .expect_get_by_username()
-            .withf(move |_, actual_username| actual_username == &UserName::new(TEST_USERNAME))
+            .withf(move |_, actual_username| {
+                actual_username == &UserName::new_unchecked(TEST_USERNAME)
+            })
             .times(1)
             .returning(move |_, _| Err(GetError::Connection));
         // Usecase Initialization
@@ -177,7 +406,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -189,14 +418,16 @@ mod tests {
         // fixtures
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // mock setup
         dependency_provider
             .db
             .user_repo
             .expect_get_by_username()
-            .withf(move |_, actual_username| actual_username == &UserName::new(TEST_USERNAME))
+            .withf(move |_, actual_username| {
+                actual_username == &UserName::new_unchecked(TEST_USERNAME)
+            })
             .times(1)
             .returning(move |_, _| Err(GetError::NotFound));
         // Usecase Initialization
@@ -204,12 +435,12 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
-            Error::NotFound(UserName::new(TEST_USERNAME))
+            Error::NotFound(UserName::new_unchecked(TEST_USERNAME))
         );
     }
     #[rstest]
@@ -220,14 +451,16 @@ mod tests {
         // fixtures
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: "fail password".to_string(),
+            password: "fail password".into(),
         };
         // mock setup
         dependency_provider
             .db
             .user_repo
             .expect_get_by_username()
-            .withf(move |_, actual_username| actual_username == &UserName::new(TEST_USERNAME))
+            .withf(move |_, actual_username| {
+                actual_username == &UserName::new_unchecked(TEST_USERNAME)
+            })
             .times(1)
             .returning(move |_, _| Ok(user_record.clone()));
         // Usecase Initialization
@@ -235,7 +468,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::InvalidLogin);
@@ -244,7 +477,7 @@ mod tests {
     fn test_authorize_admin_zero(auth_context_admin: AuthContext) {
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result = Login::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_admin));
@@ -255,7 +488,7 @@ mod tests {
     fn test_authorize_user_zero(auth_context_user: AuthContext) {
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result = Login::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
@@ -265,7 +498,7 @@ mod tests {
     fn test_authorize_none() {
         let req = Request {
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let auth_context = None;
         let result =
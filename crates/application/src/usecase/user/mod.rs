@@ -1,5 +1,18 @@
+pub mod begin_external_login;
+pub mod check_username_availability;
+pub mod complete_external_login;
+pub mod confirm_2fa;
+pub mod consume_login_link;
 pub mod delete;
+pub mod disable_2fa;
+pub mod enable_2fa;
 pub mod get_all;
+pub mod get_me;
 pub mod get_one;
+pub mod grant_role;
+pub mod link_identity;
 pub mod login;
+pub mod regenerate_recovery_codes;
+pub mod request_login_link;
 pub mod update;
+pub mod verify_mfa;
@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            mfa::{GetError, RecoveryCodeError, Repo as MfaRepo, SaveError, StepError},
+            token::AttemptError,
+            Database,
+        },
+        service::{clock::Clock, totp::TotpService},
+        ClockProvider, DatabaseProvider, TotpServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::auth_strategy::AuthStrategy;
+use ca_domain::value_object::SecretString;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    confirm_2fa::RECOVERY_CODES,
+    verify_mfa::{check_second_factor, Check},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub code: SecretString,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    /// Only shown once, the repo keeps hashes
+    pub recovery_codes: Vec<String>,
+}
+
+/// Replace the recovery codes of the current user, the old ones stop working
+pub struct RegenerateRecoveryCodes<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error("Two factor authentication was not enabled")]
+    NotEnabled,
+    #[error("Second factor code is invalid")]
+    InvalidCode,
+    #[error("Too many second factor codes tried, retry later")]
+    TooManyAttempts,
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+    #[error("Second factor repo error: {0}")]
+    RecoveryCode(#[from] RecoveryCodeError),
+    #[error("Second factor repo error: {0}")]
+    Step(#[from] StepError),
+}
+
+impl From<GetError> for Error {
+    fn from(err: GetError) -> Self {
+        match err {
+            GetError::NotFound => Self::NotEnabled,
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(_: SaveError) -> Self {
+        Self::Repo
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for RegenerateRecoveryCodes<D>
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.regenerate_recovery_codes";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Regenerate recovery codes");
+        let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+        let user_id = auth_context.user_id;
+        let now = self.dependency_provider.clock().now();
+        let record = self
+            .dependency_provider
+            .database()
+            .mfa_repo()
+            .get(None, user_id)
+            .await?;
+        if !record.confirmed {
+            return Err(Error::NotEnabled);
+        }
+        match check_second_factor::<_, Error>(
+            &*self.dependency_provider,
+            &record,
+            req.code.expose_secret(),
+            now,
+        )
+        .await?
+        {
+            Check::Accepted => {}
+            Check::Rejected => return Err(Error::InvalidCode),
+            Check::Locked => return Err(Error::TooManyAttempts),
+        }
+        let totp_service = self.dependency_provider.totp_service();
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODES)
+            .map(|_| totp_service.generate_recovery_code())
+            .collect();
+        self.dependency_provider
+            .database()
+            .mfa_repo()
+            .replace_recovery_codes(None, user_id, &recovery_codes)
+            .await?;
+        Ok(Response { recovery_codes })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Authenticated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::mfa::Record as MfaRecord, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::entity::{auth_context::AuthContext, user::Id};
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            code: TEST_TOTP_CODE.into(),
+        }
+    }
+
+    #[rstest]
+    async fn test_regenerate_recovery_codes_success(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+        user_id_zero: Id,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .totp_service
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _| Some(TEST_TOTP_STEP));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_step()
+            .withf(|_, _, actual_step| *actual_step == TEST_TOTP_STEP)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dependency_provider
+            .totp_service
+            .expect_generate_recovery_code()
+            .times(RECOVERY_CODES)
+            .returning(|| "abcd-efgh".to_string());
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_replace_recovery_codes()
+            .withf(move |_, actual_user_id, actual_codes| {
+                actual_user_id == &user_id_zero && actual_codes.len() == RECOVERY_CODES
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RegenerateRecoveryCodes<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution success
+        assert_eq!(result.unwrap().recovery_codes.len(), RECOVERY_CODES);
+    }
+
+    #[rstest]
+    async fn test_regenerate_recovery_codes_not_enabled(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mut mfa_record: MfaRecord,
+    ) {
+        // fixtures
+        mfa_record.confirmed = false;
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_replace_recovery_codes()
+            .never();
+        // Usecase Initialization
+        let usecase = <RegenerateRecoveryCodes<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::NotEnabled);
+    }
+}
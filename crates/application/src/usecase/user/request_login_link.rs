@@ -0,0 +1,464 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            token::{
+                AttemptError, InvalidateError, IssueError as TokenRepoError, Purpose,
+                Repo as TokenRepo, Scope,
+            },
+            user::{GetError, Repo},
+            Database,
+        },
+        service::{
+            clock::Clock,
+            email::{encode_query_value, EmailAddress, EmailService, EmailServiceError},
+            token::TokenGenerator,
+        },
+        ClockProvider, DatabaseProvider, EmailServiceProvider, LoginLinkPolicyProvider,
+        TokenGeneratorProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::{auth_strategy::AuthStrategy, user::UserName};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub username: String,
+}
+
+pub struct RequestLoginLink<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Too many login links requested, retry later")]
+    TooManyRequests,
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Token Repo error: {0}")]
+    TokenRepoError(#[from] TokenRepoError),
+    #[error("Token Repo error: {0}")]
+    Invalidate(#[from] InvalidateError),
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+    #[error("Email Service error: {0}")]
+    EmailServiceError(#[from] EmailServiceError),
+}
+
+impl From<GetError> for Error {
+    fn from(_: GetError) -> Self {
+        Self::Repo
+    }
+}
+
+fn login_link(url: &str, username: &str, token: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!(
+        "{url}{separator}username={}&token={}",
+        encode_query_value(username),
+        encode_query_value(token)
+    )
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for RequestLoginLink<D>
+where
+    D: DatabaseProvider
+        + EmailServiceProvider
+        + TokenGeneratorProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider,
+{
+    type Request = Request;
+    type Response = ();
+    type Error = Error;
+    const NAME: &'static str = "user.request_login_link";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Request login link: {:?}", req.username);
+        let now = self.dependency_provider.clock().now();
+        let policy = self.dependency_provider.login_link_policy();
+        // counted before the lookup, so going through usernames costs too
+        if let Some(client_ip) = &ctx.client_ip {
+            let requests = self
+                .dependency_provider
+                .database()
+                .token_repo()
+                .record_request(
+                    None,
+                    &Scope::new(Purpose::Login, format!("client:{client_ip}")),
+                    policy.request_window,
+                    now,
+                )
+                .await?;
+            if requests > policy.max_client_requests {
+                log::warn!("Too many login links requested from {client_ip}");
+                return Err(Error::TooManyRequests);
+            }
+        }
+        // only compared against stored values, like the password login.
+        // Unknown users and users over their own limit are answered like
+        // any other request, so the answer does not tell who has an account
+        let user_name = UserName::new_unchecked(&req.username);
+        let user = match self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .get_by_username(None, user_name)
+            .await
+        {
+            Ok(record) => record.user,
+            Err(GetError::NotFound) => {
+                log::debug!("Login link requested for an unknown user");
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let scope = Scope::new(Purpose::Login, user.id());
+        let requests = self
+            .dependency_provider
+            .database()
+            .token_repo()
+            .record_request(None, &scope, policy.request_window, now)
+            .await?;
+        if requests > policy.max_requests {
+            log::warn!("Too many login links requested for user {}", user.id());
+            return Ok(());
+        }
+        // only the latest link logs in
+        self.dependency_provider
+            .database()
+            .token_repo()
+            .invalidate(None, &scope)
+            .await?;
+        let token = self.dependency_provider.token_generator().generate();
+        self.dependency_provider
+            .database()
+            .token_repo()
+            .issue(None, &scope, &user.email().canonical(), &token, now)
+            .await?;
+        let body = match &policy.link_url {
+            Some(url) => format!(
+                "Open {} to log in or enter the login code: `{token}`",
+                login_link(url, user.username().as_ref(), &token)
+            ),
+            None => format!("Your login code is: `{token}`"),
+        };
+        self.dependency_provider
+            .email_service()
+            .send_email(
+                EmailAddress::new(user.email().as_ref()),
+                "Your login link",
+                &body,
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{database::user::Record as UserRecord, mock::MockDependencyProvider},
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::value_object::LoginLinkPolicy;
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            username: TEST_USERNAME.to_string(),
+        }
+    }
+
+    #[rstest]
+    async fn test_request_login_link_success(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let scope = Scope::new(Purpose::Login, user_record.user.id());
+        dependency_provider.login_link_policy =
+            LoginLinkPolicy::default().with_link_url("https://example.com/login?next=%2F");
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .withf(move |_, actual_username| {
+                actual_username == &UserName::new_unchecked(TEST_USERNAME)
+            })
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        let expected_scope = scope.clone();
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .withf(move |_, actual_scope, _, _| actual_scope == &expected_scope)
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        let expected_scope = scope.clone();
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            // makes sure an older link stops working
+            .withf(move |_, actual_scope| actual_scope == &expected_scope)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .withf(move |_, actual_scope, actual_email, actual_token, _| {
+                actual_scope == &scope && actual_email == TEST_EMAIL && actual_token == TEST_TOKEN
+            })
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .email_service
+            .expect_send_email()
+            // makes sure the link carries the username and token
+            .withf(|actual_email, _, body| {
+                actual_email.as_str() == TEST_EMAIL
+                    && body.contains(
+                        "https://example.com/login?next=%2F&username=test_username&token=test_token",
+                    )
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RequestLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
+    }
+
+    #[rstest]
+    async fn test_request_login_link_too_many_client_requests(
+        mut dependency_provider: MockDependencyProvider,
+    ) {
+        // fixtures
+        dependency_provider.login_link_policy =
+            LoginLinkPolicy::default().with_max_client_requests(2);
+        let ctx = RequestContext::default().with_client_ip("203.0.113.7");
+        // Mock setup -- the client is over its limit, no user is looked up
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .withf(|_, actual_scope, _, _| {
+                actual_scope == &Scope::new(Purpose::Login, "client:203.0.113.7")
+            })
+            .times(1)
+            .returning(|_, _, _, _| Ok(3));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .never();
+        dependency_provider
+            .email_service
+            .expect_send_email()
+            .never();
+        // Usecase Initialization
+        let usecase = <RequestLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &ctx).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::TooManyRequests);
+    }
+
+    #[rstest]
+    async fn test_request_login_link_not_found(mut dependency_provider: MockDependencyProvider) {
+        // Mock setup -- nothing is issued or sent
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(|_, _| Err(GetError::NotFound));
+        dependency_provider.db.token_repo.expect_issue().never();
+        dependency_provider
+            .email_service
+            .expect_send_email()
+            .never();
+        // Usecase Initialization
+        let usecase = <RequestLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert the answer does not differ from a known user
+        assert_eq!(result, Ok(()));
+    }
+
+    #[rstest]
+    async fn test_request_login_link_too_many_requests(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            // one more than the default allows
+            .returning(|_, _, _, _| Ok(4));
+        // nothing is issued or sent
+        dependency_provider.db.token_repo.expect_issue().never();
+        dependency_provider
+            .email_service
+            .expect_send_email()
+            .never();
+        // Usecase Initialization
+        let usecase = <RequestLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert the limit does not show
+        assert_eq!(result, Ok(()));
+    }
+
+    #[rstest]
+    async fn test_request_login_link_code_only(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .email_service
+            .expect_send_email()
+            // without a link url only the code is sent
+            .withf(|_, _, body| body == "Your login code is: `test_token`")
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <RequestLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution success
+        assert_eq!(result, Ok(()));
+    }
+
+    #[rstest]
+    async fn test_request_login_link_fail_email_service(
+        mut dependency_provider: MockDependencyProvider,
+        user_record: UserRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get_by_username()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_invalidate()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        dependency_provider
+            .token_generator
+            .expect_generate()
+            .times(1)
+            .returning(|| TEST_TOKEN.to_string());
+        dependency_provider
+            .db
+            .token_repo
+            .expect_issue()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+        dependency_provider
+            .email_service
+            .expect_send_email()
+            .times(1)
+            .returning(|_, _, _| Err(EmailServiceError::SendEmailFailed));
+        // Usecase Initialization
+        let usecase = <RequestLoginLink<MockDependencyProvider> as Usecase<
+            MockDependencyProvider,
+        >>::new(Arc::new(dependency_provider));
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(request(), &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::EmailServiceError(EmailServiceError::SendEmailFailed)
+        );
+    }
+}
@@ -6,30 +6,26 @@ use crate::{
             user::{GetError, Repo, SaveError},
             Database,
         },
-        DatabaseProvider,
+        DatabaseProvider, PasswordPolicyProvider,
     },
-    usecase::Usecase,
+    usecase::{request_context::RequestContext, Usecase},
 };
 use ca_domain::{
     entity::{
         auth_strategy::AuthStrategy,
         user::{Email, Id, UserName},
     },
-    value_object::Password,
+    value_object::{Password, PasswordRejected, Permission, SecretString, ValidationError},
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize)]
 pub struct Request {
     pub id: Id,
-    #[validate(email)]
     pub email: String,
-    #[validate(length(min = 1, max = 30))]
     pub username: String,
-    #[validate(length(min = 5, max = 60))]
-    pub password: String,
+    pub password: SecretString,
 }
 
 pub type Response = ();
@@ -43,15 +39,23 @@ pub enum Error {
     #[error("User {0} not found")]
     NotFound(Id),
     #[error(transparent)]
-    Invalidity(#[from] validator::ValidationErrors),
+    Invalidity(#[from] ValidationError),
     #[error("{}", SaveError::Connection)]
     Repo,
+    #[error("{}", SaveError::EmailTaken)]
+    EmailAlreadyRegistered,
+    #[error("{}", SaveError::UsernameTaken)]
+    UsernameTaken,
+    #[error(transparent)]
+    WeakPassword(#[from] PasswordRejected),
 }
 
 impl From<SaveError> for Error {
     fn from(err: SaveError) -> Self {
         match err {
             SaveError::Connection => Self::Repo,
+            SaveError::EmailTaken => Self::EmailAlreadyRegistered,
+            SaveError::UsernameTaken => Self::UsernameTaken,
         }
     }
 }
@@ -67,15 +71,22 @@ impl From<(GetError, Id)> for Error {
 #[async_trait::async_trait]
 impl<D> Usecase<D> for Update<D>
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + PasswordPolicyProvider,
 {
     type Request = Request;
     type Response = Response;
     type Error = Error;
+    const NAME: &'static str = "user.update";
 
-    async fn exec(&self, req: Self::Request) -> Result<Self::Response, Self::Error> {
+    async fn exec(
+        &self,
+        req: Self::Request,
+        _ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
         log::debug!("Update User: {:?}", req);
-        req.validate()?;
+        let email = Email::parse(&req.email)?;
+        let username = UserName::parse(&req.username)?;
+        let password = Password::parse(req.password.expose_secret())?;
         let mut record = self
             .dependency_provider
             .database()
@@ -83,11 +94,17 @@ where
             .get(None, req.id)
             .await
             .map_err(|err| (err, req.id))?;
-        record.user.update(
-            Email::new(&req.email),
-            UserName::new(&req.username),
-            Password::new(&req.password),
-        );
+        // passwords set before the policy changed stay valid until replaced
+        if password.ne(record.user.password()) {
+            self.dependency_provider
+                .password_policy()
+                .check(&password, &username, &email)?;
+        }
+        // users keeping a name that was reserved after they took it is fine
+        if username.is_reserved() && username.as_ref() != record.user.username().as_ref() {
+            return Err(Error::UsernameTaken);
+        }
+        record.user.update(email, username, password);
         self.dependency_provider
             .database()
             .user_repo()
@@ -107,6 +124,9 @@ where
     fn extract_owner(&self, req: &Self::Request) -> Option<Id> {
         Some(req.id)
     }
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::new(Permission::USERS_UPDATE)]
+    }
 }
 
 #[cfg(test)]
@@ -116,7 +136,10 @@ mod tests {
         gateway::{database::user::Record as UserRecord, mock::MockDependencyProvider},
         usecase::tests::fixtures::*,
     };
-    use ca_domain::entity::auth_context::{AuthContext, AuthError};
+    use ca_domain::{
+        entity::auth_context::{AuthContext, AuthError},
+        value_object::{Field, PasswordWeakness},
+    };
     use rstest::*;
 
     #[rstest]
@@ -130,13 +153,13 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let mut updated_user_record = user_record.clone();
         updated_user_record.user.update(
-            Email::new(&req.email),
-            UserName::new(&req.username),
-            Password::new(&req.password),
+            Email::new_unchecked(&req.email),
+            UserName::new_unchecked(&req.username),
+            Password::new_unchecked(req.password.expose_secret()),
         );
         let expected_user_record = user_record.clone();
         // mock setup
@@ -160,7 +183,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_ok());
     }
@@ -174,24 +197,97 @@ mod tests {
             id: user_id,
             email: "".to_string(),
             username: "".to_string(),
-            password: "".to_string(),
+            password: "".into(),
         };
         // Usecase Initialization
         let usecase = <Update<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert!(err.to_string().contains("email: Validation error: email"));
-        assert!(err
-            .to_string()
-            .contains("password: Validation error: length"));
-        assert!(err
-            .to_string()
-            .contains("username: Validation error: length"));
+        assert_eq!(err.to_string(), "email must be at least 3 characters long");
+    }
+    #[rstest]
+    async fn test_update_fail_password_similar_to_username(
+        mut dependency_provider: MockDependencyProvider,
+        user_id: Id,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: user_id,
+            email: TEST_EMAIL.to_string(),
+            username: TEST_USERNAME.to_string(),
+            password: format!("{TEST_USERNAME}1").into(),
+        };
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider.db.user_repo.expect_save().never();
+        // Usecase Initialization
+        let usecase = <Update<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(
+            result.unwrap_err(),
+            Error::WeakPassword(PasswordRejected {
+                reasons: vec![PasswordWeakness::SimilarTo {
+                    field: Field::UserName
+                }]
+            })
+        );
+    }
+    #[rstest]
+    async fn test_update_keeps_password_set_before_policy(
+        mut dependency_provider: MockDependencyProvider,
+        user_id: Id,
+        mut user_record: UserRecord,
+    ) {
+        // fixtures -- the stored password would be rejected today
+        let weak_password = format!("{TEST_USERNAME}1");
+        user_record.user.update(
+            Email::new_unchecked(TEST_EMAIL),
+            UserName::new_unchecked(TEST_USERNAME),
+            Password::new_unchecked(&weak_password),
+        );
+        let req = Request {
+            id: user_id,
+            email: "changed@test.com".to_string(),
+            username: TEST_USERNAME.to_string(),
+            password: weak_password.into(),
+        };
+        // Mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .withf(|_, record| record.user.email().as_ref() == "changed@test.com")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        // Usecase Initialization
+        let usecase = <Update<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution success
+        assert!(result.is_ok());
     }
     #[rstest]
     async fn test_update_fail_get_connection(
@@ -203,7 +299,7 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // mock setup
         dependency_provider
@@ -218,7 +314,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
@@ -233,7 +329,7 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         // mock setup
         dependency_provider
@@ -248,7 +344,7 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution error
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::NotFound(user_id));
@@ -264,13 +360,13 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let mut updated_user_record = user_record.clone();
         updated_user_record.user.update(
-            Email::new(&req.email),
-            UserName::new(&req.username),
-            Password::new(&req.password),
+            Email::new_unchecked(&req.email),
+            UserName::new_unchecked(&req.username),
+            Password::new_unchecked(req.password.expose_secret()),
         );
         let expected_user_record = user_record.clone();
         // mock setup
@@ -294,18 +390,83 @@ mod tests {
             Arc::new(dependency_provider),
         );
         // Usecase Execution -- mock predicates will fail during execution
-        let result = usecase.exec(req).await;
+        let result = usecase.exec(req, &RequestContext::default()).await;
         // Assert execution success
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Error::Repo);
     }
     #[rstest]
+    async fn test_update_fail_username_taken(
+        mut dependency_provider: MockDependencyProvider,
+        user_id: Id,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: user_id,
+            email: TEST_EMAIL.to_string(),
+            username: "taken_name".to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .user_repo
+            .expect_save()
+            .times(1)
+            .returning(|_, _| Err(SaveError::UsernameTaken));
+        // Usecase Initialization
+        let usecase = <Update<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::UsernameTaken);
+    }
+    #[rstest]
+    async fn test_update_fail_username_reserved(
+        mut dependency_provider: MockDependencyProvider,
+        user_id: Id,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let req = Request {
+            id: user_id,
+            email: TEST_EMAIL.to_string(),
+            username: "support".to_string(),
+            password: TEST_PASSWORD.into(),
+        };
+        // mock setup
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider.db.user_repo.expect_save().never();
+        // Usecase Initialization
+        let usecase = <Update<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution
+        let result = usecase.exec(req, &RequestContext::default()).await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::UsernameTaken);
+    }
+    #[rstest]
     fn test_authorize_admin_zero(user_id: Id, auth_context_admin: AuthContext) {
         let req = Request {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result = Update::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_admin));
@@ -318,12 +479,12 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let result = Update::new(Arc::new(MockDependencyProvider::default()))
             .authorize(&req, Some(auth_context_user));
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Forbidden);
     }
     #[rstest]
     fn test_authorize_user_owner(user_id: Id, mut auth_context_user: AuthContext) {
@@ -331,7 +492,7 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         auth_context_user.user_id = user_id;
         let result = Update::new(Arc::new(MockDependencyProvider::default()))
@@ -344,12 +505,12 @@ mod tests {
             id: user_id,
             email: TEST_EMAIL.to_string(),
             username: TEST_USERNAME.to_string(),
-            password: TEST_PASSWORD.to_string(),
+            password: TEST_PASSWORD.into(),
         };
         let auth_context = None;
         let result =
             Update::new(Arc::new(MockDependencyProvider::default())).authorize(&req, auth_context);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), AuthError::Unauthorized);
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
     }
 }
@@ -0,0 +1,490 @@
+use std::sync::Arc;
+
+use crate::{
+    gateway::{
+        database::{
+            mfa::{GetError, Record, RecoveryCodeError, Repo as MfaRepo, SaveError, StepError},
+            token::{AttemptError, Purpose, Repo as TokenRepo, Scope},
+            user::{GetError as UserGetError, Repo as UserRepo},
+            Database,
+        },
+        service::{clock::Clock, totp::TotpService},
+        AuthPackerProvider, ClockProvider, DatabaseProvider, TotpServiceProvider,
+    },
+    usecase::{request_context::RequestContext, Usecase},
+};
+use ca_domain::entity::{
+    auth_context::PendingStep,
+    auth_strategy::AuthStrategy,
+    user::{Id, User},
+};
+use ca_domain::value_object::SecretString;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::login::pack_auth;
+
+/// Second factor checks allowed per user within [`ATTEMPT_WINDOW_MINUTES`]
+pub(super) const MAX_ATTEMPTS: u32 = 10;
+pub(super) const ATTEMPT_WINDOW_MINUTES: i64 = 15;
+
+/// Outcome of checking a code against the second factor of a user
+#[derive(Debug, PartialEq)]
+pub(super) enum Check {
+    Accepted,
+    Rejected,
+    /// Too many codes were checked lately, nothing is compared
+    Locked,
+}
+
+/// Checks `code` as a TOTP code of `record` or else as one of its recovery
+/// codes, an accepted TOTP code moves the stored second factor past its step
+/// so it can not be replayed
+pub(super) async fn check_second_factor<D, E>(
+    dependency_provider: &D,
+    record: &Record,
+    code: &str,
+    now: DateTime<Utc>,
+) -> Result<Check, E>
+where
+    D: DatabaseProvider + TotpServiceProvider,
+    E: From<AttemptError> + From<RecoveryCodeError> + From<StepError>,
+{
+    // six digits are guessed quickly without a limit
+    let checks = dependency_provider
+        .database()
+        .token_repo()
+        .record_request(
+            None,
+            &Scope::new(Purpose::Mfa, record.user_id),
+            Duration::minutes(ATTEMPT_WINDOW_MINUTES),
+            now,
+        )
+        .await?;
+    if checks > MAX_ATTEMPTS {
+        log::warn!("Too many second factor checks for user {}", record.user_id);
+        return Ok(Check::Locked);
+    }
+    let step = dependency_provider
+        .totp_service()
+        .verify(record.secret.expose_secret(), code, now);
+    if let Some(step) = step {
+        return match dependency_provider
+            .database()
+            .mfa_repo()
+            .use_step(None, record.user_id, step)
+            .await
+        {
+            Ok(()) => Ok(Check::Accepted),
+            Err(StepError::Replayed) => Ok(Check::Rejected),
+            Err(err) => Err(err.into()),
+        };
+    }
+    match dependency_provider
+        .database()
+        .mfa_repo()
+        .use_recovery_code(None, record.user_id, code)
+        .await
+    {
+        Ok(()) => Ok(Check::Accepted),
+        Err(RecoveryCodeError::NotFound) => Ok(Check::Rejected),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub code: SecretString,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Response {
+    pub user_id: Id,
+    pub token: String,
+}
+
+/// Second step of a login, trades the partial token of a user with a second
+/// factor for a full one
+pub struct VerifyMfa<D> {
+    dependency_provider: Arc<D>,
+}
+
+#[derive(Debug, Error, Serialize, PartialEq)]
+pub enum Error {
+    #[error("Request is not authenticated")]
+    Unauthenticated,
+    #[error("Login is not pending on a second factor")]
+    NotPending,
+    #[error("Second factor code is invalid")]
+    InvalidCode,
+    #[error("Too many second factor codes tried, retry later")]
+    TooManyAttempts,
+    #[error("{}", GetError::Connection)]
+    Repo,
+    #[error("Token Repo error: {0}")]
+    Attempts(#[from] AttemptError),
+    #[error("Second factor repo error: {0}")]
+    RecoveryCode(#[from] RecoveryCodeError),
+    #[error("Second factor repo error: {0}")]
+    Step(#[from] StepError),
+}
+
+impl From<GetError> for Error {
+    fn from(err: GetError) -> Self {
+        match err {
+            // the second factor went away since the login
+            GetError::NotFound => Self::NotPending,
+            GetError::Connection => Self::Repo,
+        }
+    }
+}
+
+impl From<SaveError> for Error {
+    fn from(_: SaveError) -> Self {
+        Self::Repo
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Usecase<D> for VerifyMfa<D>
+where
+    D: DatabaseProvider + AuthPackerProvider + TotpServiceProvider + ClockProvider,
+{
+    type Request = Request;
+    type Response = Response;
+    type Error = Error;
+    const NAME: &'static str = "user.verify_mfa";
+
+    async fn exec(
+        &self,
+        req: Self::Request,
+        ctx: &RequestContext,
+    ) -> Result<Self::Response, Self::Error> {
+        log::debug!("Verify second factor");
+        let auth_context = ctx.auth_context().ok_or(Error::Unauthenticated)?;
+        if auth_context.pending() != Some(PendingStep::Mfa) {
+            return Err(Error::NotPending);
+        }
+        let user_id = auth_context.user_id;
+        let now = self.dependency_provider.clock().now();
+        let record = self
+            .dependency_provider
+            .database()
+            .mfa_repo()
+            .get(None, user_id)
+            .await?;
+        if !record.confirmed {
+            return Err(Error::NotPending);
+        }
+        match check_second_factor::<_, Error>(
+            &*self.dependency_provider,
+            &record,
+            req.code.expose_secret(),
+            now,
+        )
+        .await?
+        {
+            Check::Accepted => {}
+            Check::Rejected => return Err(Error::InvalidCode),
+            Check::Locked => return Err(Error::TooManyAttempts),
+        }
+        // the role may have changed since the partial token was issued
+        let user: User = self
+            .dependency_provider
+            .database()
+            .user_repo()
+            .get(None, user_id)
+            .await
+            .map_err(|err| match err {
+                UserGetError::NotFound => Error::Unauthenticated,
+                UserGetError::Connection => Error::Repo,
+            })?
+            .into();
+        let token = pack_auth(&*self.dependency_provider, &user, None)
+            .await
+            .map_err(|_| Error::Repo)?;
+        Ok(Response { user_id, token })
+    }
+
+    fn new(dependency_provider: Arc<D>) -> Self {
+        Self {
+            dependency_provider,
+        }
+    }
+    fn auth_strategy(&self) -> AuthStrategy {
+        AuthStrategy::Authenticated
+    }
+    fn pending_step(&self) -> Option<PendingStep> {
+        Some(PendingStep::Mfa)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gateway::{
+            database::{
+                mfa::Record as MfaRecord, role::GetError as RoleGetError,
+                user::Record as UserRecord,
+            },
+            mock::MockDependencyProvider,
+        },
+        usecase::tests::fixtures::*,
+    };
+    use ca_domain::{
+        entity::auth_context::{AuthContext, AuthError},
+        value_object::Role,
+    };
+    use rstest::*;
+
+    fn request() -> Request {
+        Request {
+            code: TEST_TOTP_CODE.into(),
+        }
+    }
+
+    fn pending_ctx(auth_context: AuthContext) -> RequestContext {
+        RequestContext::default()
+            .with_auth_context(Some(auth_context.with_pending(PendingStep::Mfa)))
+    }
+
+    #[rstest]
+    async fn test_verify_mfa_success(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+        user_id_zero: Id,
+        user_record: UserRecord,
+    ) {
+        // fixtures
+        let full_auth_context = AuthContext::new(user_record.user.id(), Role::user());
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .withf(move |_, actual_user_id| actual_user_id == &user_id_zero)
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .withf(move |_, actual_scope, _, _| {
+                actual_scope == &Scope::new(Purpose::Mfa, user_id_zero)
+            })
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .totp_service
+            .expect_verify()
+            .withf(|actual_secret, actual_code, _| {
+                actual_secret == TEST_TOTP_SECRET && actual_code == TEST_TOTP_CODE
+            })
+            .times(1)
+            .returning(|_, _, _| Some(TEST_TOTP_STEP));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_step()
+            // makes sure the code can not be used again
+            .withf(move |_, actual_user_id, actual_step| {
+                actual_user_id == &user_id_zero && *actual_step == TEST_TOTP_STEP
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dependency_provider.db.mfa_repo.expect_save().never();
+        dependency_provider
+            .db
+            .user_repo
+            .expect_get()
+            .withf(move |_, actual_user_id| actual_user_id == &user_id_zero)
+            .times(1)
+            .returning(move |_, _| Ok(user_record.clone()));
+        dependency_provider
+            .db
+            .role_repo
+            .expect_get()
+            .times(1)
+            .returning(|_, _| Err(RoleGetError::NotFound));
+        dependency_provider
+            .auth_packer
+            .expect_pack_auth()
+            // the full token is no longer pending
+            .withf(move |actual_auth_context| actual_auth_context == &full_auth_context)
+            .times(1)
+            .returning(|_| "jwt".to_string());
+        // Usecase Initialization
+        let usecase = <VerifyMfa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(request(), &pending_ctx(auth_context_user))
+            .await;
+        // Assert execution success
+        assert_eq!(result.unwrap().token, "jwt");
+    }
+
+    #[rstest]
+    async fn test_verify_mfa_replayed_code(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .totp_service
+            .expect_verify()
+            .times(1)
+            // the step of the last accepted code
+            .returning(|_, _, _| Some(TEST_TOTP_STEP - 1));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_step()
+            .times(1)
+            .returning(|_, _, _| Err(StepError::Replayed));
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_recovery_code()
+            .never();
+        dependency_provider.auth_packer.expect_pack_auth().never();
+        // Usecase Initialization
+        let usecase = <VerifyMfa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(request(), &pending_ctx(auth_context_user))
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidCode);
+    }
+
+    #[rstest]
+    async fn test_verify_mfa_wrong_code(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(1));
+        dependency_provider
+            .totp_service
+            .expect_verify()
+            .times(1)
+            .returning(|_, _, _| None);
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_use_recovery_code()
+            // makes sure the code is tried as a recovery code too
+            .withf(|_, _, actual_code| actual_code == TEST_TOTP_CODE)
+            .times(1)
+            .returning(|_, _, _| Err(RecoveryCodeError::NotFound));
+        dependency_provider.auth_packer.expect_pack_auth().never();
+        // Usecase Initialization
+        let usecase = <VerifyMfa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(request(), &pending_ctx(auth_context_user))
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::InvalidCode);
+    }
+
+    #[rstest]
+    async fn test_verify_mfa_too_many_attempts(
+        mut dependency_provider: MockDependencyProvider,
+        auth_context_user: AuthContext,
+        mfa_record: MfaRecord,
+    ) {
+        // Mock setup
+        dependency_provider
+            .db
+            .mfa_repo
+            .expect_get()
+            .times(1)
+            .returning(move |_, _| Ok(mfa_record.clone()));
+        dependency_provider
+            .db
+            .token_repo
+            .expect_record_request()
+            .times(1)
+            .returning(|_, _, _, _| Ok(MAX_ATTEMPTS + 1));
+        // even a valid code is not checked
+        dependency_provider.totp_service.expect_verify().never();
+        // Usecase Initialization
+        let usecase = <VerifyMfa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(dependency_provider),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(request(), &pending_ctx(auth_context_user))
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::TooManyAttempts);
+    }
+
+    #[rstest]
+    async fn test_verify_mfa_not_pending(auth_context_user: AuthContext) {
+        // Usecase Initialization
+        let usecase = <VerifyMfa<MockDependencyProvider> as Usecase<MockDependencyProvider>>::new(
+            Arc::new(MockDependencyProvider::default()),
+        );
+        // Usecase Execution -- mock predicates will fail during execution
+        let result = usecase
+            .exec(
+                request(),
+                &RequestContext::default().with_auth_context(Some(auth_context_user)),
+            )
+            .await;
+        // Assert execution error
+        assert_eq!(result.unwrap_err(), Error::NotPending);
+    }
+
+    #[rstest]
+    fn test_authorize_pending(auth_context_user: AuthContext) {
+        let usecase = VerifyMfa::new(Arc::new(MockDependencyProvider::default()));
+        let result = usecase.authorize(
+            &request(),
+            Some(auth_context_user.clone().with_pending(PendingStep::Mfa)),
+        );
+        assert!(result.is_ok());
+        // an enrollment token can not skip the second factor
+        let result = usecase.authorize(
+            &request(),
+            Some(auth_context_user.with_pending(PendingStep::MfaEnrollment)),
+        );
+        assert_eq!(result.unwrap_err(), AuthError::Unauthenticated);
+    }
+}
@@ -1,18 +1,73 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::entity::user::Id as UserId;
-use crate::value_object::Role;
+use crate::value_object::{Permission, Role};
+
+/// Metadata of the session the auth context was extracted from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Step a login still has to take before the context counts as
+/// authenticated, only the usecases finishing that step accept it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingStep {
+    /// The second factor has to be verified
+    Mfa,
+    /// The role requires a second factor that is not set up yet
+    MfaEnrollment,
+}
+
+impl std::fmt::Display for PendingStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Mfa => "mfa",
+            Self::MfaEnrollment => "mfa_enrollment",
+        })
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AuthContext {
     pub user_id: UserId,
     pub role: Role,
+    pub permissions: Vec<Permission>,
+    pub session: Option<Session>,
+    pub pending: Option<PendingStep>,
 }
 
 impl AuthContext {
     pub fn new(user_id: UserId, role: Role) -> Self {
-        Self { user_id, role }
+        Self {
+            user_id,
+            role,
+            permissions: Vec::new(),
+            session: None,
+            pending: None,
+        }
+    }
+
+    pub fn with_permissions(mut self, permissions: impl IntoIterator<Item = Permission>) -> Self {
+        self.permissions = permissions.into_iter().collect();
+        self
+    }
+
+    pub fn with_session(mut self, issued_at: DateTime<Utc>, expires_at: DateTime<Utc>) -> Self {
+        self.session = Some(Session {
+            issued_at,
+            expires_at,
+        });
+        self
+    }
+
+    pub fn with_pending(mut self, step: PendingStep) -> Self {
+        self.pending = Some(step);
+        self
     }
 
     pub fn user_id(&self) -> &UserId {
@@ -23,13 +78,29 @@ impl AuthContext {
         &self.role
     }
 
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    pub fn pending(&self) -> Option<PendingStep> {
+        self.pending
+    }
+
     pub fn is_admin(&self) -> bool {
-        self.role == Role::Admin
+        self.role.is_admin()
+    }
+
+    pub fn has_permission(&self, permission: &Permission) -> bool {
+        self.permissions.contains(permission)
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Error, PartialEq)]
 pub enum AuthError {
-    #[error("Unauthorized")]
-    Unauthorized,
+    /// No (valid) credentials were presented
+    #[error("Unauthenticated")]
+    Unauthenticated,
+    /// The caller is known but not allowed to perform the request
+    #[error("Forbidden")]
+    Forbidden,
 }
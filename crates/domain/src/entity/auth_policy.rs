@@ -0,0 +1,233 @@
+use std::{fmt, sync::Arc};
+
+use crate::{
+    entity::{
+        auth_context::{AuthContext, AuthError},
+        auth_strategy::AuthStrategy,
+        user::Id as UserId,
+    },
+    value_object::{Permission, Role},
+};
+
+type Predicate = dyn Fn(&AuthContext, Option<&UserId>) -> bool + Send + Sync;
+
+/// Authorization rule a request has to satisfy before a usecase runs.
+///
+/// Policies are evaluated against the caller's auth context and the owner
+/// of the object the request acts on, and can be combined with `Any`/`All`.
+#[derive(Clone)]
+pub enum Policy {
+    /// Allow access to anyone
+    Public,
+    /// Allow access to any authenticated user
+    Authenticated,
+    /// Allow access to users with the given role
+    HasRole(Role),
+    /// Allow access to users granted the given permission
+    HasPermission(Permission),
+    /// Allow access to the owner of the requested object
+    Owner,
+    /// Allow access if at least one of the policies allows it
+    Any(Vec<Policy>),
+    /// Allow access only if every policy allows it
+    All(Vec<Policy>),
+    /// Allow access if the predicate holds for the authenticated user
+    Custom(Arc<Predicate>),
+}
+
+impl Policy {
+    pub fn any(policies: impl IntoIterator<Item = Policy>) -> Self {
+        Self::Any(policies.into_iter().collect())
+    }
+
+    pub fn all(policies: impl IntoIterator<Item = Policy>) -> Self {
+        Self::All(policies.into_iter().collect())
+    }
+
+    pub fn custom<F>(predicate: F) -> Self
+    where
+        F: Fn(&AuthContext, Option<&UserId>) -> bool + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(predicate))
+    }
+
+    /// Checks the policy, `owner` is the owner of the requested object if
+    /// the usecase has one.
+    pub fn evaluate(
+        &self,
+        auth_context: Option<&AuthContext>,
+        owner: Option<&UserId>,
+    ) -> Result<(), AuthError> {
+        let check = |allowed: bool| match auth_context {
+            None => Err(AuthError::Unauthenticated),
+            Some(_) if allowed => Ok(()),
+            Some(_) => Err(AuthError::Forbidden),
+        };
+        match self {
+            Self::Public => Ok(()),
+            Self::Authenticated => check(true),
+            Self::HasRole(role) => check(auth_context.is_some_and(|auth| &auth.role == role)),
+            Self::HasPermission(permission) => {
+                check(auth_context.is_some_and(|auth| auth.has_permission(permission)))
+            }
+            Self::Owner => check(
+                auth_context
+                    .zip(owner)
+                    .is_some_and(|(auth, owner)| &auth.user_id == owner),
+            ),
+            Self::Any(policies) => {
+                if policies
+                    .iter()
+                    .any(|policy| policy.evaluate(auth_context, owner).is_ok())
+                {
+                    Ok(())
+                } else {
+                    check(false)
+                }
+            }
+            Self::All(policies) => policies
+                .iter()
+                .try_for_each(|policy| policy.evaluate(auth_context, owner)),
+            Self::Custom(predicate) => {
+                check(auth_context.is_some_and(|auth| predicate(auth, owner)))
+            }
+        }
+    }
+
+    /// Whether passing the policy takes more than being authenticated
+    pub fn is_privileged(&self) -> bool {
+        match self {
+            Self::Public | Self::Authenticated => false,
+            Self::HasRole(_) | Self::HasPermission(_) | Self::Owner | Self::Custom(_) => true,
+            Self::Any(policies) => policies.iter().all(Policy::is_privileged),
+            Self::All(policies) => policies.iter().any(Policy::is_privileged),
+        }
+    }
+}
+
+impl From<AuthStrategy> for Policy {
+    fn from(strategy: AuthStrategy) -> Self {
+        match strategy {
+            AuthStrategy::AdminOnly => Self::HasRole(Role::admin()),
+            AuthStrategy::AdminAndOwnerOnly => {
+                Self::any([Self::HasRole(Role::admin()), Self::Owner])
+            }
+            AuthStrategy::Authenticated => Self::Authenticated,
+            AuthStrategy::Public => Self::Public,
+        }
+    }
+}
+
+impl fmt::Debug for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Public => write!(f, "Public"),
+            Self::Authenticated => write!(f, "Authenticated"),
+            Self::HasRole(role) => f.debug_tuple("HasRole").field(role).finish(),
+            Self::HasPermission(permission) => {
+                f.debug_tuple("HasPermission").field(permission).finish()
+            }
+            Self::Owner => write!(f, "Owner"),
+            Self::Any(policies) => f.debug_tuple("Any").field(policies).finish(),
+            Self::All(policies) => f.debug_tuple("All").field(policies).finish(),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn user() -> AuthContext {
+        AuthContext::new(UserId::new(Uuid::from_u128(1)), Role::user())
+            .with_permissions([Permission::new("users:read")])
+    }
+
+    #[test]
+    fn public_allows_anonymous() {
+        assert_eq!(Policy::Public.evaluate(None, None), Ok(()));
+    }
+
+    #[test]
+    fn unauthenticated_and_forbidden_are_distinct() {
+        let policy = Policy::HasRole(Role::admin());
+        assert_eq!(policy.evaluate(None, None), Err(AuthError::Unauthenticated));
+        assert_eq!(
+            policy.evaluate(Some(&user()), None),
+            Err(AuthError::Forbidden)
+        );
+    }
+
+    #[test]
+    fn has_permission() {
+        let auth = user();
+        assert_eq!(
+            Policy::HasPermission(Permission::new("users:read")).evaluate(Some(&auth), None),
+            Ok(())
+        );
+        assert_eq!(
+            Policy::HasPermission(Permission::new("users:delete")).evaluate(Some(&auth), None),
+            Err(AuthError::Forbidden)
+        );
+    }
+
+    #[test]
+    fn owner_without_owner_is_forbidden() {
+        let auth = user();
+        assert_eq!(
+            Policy::Owner.evaluate(Some(&auth), Some(&auth.user_id)),
+            Ok(())
+        );
+        assert_eq!(
+            Policy::Owner.evaluate(Some(&auth), None),
+            Err(AuthError::Forbidden)
+        );
+    }
+
+    #[test]
+    fn combinators() {
+        let auth = user();
+        let other = UserId::new(Uuid::from_u128(2));
+        let admin_or_owner: Policy = AuthStrategy::AdminAndOwnerOnly.into();
+        assert_eq!(
+            admin_or_owner.evaluate(Some(&auth), Some(&auth.user_id)),
+            Ok(())
+        );
+        assert_eq!(
+            admin_or_owner.evaluate(Some(&auth), Some(&other)),
+            Err(AuthError::Forbidden)
+        );
+        let owner_with_permission = Policy::all([
+            Policy::Owner,
+            Policy::HasPermission(Permission::new("users:read")),
+        ]);
+        assert_eq!(
+            owner_with_permission.evaluate(Some(&auth), Some(&auth.user_id)),
+            Ok(())
+        );
+        assert_eq!(
+            owner_with_permission.evaluate(None, Some(&auth.user_id)),
+            Err(AuthError::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn custom_predicate() {
+        let policy = Policy::custom(|auth, _| auth.session.is_some());
+        assert_eq!(
+            policy.evaluate(Some(&user()), None),
+            Err(AuthError::Forbidden)
+        );
+        assert_eq!(policy.evaluate(None, None), Err(AuthError::Unauthenticated));
+    }
+
+    #[test]
+    fn privileged() {
+        assert!(!Policy::Authenticated.is_privileged());
+        assert!(Policy::from(AuthStrategy::AdminOnly).is_privileged());
+        assert!(Policy::from(AuthStrategy::AdminAndOwnerOnly).is_privileged());
+        assert!(!Policy::any([Policy::Public, Policy::Owner]).is_privileged());
+    }
+}
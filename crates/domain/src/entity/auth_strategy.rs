@@ -6,6 +6,8 @@ pub enum AuthStrategy {
     AdminOnly,
     // Allow access only to owner and admin
     AdminAndOwnerOnly,
+    // Allow access to any authenticated user
+    Authenticated,
     // Allow access to anyone
     Public,
 }
@@ -1,4 +1,6 @@
 pub mod auth_context;
+pub mod auth_policy;
 pub mod auth_strategy;
 pub mod signup_process;
 pub mod user;
+pub mod user_identity;
@@ -2,7 +2,7 @@ use std::{str::FromStr, sync::Arc};
 
 use crate::{
     entity::user::{Email, Password, UserName},
-    value_object::{self},
+    value_object::{self, SignupPolicy},
 };
 
 use chrono::{DateTime, Utc};
@@ -14,16 +14,27 @@ pub struct SignupProcessValue;
 
 pub type Id = value_object::Id<SignupProcessValue>;
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SignupStateEnum {
     Initialized {
         email: Email,
     },
     VerificationEmailSent {
         email: Email,
+        /// When the latest verification email went out
+        sent_at: DateTime<Utc>,
+        /// Emails sent after the first one
+        #[serde(default)]
+        resends: u32,
+        /// Times the verification window was extended after timing out
+        #[serde(default)]
+        extensions: u32,
     },
     EmailVerified {
         email: Email,
+        /// Times the completion window was extended after timing out
+        #[serde(default)]
+        extensions: u32,
     },
     Completed {
         email: Email,
@@ -45,10 +56,14 @@ pub struct Initialized {
 #[derive(Debug, Clone)]
 pub struct VerificationEmailSent {
     pub email: Email,
+    pub sent_at: DateTime<Utc>,
+    pub resends: u32,
+    pub extensions: u32,
 }
 #[derive(Debug, Clone)]
 pub struct EmailVerified {
     pub email: Email,
+    pub extensions: u32,
 }
 #[derive(Debug, Clone)]
 pub struct Completed {
@@ -59,7 +74,7 @@ pub struct Completed {
 #[derive(Debug, Clone)]
 pub struct ForDeletion {}
 
-#[derive(Debug, Clone, Serialize, Error)]
+#[derive(Debug, Clone, Serialize, Deserialize, Error, PartialEq)]
 pub enum Error {
     #[error("Token generation failed")]
     TokenGenrationFailed,
@@ -71,17 +86,28 @@ pub enum Error {
     CompletionTimedOut,
     #[error("Token Expired")]
     TokenExpired,
+    #[error("Too many verification attempts")]
+    TooManyAttempts,
 }
 
+#[derive(Debug, Clone, Error, PartialEq)]
+#[error("Unknown signup process error {0:?}")]
+pub struct UnknownError(pub String);
+
 impl FromStr for Error {
-    type Err = ();
+    type Err = UnknownError;
+    /// Parses both the display message and the variant name
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "Token generation failed" => Ok(Error::TokenGenrationFailed),
-            "Verification Email send failed" => Ok(Error::VerificationEmailSendError),
-            "Token expired" => Ok(Error::VerificationTimedOut),
-            "Completion timed out" => Ok(Error::CompletionTimedOut),
-            _ => Err(()),
+            "Token generation failed" | "TokenGenrationFailed" => Ok(Error::TokenGenrationFailed),
+            "Verification Email send failed" | "VerificationEmailSendError" => {
+                Ok(Error::VerificationEmailSendError)
+            }
+            "Token expired" | "VerificationTimedOut" => Ok(Error::VerificationTimedOut),
+            "Completion timed out" | "CompletionTimedOut" => Ok(Error::CompletionTimedOut),
+            "Token Expired" | "TokenExpired" => Ok(Error::TokenExpired),
+            "Too many verification attempts" | "TooManyAttempts" => Ok(Error::TooManyAttempts),
+            _ => Err(UnknownError(s.to_string())),
         }
     }
 }
@@ -116,7 +142,7 @@ impl<S: SignupStateTrait> SignupProcess<S> {
     pub fn id(&self) -> Id {
         self.id
     }
-    pub fn fail(&self, error: Error) -> SignupProcess<Failed<S>> {
+    pub fn fail(&self, error: Error, now: DateTime<Utc>) -> SignupProcess<Failed<S>> {
         let state = Failed {
             previous_state: self.state.clone(),
             error,
@@ -124,57 +150,101 @@ impl<S: SignupStateTrait> SignupProcess<S> {
         SignupProcess {
             id: self.id,
             state,
-            entered_at: Utc::now(),
+            entered_at: now,
         }
     }
 }
 
 impl SignupProcess<Initialized> {
-    pub fn new(id: Id, email: Email) -> Self {
+    pub fn new(id: Id, email: Email, now: DateTime<Utc>) -> Self {
         let state = Initialized { email };
         Self {
             id,
             state,
-            entered_at: Utc::now(),
+            entered_at: now,
         }
     }
-    pub fn send_verification_email(self) -> SignupProcess<VerificationEmailSent> {
+    pub fn send_verification_email(
+        self,
+        now: DateTime<Utc>,
+    ) -> SignupProcess<VerificationEmailSent> {
         let state = VerificationEmailSent {
             email: self.state.email,
+            sent_at: now,
+            resends: 0,
+            extensions: 0,
         };
         SignupProcess {
             id: self.id,
             state,
-            entered_at: Utc::now(),
+            entered_at: now,
         }
     }
 }
 
 impl SignupProcess<VerificationEmailSent> {
-    pub fn verify_email(self) -> SignupProcess<EmailVerified> {
-        let state = EmailVerified {
-            email: self.state.email,
+    /// Sends another verification email, the process stays in the same state
+    /// and counts the resend
+    pub fn resend_verification_email(
+        self,
+        now: DateTime<Utc>,
+    ) -> SignupProcess<VerificationEmailSent> {
+        let state = VerificationEmailSent {
+            sent_at: now,
+            resends: self.state.resends + 1,
+            ..self.state
         };
         SignupProcess {
             id: self.id,
             state,
-            entered_at: Utc::now(),
+            entered_at: now,
         }
     }
+    /// Verifies the email, fails the process if the verification window
+    /// of the policy has passed
+    pub fn verify_email(
+        self,
+        policy: &SignupPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<SignupProcess<EmailVerified>, SignupProcess<Failed<VerificationEmailSent>>> {
+        if policy.verification_expired(self.entered_at, now) {
+            return Err(self.fail(Error::VerificationTimedOut, now));
+        }
+        let state = EmailVerified {
+            email: self.state.email,
+            extensions: 0,
+        };
+        Ok(SignupProcess {
+            id: self.id,
+            state,
+            entered_at: now,
+        })
+    }
 }
 
 impl SignupProcess<EmailVerified> {
-    pub fn complete(self, username: UserName, password: Password) -> SignupProcess<Completed> {
+    /// Completes the signup, fails the process if the completion window of
+    /// the policy has passed
+    pub fn complete(
+        self,
+        username: UserName,
+        password: Password,
+        policy: &SignupPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<SignupProcess<Completed>, SignupProcess<Failed<EmailVerified>>> {
+        if policy.completion_expired(self.entered_at, now) {
+            return Err(self.fail(Error::CompletionTimedOut, now));
+        }
         let state = Completed {
             email: self.state.email,
             username,
             password,
         };
-        SignupProcess {
+        Ok(SignupProcess {
             id: self.id,
             state,
-            entered_at: Utc::now(),
-        }
+            entered_at: now,
+        })
     }
 }
 
@@ -190,21 +260,63 @@ impl SignupProcess<Completed> {
     }
 }
 
+impl SignupProcess<Failed<VerificationEmailSent>> {
+    /// Opens a new verification window for a timed out process and counts
+    /// the extension
+    pub fn extend(&self, now: DateTime<Utc>) -> SignupProcess<VerificationEmailSent> {
+        let mut process = self.recover(now);
+        process.state.extensions += 1;
+        process
+    }
+    /// The process to go on with once a lockout is over, back in the locked
+    /// state as it was entered at `entered_at`, so the lockout does not move
+    /// its verification window. Nothing is saved for it, so lifting the lock
+    /// leaves the chain and the counters in the state alone.
+    pub fn unlock(&self, entered_at: DateTime<Utc>) -> SignupProcess<VerificationEmailSent> {
+        SignupProcess {
+            id: self.id,
+            state: self.state.previous_state.clone(),
+            entered_at,
+        }
+    }
+}
+
+impl SignupProcess<Failed<EmailVerified>> {
+    /// Opens a new completion window for a timed out process and counts the
+    /// extension
+    pub fn extend(&self, now: DateTime<Utc>) -> SignupProcess<EmailVerified> {
+        let mut process = self.recover(now);
+        process.state.extensions += 1;
+        process
+    }
+}
+
+impl Error {
+    /// Whether the process failed because one of its windows closed, only
+    /// those failures can be extended
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            Self::VerificationTimedOut | Self::CompletionTimedOut | Self::TokenExpired
+        )
+    }
+}
+
 impl<S: SignupStateTrait> SignupProcess<Failed<S>> {
-    pub fn recover(&self) -> SignupProcess<S> {
+    pub fn recover(&self, now: DateTime<Utc>) -> SignupProcess<S> {
         let state = self.state.previous_state.clone();
         SignupProcess {
             id: self.id,
             state,
-            entered_at: Utc::now(),
+            entered_at: now,
         }
     }
-    pub fn delete(self) -> SignupProcess<ForDeletion> {
+    pub fn delete(self, now: DateTime<Utc>) -> SignupProcess<ForDeletion> {
         let state = ForDeletion {};
         SignupProcess {
             id: self.id,
             state,
-            entered_at: Utc::now(),
+            entered_at: now,
         }
     }
 }
@@ -222,7 +334,17 @@ impl TryFrom<SignupStateEnum> for VerificationEmailSent {
     type Error = ();
     fn try_from(value: SignupStateEnum) -> Result<Self, Self::Error> {
         match value {
-            SignupStateEnum::VerificationEmailSent { email } => Ok(Self { email }),
+            SignupStateEnum::VerificationEmailSent {
+                email,
+                sent_at,
+                resends,
+                extensions,
+            } => Ok(Self {
+                email,
+                sent_at,
+                resends,
+                extensions,
+            }),
             _ => Err(()),
         }
     }
@@ -231,7 +353,7 @@ impl TryFrom<SignupStateEnum> for EmailVerified {
     type Error = ();
     fn try_from(value: SignupStateEnum) -> Result<Self, Self::Error> {
         match value {
-            SignupStateEnum::EmailVerified { email } => Ok(Self { email }),
+            SignupStateEnum::EmailVerified { email, extensions } => Ok(Self { email, extensions }),
             _ => Err(()),
         }
     }
@@ -303,13 +425,21 @@ impl Into<SignupStateEnum> for Initialized {
 #[allow(clippy::from_over_into)]
 impl Into<SignupStateEnum> for VerificationEmailSent {
     fn into(self) -> SignupStateEnum {
-        SignupStateEnum::VerificationEmailSent { email: self.email }
+        SignupStateEnum::VerificationEmailSent {
+            email: self.email,
+            sent_at: self.sent_at,
+            resends: self.resends,
+            extensions: self.extensions,
+        }
     }
 }
 #[allow(clippy::from_over_into)]
 impl Into<SignupStateEnum> for EmailVerified {
     fn into(self) -> SignupStateEnum {
-        SignupStateEnum::EmailVerified { email: self.email }
+        SignupStateEnum::EmailVerified {
+            email: self.email,
+            extensions: self.extensions,
+        }
     }
 }
 #[allow(clippy::from_over_into)]
@@ -352,11 +482,11 @@ mod tests {
 
         #[fixture]
         pub fn username() -> UserName {
-            UserName::new("test_username".to_string())
+            UserName::new_unchecked("test_username".to_string())
         }
         #[fixture]
         pub fn password() -> Password {
-            Password::new("test_pass".to_string())
+            Password::new_unchecked("test_pass".to_string())
         }
         #[fixture]
         pub fn id() -> Id {
@@ -364,13 +494,15 @@ mod tests {
         }
         #[fixture]
         pub fn email() -> Email {
-            Email::new("test_email")
+            Email::new_unchecked("test_email")
         }
         #[rstest]
         // Test that a new SignupProcess<Initialized> is created with the correct id and username
         fn test_signup_process_initialization(id: Id, email: Email) {
-            let signup_process = SignupProcess::new(id, email.clone());
+            let now = Utc::now();
+            let signup_process = SignupProcess::new(id, email.clone(), now);
             assert_eq!(signup_process.id, id);
+            assert_eq!(signup_process.entered_at, now);
             assert_eq!(signup_process.state.email.to_string(), email.to_string());
         }
         #[rstest]
@@ -389,5 +521,128 @@ mod tests {
                 unreachable!("Invalid state");
             }
         }
+        #[rstest]
+        // Test that verification and completion fail once the policy windows have passed
+        fn test_signup_process_policy_windows(
+            id: Id,
+            email: Email,
+            username: UserName,
+            password: Password,
+        ) {
+            let policy = SignupPolicy::default();
+            let start = Utc::now();
+            let sent = SignupProcess::new(id, email, start).send_verification_email(start);
+            let late = start + policy.verification_ttl + chrono::Duration::seconds(1);
+            let failed = sent.clone().verify_email(&policy, late).unwrap_err();
+            assert!(matches!(failed.state.error, Error::VerificationTimedOut));
+            assert_eq!(failed.entered_at, late);
+            let verified = sent
+                .verify_email(&policy, start + policy.verification_ttl)
+                .unwrap();
+            let verified_at = verified.entered_at;
+            let late = verified_at + policy.completion_ttl + chrono::Duration::seconds(1);
+            let failed = verified
+                .clone()
+                .complete(username.clone(), password.clone(), &policy, late)
+                .unwrap_err();
+            assert!(matches!(failed.state.error, Error::CompletionTimedOut));
+            assert!(verified
+                .complete(
+                    username,
+                    password,
+                    &policy,
+                    verified_at + policy.completion_ttl
+                )
+                .is_ok());
+        }
+        #[rstest]
+        // Test that resends are counted in the state and survive failing and recovering
+        fn test_signup_process_resend_counter(id: Id, email: Email) {
+            let start = Utc::now();
+            let sent = SignupProcess::new(id, email, start).send_verification_email(start);
+            assert_eq!(sent.state.resends, 0);
+            let later = start + chrono::Duration::minutes(5);
+            let resent = sent.resend_verification_email(later);
+            assert_eq!(resent.state.resends, 1);
+            assert_eq!(resent.state.sent_at, later);
+            let recovered = resent
+                .fail(Error::VerificationTimedOut, later)
+                .recover(later + chrono::Duration::minutes(1));
+            assert_eq!(recovered.state.resends, 1);
+            assert_eq!(recovered.state.sent_at, later);
+        }
+        #[rstest]
+        // Test that only extending counts as an extension, recovering does not
+        fn test_signup_process_extension_counter(id: Id, email: Email) {
+            let now = Utc::now();
+            let sent = SignupProcess::new(id, email, now).send_verification_email(now);
+            let extended = sent.fail(Error::VerificationTimedOut, now).extend(now);
+            assert_eq!(extended.state.extensions, 1);
+            let recovered = extended.fail(Error::TooManyAttempts, now).recover(now);
+            assert_eq!(recovered.state.extensions, 1);
+            let verified = recovered
+                .verify_email(&SignupPolicy::default(), now)
+                .unwrap();
+            assert_eq!(verified.state.extensions, 0);
+            let extended = verified.fail(Error::CompletionTimedOut, now).extend(now);
+            assert_eq!(extended.state.extensions, 1);
+            assert!(Error::CompletionTimedOut.is_timeout());
+            assert!(!Error::TooManyAttempts.is_timeout());
+        }
+        #[rstest]
+        // Test that lifting a lockout goes on with the locked state as it was
+        fn test_signup_process_unlock(id: Id, email: Email) {
+            let start = Utc::now();
+            let locked_at = start + chrono::Duration::minutes(5);
+            let locked = SignupProcess::new(id, email, start)
+                .send_verification_email(start)
+                .resend_verification_email(start)
+                .fail(Error::TooManyAttempts, locked_at);
+            let unlocked = locked.unlock(start);
+            assert_eq!(unlocked.entered_at, start);
+            assert_eq!(unlocked.state.resends, 1);
+            assert_eq!(unlocked.state.sent_at, start);
+        }
+        #[rstest]
+        // Test that a lockout ending after the verification window does not reopen it
+        fn test_signup_process_unlock_after_window(id: Id, email: Email) {
+            let policy = SignupPolicy::default();
+            let start = Utc::now();
+            let locked_at = start + policy.verification_ttl - chrono::Duration::minutes(1);
+            let locked = SignupProcess::new(id, email, start)
+                .send_verification_email(start)
+                .fail(Error::TooManyAttempts, locked_at);
+            let unlocked_at = locked_at + policy.attempt_lockout;
+            let failed = locked
+                .unlock(start)
+                .verify_email(&policy, unlocked_at)
+                .unwrap_err();
+            assert!(matches!(failed.state.error, Error::VerificationTimedOut));
+        }
+    }
+
+    mod error {
+        use super::*;
+        use rstest::*;
+
+        #[rstest]
+        #[case(Error::TokenGenrationFailed)]
+        #[case(Error::VerificationEmailSendError)]
+        #[case(Error::VerificationTimedOut)]
+        #[case(Error::CompletionTimedOut)]
+        #[case(Error::TokenExpired)]
+        #[case(Error::TooManyAttempts)]
+        // Test that every error can be parsed back from its message and variant name
+        fn test_error_from_str(#[case] error: Error) {
+            assert_eq!(Error::from_str(&error.to_string()), Ok(error.clone()));
+            assert_eq!(Error::from_str(&format!("{error:?}")), Ok(error));
+        }
+        #[rstest]
+        fn test_error_from_str_unknown() {
+            assert_eq!(
+                Error::from_str("Out of coffee"),
+                Err(UnknownError("Out of coffee".to_string()))
+            );
+        }
     }
 }
@@ -18,16 +18,6 @@ pub struct User {
 
 impl User {
     pub fn new(id: Id, role: Role, email: Email, username: UserName, password: Password) -> Self {
-        // Never construct an area of life with invalid name
-        debug_assert!(username.as_ref().len() <= UserName::max_len());
-        debug_assert!(username.as_ref().len() >= UserName::min_len());
-
-        debug_assert!(email.as_ref().len() <= Email::max_len());
-        debug_assert!(email.as_ref().len() >= Email::min_len());
-
-        debug_assert!(password.as_ref().len() <= Password::max_len());
-        debug_assert!(password.as_ref().len() >= Password::min_len());
-
         Self {
             id,
             role,
@@ -37,20 +27,13 @@ impl User {
         }
     }
     pub fn update(&mut self, email: Email, username: UserName, password: Password) {
-        // Never construct an area of life with invalid name
-        debug_assert!(username.as_ref().len() <= UserName::max_len());
-        debug_assert!(username.as_ref().len() >= UserName::min_len());
-
-        debug_assert!(email.as_ref().len() <= Email::max_len());
-        debug_assert!(email.as_ref().len() >= Email::min_len());
-
-        debug_assert!(password.as_ref().len() <= Password::max_len());
-        debug_assert!(password.as_ref().len() >= Password::min_len());
-
         self.email = email;
         self.username = username;
         self.password = password;
     }
+    pub fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
     pub const fn id(&self) -> Id {
         self.id
     }
@@ -68,38 +51,27 @@ impl User {
     }
 }
 
-const MAX_NAME_LEN: usize = 30;
-const MIN_NAME_LEN: usize = 5;
+/// Names nobody can sign up with, compared by their canonical form
+const RESERVED_NAMES: &[&str] = &[
+    "admin",
+    "administrator",
+    "anonymous",
+    "moderator",
+    "postmaster",
+    "support",
+    "system",
+    "webmaster",
+];
 
 impl UserName {
-    pub const fn min_len() -> usize {
-        MIN_NAME_LEN
-    }
-    pub const fn max_len() -> usize {
-        MAX_NAME_LEN
-    }
-}
-
-const MAX_EMAIL_LEN: usize = 30;
-const MIN_EMAIL_LEN: usize = 5;
-
-impl Email {
-    pub const fn min_len() -> usize {
-        MIN_EMAIL_LEN
-    }
-    pub const fn max_len() -> usize {
-        MAX_EMAIL_LEN
-    }
-}
-
-const MAX_PASSWORD_LEN: usize = 30;
-const MIN_PASSWORD_LEN: usize = 5;
-
-impl Password {
-    pub const fn min_len() -> usize {
-        MIN_PASSWORD_LEN
+    pub const fn reserved() -> &'static [&'static str] {
+        RESERVED_NAMES
     }
-    pub const fn max_len() -> usize {
-        MAX_PASSWORD_LEN
+    /// Whether the name is kept back for the system and staff
+    pub fn is_reserved(&self) -> bool {
+        let canonical = self.canonical();
+        RESERVED_NAMES
+            .iter()
+            .any(|name| UserName::new_unchecked(*name).canonical() == canonical)
     }
 }
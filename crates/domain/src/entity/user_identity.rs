@@ -0,0 +1,33 @@
+use serde::Serialize;
+
+use crate::entity::user::Id as UserId;
+
+/// Account of a user at an external OpenID Connect provider, the user logs
+/// in through it instead of a password
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct UserIdentity {
+    provider: String,
+    subject: String,
+    user_id: UserId,
+}
+
+impl UserIdentity {
+    /// `subject` is the `sub` claim, which the provider keeps stable and
+    /// unique, unlike the email
+    pub fn new(provider: impl Into<String>, subject: impl Into<String>, user_id: UserId) -> Self {
+        Self {
+            provider: provider.into(),
+            subject: subject.into(),
+            user_id,
+        }
+    }
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+    pub const fn user_id(&self) -> UserId {
+        self.user_id
+    }
+}
@@ -1,16 +1,169 @@
 use std::{
     fmt::{self, Debug, Display},
     marker::PhantomData,
+    str::FromStr,
 };
 
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use super::validation::{check_len, Field, ValidationError};
+
+/// RFC 5321 limit of a forward path
+const MAX_EMAIL_LEN: usize = 254;
+const MIN_EMAIL_LEN: usize = 3;
+const MAX_LOCAL_PART_LEN: usize = 64;
+const MAX_LABEL_LEN: usize = 63;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Email<T>(String, PhantomData<T>);
 
 impl<T> Email<T> {
-    pub fn new(name: impl Into<String>) -> Self {
-        Self(name.into(), PhantomData)
+    /// Parses an RFC 5322 `addr-spec` with a dot-atom or quoted local part
+    /// and a host name domain, address literals are not accepted
+    pub fn parse(email: impl Into<String>) -> Result<Self, ValidationError> {
+        let email = email.into();
+        check_len(Field::Email, &email, MIN_EMAIL_LEN, MAX_EMAIL_LEN)?;
+        let (local, domain) = email.rsplit_once('@').ok_or(malformed("missing @"))?;
+        check_local_part(local)?;
+        check_domain(domain)?;
+        Ok(Self(email, PhantomData))
+    }
+    /// Wraps an address that was validated before, e.g. when loading it
+    /// from storage
+    pub fn new_unchecked(email: impl Into<String>) -> Self {
+        Self(email.into(), PhantomData)
+    }
+    /// Form addresses are compared and looked up by: NFKC normalized with a
+    /// lower cased domain. The local part is lower cased as well since
+    /// providers treat it case insensitively in practice, a quoted local
+    /// part is the explicit way to ask for exact matching and is kept as is
+    pub fn canonical(&self) -> String {
+        let email: String = self.0.nfkc().collect();
+        match email.rsplit_once('@') {
+            Some((local, domain)) if local.starts_with('"') => {
+                format!("{local}@{}", domain.to_lowercase())
+            }
+            Some((local, domain)) => {
+                format!("{}@{}", local.to_lowercase(), domain.to_lowercase())
+            }
+            None => email.to_lowercase(),
+        }
+    }
+    pub const fn min_len() -> usize {
+        MIN_EMAIL_LEN
+    }
+    pub const fn max_len() -> usize {
+        MAX_EMAIL_LEN
+    }
+}
+
+const fn malformed(reason: &'static str) -> ValidationError {
+    ValidationError::MalformedEmail { reason }
+}
+
+/// `atext` of RFC 5322
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c)
+}
+
+fn check_local_part(local: &str) -> Result<(), ValidationError> {
+    if local.is_empty() {
+        return Err(malformed("empty local part"));
+    }
+    if local.len() > MAX_LOCAL_PART_LEN {
+        return Err(malformed("local part longer than 64 characters"));
+    }
+    if let Some(quoted) = local
+        .strip_prefix('"')
+        .and_then(|local| local.strip_suffix('"'))
+    {
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                // quoted-pair
+                '\\' => match chars.next() {
+                    Some(' '..='~') => {}
+                    _ => return Err(malformed("invalid escape in quoted local part")),
+                },
+                '"' => return Err(malformed("unescaped quote in quoted local part")),
+                ' '..='~' => {}
+                character => {
+                    return Err(ValidationError::InvalidCharacter {
+                        field: Field::Email,
+                        character,
+                    })
+                }
+            }
+        }
+        return Ok(());
+    }
+    for atom in local.split('.') {
+        if atom.is_empty() {
+            return Err(malformed("empty dot separated part in local part"));
+        }
+        if let Some(character) = atom.chars().find(|c| !is_atext(*c)) {
+            return Err(ValidationError::InvalidCharacter {
+                field: Field::Email,
+                character,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_domain(domain: &str) -> Result<(), ValidationError> {
+    if domain.is_empty() {
+        return Err(malformed("empty domain"));
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 {
+        return Err(malformed("domain without a top level domain"));
+    }
+    for label in &labels {
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return Err(malformed("domain label must be 1 to 63 characters long"));
+        }
+        if let Some(character) = label
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && *c != '-')
+        {
+            return Err(ValidationError::InvalidCharacter {
+                field: Field::Email,
+                character,
+            });
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(malformed("domain label starts or ends with a hyphen"));
+        }
+    }
+    if labels
+        .last()
+        .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(malformed("numeric top level domain"));
+    }
+    Ok(())
+}
+
+impl<T> TryFrom<&str> for Email<T> {
+    type Error = ValidationError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl<T> TryFrom<String> for Email<T> {
+    type Error = ValidationError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl<T> FromStr for Email<T> {
+    type Err = ValidationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
     }
 }
 
@@ -37,3 +190,70 @@ impl<T> Display for Email<T> {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestEmail = Email<()>;
+
+    #[test]
+    fn valid() {
+        for email in [
+            "test@email.com",
+            "first.last+tag@sub.example.org",
+            "o'brien@example.co.uk",
+            "\"john doe\"@example.com",
+            "x@a-b.io",
+        ] {
+            assert!(TestEmail::parse(email).is_ok(), "{email}");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for email in [
+            "ttt",
+            "@example.com",
+            "test@",
+            "test@localhost",
+            "te..st@example.com",
+            ".test@example.com",
+            "test@-example.com",
+            "test@example.123",
+            "te st@example.com",
+            "test@exa_mple.com",
+            "\"unterminated@example.com",
+        ] {
+            assert!(TestEmail::parse(email).is_err(), "{email}");
+        }
+        let long = format!("{}@example.com", "a".repeat(65));
+        assert_eq!(
+            TestEmail::parse(long),
+            Err(malformed("local part longer than 64 characters"))
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        assert_eq!(
+            TestEmail::parse("Foo.Bar@Example.COM").unwrap().canonical(),
+            "foo.bar@example.com"
+        );
+        assert_eq!(
+            TestEmail::parse("foo@example.com").unwrap().canonical(),
+            TestEmail::parse("FOO@EXAMPLE.com").unwrap().canonical()
+        );
+        assert_eq!(
+            TestEmail::parse("\"John Doe\"@Example.com")
+                .unwrap()
+                .canonical(),
+            "\"John Doe\"@example.com"
+        );
+        // compatibility forms of values loaded from storage are folded
+        assert_eq!(
+            TestEmail::new_unchecked("ｆｏｏ@example.com").canonical(),
+            "foo@example.com"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Lifetime and limits of the links that log a user in without a password.
+///
+/// The defaults are a link valid for a quarter of an hour, at most three
+/// links an hour per user and twenty per client address, and five wrong
+/// tokens before the outstanding link is thrown away.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginLinkPolicy {
+    /// Time a login link stays valid
+    pub ttl: Duration,
+    /// Number of links a user can request within `request_window`
+    pub max_requests: u32,
+    /// Number of links one client address can request within
+    /// `request_window`, whichever users they are for
+    pub max_client_requests: u32,
+    /// Window the requested links are counted in
+    pub request_window: Duration,
+    /// Failed logins after which the outstanding link is invalidated
+    pub max_attempts: u32,
+    /// Page the link points to, it gets the `username` and `token` appended
+    /// as query parameters, without one the email only holds the token
+    pub link_url: Option<String>,
+}
+
+impl Default for LoginLinkPolicy {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::minutes(15),
+            max_requests: 3,
+            max_client_requests: 20,
+            request_window: Duration::hours(1),
+            max_attempts: 5,
+            link_url: None,
+        }
+    }
+}
+
+impl LoginLinkPolicy {
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+    pub fn with_max_requests(mut self, max_requests: u32) -> Self {
+        self.max_requests = max_requests;
+        self
+    }
+    pub fn with_max_client_requests(mut self, max_client_requests: u32) -> Self {
+        self.max_client_requests = max_client_requests;
+        self
+    }
+    pub fn with_request_window(mut self, window: Duration) -> Self {
+        self.request_window = window;
+        self
+    }
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+    pub fn with_link_url(mut self, url: impl Into<String>) -> Self {
+        self.link_url = Some(url.into());
+        self
+    }
+}
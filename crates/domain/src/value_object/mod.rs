@@ -1,11 +1,23 @@
 mod email;
 mod id;
+mod login_link_policy;
 mod password;
+mod password_policy;
+mod permission;
 mod role;
+mod secret;
+mod signup_policy;
 mod username;
+mod validation;
 
 pub use email::*;
 pub use id::*;
+pub use login_link_policy::*;
 pub use password::*;
+pub use password_policy::*;
+pub use permission::*;
 pub use role::*;
+pub use secret::*;
+pub use signup_policy::*;
 pub use username::*;
+pub use validation::{Field, ValidationError};
@@ -1,39 +1,80 @@
 use std::{
     fmt::{self, Debug, Display},
     marker::PhantomData,
+    str::FromStr,
 };
 
 use serde::{Deserialize, Serialize};
 
+use super::{
+    secret::SecretString,
+    validation::{check_len, Field, ValidationError},
+};
+
+const MAX_PASSWORD_LEN: usize = 60;
+const MIN_PASSWORD_LEN: usize = 5;
+
+/// Password kept as a [`SecretString`], redacted wherever it is printed
+/// or serialized
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct Password<T>(String, PhantomData<T>);
+pub struct Password<T>(SecretString, PhantomData<T>);
 
 impl<T> Password<T> {
-    pub fn new(name: impl Into<String>) -> Self {
-        Self(name.into(), PhantomData)
+    pub fn parse(password: impl Into<String>) -> Result<Self, ValidationError> {
+        let password = password.into();
+        check_len(
+            Field::Password,
+            &password,
+            MIN_PASSWORD_LEN,
+            MAX_PASSWORD_LEN,
+        )?;
+        Ok(Self(password.into(), PhantomData))
+    }
+    /// Wraps a password that was validated before, e.g. when loading it
+    /// from storage
+    pub fn new_unchecked(password: impl Into<String>) -> Self {
+        Self(SecretString::new(password), PhantomData)
+    }
+    pub fn expose_secret(&self) -> &str {
+        self.0.expose_secret()
+    }
+    pub const fn min_len() -> usize {
+        MIN_PASSWORD_LEN
+    }
+    pub const fn max_len() -> usize {
+        MAX_PASSWORD_LEN
+    }
+}
+
+impl<T> TryFrom<&str> for Password<T> {
+    type Error = ValidationError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
     }
 }
 
-impl<T> AsRef<str> for Password<T> {
-    fn as_ref(&self) -> &str {
-        &self.0
+impl<T> TryFrom<String> for Password<T> {
+    type Error = ValidationError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(value)
     }
 }
 
-impl<T> From<Password<T>> for String {
-    fn from(from: Password<T>) -> Self {
-        from.0
+impl<T> FromStr for Password<T> {
+    type Err = ValidationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
     }
 }
 
 impl<T> Debug for Password<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        Debug::fmt(&self.0, f)
     }
 }
 
 impl<T> Display for Password<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        Display::fmt(&self.0, f)
     }
 }
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Debug, Display},
+    sync::Arc,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{Email, Field, Password, UserName};
+
+/// Strength rules a new password has to meet on top of the length limits
+/// of [`Password`].
+///
+/// The defaults ask for two character classes and about 35 bits of
+/// estimated entropy, the breached list is empty until one is loaded.
+#[derive(Clone, PartialEq)]
+pub struct PasswordPolicy {
+    /// Number of classes out of lower case, upper case, digits and symbols
+    pub min_character_classes: u8,
+    /// Estimated entropy in bits, see [`PasswordPolicy::entropy_bits`]
+    pub min_entropy_bits: u32,
+    /// Whether passwords close to the username or email are rejected
+    pub reject_similar_to_identity: bool,
+    /// Lower cased breached or common passwords
+    breached: Arc<HashSet<String>>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_character_classes: 2,
+            min_entropy_bits: 35,
+            reject_similar_to_identity: true,
+            breached: Arc::default(),
+        }
+    }
+}
+
+impl Debug for PasswordPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordPolicy")
+            .field("min_character_classes", &self.min_character_classes)
+            .field("min_entropy_bits", &self.min_entropy_bits)
+            .field(
+                "reject_similar_to_identity",
+                &self.reject_similar_to_identity,
+            )
+            .field("breached", &self.breached.len())
+            .finish()
+    }
+}
+
+/// One reason a password was rejected
+#[derive(Debug, Clone, Serialize, Error, PartialEq, Eq)]
+pub enum PasswordWeakness {
+    #[error(
+        "password mixes {found} of lower case, upper case, digits and symbols, {min} are needed"
+    )]
+    TooFewCharacterClasses { found: u8, min: u8 },
+    #[error("password is too predictable, about {bits} bits of entropy where {min} are needed")]
+    TooPredictable { bits: u32, min: u32 },
+    #[error("password is too similar to the {field}")]
+    SimilarTo { field: Field },
+    #[error("password appears in a list of breached or common passwords")]
+    Breached,
+}
+
+/// All the reasons a password did not meet a [`PasswordPolicy`]
+#[derive(Debug, Clone, Serialize, Error, PartialEq, Eq)]
+pub struct PasswordRejected {
+    pub reasons: Vec<PasswordWeakness>,
+}
+
+impl Display for PasswordRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "password rejected: ")?;
+        for (i, reason) in self.reasons.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PasswordPolicy {
+    pub fn with_min_character_classes(mut self, min: u8) -> Self {
+        self.min_character_classes = min;
+        self
+    }
+    pub fn with_min_entropy_bits(mut self, min: u32) -> Self {
+        self.min_entropy_bits = min;
+        self
+    }
+    pub fn with_reject_similar_to_identity(mut self, reject: bool) -> Self {
+        self.reject_similar_to_identity = reject;
+        self
+    }
+    /// Replaces the breached list, e.g. with the lines of an offline file
+    pub fn with_breached<I, S>(mut self, passwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.breached = Arc::new(
+            passwords
+                .into_iter()
+                .map(|password| password.as_ref().trim().to_lowercase())
+                .filter(|password| !password.is_empty())
+                .collect(),
+        );
+        self
+    }
+    pub fn breached_len(&self) -> usize {
+        self.breached.len()
+    }
+
+    /// Checks the password of the user with `username` and `email`
+    pub fn check<T>(
+        &self,
+        password: &Password<T>,
+        username: &UserName<T>,
+        email: &Email<T>,
+    ) -> Result<(), PasswordRejected> {
+        let password = password.expose_secret();
+        let mut reasons = vec![];
+        let found = character_classes(password);
+        if found < self.min_character_classes {
+            reasons.push(PasswordWeakness::TooFewCharacterClasses {
+                found,
+                min: self.min_character_classes,
+            });
+        }
+        let bits = Self::entropy_bits(password);
+        if bits < self.min_entropy_bits {
+            reasons.push(PasswordWeakness::TooPredictable {
+                bits,
+                min: self.min_entropy_bits,
+            });
+        }
+        if self.reject_similar_to_identity {
+            let local_part = email
+                .as_ref()
+                .rsplit_once('@')
+                .map_or(email.as_ref(), |(local, _)| local);
+            for (field, identity) in [
+                (Field::UserName, username.as_ref()),
+                (Field::Email, local_part),
+            ] {
+                if similar(password, identity) {
+                    reasons.push(PasswordWeakness::SimilarTo { field });
+                }
+            }
+        }
+        if self.breached.contains(&password.to_lowercase()) {
+            reasons.push(PasswordWeakness::Breached);
+        }
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(PasswordRejected { reasons })
+        }
+    }
+
+    /// Rough guess of the entropy in the spirit of zxcvbn: each character
+    /// is worth the size of the character classes in use, except that
+    /// repeats and steps of a sequence like `aaa`, `abc` or `321` are only
+    /// worth a bit
+    pub fn entropy_bits(password: &str) -> u32 {
+        let pool = [
+            (char::is_ascii_lowercase as fn(&char) -> bool, 26),
+            (char::is_ascii_uppercase, 26),
+            (char::is_ascii_digit, 10),
+            (char::is_ascii_punctuation, 33),
+            (|c: &char| !c.is_ascii() || *c == ' ', 100),
+        ]
+        .iter()
+        .filter(|(class, _)| password.chars().any(|c| class(&c)))
+        .map(|(_, size)| size)
+        .sum::<u32>();
+        let per_char = f64::from(pool.max(1)).log2();
+        let mut bits = 0.0;
+        let mut previous: Option<char> = None;
+        for c in password.chars() {
+            let predictable =
+                previous.is_some_and(|previous| (c as i64 - previous as i64).abs() <= 1);
+            bits += if predictable { 1.0 } else { per_char };
+            previous = Some(c);
+        }
+        bits as u32
+    }
+}
+
+fn character_classes(password: &str) -> u8 {
+    let has = |class: fn(char) -> bool| password.chars().any(class) as u8;
+    has(char::is_lowercase)
+        + has(char::is_uppercase)
+        + has(char::is_numeric)
+        + has(|c| !c.is_alphanumeric())
+}
+
+/// Whether the identity makes up most of the password or the two are a
+/// few edits apart
+fn similar(password: &str, identity: &str) -> bool {
+    let password = password.to_lowercase();
+    let identity = identity.to_lowercase();
+    let (password_len, identity_len) = (password.chars().count(), identity.chars().count());
+    if identity_len < 3 {
+        return false;
+    }
+    if password.contains(&identity) && identity_len * 2 >= password_len {
+        return true;
+    }
+    if identity.contains(&password) {
+        return true;
+    }
+    levenshtein(&password, &identity) * 3 <= password_len.max(identity_len)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestPassword = Password<()>;
+
+    fn check(policy: &PasswordPolicy, password: &str) -> Result<(), PasswordRejected> {
+        policy.check(
+            &TestPassword::new_unchecked(password),
+            &UserName::new_unchecked("jane_doe"),
+            &Email::new_unchecked("jane.doe@example.com"),
+        )
+    }
+
+    #[test]
+    fn strong() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(check(&policy, "correct-horse-battery"), Ok(()));
+        assert_eq!(check(&policy, "Tr0ub4dor&3"), Ok(()));
+    }
+
+    #[test]
+    fn weak() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(
+            check(&policy, "abcdefgh").unwrap_err().reasons,
+            vec![
+                PasswordWeakness::TooFewCharacterClasses { found: 1, min: 2 },
+                PasswordWeakness::TooPredictable { bits: 11, min: 35 },
+            ]
+        );
+        assert!(PasswordPolicy::entropy_bits("aaaaaaaaaaaa") < 20);
+        assert!(PasswordPolicy::entropy_bits("x7#Kq9!m") > 40);
+    }
+
+    #[test]
+    fn similar_to_identity() {
+        let policy = PasswordPolicy::default();
+        let reasons = check(&policy, "Jane_Doe1").unwrap_err().reasons;
+        assert!(reasons.contains(&PasswordWeakness::SimilarTo {
+            field: Field::UserName
+        }));
+        let reasons = check(&policy, "jane.doe!!").unwrap_err().reasons;
+        assert!(reasons.contains(&PasswordWeakness::SimilarTo {
+            field: Field::Email
+        }));
+        // a short identity inside a long password is fine
+        assert_eq!(check(&policy, "jane-likes-long-walks"), Ok(()));
+        let policy = policy.with_reject_similar_to_identity(false);
+        assert_eq!(check(&policy, "Jane_Doe1"), Ok(()));
+    }
+
+    #[test]
+    fn breached() {
+        let policy = PasswordPolicy::default().with_breached(["", " Tr0ub4dor&3 ", "123456"]);
+        assert_eq!(policy.breached_len(), 2);
+        assert_eq!(
+            check(&policy, "tr0ub4dor&3").unwrap_err().reasons,
+            vec![PasswordWeakness::Breached]
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+/// Named permission scope such as `users:read`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Permission(String);
+
+impl Permission {
+    pub const USERS_READ: &'static str = "users:read";
+    pub const USERS_UPDATE: &'static str = "users:update";
+    pub const USERS_DELETE: &'static str = "users:delete";
+    pub const SIGNUP_READ: &'static str = "signup:read";
+    pub const SIGNUP_MANAGE: &'static str = "signup:manage";
+    pub const AUDIT_LOG_READ: &'static str = "audit_log:read";
+    pub const ROLES_MANAGE: &'static str = "roles:manage";
+
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+impl AsRef<str> for Permission {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -5,18 +5,41 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum Role {
-    Admin,
-    User,
+const ADMIN: &str = "Admin";
+const USER: &str = "User";
+
+/// Name of a role, the permissions granted by a role are kept in the
+/// role repository.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Role(String);
+
+impl Role {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+    /// Built-in role with unrestricted access
+    pub fn admin() -> Self {
+        Self::new(ADMIN)
+    }
+    /// Built-in role new users are created with
+    pub fn user() -> Self {
+        Self::new(USER)
+    }
+    pub fn is_admin(&self) -> bool {
+        self.0 == ADMIN
+    }
+}
+
+impl AsRef<str> for Role {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Display for Role {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Role::Admin => write!(f, "Admin"),
-            Role::User => write!(f, "User"),
-        }
+        write!(f, "{}", self.0)
     }
 }
 
@@ -24,10 +47,10 @@ impl FromStr for Role {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "Admin" => Ok(Role::Admin),
-            "User" => Ok(Role::User),
-            _ => Err(()),
+        if s.is_empty() {
+            Err(())
+        } else {
+            Ok(Self::new(s))
         }
     }
 }
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Time windows and limits a signup process has to stay within.
+///
+/// The defaults are one day to verify the email and one day to complete
+/// the signup afterwards, five wrong tokens lock verification for a
+/// quarter of an hour.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignupPolicy {
+    /// Time a verification email stays valid
+    pub verification_ttl: Duration,
+    /// Time to complete the signup once the email is verified
+    pub completion_ttl: Duration,
+    /// Number of times a timed out window can be extended
+    pub max_extensions: u32,
+    /// Number of times a verification email can be resent
+    pub max_resends: u32,
+    /// Minimum time between two verification emails
+    pub resend_cooldown: Duration,
+    /// Failed verifications after which the process is locked
+    pub max_verification_attempts: u32,
+    /// Time a locked process waits before verification is allowed again
+    pub attempt_lockout: Duration,
+}
+
+impl Default for SignupPolicy {
+    fn default() -> Self {
+        Self {
+            verification_ttl: Duration::days(1),
+            completion_ttl: Duration::days(1),
+            max_extensions: 3,
+            max_resends: 3,
+            resend_cooldown: Duration::seconds(60),
+            max_verification_attempts: 5,
+            attempt_lockout: Duration::minutes(15),
+        }
+    }
+}
+
+impl SignupPolicy {
+    pub fn with_verification_ttl(mut self, ttl: Duration) -> Self {
+        self.verification_ttl = ttl;
+        self
+    }
+    pub fn with_completion_ttl(mut self, ttl: Duration) -> Self {
+        self.completion_ttl = ttl;
+        self
+    }
+    pub fn with_max_extensions(mut self, max_extensions: u32) -> Self {
+        self.max_extensions = max_extensions;
+        self
+    }
+    pub fn with_max_resends(mut self, max_resends: u32) -> Self {
+        self.max_resends = max_resends;
+        self
+    }
+    pub fn with_resend_cooldown(mut self, cooldown: Duration) -> Self {
+        self.resend_cooldown = cooldown;
+        self
+    }
+    pub fn with_max_verification_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_verification_attempts = max_attempts;
+        self
+    }
+    pub fn with_attempt_lockout(mut self, lockout: Duration) -> Self {
+        self.attempt_lockout = lockout;
+        self
+    }
+    /// Whether a verification email sent at `sent_at` is too old at `now`
+    pub fn verification_expired(&self, sent_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        now - sent_at > self.verification_ttl
+    }
+    /// Whether an email verified at `verified_at` is too old at `now`
+    pub fn completion_expired(&self, verified_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        now - verified_at > self.completion_ttl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows() {
+        let policy = SignupPolicy::default().with_verification_ttl(Duration::minutes(15));
+        let now = Utc::now();
+        assert!(!policy.verification_expired(now - Duration::minutes(15), now));
+        assert!(policy.verification_expired(now - Duration::minutes(16), now));
+        assert!(!policy.completion_expired(now - Duration::hours(23), now));
+        assert!(policy.completion_expired(now - Duration::days(2), now));
+    }
+}
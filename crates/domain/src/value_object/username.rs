@@ -1,17 +1,83 @@
 use std::{
     fmt::{Debug, Display},
     marker::PhantomData,
+    str::FromStr,
 };
 
 use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::confusable_detection::skeleton;
+
+use super::validation::{check_len, Field, ValidationError};
+
+const MAX_NAME_LEN: usize = 30;
+const MIN_NAME_LEN: usize = 5;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UserName<T>(String, PhantomData<T>);
 
 impl<T> UserName<T> {
-    pub fn new(name: impl Into<String>) -> Self {
+    /// Accepts letters, digits, `_`, `-` and `.`, starting with a letter or
+    /// a digit
+    pub fn parse(name: impl Into<String>) -> Result<Self, ValidationError> {
+        let name = name.into();
+        check_len(Field::UserName, &name, MIN_NAME_LEN, MAX_NAME_LEN)?;
+        if let Some(character) = name
+            .chars()
+            .find(|c| !c.is_alphanumeric() && !"_-.".contains(*c))
+        {
+            return Err(ValidationError::InvalidCharacter {
+                field: Field::UserName,
+                character,
+            });
+        }
+        if !name.starts_with(char::is_alphanumeric) {
+            return Err(ValidationError::InvalidStart {
+                field: Field::UserName,
+            });
+        }
+        Ok(Self(name, PhantomData))
+    }
+    /// Wraps a name that was validated before, e.g. when loading it from
+    /// storage
+    pub fn new_unchecked(name: impl Into<String>) -> Self {
         Self(name.into(), PhantomData)
     }
+    /// Form names are compared and looked up by. Compatibility and case
+    /// variants are folded (NFKC, lower case) and confusable characters are
+    /// mapped to a shared prototype (UTS #39 skeleton), so lookalikes such
+    /// as `admin`, `Αdmin` and `adrnin` end up the same
+    pub fn canonical(&self) -> String {
+        let folded = self.0.nfkc().collect::<String>().to_lowercase();
+        skeleton(&folded).collect::<String>().to_lowercase()
+    }
+    pub const fn min_len() -> usize {
+        MIN_NAME_LEN
+    }
+    pub const fn max_len() -> usize {
+        MAX_NAME_LEN
+    }
+}
+
+impl<T> TryFrom<&str> for UserName<T> {
+    type Error = ValidationError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl<T> TryFrom<String> for UserName<T> {
+    type Error = ValidationError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl<T> FromStr for UserName<T> {
+    type Err = ValidationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
 }
 
 impl<T> AsRef<str> for UserName<T> {
@@ -37,3 +103,57 @@ impl<T> Display for UserName<T> {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestUserName = UserName<()>;
+
+    #[test]
+    fn parse() {
+        assert!(TestUserName::parse("test_username").is_ok());
+        assert!(TestUserName::parse("jörg.müller").is_ok());
+        assert_eq!(
+            TestUserName::parse("abc"),
+            Err(ValidationError::TooShort {
+                field: Field::UserName,
+                min: MIN_NAME_LEN
+            })
+        );
+        assert_eq!(
+            TestUserName::parse("a".repeat(31)),
+            Err(ValidationError::TooLong {
+                field: Field::UserName,
+                max: MAX_NAME_LEN
+            })
+        );
+        assert_eq!(
+            TestUserName::parse("test user"),
+            Err(ValidationError::InvalidCharacter {
+                field: Field::UserName,
+                character: ' '
+            })
+        );
+        assert_eq!(
+            TestUserName::parse("_test_user"),
+            Err(ValidationError::InvalidStart {
+                field: Field::UserName
+            })
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let canonical = |name: &str| TestUserName::new_unchecked(name).canonical();
+        assert_eq!(canonical("Test_User"), canonical("test_user"));
+        // full width compatibility forms
+        assert_eq!(canonical("ｔｅｓｔ_user"), canonical("test_user"));
+        // Cyrillic а and е
+        assert_eq!(canonical("tеst_usаr"), canonical("test_usar"));
+        // digit and letter lookalikes
+        assert_eq!(canonical("paypa1"), canonical("paypal"));
+        assert_eq!(canonical("adrnin"), canonical("admin"));
+        assert_ne!(canonical("test_user"), canonical("test_users"));
+    }
+}
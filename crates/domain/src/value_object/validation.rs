@@ -0,0 +1,66 @@
+use std::fmt::{self, Display};
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// Value object a [`ValidationError`] is about
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum Field {
+    Email,
+    UserName,
+    Password,
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Email => write!(f, "email"),
+            Field::UserName => write!(f, "username"),
+            Field::Password => write!(f, "password"),
+        }
+    }
+}
+
+/// Reason a string was rejected by a value object constructor
+#[derive(Debug, Clone, Serialize, Error, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("{field} must be at least {min} characters long")]
+    TooShort { field: Field, min: usize },
+    #[error("{field} must be at most {max} characters long")]
+    TooLong { field: Field, max: usize },
+    #[error("{field} must not contain {character:?}")]
+    InvalidCharacter { field: Field, character: char },
+    #[error("{field} must start with a letter or a digit")]
+    InvalidStart { field: Field },
+    #[error("email is malformed: {reason}")]
+    MalformedEmail { reason: &'static str },
+}
+
+impl ValidationError {
+    pub const fn field(&self) -> Field {
+        match self {
+            ValidationError::TooShort { field, .. }
+            | ValidationError::TooLong { field, .. }
+            | ValidationError::InvalidCharacter { field, .. }
+            | ValidationError::InvalidStart { field } => *field,
+            ValidationError::MalformedEmail { .. } => Field::Email,
+        }
+    }
+}
+
+/// Checks the length in characters against `min..=max`
+pub(crate) fn check_len(
+    field: Field,
+    value: &str,
+    min: usize,
+    max: usize,
+) -> Result<(), ValidationError> {
+    let len = value.chars().count();
+    if len < min {
+        return Err(ValidationError::TooShort { field, min });
+    }
+    if len > max {
+        return Err(ValidationError::TooLong { field, max });
+    }
+    Ok(())
+}
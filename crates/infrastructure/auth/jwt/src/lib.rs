@@ -1,44 +1,73 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 use uuid::Uuid;
 
-use ca_application::gateway::service::auth::{AuthExtractor, AuthPacker};
-use ca_domain::{entity::auth_context::AuthContext, value_object::Role};
+use ca_application::gateway::service::{
+    auth::{AuthExtractor, AuthPacker},
+    clock::{Clock, SystemClock},
+};
+use ca_domain::{
+    entity::auth_context::{AuthContext, PendingStep},
+    value_object::{Permission, Role},
+};
 
 #[derive(Clone)]
 pub struct JwtAuth {
     secret: String,
+    clock: Arc<dyn Clock>,
 }
 
 impl JwtAuth {
     pub fn new(secret: String) -> Self {
-        Self { secret }
+        Self {
+            secret,
+            clock: Arc::new(SystemClock),
+        }
+    }
+    /// Clock used to issue and expire tokens
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
+    iat: usize,
     exp: usize,
     user_id: String,
     role: String,
+    // permission scopes granted by the role at login time
+    #[serde(default)]
+    permissions: Vec<String>,
+    // login step the token is still waiting for, e.g. the second factor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pending: Option<PendingStep>,
 }
 impl Claims {
-    fn new(auth_context: AuthContext) -> Self {
+    fn new(auth_context: AuthContext, now: DateTime<Utc>) -> Self {
         Self {
-            exp: (Utc::now() + chrono::Duration::minutes(10))
+            iat: now.timestamp().try_into().unwrap(),
+            exp: (now + chrono::Duration::minutes(10))
                 .timestamp()
                 .try_into()
                 .unwrap(),
             user_id: auth_context.user_id.to_string(),
             role: auth_context.role.to_string(),
+            permissions: auth_context
+                .permissions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            pending: auth_context.pending,
         }
     }
 }
 #[async_trait::async_trait]
 impl AuthPacker for &JwtAuth {
     async fn pack_auth(&self, auth: AuthContext) -> String {
-        let claims = Claims::new(auth);
+        let claims = Claims::new(auth, self.clock.now());
         let header = jsonwebtoken::Header::default();
         let encoding_key = jsonwebtoken::EncodingKey::from_secret(self.secret.as_ref());
         jsonwebtoken::encode(&header, &claims, &encoding_key).unwrap()
@@ -48,46 +77,105 @@ impl AuthPacker for &JwtAuth {
 impl AuthExtractor for &JwtAuth {
     async fn extract_auth(&self, input: String) -> Option<AuthContext> {
         let decoding_key = jsonwebtoken::DecodingKey::from_secret(self.secret.as_ref());
-        let token_data = jsonwebtoken::decode::<Claims>(
-            &input,
-            &decoding_key,
-            &jsonwebtoken::Validation::default(),
-        )
-        .ok()?;
+        // expiry is checked against the clock below
+        let mut validation = jsonwebtoken::Validation::default();
+        validation.validate_exp = false;
+        let token_data = jsonwebtoken::decode::<Claims>(&input, &decoding_key, &validation).ok()?;
         let claims = token_data.claims;
         let user_id = Uuid::from_str(&claims.user_id)
             .ok()
             .map(ca_domain::entity::user::Id::from)?;
         let role = Role::from_str(&claims.role).ok()?;
-        Some(AuthContext { user_id, role })
+        let issued_at = DateTime::<Utc>::from_timestamp(claims.iat.try_into().ok()?, 0)?;
+        let expires_at = DateTime::<Utc>::from_timestamp(claims.exp.try_into().ok()?, 0)?;
+        if expires_at <= self.clock.now() {
+            return None;
+        }
+        let permissions = claims.permissions.into_iter().map(Permission::new);
+        let auth_context = AuthContext::new(user_id, role)
+            .with_permissions(permissions)
+            .with_session(issued_at, expires_at);
+        Some(match claims.pending {
+            Some(step) => auth_context.with_pending(step),
+            None => auth_context,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ca_application::gateway::service::clock::ManualClock;
 
     #[tokio::test]
     #[ignore]
     /// This test is ignored because it requires a secret key to run.
     async fn generate_admin() {
         let jwt_auth = JwtAuth::new("secret".to_string());
-        let auth_context = AuthContext {
-            user_id: ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(0)),
-            role: Role::Admin,
-        };
+        let auth_context = AuthContext::new(
+            ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(0)),
+            Role::admin(),
+        );
         let token = (&jwt_auth).pack_auth(auth_context).await;
         println!("token: {}", token);
     }
     #[tokio::test]
     async fn test_exp() {
         let jwt_auth = JwtAuth::new("secret".to_string());
-        let auth_context = AuthContext {
-            user_id: ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(0)),
-            role: Role::Admin,
-        };
+        let auth_context = AuthContext::new(
+            ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(0)),
+            Role::admin(),
+        );
         let token = (&jwt_auth).pack_auth(auth_context).await;
         let decoded = (&jwt_auth).extract_auth(token.clone()).await;
         assert!(decoded.is_some());
+        let session = decoded.unwrap().session.unwrap();
+        assert_eq!(
+            session.expires_at - session.issued_at,
+            chrono::Duration::minutes(10)
+        );
+    }
+    #[tokio::test]
+    async fn test_expired_by_clock() {
+        let clock = Arc::new(ManualClock::default());
+        let jwt_auth = JwtAuth::new("secret".to_string()).with_clock(clock.clone());
+        let auth_context = AuthContext::new(
+            ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(0)),
+            Role::admin(),
+        );
+        let token = (&jwt_auth).pack_auth(auth_context).await;
+        clock.advance(chrono::Duration::minutes(9));
+        assert!((&jwt_auth).extract_auth(token.clone()).await.is_some());
+        clock.advance(chrono::Duration::minutes(1));
+        assert!((&jwt_auth).extract_auth(token).await.is_none());
+    }
+    #[tokio::test]
+    async fn test_permissions() {
+        let jwt_auth = JwtAuth::new("secret".to_string());
+        let auth_context = AuthContext::new(
+            ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(1)),
+            Role::new("Support"),
+        )
+        .with_permissions([Permission::new(Permission::USERS_READ)]);
+        let token = (&jwt_auth).pack_auth(auth_context.clone()).await;
+        let decoded = (&jwt_auth).extract_auth(token).await.unwrap();
+        assert_eq!(decoded.role, auth_context.role);
+        assert_eq!(decoded.permissions, auth_context.permissions);
+    }
+    #[tokio::test]
+    async fn test_pending() {
+        let jwt_auth = JwtAuth::new("secret".to_string());
+        let auth_context = AuthContext::new(
+            ca_domain::entity::user::Id::new(uuid::Uuid::from_u128(1)),
+            Role::user(),
+        );
+        let token = (&jwt_auth).pack_auth(auth_context.clone()).await;
+        let decoded = (&jwt_auth).extract_auth(token).await.unwrap();
+        assert_eq!(decoded.pending, None);
+        let token = (&jwt_auth)
+            .pack_auth(auth_context.with_pending(PendingStep::Mfa))
+            .await;
+        let decoded = (&jwt_auth).extract_auth(token).await.unwrap();
+        assert_eq!(decoded.pending, Some(PendingStep::Mfa));
     }
 }
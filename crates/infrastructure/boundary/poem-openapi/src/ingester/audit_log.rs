@@ -0,0 +1,51 @@
+use std::str::FromStr;
+
+use ca_adapter::boundary::{Error, Ingester, UsecaseRequestResult};
+use ca_application::{
+    gateway::DatabaseProvider,
+    usecase::audit_log::query::{QueryAuditLog, Request as UsecaseQueryAuditLogRequest},
+};
+use ca_domain::entity::user::Id;
+use chrono::{DateTime, Utc};
+use poem_openapi::Object;
+use uuid::Uuid;
+
+use crate::Boundary;
+
+// ========================================
+// Query Audit Log Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct QueryAuditLogRequest {
+    pub actor_id: Option<String>,
+    pub target_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, QueryAuditLog<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = QueryAuditLogRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, QueryAuditLog<D>> {
+        let actor_id = input
+            .actor_id
+            .map(|id| id.parse())
+            .transpose()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))?
+            .map(|uuid: Uuid| Id::from(uuid));
+        Ok(UsecaseQueryAuditLogRequest {
+            actor_id,
+            target_id: input.target_id,
+            from: input.from,
+            to: input.to,
+            limit: input.limit,
+            offset: input.offset,
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use std::net::IpAddr;
+
+use ca_adapter::boundary::ContextIngester;
+use ca_application::usecase::request_context::RequestContext;
+use http::HeaderMap;
+use uuid::Uuid;
+
+use crate::Boundary;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Request metadata collected by the http interface
+pub struct ContextRequest {
+    pub token: Option<String>,
+    pub headers: HeaderMap,
+    pub remote_ip: Option<String>,
+    /// Proxies whose forwarded for header is believed, any other peer could
+    /// claim to be whoever it likes
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Address of the client, the peer unless it is a trusted proxy. Behind one
+/// the forwarded for header is walked back from the nearest hop and the
+/// first address no trusted proxy owns is the client.
+fn client_ip(input: &ContextRequest) -> Option<String> {
+    let trusted = |ip: &str| {
+        ip.parse::<IpAddr>()
+            .is_ok_and(|ip| input.trusted_proxies.contains(&ip))
+    };
+    let remote_ip = input.remote_ip.as_deref()?;
+    if !trusted(remote_ip) {
+        return Some(remote_ip.to_string());
+    }
+    let hops: Vec<&str> = header(&input.headers, FORWARDED_FOR_HEADER)
+        .map(|value| value.split(',').map(str::trim).collect())
+        .unwrap_or_default();
+    // a chain of trusted proxies only, the farthest one is all there is
+    let client = hops.iter().rev().find(|hop| !trusted(hop)).or(hops.first());
+    Some(
+        client
+            .filter(|hop| !hop.is_empty())
+            .copied()
+            .unwrap_or(remote_ip)
+            .to_string(),
+    )
+}
+
+#[async_trait::async_trait]
+impl ContextIngester for Boundary {
+    type ContextModel = ContextRequest;
+    async fn ingest_context(input: Self::ContextModel) -> (Option<String>, RequestContext) {
+        let headers = &input.headers;
+        let request_id = header(headers, REQUEST_ID_HEADER)
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let mut ctx = RequestContext::default().with_request_id(request_id);
+        if let Some(client_ip) = client_ip(&input) {
+            ctx = ctx.with_client_ip(client_ip);
+        }
+        if let Some(user_agent) = header(headers, http::header::USER_AGENT.as_str()) {
+            ctx = ctx.with_user_agent(user_agent);
+        }
+        // most preferred language tag, e.g. `en-US,en;q=0.9` -> `en-US`
+        if let Some(locale) = header(headers, http::header::ACCEPT_LANGUAGE.as_str())
+            .and_then(|value| value.split(',').next())
+            .and_then(|value| value.split(';').next())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            ctx = ctx.with_locale(locale);
+        }
+        (input.token, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: &str = "10.0.0.1";
+    const CLIENT: &str = "203.0.113.7";
+
+    fn request(remote_ip: &str, forwarded_for: Option<&str>, trusted: &[&str]) -> ContextRequest {
+        let mut headers = HeaderMap::new();
+        if let Some(forwarded_for) = forwarded_for {
+            headers.insert(FORWARDED_FOR_HEADER, forwarded_for.parse().unwrap());
+        }
+        headers.insert(REQUEST_ID_HEADER, "req-1".parse().unwrap());
+        headers.insert(http::header::USER_AGENT, "test-agent".parse().unwrap());
+        headers.insert(
+            http::header::ACCEPT_LANGUAGE,
+            "en-US,en;q=0.9".parse().unwrap(),
+        );
+        ContextRequest {
+            token: Some("token".to_string()),
+            headers,
+            remote_ip: Some(remote_ip.to_string()),
+            trusted_proxies: trusted.iter().map(|ip| ip.parse().unwrap()).collect(),
+        }
+    }
+
+    async fn ingest(input: ContextRequest) -> (Option<String>, RequestContext) {
+        <Boundary as ContextIngester>::ingest_context(input).await
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_headers() {
+        let (token, ctx) = ingest(request(CLIENT, None, &[])).await;
+        assert_eq!(token.as_deref(), Some("token"));
+        assert_eq!(ctx.request_id.as_deref(), Some("req-1"));
+        assert_eq!(ctx.client_ip.as_deref(), Some(CLIENT));
+        assert_eq!(ctx.user_agent.as_deref(), Some("test-agent"));
+        assert_eq!(ctx.locale.as_deref(), Some("en-US"));
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_generates_request_id() {
+        let mut input = request(CLIENT, None, &[]);
+        input.headers.remove(REQUEST_ID_HEADER);
+        let (_, ctx) = ingest(input).await;
+        assert!(ctx
+            .request_id
+            .is_some_and(|id| Uuid::parse_str(&id).is_ok()));
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_ignores_forwarded_for_from_untrusted_peer() {
+        let (_, ctx) = ingest(request(CLIENT, Some("198.51.100.1"), &[PROXY])).await;
+        assert_eq!(ctx.client_ip.as_deref(), Some(CLIENT));
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_ignores_forwarded_for_without_trusted_proxies() {
+        let (_, ctx) = ingest(request(PROXY, Some(CLIENT), &[])).await;
+        assert_eq!(ctx.client_ip.as_deref(), Some(PROXY));
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_forwarded_for_from_trusted_proxy() {
+        let (_, ctx) = ingest(request(PROXY, Some(CLIENT), &[PROXY])).await;
+        assert_eq!(ctx.client_ip.as_deref(), Some(CLIENT));
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_skips_spoofed_hops() {
+        // the client made up the first hop, the trusted proxy appended its peer
+        let forwarded_for = format!("198.51.100.1, {CLIENT}, 10.0.0.2");
+        let (_, ctx) = ingest(request(PROXY, Some(&forwarded_for), &[PROXY, "10.0.0.2"])).await;
+        assert_eq!(ctx.client_ip.as_deref(), Some(CLIENT));
+    }
+
+    #[async_std::test]
+    async fn test_ingest_context_trusted_proxy_without_forwarded_for() {
+        let (_, ctx) = ingest(request(PROXY, None, &[PROXY])).await;
+        assert_eq!(ctx.client_ip.as_deref(), Some(PROXY));
+    }
+}
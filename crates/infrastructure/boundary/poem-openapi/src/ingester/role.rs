@@ -0,0 +1,69 @@
+use ca_adapter::boundary::{Ingester, UsecaseRequestResult};
+use ca_application::{
+    gateway::DatabaseProvider,
+    usecase::role::{
+        assign_permissions::{AssignPermissions, Request as UsecaseAssignPermissionsRequest},
+        create::{Create, Request as UsecaseCreateRequest},
+        get_all::{GetAll, Request as UsecaseGetAllRequest},
+    },
+};
+use poem_openapi::Object;
+
+use crate::Boundary;
+
+#[derive(Object)]
+pub struct RoleRequest {
+    pub name: String,
+    pub permissions: Vec<String>,
+}
+
+// ========================================
+// Create Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Create<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = RoleRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Create<D>> {
+        Ok(UsecaseCreateRequest {
+            name: input.name,
+            permissions: input.permissions,
+        })
+    }
+}
+
+// ========================================
+// Assign Permissions Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, AssignPermissions<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = RoleRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, AssignPermissions<D>> {
+        Ok(UsecaseAssignPermissionsRequest {
+            name: input.name,
+            permissions: input.permissions,
+        })
+    }
+}
+
+// ========================================
+// Get All Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, GetAll<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = ();
+    async fn ingest(_: Self::InputModel) -> UsecaseRequestResult<D, GetAll<D>> {
+        Ok(UsecaseGetAllRequest)
+    }
+}
@@ -2,10 +2,14 @@ use std::str::FromStr;
 
 use ca_adapter::boundary::{Error, Ingester, UsecaseRequestResult};
 use ca_application::{
-    gateway::{DatabaseProvider, EmailVerificationServiceProvider},
+    gateway::{
+        ClockProvider, DatabaseProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+        PasswordPolicyProvider, SignupPolicyProvider, TokenGeneratorProvider,
+    },
     usecase::signup_process::{
         complete::{Complete, Request as UsecaseCompleteRequest},
         delete::{Delete, Request as UsecaseDeleteRequest},
+        expire::{Expire, Request as UsecaseExpireRequest},
         extend_completion_time::{
             ExtendCompletionTime, Request as UsecaseExtendCompletionTimeRequest,
         },
@@ -14,13 +18,21 @@ use ca_application::{
         },
         get_state_chain::{GetStateChain, Request as UsecaseGetStateChainRequest},
         initialize::{Initialize, Request as UsecaseInitializeRequest},
+        purge::{Purge, Request as UsecasePurgeRequest},
+        recover::{RecoverSignupProcess, Request as UsecaseRecoverSignupProcessRequest},
+        resend_verification_email::{
+            Request as UsecaseResendVerificationEmailRequest, ResendVerificationEmail,
+        },
         send_verification_email::{
             Request as UsecaseSendVerificationEmailRequest, SendVerificationEmail,
         },
         verify_email::{Request as UsecaseVerifyEmailRequest, VerifyEmail},
     },
 };
-use ca_domain::entity::signup_process::Id;
+use ca_domain::entity::{
+    signup_process::Id,
+    user::{Email, Password, UserName},
+};
 use poem_openapi::Object;
 use uuid::Uuid;
 
@@ -44,10 +56,17 @@ pub struct CompleteRequest {
 #[async_trait::async_trait]
 impl<D> Ingester<D, Complete<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + PasswordPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = CompleteRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Complete<D>> {
+        UserName::parse(&input.username)?;
+        Password::parse(&input.password)?;
         input
             .id
             .parse()
@@ -55,7 +74,7 @@ where
             .map(|uuid: Uuid| UsecaseCompleteRequest {
                 id: Id::from(uuid),
                 username: input.username,
-                password: input.password,
+                password: input.password.into(),
             })
     }
 }
@@ -67,7 +86,11 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, Delete<D>> for Boundary
 where
-    D: DatabaseProvider + EmailVerificationServiceProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + EmailVerificationServiceProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = IdRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Delete<D>> {
@@ -86,7 +109,12 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, ExtendCompletionTime<D>> for Boundary
 where
-    D: DatabaseProvider + EmailVerificationServiceProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + EmailVerificationServiceProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = IdRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, ExtendCompletionTime<D>> {
@@ -105,7 +133,12 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, ExtendVerificationTime<D>> for Boundary
 where
-    D: DatabaseProvider + EmailVerificationServiceProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + EmailVerificationServiceProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = IdRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, ExtendVerificationTime<D>> {
@@ -147,10 +180,15 @@ pub struct InitializeRequest {
 #[async_trait::async_trait]
 impl<D> Ingester<D, Initialize<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = InitializeRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Initialize<D>> {
+        Email::parse(&input.email)?;
         Ok(UsecaseInitializeRequest { email: input.email })
     }
 }
@@ -162,7 +200,12 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, SendVerificationEmail<D>> for Boundary
 where
-    D: DatabaseProvider + EmailVerificationServiceProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = IdRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, SendVerificationEmail<D>> {
@@ -174,6 +217,99 @@ where
     }
 }
 
+// ========================================
+// Resend Verification Email Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, ResendVerificationEmail<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = IdRequest;
+    async fn ingest(
+        input: Self::InputModel,
+    ) -> UsecaseRequestResult<D, ResendVerificationEmail<D>> {
+        input
+            .id
+            .parse()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
+            .map(|uuid: Uuid| UsecaseResendVerificationEmailRequest { id: Id::from(uuid) })
+    }
+}
+
+// ========================================
+// Expire Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Expire<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + JobMetricsProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = ();
+    async fn ingest(_input: Self::InputModel) -> UsecaseRequestResult<D, Expire<D>> {
+        Ok(UsecaseExpireRequest)
+    }
+}
+
+// ========================================
+// Purge Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct PurgeRequest {
+    pub grace_period_days: Option<u32>,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Purge<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = PurgeRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Purge<D>> {
+        Ok(UsecasePurgeRequest {
+            grace_period_days: input.grace_period_days,
+        })
+    }
+}
+
+// ========================================
+// Recover Signup Process Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, RecoverSignupProcess<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = IdRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, RecoverSignupProcess<D>> {
+        input
+            .id
+            .parse()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
+            .map(|uuid: Uuid| UsecaseRecoverSignupProcessRequest { id: Id::from(uuid) })
+    }
+}
+
 // ========================================
 // Verify Email Use Case
 // ========================================
@@ -186,7 +322,11 @@ pub struct VerifyEmailRequest {
 #[async_trait::async_trait]
 impl<D> Ingester<D, VerifyEmail<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = VerifyEmailRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, VerifyEmail<D>> {
@@ -196,7 +336,7 @@ where
             .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
             .map(|uuid: Uuid| UsecaseVerifyEmailRequest {
                 id: Id::from(uuid),
-                token: input.token,
+                token: input.token.into(),
             })
     }
 }
@@ -6,16 +6,39 @@ use std::str::FromStr;
 
 use ca_adapter::boundary::{Error, Ingester, UsecaseRequestResult};
 use ca_application::{
-    gateway::{AuthPackerProvider, DatabaseProvider},
+    gateway::{
+        AuthPackerProvider, ClockProvider, DatabaseProvider, EmailServiceProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
+    },
     usecase::user::{
+        begin_external_login::{BeginExternalLogin, Request as UsecaseBeginExternalLoginRequest},
+        check_username_availability::{
+            CheckUsernameAvailability, Request as UsecaseCheckUsernameAvailabilityRequest,
+        },
+        complete_external_login::{
+            CompleteExternalLogin, Request as UsecaseCompleteExternalLoginRequest,
+        },
+        confirm_2fa::{Confirm2fa, Request as UsecaseConfirm2faRequest},
+        consume_login_link::{ConsumeLoginLink, Request as UsecaseConsumeLoginLinkRequest},
         delete::{Delete, Request as UsecaseDeleteRequest},
+        disable_2fa::{Disable2fa, Request as UsecaseDisable2faRequest},
+        enable_2fa::{Enable2fa, Request as UsecaseEnable2faRequest},
         get_all::{GetAll, Request as UsecaseGetAllRequest},
+        get_me::{GetMe, Request as UsecaseGetMeRequest},
         get_one::{GetOne, Request as UsecaseGetOneRequest},
+        grant_role::{GrantRole, Request as UsecaseGrantRoleRequest},
+        link_identity::{LinkIdentity, Request as UsecaseLinkIdentityRequest},
         login::{Login, Request as UsecaseLoginRequest},
+        regenerate_recovery_codes::{
+            RegenerateRecoveryCodes, Request as UsecaseRegenerateRecoveryCodesRequest,
+        },
+        request_login_link::{Request as UsecaseRequestLoginLinkRequest, RequestLoginLink},
         update::{Request as UsecaseUpdateRequest, Update},
+        verify_mfa::{Request as UsecaseVerifyMfaRequest, VerifyMfa},
     },
 };
-use ca_domain::entity::user::Id;
+use ca_domain::entity::user::{Email, Id, Password, UserName};
 use poem_openapi::Object;
 use uuid::Uuid;
 
@@ -57,6 +80,21 @@ where
     }
 }
 
+// ========================================
+// Get Me Use Case
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, GetMe<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = ();
+    async fn ingest(_: Self::InputModel) -> UsecaseRequestResult<D, GetMe<D>> {
+        Ok(UsecaseGetMeRequest)
+    }
+}
+
 // ========================================
 // Get One Use Case
 // ========================================
@@ -76,6 +114,34 @@ where
     }
 }
 
+// ========================================
+// Grant Role Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct GrantRoleRequest {
+    pub id: String,
+    pub role: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, GrantRole<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = GrantRoleRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, GrantRole<D>> {
+        input
+            .id
+            .parse()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
+            .map(|uuid: Uuid| UsecaseGrantRoleRequest {
+                id: Id::from(uuid),
+                role: input.role,
+            })
+    }
+}
+
 // ========================================
 // Login Use Case
 // ========================================
@@ -95,7 +161,7 @@ where
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Login<D>> {
         Ok(UsecaseLoginRequest {
             username: input.username,
-            password: input.password,
+            password: input.password.into(),
         })
     }
 }
@@ -115,10 +181,17 @@ pub struct UpdateRequest {
 #[async_trait::async_trait]
 impl<D> Ingester<D, Update<D>> for Boundary
 where
-    D: DatabaseProvider + AuthPackerProvider + std::marker::Sync + std::marker::Send,
+    D: DatabaseProvider
+        + AuthPackerProvider
+        + PasswordPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send,
 {
     type InputModel = UpdateRequest;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Update<D>> {
+        Email::parse(&input.email)?;
+        UserName::parse(&input.username)?;
+        Password::parse(&input.password)?;
         input
             .id
             .parse()
@@ -127,7 +200,251 @@ where
                 id: Id::from(uuid),
                 username: input.username,
                 email: input.email,
-                password: input.password,
+                password: input.password.into(),
             })
     }
 }
+
+// ========================================
+// Check Username Availability Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct CheckUsernameAvailabilityRequest {
+    pub username: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, CheckUsernameAvailability<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = CheckUsernameAvailabilityRequest;
+    async fn ingest(
+        input: Self::InputModel,
+    ) -> UsecaseRequestResult<D, CheckUsernameAvailability<D>> {
+        UserName::parse(&input.username)?;
+        Ok(UsecaseCheckUsernameAvailabilityRequest {
+            username: input.username,
+        })
+    }
+}
+
+// ========================================
+// Login Link Use Cases
+// ========================================
+
+#[derive(Object)]
+pub struct RequestLoginLinkRequest {
+    pub username: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, RequestLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider
+        + EmailServiceProvider
+        + TokenGeneratorProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = RequestLoginLinkRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, RequestLoginLink<D>> {
+        Ok(UsecaseRequestLoginLinkRequest {
+            username: input.username,
+        })
+    }
+}
+
+#[derive(Object)]
+pub struct ConsumeLoginLinkRequest {
+    pub username: String,
+    pub token: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, ConsumeLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider
+        + AuthPackerProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = ConsumeLoginLinkRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, ConsumeLoginLink<D>> {
+        Ok(UsecaseConsumeLoginLinkRequest {
+            username: input.username,
+            token: input.token.into(),
+        })
+    }
+}
+
+// ========================================
+// Two Factor Use Cases
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Enable2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + std::marker::Sync + std::marker::Send,
+{
+    type InputModel = ();
+    async fn ingest(_: Self::InputModel) -> UsecaseRequestResult<D, Enable2fa<D>> {
+        Ok(UsecaseEnable2faRequest)
+    }
+}
+
+/// TOTP code of the authenticator app, or a recovery code where accepted
+#[derive(Object)]
+pub struct MfaCodeRequest {
+    pub code: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Confirm2fa<D>> for Boundary
+where
+    D: DatabaseProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = MfaCodeRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Confirm2fa<D>> {
+        Ok(UsecaseConfirm2faRequest {
+            code: input.code.into(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Disable2fa<D>> for Boundary
+where
+    D: DatabaseProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = MfaCodeRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Disable2fa<D>> {
+        Ok(UsecaseDisable2faRequest {
+            code: input.code.into(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, RegenerateRecoveryCodes<D>> for Boundary
+where
+    D: DatabaseProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = MfaCodeRequest;
+    async fn ingest(
+        input: Self::InputModel,
+    ) -> UsecaseRequestResult<D, RegenerateRecoveryCodes<D>> {
+        Ok(UsecaseRegenerateRecoveryCodesRequest {
+            code: input.code.into(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, VerifyMfa<D>> for Boundary
+where
+    D: DatabaseProvider
+        + AuthPackerProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = MfaCodeRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, VerifyMfa<D>> {
+        Ok(UsecaseVerifyMfaRequest {
+            code: input.code.into(),
+        })
+    }
+}
+
+// ========================================
+// External Login Use Cases
+// ========================================
+
+#[derive(Object)]
+pub struct BeginExternalLoginRequest {
+    /// Name of a configured OpenID Connect provider
+    pub provider: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, BeginExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider
+        + OidcServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    /// The request and whether the identity is linked to the current user
+    type InputModel = (BeginExternalLoginRequest, bool);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, BeginExternalLogin<D>> {
+        let (request, link) = input;
+        Ok(UsecaseBeginExternalLoginRequest {
+            provider: request.provider,
+            link,
+        })
+    }
+}
+
+/// Query parameters the provider redirected the user back with
+#[derive(Object)]
+pub struct ExternalLoginCallbackRequest {
+    pub state: String,
+    pub code: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, CompleteExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider
+        + OidcServiceProvider
+        + AuthPackerProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = ExternalLoginCallbackRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, CompleteExternalLogin<D>> {
+        Ok(UsecaseCompleteExternalLoginRequest {
+            state: input.state.into(),
+            code: input.code.into(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, LinkIdentity<D>> for Boundary
+where
+    D: DatabaseProvider
+        + OidcServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send,
+{
+    type InputModel = ExternalLoginCallbackRequest;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, LinkIdentity<D>> {
+        Ok(UsecaseLinkIdentityRequest {
+            state: input.state.into(),
+            code: input.code.into(),
+        })
+    }
+}
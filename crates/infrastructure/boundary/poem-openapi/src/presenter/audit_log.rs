@@ -0,0 +1,72 @@
+use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
+use ca_application::{
+    gateway::{
+        database::audit_log::{Outcome, Record},
+        DatabaseProvider,
+    },
+    usecase::audit_log::query::QueryAuditLog,
+};
+use chrono::{DateTime, Utc};
+use poem_openapi::{payload::Json, Object};
+
+use crate::Boundary;
+
+use super::signup_process::TheApiResponse;
+
+// ========================================
+// Query Audit Log Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct AuditLogEntryResponse {
+    pub actor_id: Option<String>,
+    pub actor_role: Option<String>,
+    pub usecase: String,
+    pub target_ids: Vec<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<Record> for AuditLogEntryResponse {
+    fn from(value: Record) -> Self {
+        let (outcome, error) = match value.outcome {
+            Outcome::Success => ("success", None),
+            Outcome::Failure(err) => ("failure", Some(err)),
+            Outcome::Denied => ("denied", None),
+        };
+        Self {
+            actor_id: value.actor_id.map(|id| id.to_string()),
+            actor_role: value.actor_role.map(|role| role.to_string()),
+            usecase: value.usecase,
+            target_ids: value.target_ids,
+            outcome: outcome.to_string(),
+            error,
+            request_id: value.request_id,
+            client_ip: value.client_ip,
+            occurred_at: value.occurred_at,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, QueryAuditLog<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<Vec<AuditLogEntryResponse>>;
+
+    async fn present(data: UsecaseResponseResult<D, QueryAuditLog<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(
+                data.entries
+                    .into_iter()
+                    .map(AuditLogEntryResponse::from)
+                    .collect(),
+            )),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
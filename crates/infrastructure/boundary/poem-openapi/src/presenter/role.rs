@@ -0,0 +1,87 @@
+use std::collections::BTreeSet;
+
+use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
+use ca_application::{
+    gateway::DatabaseProvider,
+    usecase::role::{assign_permissions::AssignPermissions, create::Create, get_all::GetAll},
+};
+use ca_domain::value_object::{Permission, Role};
+use poem_openapi::{payload::Json, Object};
+
+use crate::Boundary;
+
+use super::signup_process::TheApiResponse;
+
+#[derive(Object)]
+pub struct RoleResponse {
+    pub name: String,
+    pub permissions: Vec<String>,
+}
+
+impl RoleResponse {
+    fn new(role: Role, permissions: BTreeSet<Permission>) -> Self {
+        Self {
+            name: role.to_string(),
+            permissions: permissions.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+// ========================================
+// Create Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, Create<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<RoleResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, Create<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(RoleResponse::new(data.role, data.permissions))),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Assign Permissions Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, AssignPermissions<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<RoleResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, AssignPermissions<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(RoleResponse::new(data.role, data.permissions))),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Get All Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, GetAll<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<Vec<RoleResponse>>;
+
+    async fn present(data: UsecaseResponseResult<D, GetAll<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(
+                data.roles
+                    .into_iter()
+                    .map(|record| RoleResponse::new(record.role, record.permissions))
+                    .collect(),
+            )),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
@@ -1,21 +1,24 @@
 use ca_adapter::boundary::{Error, Presenter, UsecaseResponseResult};
 use ca_application::{
     gateway::{
-        database::signup_process::Record as SignupProcessRecord, DatabaseProvider,
-        EmailVerificationServiceProvider,
+        database::signup_process::Record as SignupProcessRecord, ClockProvider, DatabaseProvider,
+        EmailVerificationServiceProvider, JobMetricsProvider, PasswordPolicyProvider,
+        SignupPolicyProvider, TokenGeneratorProvider,
     },
     usecase::{
         signup_process::{
-            complete::Complete, delete::Delete, extend_completion_time::ExtendCompletionTime,
+            complete::Complete, delete::Delete, expire::Expire,
+            extend_completion_time::ExtendCompletionTime,
             extend_verification_time::ExtendVerificationTime, get_state_chain::GetStateChain,
-            initialize::Initialize, send_verification_email::SendVerificationEmail,
-            verify_email::VerifyEmail,
+            initialize::Initialize, purge::Purge, recover::RecoverSignupProcess,
+            resend_verification_email::ResendVerificationEmail,
+            send_verification_email::SendVerificationEmail, verify_email::VerifyEmail,
         },
         Usecase,
     },
 };
 
-use ca_domain::entity::signup_process::SignupStateEnum;
+use ca_domain::entity::{auth_context::AuthError, signup_process::SignupStateEnum};
 use chrono::{DateTime, Utc};
 use poem_openapi::{payload::Json, types::ToJSON, ApiResponse, Enum, Object};
 
@@ -28,6 +31,31 @@ pub struct IdResponse {
     pub id: String,
 }
 
+#[derive(Object)]
+pub struct ExpireMetricsResponse {
+    pub runs: u64,
+    pub failed_runs: u64,
+    pub verification_timed_out: u64,
+    pub completion_timed_out: u64,
+    pub conflicts: u64,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+#[derive(Object)]
+pub struct ExpireResponse {
+    pub verification_timed_out: u64,
+    pub completion_timed_out: u64,
+    pub conflicts: u64,
+    /// Totals since the server started, background sweeps included
+    pub totals: ExpireMetricsResponse,
+}
+
+#[derive(Object)]
+pub struct PurgeResponse {
+    pub purged: u64,
+    pub tombstones: u64,
+}
+
 #[derive(Enum)]
 pub enum SignupStateResponseEnum {
     Initialized,
@@ -59,7 +87,7 @@ impl From<SignupProcessRecord> for SignupProcessResponse {
                 email: Some(email.to_string()),
                 error: None,
             },
-            SignupStateEnum::VerificationEmailSent { email } => Self {
+            SignupStateEnum::VerificationEmailSent { email, .. } => Self {
                 id: record.id.to_string(),
                 state: SignupStateResponseEnum::VerificationEmailSent,
                 entered_at: record.entered_at,
@@ -67,7 +95,7 @@ impl From<SignupProcessRecord> for SignupProcessResponse {
                 email: Some(email.to_string()),
                 error: None,
             },
-            SignupStateEnum::EmailVerified { email } => Self {
+            SignupStateEnum::EmailVerified { email, .. } => Self {
                 id: record.id.to_string(),
                 state: SignupStateResponseEnum::EmailVerified,
                 entered_at: record.entered_at,
@@ -120,6 +148,12 @@ pub enum TheApiResponse<T: ToJSON> {
     /// Returns a bad request.
     #[oai(status = 400)]
     BadRequest(Json<String>),
+    /// Returns when the request is not authenticated.
+    #[oai(status = 401)]
+    Unauthorized(Json<String>),
+    /// Returns when the caller is not allowed to perform the request.
+    #[oai(status = 403)]
+    Forbidden(Json<String>),
     /// Returns an internal server error.
     #[oai(status = 500)]
     InternalServerError(Json<String>),
@@ -133,8 +167,11 @@ impl<D, U: Usecase<D>, T: ToJSON> From<Error<D, U>> for TheApiResponse<T> {
             Error::UsecaseError(err) => {
                 TheApiResponse::InternalServerError(Json(format!("Usecase error: {err:?}")))
             }
-            Error::AuthError(err) => {
-                TheApiResponse::InternalServerError(Json(format!("Authorization error: {err:?}")))
+            Error::AuthError(err @ AuthError::Unauthenticated) => {
+                TheApiResponse::Unauthorized(Json(format!("Authorization error: {err}")))
+            }
+            Error::AuthError(err @ AuthError::Forbidden) => {
+                TheApiResponse::Forbidden(Json(format!("Authorization error: {err}")))
             }
         }
     }
@@ -146,7 +183,13 @@ impl<D, U: Usecase<D>, T: ToJSON> From<Error<D, U>> for TheApiResponse<T> {
 #[async_trait::async_trait]
 impl<D> Presenter<D, Complete<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + PasswordPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
 {
     type ViewModel = TheApiResponse<UserResponse>;
 
@@ -168,7 +211,7 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, Delete<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+    D: DatabaseProvider + ClockProvider + std::marker::Sync + std::marker::Send + 'static,
 {
     type ViewModel = TheApiResponse<IdResponse>;
 
@@ -188,7 +231,12 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, ExtendCompletionTime<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
 {
     type ViewModel = TheApiResponse<IdResponse>;
 
@@ -208,7 +256,12 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, ExtendVerificationTime<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
 {
     type ViewModel = TheApiResponse<IdResponse>;
 
@@ -253,7 +306,12 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, Initialize<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
 {
     type ViewModel = TheApiResponse<IdResponse>;
 
@@ -274,7 +332,9 @@ where
 impl<D> Presenter<D, SendVerificationEmail<D>> for Boundary
 where
     D: DatabaseProvider
+        + ClockProvider
         + EmailVerificationServiceProvider
+        + TokenGeneratorProvider
         + std::marker::Sync
         + std::marker::Send
         + 'static,
@@ -291,6 +351,118 @@ where
     }
 }
 
+// ========================================
+// Resend Verification Email Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, ResendVerificationEmail<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<IdResponse>;
+
+    async fn present(
+        data: UsecaseResponseResult<D, ResendVerificationEmail<D>>,
+    ) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(IdResponse {
+                id: data.id.to_string(),
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Expire Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, Expire<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + JobMetricsProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<ExpireResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, Expire<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(ExpireResponse {
+                verification_timed_out: data.verification_timed_out,
+                completion_timed_out: data.completion_timed_out,
+                conflicts: data.conflicts,
+                totals: ExpireMetricsResponse {
+                    runs: data.totals.runs,
+                    failed_runs: data.totals.failed_runs,
+                    verification_timed_out: data.totals.verification_timed_out,
+                    completion_timed_out: data.totals.completion_timed_out,
+                    conflicts: data.totals.conflicts,
+                    last_run: data.totals.last_run,
+                },
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Purge Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, Purge<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<PurgeResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, Purge<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(PurgeResponse {
+                purged: data.purged,
+                tombstones: data.tombstones,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Recover Signup Process Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, RecoverSignupProcess<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<IdResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, RecoverSignupProcess<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(IdResponse {
+                id: data.id.to_string(),
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
 // ========================================
 // Verify Email Use Case
 // ========================================
@@ -298,6 +470,8 @@ where
 impl<D> Presenter<D, VerifyEmail<D>> for Boundary
 where
     D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
         + EmailVerificationServiceProvider
         + std::marker::Sync
         + std::marker::Send
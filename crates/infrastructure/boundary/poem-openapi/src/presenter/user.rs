@@ -1,11 +1,23 @@
 use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
 use ca_application::{
-    gateway::{AuthPackerProvider, DatabaseProvider},
+    gateway::{
+        AuthPackerProvider, ClockProvider, DatabaseProvider, EmailServiceProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
+    },
     usecase::user::{
-        delete::Delete, get_all::GetAll, get_one::GetOne, login::Login, update::Update,
+        begin_external_login::BeginExternalLogin,
+        check_username_availability::CheckUsernameAvailability,
+        complete_external_login::CompleteExternalLogin, confirm_2fa::Confirm2fa,
+        consume_login_link::ConsumeLoginLink, delete::Delete, disable_2fa::Disable2fa,
+        enable_2fa::Enable2fa, get_all::GetAll, get_me::GetMe, get_one::GetOne,
+        grant_role::GrantRole, link_identity::LinkIdentity, login::Login,
+        regenerate_recovery_codes::RegenerateRecoveryCodes, request_login_link::RequestLoginLink,
+        update::Update, verify_mfa::VerifyMfa,
     },
 };
-use ca_domain::entity::user::User;
+use ca_domain::entity::{auth_context::Session, user::User};
+use chrono::{DateTime, Utc};
 use poem_openapi::{payload::Json, Object};
 
 use crate::Boundary;
@@ -67,6 +79,51 @@ where
     }
 }
 
+// ========================================
+// Get Me Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct SessionResponse {
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<Session> for SessionResponse {
+    fn from(value: Session) -> Self {
+        Self {
+            issued_at: value.issued_at,
+            expires_at: value.expires_at,
+        }
+    }
+}
+
+#[derive(Object)]
+pub struct MeResponse {
+    pub user: UserResponse,
+    pub role: String,
+    pub session: Option<SessionResponse>,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, GetMe<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<MeResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, GetMe<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(MeResponse {
+                user: UserResponse::from(data.user),
+                role: data.role.to_string(),
+                session: data.session.map(SessionResponse::from),
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
 // ========================================
 // Get One Use Case
 // ========================================
@@ -85,6 +142,24 @@ where
     }
 }
 
+// ========================================
+// Grant Role Use Case
+// ========================================
+#[async_trait::async_trait]
+impl<D> Presenter<D, GrantRole<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<Empty>;
+
+    async fn present(data: UsecaseResponseResult<D, GrantRole<D>>) -> Self::ViewModel {
+        match data {
+            Ok(_) => TheApiResponse::Ok(Json(Empty)),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
 // ========================================
 // Login Use Case
 // ========================================
@@ -93,6 +168,9 @@ where
 pub struct LoginResponse {
     id: String,
     token: String,
+    /// Step the token still waits for, `mfa` or `mfa_enrollment`, it grants
+    /// nothing else until then
+    pending: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -107,6 +185,182 @@ where
             Ok(data) => TheApiResponse::Ok(Json(LoginResponse {
                 id: data.user_id.to_string(),
                 token: data.token,
+                pending: data.pending.map(|step| step.to_string()),
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Login Link Use Cases
+// ========================================
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, RequestLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider
+        + EmailServiceProvider
+        + TokenGeneratorProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<Empty>;
+
+    async fn present(data: UsecaseResponseResult<D, RequestLoginLink<D>>) -> Self::ViewModel {
+        match data {
+            Ok(()) => TheApiResponse::Ok(Json(Empty)),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, ConsumeLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider
+        + AuthPackerProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<LoginResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, ConsumeLoginLink<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(LoginResponse {
+                id: data.user_id.to_string(),
+                token: data.token,
+                pending: data.pending.map(|step| step.to_string()),
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// Two Factor Use Cases
+// ========================================
+
+#[derive(Object)]
+pub struct Enable2faResponse {
+    secret: String,
+    uri: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, Enable2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<Enable2faResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, Enable2fa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(Enable2faResponse {
+                secret: data.secret,
+                uri: data.uri,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[derive(Object)]
+pub struct RecoveryCodesResponse {
+    recovery_codes: Vec<String>,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, Confirm2fa<D>> for Boundary
+where
+    D: DatabaseProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<RecoveryCodesResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, Confirm2fa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(RecoveryCodesResponse {
+                recovery_codes: data.recovery_codes,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, Disable2fa<D>> for Boundary
+where
+    D: DatabaseProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<Empty>;
+
+    async fn present(data: UsecaseResponseResult<D, Disable2fa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(()) => TheApiResponse::Ok(Json(Empty)),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, RegenerateRecoveryCodes<D>> for Boundary
+where
+    D: DatabaseProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<RecoveryCodesResponse>;
+
+    async fn present(
+        data: UsecaseResponseResult<D, RegenerateRecoveryCodes<D>>,
+    ) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(RecoveryCodesResponse {
+                recovery_codes: data.recovery_codes,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, VerifyMfa<D>> for Boundary
+where
+    D: DatabaseProvider
+        + AuthPackerProvider
+        + TotpServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<LoginResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, VerifyMfa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(LoginResponse {
+                id: data.user_id.to_string(),
+                token: data.token,
+                pending: None,
             })),
             Err(err) => TheApiResponse::from(err),
         }
@@ -119,7 +373,7 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, Update<D>> for Boundary
 where
-    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+    D: DatabaseProvider + PasswordPolicyProvider + std::marker::Sync + std::marker::Send + 'static,
 {
     type ViewModel = TheApiResponse<Empty>;
 
@@ -130,3 +384,128 @@ where
         }
     }
 }
+
+// ========================================
+// Check Username Availability Use Case
+// ========================================
+
+#[derive(Object)]
+pub struct UsernameAvailabilityResponse {
+    pub available: bool,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, CheckUsernameAvailability<D>> for Boundary
+where
+    D: DatabaseProvider + std::marker::Sync + std::marker::Send + 'static,
+{
+    type ViewModel = TheApiResponse<UsernameAvailabilityResponse>;
+
+    async fn present(
+        data: UsecaseResponseResult<D, CheckUsernameAvailability<D>>,
+    ) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(UsernameAvailabilityResponse {
+                available: data.available,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+// ========================================
+// External Login Use Cases
+// ========================================
+
+#[derive(Object)]
+pub struct BeginExternalLoginResponse {
+    /// Where to send the user to log in at the provider
+    authorization_url: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, BeginExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider
+        + OidcServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<BeginExternalLoginResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, BeginExternalLogin<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(BeginExternalLoginResponse {
+                authorization_url: data.authorization_url,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[derive(Object)]
+pub struct ExternalLoginResponse {
+    id: String,
+    token: String,
+    /// Step the token still waits for, `mfa` or `mfa_enrollment`
+    pending: Option<String>,
+    /// Whether the user signed up with this login
+    created: bool,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, CompleteExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider
+        + OidcServiceProvider
+        + AuthPackerProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<ExternalLoginResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, CompleteExternalLogin<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(ExternalLoginResponse {
+                id: data.user_id.to_string(),
+                token: data.token,
+                pending: data.pending.map(|step| step.to_string()),
+                created: data.created,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
+
+#[derive(Object)]
+pub struct IdentityResponse {
+    provider: String,
+    subject: String,
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, LinkIdentity<D>> for Boundary
+where
+    D: DatabaseProvider
+        + OidcServiceProvider
+        + ClockProvider
+        + std::marker::Sync
+        + std::marker::Send
+        + 'static,
+{
+    type ViewModel = TheApiResponse<IdentityResponse>;
+
+    async fn present(data: UsecaseResponseResult<D, LinkIdentity<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => TheApiResponse::Ok(Json(IdentityResponse {
+                provider: data.provider,
+                subject: data.subject,
+            })),
+            Err(err) => TheApiResponse::from(err),
+        }
+    }
+}
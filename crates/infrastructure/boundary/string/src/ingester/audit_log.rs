@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::super::Boundary;
+use ca_adapter::boundary::{Error, Ingester, UsecaseRequestResult};
+use ca_application::{
+    gateway::DatabaseProvider,
+    usecase::audit_log::query::{QueryAuditLog, Request as QueryAuditLogRequest},
+};
+use ca_domain::entity::user::Id as UserId;
+
+fn parse_time(input: Option<String>) -> Result<Option<DateTime<Utc>>, String> {
+    input
+        .map(|time| DateTime::parse_from_rfc3339(&time).map(|time| time.to_utc()))
+        .transpose()
+        .map_err(|e| e.to_string())
+}
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, QueryAuditLog<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<u32>,
+        Option<u32>,
+    );
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, QueryAuditLog<D>> {
+        let (actor_id, target_id, from, to, limit, offset) = input;
+        let actor_id = actor_id
+            .map(|id| id.parse())
+            .transpose()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))?
+            .map(|uuid: Uuid| UserId::from(uuid));
+        Ok(QueryAuditLogRequest {
+            actor_id,
+            target_id,
+            from: parse_time(from).map_err(Error::ParseInputError)?,
+            to: parse_time(to).map_err(Error::ParseInputError)?,
+            limit,
+            offset,
+        })
+    }
+}
@@ -0,0 +1,77 @@
+use std::env;
+
+use uuid::Uuid;
+
+use super::super::Boundary;
+use ca_adapter::boundary::ContextIngester;
+use ca_application::usecase::request_context::RequestContext;
+
+/// Environment variable a caller can set to correlate cli runs with logs
+pub const REQUEST_ID_ENV: &str = "CA_REQUEST_ID";
+/// Environment variable a caller can set to identify the calling tool
+pub const USER_AGENT_ENV: &str = "CA_USER_AGENT";
+
+const DEFAULT_USER_AGENT: &str = concat!("ca-cli/", env!("CARGO_PKG_VERSION"));
+
+#[async_trait::async_trait]
+impl ContextIngester for Boundary {
+    type ContextModel = Option<String>;
+    async fn ingest_context(input: Self::ContextModel) -> (Option<String>, RequestContext) {
+        let request_id = env::var(REQUEST_ID_ENV).unwrap_or_else(|_| Uuid::new_v4().to_string());
+        let user_agent =
+            env::var(USER_AGENT_ENV).unwrap_or_else(|_| DEFAULT_USER_AGENT.to_string());
+        let mut ctx = RequestContext::default()
+            .with_request_id(request_id)
+            .with_user_agent(user_agent);
+        // POSIX locale precedence, e.g. `en_US.UTF-8` -> `en-US`
+        if let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+        {
+            let locale = locale
+                .split('.')
+                .next()
+                .unwrap_or_default()
+                .replace('_', "-");
+            ctx = ctx.with_locale(locale);
+        }
+        (input, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn ingest(input: Option<String>) -> (Option<String>, RequestContext) {
+        <Boundary as ContextIngester>::ingest_context(input).await
+    }
+
+    // one test, the environment is shared by all of them
+    #[async_std::test]
+    async fn test_ingest_context_environment() {
+        env::remove_var(REQUEST_ID_ENV);
+        env::remove_var(USER_AGENT_ENV);
+        let (token, ctx) = ingest(Some("token".to_string())).await;
+        assert_eq!(token.as_deref(), Some("token"));
+        assert!(ctx
+            .request_id
+            .is_some_and(|id| Uuid::parse_str(&id).is_ok()));
+        assert_eq!(ctx.user_agent.as_deref(), Some(DEFAULT_USER_AGENT));
+        // a cli run has no client address
+        assert_eq!(ctx.client_ip, None);
+
+        env::set_var(REQUEST_ID_ENV, "req-1");
+        env::set_var(USER_AGENT_ENV, "deploy-script");
+        env::set_var("LC_ALL", "de_DE.UTF-8");
+        let (token, ctx) = ingest(None).await;
+        assert_eq!(token, None);
+        assert_eq!(ctx.request_id.as_deref(), Some("req-1"));
+        assert_eq!(ctx.user_agent.as_deref(), Some("deploy-script"));
+        assert_eq!(ctx.locale.as_deref(), Some("de-DE"));
+        for var in [REQUEST_ID_ENV, USER_AGENT_ENV, "LC_ALL"] {
+            env::remove_var(var);
+        }
+    }
+}
@@ -1,2 +1,5 @@
+pub mod audit_log;
+pub mod context;
+pub mod role;
 pub mod signup_process;
 pub mod user;
@@ -0,0 +1,43 @@
+use super::super::Boundary;
+use ca_adapter::boundary::{Ingester, UsecaseRequestResult};
+use ca_application::{
+    gateway::DatabaseProvider,
+    usecase::role::{
+        assign_permissions::{AssignPermissions, Request as AssignPermissionsRequest},
+        create::{Create, Request as CreateRequest},
+        get_all::{GetAll, Request as GetAllRequest},
+    },
+};
+
+#[async_trait::async_trait]
+impl<D> Ingester<D, Create<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = (String, Vec<String>);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Create<D>> {
+        let (name, permissions) = input;
+        Ok(CreateRequest { name, permissions })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, AssignPermissions<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = (String, Vec<String>);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, AssignPermissions<D>> {
+        let (name, permissions) = input;
+        Ok(AssignPermissionsRequest { name, permissions })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, GetAll<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = ();
+    async fn ingest(_: Self::InputModel) -> UsecaseRequestResult<D, GetAll<D>> {
+        Ok(GetAllRequest {})
+    }
+}
@@ -5,42 +5,56 @@ use uuid::Uuid;
 use super::super::Boundary;
 use ca_adapter::boundary::{Error, Ingester, UsecaseRequestResult};
 use ca_application::{
-    gateway::{DatabaseProvider, EmailVerificationServiceProvider},
+    gateway::{
+        ClockProvider, DatabaseProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+        PasswordPolicyProvider, SignupPolicyProvider, TokenGeneratorProvider,
+    },
     usecase::signup_process::{
         complete::{Complete, Request as CompleteRequest},
         delete::{Delete, Request as DeleteRequest},
+        expire::{Expire, Request as ExpireRequest},
         extend_completion_time::{ExtendCompletionTime, Request as ExtendCompletionTimeRequest},
         extend_verification_time::{
             ExtendVerificationTime, Request as ExtendVerificationTimeRequest,
         },
         get_state_chain::{GetStateChain, Request as GetStateChainRequest},
         initialize::{Initialize, Request as InitializeRequest},
+        purge::{Purge, Request as PurgeRequest},
+        recover::{RecoverSignupProcess, Request as RecoverSignupProcessRequest},
+        resend_verification_email::{
+            Request as ResendVerificationEmailRequest, ResendVerificationEmail,
+        },
         send_verification_email::{Request as SendVerificationEmailRequest, SendVerificationEmail},
         verify_email::{Request as VerifyEmailRequest, VerifyEmail},
     },
 };
-use ca_domain::entity::signup_process::Id;
+use ca_domain::entity::{
+    signup_process::Id,
+    user::{Email, Password, UserName},
+};
 #[async_trait::async_trait]
 impl<D> Ingester<D, Complete<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + PasswordPolicyProvider,
 {
     type InputModel = (String, String, String);
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Complete<D>> {
         let (id, username, password) = input;
+        UserName::parse(&username)?;
+        Password::parse(&password)?;
         id.parse()
             .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
             .map(|uuid: Uuid| CompleteRequest {
                 id: Id::from(uuid),
                 username,
-                password,
+                password: password.into(),
             })
     }
 }
 #[async_trait::async_trait]
 impl<D> Ingester<D, ExtendCompletionTime<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider,
 {
     type InputModel = String;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, ExtendCompletionTime<D>> {
@@ -53,7 +67,7 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, ExtendVerificationTime<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider,
 {
     type InputModel = String;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, ExtendVerificationTime<D>> {
@@ -79,17 +93,18 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, Initialize<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider,
 {
     type InputModel = String;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Initialize<D>> {
+        Email::parse(&input)?;
         Ok(InitializeRequest { email: input })
     }
 }
 #[async_trait::async_trait]
 impl<D> Ingester<D, SendVerificationEmail<D>> for Boundary
 where
-    D: DatabaseProvider + EmailVerificationServiceProvider,
+    D: DatabaseProvider + ClockProvider + EmailVerificationServiceProvider + TokenGeneratorProvider,
 {
     type InputModel = String;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, SendVerificationEmail<D>> {
@@ -100,9 +115,63 @@ where
     }
 }
 #[async_trait::async_trait]
+impl<D> Ingester<D, ResendVerificationEmail<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider,
+{
+    type InputModel = String;
+    async fn ingest(
+        input: Self::InputModel,
+    ) -> UsecaseRequestResult<D, ResendVerificationEmail<D>> {
+        input
+            .parse()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
+            .map(|uuid: Uuid| ResendVerificationEmailRequest { id: Id::from(uuid) })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, Expire<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + JobMetricsProvider,
+{
+    type InputModel = ();
+    async fn ingest(_input: Self::InputModel) -> UsecaseRequestResult<D, Expire<D>> {
+        Ok(ExpireRequest)
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, Purge<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider,
+{
+    type InputModel = Option<u32>;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Purge<D>> {
+        Ok(PurgeRequest {
+            grace_period_days: input,
+        })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, RecoverSignupProcess<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider + EmailVerificationServiceProvider + TokenGeneratorProvider,
+{
+    type InputModel = String;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, RecoverSignupProcess<D>> {
+        input
+            .parse()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
+            .map(|uuid: Uuid| RecoverSignupProcessRequest { id: Id::from(uuid) })
+    }
+}
+#[async_trait::async_trait]
 impl<D> Ingester<D, VerifyEmail<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider,
 {
     type InputModel = (String, String);
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, VerifyEmail<D>> {
@@ -111,14 +180,14 @@ where
             .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
             .map(|uuid: Uuid| VerifyEmailRequest {
                 id: Id::from(uuid),
-                token,
+                token: token.into(),
             })
     }
 }
 #[async_trait::async_trait]
 impl<D> Ingester<D, Delete<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + ClockProvider,
 {
     type InputModel = String;
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Delete<D>> {
@@ -4,16 +4,37 @@ use uuid::Uuid;
 
 use ca_adapter::boundary::{Error, Ingester, UsecaseRequestResult};
 use ca_application::{
-    gateway::{AuthPackerProvider, DatabaseProvider},
+    gateway::{
+        AuthPackerProvider, ClockProvider, DatabaseProvider, EmailServiceProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
+    },
     usecase::user::{
+        begin_external_login::{BeginExternalLogin, Request as BeginExternalLoginRequest},
+        check_username_availability::{
+            CheckUsernameAvailability, Request as CheckUsernameAvailabilityRequest,
+        },
+        complete_external_login::{CompleteExternalLogin, Request as CompleteExternalLoginRequest},
+        confirm_2fa::{Confirm2fa, Request as Confirm2faRequest},
+        consume_login_link::{ConsumeLoginLink, Request as ConsumeLoginLinkRequest},
         delete::{Delete, Request as DeleteRequest},
+        disable_2fa::{Disable2fa, Request as Disable2faRequest},
+        enable_2fa::{Enable2fa, Request as Enable2faRequest},
         get_all::{GetAll, Request as GetAllRequest},
+        get_me::{GetMe, Request as GetMeRequest},
         get_one::{GetOne, Request as GetOneRequest},
+        grant_role::{GrantRole, Request as GrantRoleRequest},
+        link_identity::{LinkIdentity, Request as LinkIdentityRequest},
         login::{Login, Request as LoginRequest},
+        regenerate_recovery_codes::{
+            RegenerateRecoveryCodes, Request as RegenerateRecoveryCodesRequest,
+        },
+        request_login_link::{Request as RequestLoginLinkRequest, RequestLoginLink},
         update::{Request as UpdateRequest, Update},
+        verify_mfa::{Request as VerifyMfaRequest, VerifyMfa},
     },
 };
-use ca_domain::entity::user::Id;
+use ca_domain::entity::user::{Email, Id, Password, UserName};
 
 use super::super::Boundary;
 #[async_trait::async_trait]
@@ -32,18 +53,21 @@ where
 #[async_trait::async_trait]
 impl<D> Ingester<D, Update<D>> for Boundary
 where
-    D: DatabaseProvider,
+    D: DatabaseProvider + PasswordPolicyProvider,
 {
     type InputModel = (String, String, String, String);
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Update<D>> {
         let (id, email, username, password) = input;
+        Email::parse(&email)?;
+        UserName::parse(&username)?;
+        Password::parse(&password)?;
         id.parse()
             .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
             .map(|uuid: Uuid| UpdateRequest {
                 id: Id::from(uuid),
                 email,
                 username,
-                password,
+                password: password.into(),
             })
     }
 }
@@ -71,6 +95,16 @@ where
     }
 }
 #[async_trait::async_trait]
+impl<D> Ingester<D, GetMe<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = ();
+    async fn ingest(_: Self::InputModel) -> UsecaseRequestResult<D, GetMe<D>> {
+        Ok(GetMeRequest {})
+    }
+}
+#[async_trait::async_trait]
 impl<D> Ingester<D, Login<D>> for Boundary
 where
     D: DatabaseProvider + AuthPackerProvider,
@@ -78,6 +112,157 @@ where
     type InputModel = (String, String);
     async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Login<D>> {
         let (username, password) = input;
-        Ok(LoginRequest { username, password })
+        Ok(LoginRequest {
+            username,
+            password: password.into(),
+        })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, GrantRole<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = (String, String);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, GrantRole<D>> {
+        let (id, role) = input;
+        id.parse()
+            .map_err(|e: <Uuid as FromStr>::Err| Error::ParseInputError(e.to_string()))
+            .map(|uuid: Uuid| GrantRoleRequest {
+                id: Id::from(uuid),
+                role,
+            })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, CheckUsernameAvailability<D>> for Boundary
+where
+    D: DatabaseProvider,
+{
+    type InputModel = String;
+    async fn ingest(
+        input: Self::InputModel,
+    ) -> UsecaseRequestResult<D, CheckUsernameAvailability<D>> {
+        UserName::parse(&input)?;
+        Ok(CheckUsernameAvailabilityRequest { username: input })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, RequestLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider
+        + EmailServiceProvider
+        + TokenGeneratorProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider,
+{
+    type InputModel = String;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, RequestLoginLink<D>> {
+        Ok(RequestLoginLinkRequest { username: input })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, ConsumeLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider + AuthPackerProvider + LoginLinkPolicyProvider + ClockProvider,
+{
+    type InputModel = (String, String);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, ConsumeLoginLink<D>> {
+        let (username, token) = input;
+        Ok(ConsumeLoginLinkRequest {
+            username,
+            token: token.into(),
+        })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, Enable2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider,
+{
+    type InputModel = ();
+    async fn ingest(_: Self::InputModel) -> UsecaseRequestResult<D, Enable2fa<D>> {
+        Ok(Enable2faRequest)
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, Confirm2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider,
+{
+    type InputModel = String;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Confirm2fa<D>> {
+        Ok(Confirm2faRequest { code: input.into() })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, Disable2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider,
+{
+    type InputModel = String;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, Disable2fa<D>> {
+        Ok(Disable2faRequest { code: input.into() })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, RegenerateRecoveryCodes<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider,
+{
+    type InputModel = String;
+    async fn ingest(
+        input: Self::InputModel,
+    ) -> UsecaseRequestResult<D, RegenerateRecoveryCodes<D>> {
+        Ok(RegenerateRecoveryCodesRequest { code: input.into() })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, VerifyMfa<D>> for Boundary
+where
+    D: DatabaseProvider + AuthPackerProvider + TotpServiceProvider + ClockProvider,
+{
+    type InputModel = String;
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, VerifyMfa<D>> {
+        Ok(VerifyMfaRequest { code: input.into() })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, BeginExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider + OidcServiceProvider + ClockProvider,
+{
+    type InputModel = (String, bool);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, BeginExternalLogin<D>> {
+        let (provider, link) = input;
+        Ok(BeginExternalLoginRequest { provider, link })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, CompleteExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider + OidcServiceProvider + AuthPackerProvider + ClockProvider,
+{
+    type InputModel = (String, String);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, CompleteExternalLogin<D>> {
+        let (state, code) = input;
+        Ok(CompleteExternalLoginRequest {
+            state: state.into(),
+            code: code.into(),
+        })
+    }
+}
+#[async_trait::async_trait]
+impl<D> Ingester<D, LinkIdentity<D>> for Boundary
+where
+    D: DatabaseProvider + OidcServiceProvider + ClockProvider,
+{
+    type InputModel = (String, String);
+    async fn ingest(input: Self::InputModel) -> UsecaseRequestResult<D, LinkIdentity<D>> {
+        let (state, code) = input;
+        Ok(LinkIdentityRequest {
+            state: state.into(),
+            code: code.into(),
+        })
     }
 }
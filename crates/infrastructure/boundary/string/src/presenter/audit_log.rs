@@ -0,0 +1,45 @@
+use super::super::Boundary;
+
+use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
+use ca_application::{
+    gateway::{database::audit_log::Outcome, DatabaseProvider},
+    usecase::audit_log::query::QueryAuditLog,
+};
+#[async_trait::async_trait]
+impl<D> Presenter<D, QueryAuditLog<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, QueryAuditLog<D>>) -> Self::ViewModel {
+        match data {
+            Ok(resp) => resp
+                .entries
+                .into_iter()
+                .map(|entry| {
+                    let actor = match (entry.actor_id, entry.actor_role) {
+                        (Some(id), Some(role)) => format!("{id} ({role})"),
+                        (Some(id), None) => id.to_string(),
+                        _ => "anonymous".to_string(),
+                    };
+                    let outcome = match entry.outcome {
+                        Outcome::Success => "success".to_string(),
+                        Outcome::Denied => "denied".to_string(),
+                        Outcome::Failure(err) => format!("failure: {err}"),
+                    };
+                    format!(
+                        "- {} {} by {} on [{}]: {}",
+                        entry.occurred_at,
+                        entry.usecase,
+                        actor,
+                        entry.target_ids.join(", "),
+                        outcome
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(err) => format!("Unable to query audit log: {err}"),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use super::super::Boundary;
+
+use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
+use ca_application::{
+    gateway::DatabaseProvider,
+    usecase::role::{assign_permissions::AssignPermissions, create::Create, get_all::GetAll},
+};
+use ca_domain::value_object::Permission;
+
+fn format_permissions<'a>(permissions: impl IntoIterator<Item = &'a Permission>) -> String {
+    permissions
+        .into_iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[async_trait::async_trait]
+impl<D> Presenter<D, Create<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, Create<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "Created role {} [{}]",
+                data.role,
+                format_permissions(&data.permissions)
+            ),
+            Err(err) => format!("Unable to create role: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, AssignPermissions<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, AssignPermissions<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "Role {} now grants [{}]",
+                data.role,
+                format_permissions(&data.permissions)
+            ),
+            Err(err) => format!("Unable to assign permissions: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, GetAll<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, GetAll<D>>) -> Self::ViewModel {
+        match data {
+            Ok(resp) => resp
+                .roles
+                .into_iter()
+                .map(|r| format!("- {} [{}]", r.role, format_permissions(&r.permissions)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(err) => format!("Unable to read all roles: {err}"),
+        }
+    }
+}
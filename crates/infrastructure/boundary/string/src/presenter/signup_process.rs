@@ -1,11 +1,16 @@
 use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
 use ca_application::{
-    gateway::{DatabaseProvider, EmailVerificationServiceProvider},
+    gateway::{
+        ClockProvider, DatabaseProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+        PasswordPolicyProvider, SignupPolicyProvider, TokenGeneratorProvider,
+    },
     usecase::signup_process::{
-        complete::Complete, delete::Delete, extend_completion_time::ExtendCompletionTime,
+        complete::Complete, delete::Delete, expire::Expire,
+        extend_completion_time::ExtendCompletionTime,
         extend_verification_time::ExtendVerificationTime, get_state_chain::GetStateChain,
-        initialize::Initialize, send_verification_email::SendVerificationEmail,
-        verify_email::VerifyEmail,
+        initialize::Initialize, purge::Purge, recover::RecoverSignupProcess,
+        resend_verification_email::ResendVerificationEmail,
+        send_verification_email::SendVerificationEmail, verify_email::VerifyEmail,
     },
 };
 
@@ -13,7 +18,7 @@ use super::super::Boundary;
 #[async_trait::async_trait]
 impl<D> Presenter<D, Complete<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + PasswordPolicyProvider + 'static,
 {
     type ViewModel = String;
 
@@ -27,7 +32,7 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, Delete<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + ClockProvider + 'static,
 {
     type ViewModel = String;
 
@@ -41,7 +46,7 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, ExtendCompletionTime<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + 'static,
 {
     type ViewModel = String;
 
@@ -58,7 +63,7 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, ExtendVerificationTime<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + 'static,
 {
     type ViewModel = String;
 
@@ -89,7 +94,7 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, Initialize<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + 'static,
 {
     type ViewModel = String;
 
@@ -103,7 +108,11 @@ where
 #[async_trait::async_trait]
 impl<D> Presenter<D, SendVerificationEmail<D>> for Boundary
 where
-    D: DatabaseProvider + 'static + EmailVerificationServiceProvider,
+    D: DatabaseProvider
+        + ClockProvider
+        + 'static
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider,
 {
     type ViewModel = String;
 
@@ -115,9 +124,82 @@ where
     }
 }
 #[async_trait::async_trait]
+impl<D> Presenter<D, ResendVerificationEmail<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + SignupPolicyProvider
+        + 'static
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider,
+{
+    type ViewModel = String;
+
+    async fn present(
+        data: UsecaseResponseResult<D, ResendVerificationEmail<D>>,
+    ) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!("Verification email resent(ID = {})", data.id),
+            Err(err) => format!("Unable to resend verification email: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, Expire<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + JobMetricsProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, Expire<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "Expired {} SignupProcesses awaiting verification and {} awaiting completion ({} conflicts), totals: {:?}",
+                data.verification_timed_out, data.completion_timed_out, data.conflicts, data.totals
+            ),
+            Err(err) => format!("Unable to expire SignupProcesses: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, Purge<D>> for Boundary
+where
+    D: DatabaseProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, Purge<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "Purged {} SignupProcesses ({} purged in total)",
+                data.purged, data.tombstones
+            ),
+            Err(err) => format!("Unable to purge SignupProcesses: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, RecoverSignupProcess<D>> for Boundary
+where
+    D: DatabaseProvider
+        + ClockProvider
+        + 'static
+        + EmailVerificationServiceProvider
+        + TokenGeneratorProvider,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, RecoverSignupProcess<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!("Recovered SignupProcess(ID = {})", data.id),
+            Err(err) => format!("Unable to recover SignupProcess: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
 impl<D> Presenter<D, VerifyEmail<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + ClockProvider + SignupPolicyProvider + 'static,
 {
     type ViewModel = String;
 
@@ -2,15 +2,33 @@ use super::super::Boundary;
 
 use ca_adapter::boundary::{Presenter, UsecaseResponseResult};
 use ca_application::{
-    gateway::{AuthPackerProvider, DatabaseProvider},
+    gateway::{
+        AuthPackerProvider, ClockProvider, DatabaseProvider, EmailServiceProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
+    },
     usecase::user::{
-        delete::Delete, get_all::GetAll, get_one::GetOne, login::Login, update::Update,
+        begin_external_login::BeginExternalLogin,
+        check_username_availability::CheckUsernameAvailability,
+        complete_external_login::CompleteExternalLogin, confirm_2fa::Confirm2fa,
+        consume_login_link::ConsumeLoginLink, delete::Delete, disable_2fa::Disable2fa,
+        enable_2fa::Enable2fa, get_all::GetAll, get_me::GetMe, get_one::GetOne,
+        grant_role::GrantRole, link_identity::LinkIdentity, login::Login,
+        regenerate_recovery_codes::RegenerateRecoveryCodes, request_login_link::RequestLoginLink,
+        update::Update, verify_mfa::VerifyMfa,
     },
 };
+use ca_domain::entity::auth_context::PendingStep;
+
+fn pending_line(pending: Option<PendingStep>) -> String {
+    pending
+        .map(|step| format!("\nPENDING: {step}"))
+        .unwrap_or_default()
+}
 #[async_trait::async_trait]
 impl<D> Presenter<D, Update<D>> for Boundary
 where
-    D: DatabaseProvider + 'static,
+    D: DatabaseProvider + PasswordPolicyProvider + 'static,
 {
     type ViewModel = String;
 
@@ -36,6 +54,38 @@ where
     }
 }
 #[async_trait::async_trait]
+impl<D> Presenter<D, GetMe<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, GetMe<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => {
+                let session = data
+                    .session
+                    .map(|session| {
+                        format!(
+                            "\nISSUED_AT: {}\nEXPIRES_AT: {}",
+                            session.issued_at, session.expires_at
+                        )
+                    })
+                    .unwrap_or_default();
+                format!(
+                    "USER_ID: {}\nUSERNAME: {}\nEMAIL: {}\nROLE: {}{}",
+                    data.user.id(),
+                    data.user.username(),
+                    data.user.email(),
+                    data.role,
+                    session
+                )
+            }
+            Err(err) => format!("Unable to find current user: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
 impl<D> Presenter<D, GetAll<D>> for Boundary
 where
     D: DatabaseProvider + 'static,
@@ -76,13 +126,210 @@ where
     type ViewModel = String;
 
     async fn present(data: UsecaseResponseResult<D, Login<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "TOKEN: {:?}\nUSER_ID: {:?}{}",
+                data.token,
+                data.user_id.to_string(),
+                pending_line(data.pending)
+            ),
+            Err(err) => format!("Unable to find user: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, GrantRole<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, GrantRole<D>>) -> Self::ViewModel {
+        match data {
+            Ok(()) => "Granted role".to_string(),
+            Err(err) => format!("Unable to grant role: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, CheckUsernameAvailability<D>> for Boundary
+where
+    D: DatabaseProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(
+        data: UsecaseResponseResult<D, CheckUsernameAvailability<D>>,
+    ) -> Self::ViewModel {
+        match data {
+            Ok(data) if data.available => "Username is available".to_string(),
+            Ok(_) => "Username is taken".to_string(),
+            Err(err) => format!("Unable to check username: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, RequestLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider
+        + EmailServiceProvider
+        + TokenGeneratorProvider
+        + LoginLinkPolicyProvider
+        + ClockProvider
+        + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, RequestLoginLink<D>>) -> Self::ViewModel {
+        match data {
+            Ok(()) => "Login link sent".to_string(),
+            Err(err) => format!("Unable to send login link: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, ConsumeLoginLink<D>> for Boundary
+where
+    D: DatabaseProvider + AuthPackerProvider + LoginLinkPolicyProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, ConsumeLoginLink<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "TOKEN: {:?}\nUSER_ID: {:?}{}",
+                data.token,
+                data.user_id.to_string(),
+                pending_line(data.pending)
+            ),
+            Err(err) => format!("Unable to log in: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, Enable2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, Enable2fa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!("SECRET: {}\nURI: {}", data.secret, data.uri),
+            Err(err) => format!("Unable to enable two factor authentication: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, Confirm2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, Confirm2fa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "Two factor authentication enabled, recovery codes:\n{}",
+                data.recovery_codes.join("\n")
+            ),
+            Err(err) => format!("Unable to confirm two factor authentication: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, Disable2fa<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, Disable2fa<D>>) -> Self::ViewModel {
+        match data {
+            Ok(()) => "Two factor authentication disabled".to_string(),
+            Err(err) => format!("Unable to disable two factor authentication: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, RegenerateRecoveryCodes<D>> for Boundary
+where
+    D: DatabaseProvider + TotpServiceProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(
+        data: UsecaseResponseResult<D, RegenerateRecoveryCodes<D>>,
+    ) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!("Recovery codes:\n{}", data.recovery_codes.join("\n")),
+            Err(err) => format!("Unable to regenerate recovery codes: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, VerifyMfa<D>> for Boundary
+where
+    D: DatabaseProvider + AuthPackerProvider + TotpServiceProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, VerifyMfa<D>>) -> Self::ViewModel {
         match data {
             Ok(data) => format!(
                 "TOKEN: {:?}\nUSER_ID: {:?}",
                 data.token,
                 data.user_id.to_string()
             ),
-            Err(err) => format!("Unable to find user: {err}"),
+            Err(err) => format!("Unable to verify second factor: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, BeginExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider + OidcServiceProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, BeginExternalLogin<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!("AUTHORIZATION_URL: {}", data.authorization_url),
+            Err(err) => format!("Unable to begin external login: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, CompleteExternalLogin<D>> for Boundary
+where
+    D: DatabaseProvider + OidcServiceProvider + AuthPackerProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, CompleteExternalLogin<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!(
+                "TOKEN: {:?}\nUSER_ID: {:?}\nCREATED: {}{}",
+                data.token,
+                data.user_id.to_string(),
+                data.created,
+                pending_line(data.pending)
+            ),
+            Err(err) => format!("Unable to complete external login: {err}"),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl<D> Presenter<D, LinkIdentity<D>> for Boundary
+where
+    D: DatabaseProvider + OidcServiceProvider + ClockProvider + 'static,
+{
+    type ViewModel = String;
+
+    async fn present(data: UsecaseResponseResult<D, LinkIdentity<D>>) -> Self::ViewModel {
+        match data {
+            Ok(data) => format!("Identity {} of {} linked", data.subject, data.provider),
+            Err(err) => format!("Unable to link identity: {err}"),
         }
     }
 }
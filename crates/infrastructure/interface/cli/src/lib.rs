@@ -18,19 +18,34 @@ use clap::Subcommand;
 use ca_adapter::controller::{Controller, ControllerTrait};
 use ca_application::{
     gateway::{
-        AuthExtractorProvider, AuthPackerProvider, DatabaseProvider,
-        EmailVerificationServiceProvider,
+        AuthExtractorProvider, AuthPackerProvider, ClockProvider, DatabaseProvider,
+        EmailServiceProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider, SignupPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
     },
     usecase::{
+        audit_log::query::QueryAuditLog,
+        role::{
+            assign_permissions::AssignPermissions, create::Create as CreateRole,
+            get_all::GetAll as GetAllRoles,
+        },
         signup_process::{
-            complete::Complete, delete::Delete, extend_completion_time::ExtendCompletionTime,
+            complete::Complete, delete::Delete, expire::Expire,
+            extend_completion_time::ExtendCompletionTime,
             extend_verification_time::ExtendVerificationTime, get_state_chain::GetStateChain,
-            initialize::Initialize, send_verification_email::SendVerificationEmail,
-            verify_email::VerifyEmail,
+            initialize::Initialize, purge::Purge, recover::RecoverSignupProcess,
+            resend_verification_email::ResendVerificationEmail,
+            send_verification_email::SendVerificationEmail, verify_email::VerifyEmail,
         },
         user::{
-            delete::Delete as UserDelete, get_all::GetAll, get_one::GetOne, login::Login,
-            update::Update,
+            begin_external_login::BeginExternalLogin,
+            check_username_availability::CheckUsernameAvailability,
+            complete_external_login::CompleteExternalLogin, confirm_2fa::Confirm2fa,
+            consume_login_link::ConsumeLoginLink, delete::Delete as UserDelete,
+            disable_2fa::Disable2fa, enable_2fa::Enable2fa, get_all::GetAll, get_me::GetMe,
+            get_one::GetOne, grant_role::GrantRole, link_identity::LinkIdentity, login::Login,
+            regenerate_recovery_codes::RegenerateRecoveryCodes,
+            request_login_link::RequestLoginLink, update::Update, verify_mfa::VerifyMfa,
         },
     },
 };
@@ -50,6 +65,27 @@ pub enum Command {
         alias = "sp-send-verify"
     )]
     SendVerificationEmail { id: String, token: Option<String> },
+    #[clap(
+        about = "Resend verification email for signup process",
+        alias = "sp-resend-verify"
+    )]
+    ResendVerificationEmail { id: String, token: Option<String> },
+    #[clap(about = "Recover failed signup process", alias = "sp-recover")]
+    RecoverSignupProcess { id: String, token: Option<String> },
+    #[clap(
+        about = "Fail signup processes past their deadline",
+        alias = "sp-expire"
+    )]
+    ExpireSignupProcesses { token: Option<String> },
+    #[clap(
+        about = "Purge signup processes deleted longer than the grace period ago",
+        alias = "sp-purge"
+    )]
+    PurgeSignupProcesses {
+        #[clap(long)]
+        grace_period_days: Option<u32>,
+        token: Option<String>,
+    },
     #[clap(
         about = "Extend verification time of signup process",
         alias = "sp-extend-verify"
@@ -79,8 +115,45 @@ pub enum Command {
     GetStateChain { id: String, token: Option<String> },
     #[clap(about = "Login user")]
     Login { username: String, password: String },
+    #[clap(about = "Email a login link to user")]
+    RequestLoginLink { username: String },
+    #[clap(about = "Login user with the token of a login link")]
+    ConsumeLoginLink { username: String, token: String },
+    #[clap(about = "Finish a login pending on the second factor")]
+    VerifyMfa { code: String, token: Option<String> },
+    #[clap(about = "Start enrolling a TOTP second factor", alias = "2fa-enable")]
+    Enable2fa { token: Option<String> },
+    #[clap(
+        about = "Confirm the TOTP second factor with a code",
+        alias = "2fa-confirm"
+    )]
+    Confirm2fa { code: String, token: Option<String> },
+    #[clap(about = "Turn off the TOTP second factor", alias = "2fa-disable")]
+    Disable2fa { code: String, token: Option<String> },
+    #[clap(about = "Replace the recovery codes", alias = "2fa-recovery-codes")]
+    RegenerateRecoveryCodes { code: String, token: Option<String> },
+    #[clap(about = "Start a login at an OpenID Connect provider")]
+    BeginExternalLogin {
+        provider: String,
+        /// Link the identity to the user of the token instead
+        #[clap(long)]
+        link: bool,
+        token: Option<String>,
+    },
+    #[clap(about = "Finish a login with the state and code handed back by the provider")]
+    CompleteExternalLogin { state: String, code: String },
+    #[clap(about = "Link the identity of a login started with --link")]
+    LinkIdentity {
+        state: String,
+        code: String,
+        token: Option<String>,
+    },
+    #[clap(about = "Check whether a username can still be picked")]
+    CheckUsername { username: String },
     #[clap(about = "List all users")]
     ListUsers { token: Option<String> },
+    #[clap(about = "Show the user the token belongs to")]
+    Whoami { token: Option<String> },
     #[clap(about = "Read user")]
     ReadUser { id: String, token: Option<String> },
     #[clap(about = "Update user")]
@@ -93,17 +166,64 @@ pub enum Command {
     },
     #[clap(about = "Delete user")]
     DeleteUser { id: String, token: Option<String> },
+    #[clap(about = "Grant a role to user")]
+    GrantRole {
+        id: String,
+        role: String,
+        token: Option<String>,
+    },
+    #[clap(about = "List all roles")]
+    ListRoles { token: Option<String> },
+    #[clap(about = "Create role")]
+    CreateRole {
+        name: String,
+        #[clap(long = "permission")]
+        permissions: Vec<String>,
+        token: Option<String>,
+    },
+    #[clap(about = "Assign permissions to role")]
+    AssignPermissions {
+        name: String,
+        #[clap(long = "permission")]
+        permissions: Vec<String>,
+        token: Option<String>,
+    },
+    #[clap(about = "Query the audit log", alias = "audit")]
+    QueryAuditLog {
+        #[clap(long)]
+        actor_id: Option<String>,
+        #[clap(long)]
+        target_id: Option<String>,
+        #[clap(long, help = "RFC 3339 timestamp")]
+        from: Option<String>,
+        #[clap(long, help = "RFC 3339 timestamp")]
+        to: Option<String>,
+        #[clap(long, help = "Entries to return, 100 by default and 1000 at most")]
+        limit: Option<u32>,
+        #[clap(long, help = "Entries to skip")]
+        offset: Option<u32>,
+        token: Option<String>,
+    },
 }
 
 pub async fn run<D>(db: Arc<D>, cmd: Command)
 where
     D: DatabaseProvider
         + EmailVerificationServiceProvider
+        + EmailServiceProvider
         + AuthPackerProvider
         + AuthExtractorProvider
+        + SignupPolicyProvider
+        + PasswordPolicyProvider
+        + LoginLinkPolicyProvider
+        + TokenGeneratorProvider
+        + TotpServiceProvider
+        + OidcServiceProvider
+        + ClockProvider
+        + JobMetricsProvider
         + 'static,
 {
-    let app_controller = Controller::<D, string::Boundary>::new(db);
+    let app_controller = Controller::<D, string::Boundary>::new(db.clone());
 
     match cmd {
         Command::InitializeSignupProcess { email, token } => {
@@ -118,6 +238,31 @@ where
                 .await;
             println!("{res}");
         }
+        Command::ResendVerificationEmail { id, token } => {
+            let res = app_controller
+                .handle_usecase::<ResendVerificationEmail<D>>(id, token)
+                .await;
+            println!("{res}");
+        }
+        Command::RecoverSignupProcess { id, token } => {
+            let res = app_controller
+                .handle_usecase::<RecoverSignupProcess<D>>(id, token)
+                .await;
+            println!("{res}");
+        }
+        Command::ExpireSignupProcesses { token } => {
+            let res = app_controller.handle_usecase::<Expire<D>>((), token).await;
+            println!("{res}");
+        }
+        Command::PurgeSignupProcesses {
+            grace_period_days,
+            token,
+        } => {
+            let res = app_controller
+                .handle_usecase::<Purge<D>>(grace_period_days, token)
+                .await;
+            println!("{res}");
+        }
         Command::ExtendVerificationTimeOfSignupProcess { id, token } => {
             let res = app_controller
                 .handle_usecase::<ExtendVerificationTime<D>>(id, token)
@@ -167,10 +312,84 @@ where
                 .await;
             println!("{res}");
         }
+        Command::RequestLoginLink { username } => {
+            let res = app_controller
+                .handle_usecase::<RequestLoginLink<D>>(username, None)
+                .await;
+            println!("{res}");
+        }
+        Command::ConsumeLoginLink { username, token } => {
+            let res = app_controller
+                .handle_usecase::<ConsumeLoginLink<D>>((username, token), None)
+                .await;
+            println!("{res}");
+        }
+        Command::VerifyMfa { code, token } => {
+            let res = app_controller
+                .handle_usecase::<VerifyMfa<D>>(code, token)
+                .await;
+            println!("{res}");
+        }
+        Command::Enable2fa { token } => {
+            let res = app_controller
+                .handle_usecase::<Enable2fa<D>>((), token)
+                .await;
+            println!("{res}");
+        }
+        Command::Confirm2fa { code, token } => {
+            let res = app_controller
+                .handle_usecase::<Confirm2fa<D>>(code, token)
+                .await;
+            println!("{res}");
+        }
+        Command::Disable2fa { code, token } => {
+            let res = app_controller
+                .handle_usecase::<Disable2fa<D>>(code, token)
+                .await;
+            println!("{res}");
+        }
+        Command::RegenerateRecoveryCodes { code, token } => {
+            let res = app_controller
+                .handle_usecase::<RegenerateRecoveryCodes<D>>(code, token)
+                .await;
+            println!("{res}");
+        }
+        Command::BeginExternalLogin {
+            provider,
+            link,
+            token,
+        } => {
+            let res = app_controller
+                .handle_usecase::<BeginExternalLogin<D>>((provider, link), token)
+                .await;
+            println!("{res}");
+        }
+        Command::CompleteExternalLogin { state, code } => {
+            let res = app_controller
+                .handle_usecase::<CompleteExternalLogin<D>>((state, code), None)
+                .await;
+            println!("{res}");
+        }
+        Command::LinkIdentity { state, code, token } => {
+            let res = app_controller
+                .handle_usecase::<LinkIdentity<D>>((state, code), token)
+                .await;
+            println!("{res}");
+        }
+        Command::CheckUsername { username } => {
+            let res = app_controller
+                .handle_usecase::<CheckUsernameAvailability<D>>(username, None)
+                .await;
+            println!("{res}");
+        }
         Command::ListUsers { token } => {
             let res = app_controller.handle_usecase::<GetAll<D>>((), token).await;
             println!("{res}");
         }
+        Command::Whoami { token } => {
+            let res = app_controller.handle_usecase::<GetMe<D>>((), token).await;
+            println!("{res}");
+        }
         Command::DeleteUser { id, token } => {
             let res = app_controller
                 .handle_usecase::<UserDelete<D>>(id, token)
@@ -193,5 +412,54 @@ where
                 .await;
             println!("{res}");
         }
+        Command::GrantRole { id, role, token } => {
+            let res = app_controller
+                .handle_usecase::<GrantRole<D>>((id, role), token)
+                .await;
+            println!("{res}");
+        }
+        Command::ListRoles { token } => {
+            let res = app_controller
+                .handle_usecase::<GetAllRoles<D>>((), token)
+                .await;
+            println!("{res}");
+        }
+        Command::CreateRole {
+            name,
+            permissions,
+            token,
+        } => {
+            let res = app_controller
+                .handle_usecase::<CreateRole<D>>((name, permissions), token)
+                .await;
+            println!("{res}");
+        }
+        Command::AssignPermissions {
+            name,
+            permissions,
+            token,
+        } => {
+            let res = app_controller
+                .handle_usecase::<AssignPermissions<D>>((name, permissions), token)
+                .await;
+            println!("{res}");
+        }
+        Command::QueryAuditLog {
+            actor_id,
+            target_id,
+            from,
+            to,
+            limit,
+            offset,
+            token,
+        } => {
+            let res = app_controller
+                .handle_usecase::<QueryAuditLog<D>>(
+                    (actor_id, target_id, from, to, limit, offset),
+                    token,
+                )
+                .await;
+            println!("{res}");
+        }
     }
 }
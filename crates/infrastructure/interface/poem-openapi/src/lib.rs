@@ -11,24 +11,39 @@
 //!   and match them to the defined commands.
 //! * Command Execution: Map the parsed commands to the appropriate functions or
 //!   methods in the application.
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc};
 
 use ca_adapter::controller::{Controller, ControllerTrait};
 use ca_application::{
     gateway::{
-        AuthExtractorProvider, AuthPackerProvider, DatabaseProvider,
-        EmailVerificationServiceProvider,
+        AuthExtractorProvider, AuthPackerProvider, ClockProvider, DatabaseProvider,
+        EmailServiceProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider, SignupPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
     },
     usecase::{
+        audit_log::query::QueryAuditLog,
+        role::{
+            assign_permissions::AssignPermissions, create::Create as CreateRole,
+            get_all::GetAll as GetAllRoles,
+        },
         signup_process::{
-            complete::Complete, delete::Delete, extend_completion_time::ExtendCompletionTime,
+            complete::Complete, delete::Delete, expire::Expire,
+            extend_completion_time::ExtendCompletionTime,
             extend_verification_time::ExtendVerificationTime, get_state_chain::GetStateChain,
-            initialize::Initialize, send_verification_email::SendVerificationEmail,
-            verify_email::VerifyEmail,
+            initialize::Initialize, purge::Purge, recover::RecoverSignupProcess,
+            resend_verification_email::ResendVerificationEmail,
+            send_verification_email::SendVerificationEmail, verify_email::VerifyEmail,
         },
         user::{
-            delete::Delete as UserDelete, get_all::GetAll, get_one::GetOne, login::Login,
-            update::Update,
+            begin_external_login::BeginExternalLogin,
+            check_username_availability::CheckUsernameAvailability,
+            complete_external_login::CompleteExternalLogin, confirm_2fa::Confirm2fa,
+            consume_login_link::ConsumeLoginLink, delete::Delete as UserDelete,
+            disable_2fa::Disable2fa, enable_2fa::Enable2fa, get_all::GetAll, get_me::GetMe,
+            get_one::GetOne, grant_role::GrantRole, link_identity::LinkIdentity, login::Login,
+            regenerate_recovery_codes::RegenerateRecoveryCodes,
+            request_login_link::RequestLoginLink, update::Update, verify_mfa::VerifyMfa,
         },
     },
 };
@@ -36,15 +51,38 @@ use ca_application::{
 use ca_infrastructure_boundary_poem_openapi::{
     self as boundary,
     ingester::{
-        signup_process::{CompleteRequest, IdRequest, InitializeRequest, VerifyEmailRequest},
-        user::{LoginRequest, UpdateRequest},
+        audit_log::QueryAuditLogRequest,
+        context::ContextRequest,
+        role::RoleRequest,
+        signup_process::{
+            CompleteRequest, IdRequest, InitializeRequest, PurgeRequest, VerifyEmailRequest,
+        },
+        user::{
+            BeginExternalLoginRequest, CheckUsernameAvailabilityRequest, ConsumeLoginLinkRequest,
+            ExternalLoginCallbackRequest, GrantRoleRequest, LoginRequest, MfaCodeRequest,
+            RequestLoginLinkRequest, UpdateRequest,
+        },
     },
     presenter::{
-        signup_process::{Empty, IdResponse, SignupProcessResponse, TheApiResponse},
-        user::{LoginResponse, UserResponse},
+        audit_log::AuditLogEntryResponse,
+        role::RoleResponse,
+        signup_process::{
+            Empty, ExpireResponse, IdResponse, PurgeResponse, SignupProcessResponse, TheApiResponse,
+        },
+        user::{
+            BeginExternalLoginResponse, Enable2faResponse, ExternalLoginResponse, IdentityResponse,
+            LoginResponse, MeResponse, RecoveryCodesResponse, UserResponse,
+            UsernameAvailabilityResponse,
+        },
     },
 };
-use poem_openapi::{auth::Bearer, param::Path, payload::Json, OpenApi, SecurityScheme, Tags};
+use poem::Request;
+use poem_openapi::{
+    auth::Bearer,
+    param::{Path, Query},
+    payload::Json,
+    ApiResponse, OpenApi, SecurityScheme, Tags,
+};
 
 #[derive(Tags)]
 enum ApiTags {
@@ -52,14 +90,80 @@ enum ApiTags {
     User,
     /// Operations about pet
     SignupProcess,
+    /// Operations about the audit log
+    AuditLog,
+    /// Operations about roles and their permissions
+    Role,
 }
 
 #[derive(SecurityScheme)]
 #[oai(ty = "bearer", key_name = "X-Token", key_in = "header")]
 struct ApiSecurityScheme(Bearer);
 
+#[derive(ApiResponse)]
+enum RedirectResponse {
+    /// Redirects the browser to the given location.
+    #[oai(status = 303)]
+    SeeOther(#[oai(header = "Location")] String),
+}
+
+/// Pages the verification link of the email redirects to, both get the
+/// signup process id appended as `id` query parameter
+#[derive(Debug, Clone)]
+pub struct VerificationRedirects {
+    pub success_url: String,
+    pub failure_url: String,
+}
+
+impl Default for VerificationRedirects {
+    fn default() -> Self {
+        Self {
+            success_url: "/".to_string(),
+            failure_url: "/".to_string(),
+        }
+    }
+}
+
+/// Adds `id` to the query of `url`, in front of a fragment if there is one
+fn with_id(url: &str, id: &str) -> String {
+    let (url, fragment) = match url.split_once('#') {
+        Some((url, fragment)) => (url, Some(fragment)),
+        None => (url, None),
+    };
+    let separator = match url.find('?') {
+        None => "?",
+        Some(_) if url.ends_with(['?', '&']) => "",
+        Some(_) => "&",
+    };
+    let id: String = id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    match fragment {
+        Some(fragment) => format!("{url}{separator}id={id}#{fragment}"),
+        None => format!("{url}{separator}id={id}"),
+    }
+}
+
 pub struct Api<D> {
     pub controller: Controller<D, boundary::Boundary>,
+    pub verification_redirects: VerificationRedirects,
+    /// Proxies trusted to tell the client address in `X-Forwarded-For`
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl<D> Api<D> {
+    fn context(&self, req: &Request, token: Option<String>) -> ContextRequest {
+        ContextRequest {
+            token,
+            headers: req.headers().clone(),
+            remote_ip: req
+                .remote_addr()
+                .as_socket_addr()
+                .map(|addr| addr.ip().to_string()),
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
 }
 
 #[OpenApi]
@@ -67,15 +171,34 @@ impl<D> Api<D>
 where
     D: DatabaseProvider
         + EmailVerificationServiceProvider
+        + EmailServiceProvider
         + AuthPackerProvider
         + AuthExtractorProvider
+        + SignupPolicyProvider
+        + PasswordPolicyProvider
+        + LoginLinkPolicyProvider
+        + TokenGeneratorProvider
+        + TotpServiceProvider
+        + OidcServiceProvider
+        + ClockProvider
+        + JobMetricsProvider
         + 'static,
 {
     pub fn new(dependancy_provider: Arc<D>) -> Self {
         Self {
             controller: Controller::<D, boundary::Boundary>::new(dependancy_provider),
+            verification_redirects: VerificationRedirects::default(),
+            trusted_proxies: Vec::new(),
         }
     }
+    pub fn with_verification_redirects(mut self, redirects: VerificationRedirects) -> Self {
+        self.verification_redirects = redirects;
+        self
+    }
+    pub fn with_trusted_proxies(mut self, trusted_proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
     #[oai(
         path = "/signup_processes/initialize",
         method = "post",
@@ -83,10 +206,11 @@ where
     )]
     async fn initialize_signup_process(
         &self,
+        req: &Request,
         request: Json<InitializeRequest>,
     ) -> TheApiResponse<IdResponse> {
         self.controller
-            .handle_usecase::<Initialize<D>>(request.0, None)
+            .handle_usecase::<Initialize<D>>(request.0, self.context(req, None))
             .await
     }
     #[oai(
@@ -96,11 +220,76 @@ where
     )]
     async fn send_verification_email_signup_process(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<IdRequest>,
     ) -> TheApiResponse<IdResponse> {
         self.controller
-            .handle_usecase::<SendVerificationEmail<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<SendVerificationEmail<D>>(
+                request.0,
+                self.context(req, Some(auth.0.token)),
+            )
+            .await
+    }
+    #[oai(
+        path = "/signup_processes/resend_verification_email",
+        method = "post",
+        tag = "ApiTags::SignupProcess"
+    )]
+    async fn resend_verification_email_signup_process(
+        &self,
+        req: &Request,
+        request: Json<IdRequest>,
+    ) -> TheApiResponse<IdResponse> {
+        self.controller
+            .handle_usecase::<ResendVerificationEmail<D>>(request.0, self.context(req, None))
+            .await
+    }
+    #[oai(
+        path = "/signup_processes/recover",
+        method = "post",
+        tag = "ApiTags::SignupProcess"
+    )]
+    async fn recover_signup_process(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<IdRequest>,
+    ) -> TheApiResponse<IdResponse> {
+        self.controller
+            .handle_usecase::<RecoverSignupProcess<D>>(
+                request.0,
+                self.context(req, Some(auth.0.token)),
+            )
+            .await
+    }
+    #[oai(
+        path = "/signup_processes/expire",
+        method = "post",
+        tag = "ApiTags::SignupProcess"
+    )]
+    async fn expire_signup_processes(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+    ) -> TheApiResponse<ExpireResponse> {
+        self.controller
+            .handle_usecase::<Expire<D>>((), self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(
+        path = "/signup_processes/purge",
+        method = "post",
+        tag = "ApiTags::SignupProcess"
+    )]
+    async fn purge_signup_processes(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<PurgeRequest>,
+    ) -> TheApiResponse<PurgeResponse> {
+        self.controller
+            .handle_usecase::<Purge<D>>(request.0, self.context(req, Some(auth.0.token)))
             .await
     }
     #[oai(
@@ -110,11 +299,39 @@ where
     )]
     async fn verify_email_signup_process(
         &self,
+        req: &Request,
         request: Json<VerifyEmailRequest>,
     ) -> TheApiResponse<IdResponse> {
         self.controller
-            .handle_usecase::<VerifyEmail<D>>(request.0, None)
+            .handle_usecase::<VerifyEmail<D>>(request.0, self.context(req, None))
+            .await
+    }
+    /// Target of the link in the verification email, opening it again once
+    /// the email is verified still lands on the success page
+    #[oai(
+        path = "/signup_processes/:id/verify",
+        method = "get",
+        tag = "ApiTags::SignupProcess"
+    )]
+    async fn verify_email_link_signup_process(
+        &self,
+        req: &Request,
+        id: Path<String>,
+        token: Query<String>,
+    ) -> RedirectResponse {
+        let request = VerifyEmailRequest {
+            id: id.0.clone(),
+            token: token.0,
+        };
+        let url = match self
+            .controller
+            .handle_usecase::<VerifyEmail<D>>(request, self.context(req, None))
             .await
+        {
+            TheApiResponse::Ok(_) => &self.verification_redirects.success_url,
+            _ => &self.verification_redirects.failure_url,
+        };
+        RedirectResponse::SeeOther(with_id(url, &id.0))
     }
     #[oai(
         path = "/signup_processes/extend_verification_time",
@@ -123,11 +340,15 @@ where
     )]
     async fn extend_verification_time_signup_process(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<IdRequest>,
     ) -> TheApiResponse<IdResponse> {
         self.controller
-            .handle_usecase::<ExtendVerificationTime<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<ExtendVerificationTime<D>>(
+                request.0,
+                self.context(req, Some(auth.0.token)),
+            )
             .await
     }
     #[oai(
@@ -137,10 +358,11 @@ where
     )]
     async fn complete_signup_process(
         &self,
+        req: &Request,
         request: Json<CompleteRequest>,
     ) -> TheApiResponse<UserResponse> {
         self.controller
-            .handle_usecase::<Complete<D>>(request.0, None)
+            .handle_usecase::<Complete<D>>(request.0, self.context(req, None))
             .await
     }
     #[oai(
@@ -150,11 +372,15 @@ where
     )]
     async fn extend_completion_time_signup_process(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<IdRequest>,
     ) -> TheApiResponse<IdResponse> {
         self.controller
-            .handle_usecase::<ExtendCompletionTime<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<ExtendCompletionTime<D>>(
+                request.0,
+                self.context(req, Some(auth.0.token)),
+            )
             .await
     }
     #[oai(
@@ -164,11 +390,12 @@ where
     )]
     async fn delete_signup_process(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<IdRequest>,
     ) -> TheApiResponse<IdResponse> {
         self.controller
-            .handle_usecase::<Delete<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<Delete<D>>(request.0, self.context(req, Some(auth.0.token)))
             .await
     }
     #[oai(
@@ -178,53 +405,344 @@ where
     )]
     async fn get_state_chain_signup_process(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<IdRequest>,
     ) -> TheApiResponse<Vec<SignupProcessResponse>> {
         self.controller
-            .handle_usecase::<GetStateChain<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<GetStateChain<D>>(request.0, self.context(req, Some(auth.0.token)))
             .await
     }
     #[oai(path = "/users/delete", method = "post", tag = "ApiTags::User")]
     async fn delete_user(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<IdRequest>,
     ) -> TheApiResponse<Empty> {
         self.controller
-            .handle_usecase::<UserDelete<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<UserDelete<D>>(request.0, self.context(req, Some(auth.0.token)))
             .await
     }
     #[oai(path = "/users", method = "get", tag = "ApiTags::User")]
-    async fn get_all_user(&self, auth: ApiSecurityScheme) -> TheApiResponse<Vec<UserResponse>> {
+    async fn get_all_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+    ) -> TheApiResponse<Vec<UserResponse>> {
         self.controller
-            .handle_usecase::<GetAll<D>>((), Some(auth.0.token))
+            .handle_usecase::<GetAll<D>>((), self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/users/me", method = "get", tag = "ApiTags::User")]
+    async fn get_me_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+    ) -> TheApiResponse<MeResponse> {
+        self.controller
+            .handle_usecase::<GetMe<D>>((), self.context(req, Some(auth.0.token)))
             .await
     }
     #[oai(path = "/users/:user_id", method = "get", tag = "ApiTags::User")]
     async fn get_one_user(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         user_id: Path<String>,
     ) -> TheApiResponse<UserResponse> {
         self.controller
-            .handle_usecase::<GetOne<D>>(IdRequest { id: user_id.0 }, Some(auth.0.token))
+            .handle_usecase::<GetOne<D>>(
+                IdRequest { id: user_id.0 },
+                self.context(req, Some(auth.0.token)),
+            )
             .await
     }
     #[oai(path = "/users/login", method = "post", tag = "ApiTags::User")]
-    async fn login_user(&self, request: Json<LoginRequest>) -> TheApiResponse<LoginResponse> {
+    async fn login_user(
+        &self,
+        req: &Request,
+        request: Json<LoginRequest>,
+    ) -> TheApiResponse<LoginResponse> {
         self.controller
-            .handle_usecase::<Login<D>>(request.0, None)
+            .handle_usecase::<Login<D>>(request.0, self.context(req, None))
+            .await
+    }
+    #[oai(path = "/users/login_link", method = "post", tag = "ApiTags::User")]
+    async fn request_login_link_user(
+        &self,
+        req: &Request,
+        request: Json<RequestLoginLinkRequest>,
+    ) -> TheApiResponse<Empty> {
+        self.controller
+            .handle_usecase::<RequestLoginLink<D>>(request.0, self.context(req, None))
+            .await
+    }
+    #[oai(
+        path = "/users/login_link/consume",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn consume_login_link_user(
+        &self,
+        req: &Request,
+        request: Json<ConsumeLoginLinkRequest>,
+    ) -> TheApiResponse<LoginResponse> {
+        self.controller
+            .handle_usecase::<ConsumeLoginLink<D>>(request.0, self.context(req, None))
+            .await
+    }
+    /// Trades the partial token of a login pending on the second factor for
+    /// a full one
+    #[oai(
+        path = "/users/login/verify_mfa",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn verify_mfa_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<MfaCodeRequest>,
+    ) -> TheApiResponse<LoginResponse> {
+        self.controller
+            .handle_usecase::<VerifyMfa<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/users/me/2fa/enable", method = "post", tag = "ApiTags::User")]
+    async fn enable_2fa_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+    ) -> TheApiResponse<Enable2faResponse> {
+        self.controller
+            .handle_usecase::<Enable2fa<D>>((), self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/users/me/2fa/confirm", method = "post", tag = "ApiTags::User")]
+    async fn confirm_2fa_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<MfaCodeRequest>,
+    ) -> TheApiResponse<RecoveryCodesResponse> {
+        self.controller
+            .handle_usecase::<Confirm2fa<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/users/me/2fa/disable", method = "post", tag = "ApiTags::User")]
+    async fn disable_2fa_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<MfaCodeRequest>,
+    ) -> TheApiResponse<Empty> {
+        self.controller
+            .handle_usecase::<Disable2fa<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(
+        path = "/users/me/2fa/recovery_codes",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn regenerate_recovery_codes_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<MfaCodeRequest>,
+    ) -> TheApiResponse<RecoveryCodesResponse> {
+        self.controller
+            .handle_usecase::<RegenerateRecoveryCodes<D>>(
+                request.0,
+                self.context(req, Some(auth.0.token)),
+            )
+            .await
+    }
+    /// Starts a login at an OpenID Connect provider, send the user to the
+    /// returned url and finish with the state and code it hands back
+    #[oai(path = "/users/external/begin", method = "post", tag = "ApiTags::User")]
+    async fn begin_external_login_user(
+        &self,
+        req: &Request,
+        request: Json<BeginExternalLoginRequest>,
+    ) -> TheApiResponse<BeginExternalLoginResponse> {
+        self.controller
+            .handle_usecase::<BeginExternalLogin<D>>((request.0, false), self.context(req, None))
+            .await
+    }
+    /// Logs in with an external identity, signing the user up on first use
+    #[oai(
+        path = "/users/external/complete",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn complete_external_login_user(
+        &self,
+        req: &Request,
+        request: Json<ExternalLoginCallbackRequest>,
+    ) -> TheApiResponse<ExternalLoginResponse> {
+        self.controller
+            .handle_usecase::<CompleteExternalLogin<D>>(request.0, self.context(req, None))
+            .await
+    }
+    /// Starts a login at an OpenID Connect provider to link its identity to
+    /// the current user
+    #[oai(
+        path = "/users/me/identities/begin",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn begin_link_identity_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<BeginExternalLoginRequest>,
+    ) -> TheApiResponse<BeginExternalLoginResponse> {
+        self.controller
+            .handle_usecase::<BeginExternalLogin<D>>(
+                (request.0, true),
+                self.context(req, Some(auth.0.token)),
+            )
+            .await
+    }
+    #[oai(
+        path = "/users/me/identities/link",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn link_identity_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<ExternalLoginCallbackRequest>,
+    ) -> TheApiResponse<IdentityResponse> {
+        self.controller
+            .handle_usecase::<LinkIdentity<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(
+        path = "/users/username_available",
+        method = "post",
+        tag = "ApiTags::User"
+    )]
+    async fn check_username_availability(
+        &self,
+        req: &Request,
+        request: Json<CheckUsernameAvailabilityRequest>,
+    ) -> TheApiResponse<UsernameAvailabilityResponse> {
+        self.controller
+            .handle_usecase::<CheckUsernameAvailability<D>>(request.0, self.context(req, None))
             .await
     }
     #[oai(path = "/users/update", method = "post", tag = "ApiTags::User")]
     async fn update_user(
         &self,
+        req: &Request,
         auth: ApiSecurityScheme,
         request: Json<UpdateRequest>,
     ) -> TheApiResponse<Empty> {
         self.controller
-            .handle_usecase::<Update<D>>(request.0, Some(auth.0.token))
+            .handle_usecase::<Update<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/audit_log/query", method = "post", tag = "ApiTags::AuditLog")]
+    async fn query_audit_log(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<QueryAuditLogRequest>,
+    ) -> TheApiResponse<Vec<AuditLogEntryResponse>> {
+        self.controller
+            .handle_usecase::<QueryAuditLog<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/users/grant_role", method = "post", tag = "ApiTags::User")]
+    async fn grant_role_user(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<GrantRoleRequest>,
+    ) -> TheApiResponse<Empty> {
+        self.controller
+            .handle_usecase::<GrantRole<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(path = "/roles", method = "get", tag = "ApiTags::Role")]
+    async fn get_all_role(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+    ) -> TheApiResponse<Vec<RoleResponse>> {
+        self.controller
+            .handle_usecase::<GetAllRoles<D>>((), self.context(req, Some(auth.0.token)))
             .await
     }
+    #[oai(path = "/roles/create", method = "post", tag = "ApiTags::Role")]
+    async fn create_role(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<RoleRequest>,
+    ) -> TheApiResponse<RoleResponse> {
+        self.controller
+            .handle_usecase::<CreateRole<D>>(request.0, self.context(req, Some(auth.0.token)))
+            .await
+    }
+    #[oai(
+        path = "/roles/assign_permissions",
+        method = "post",
+        tag = "ApiTags::Role"
+    )]
+    async fn assign_permissions_role(
+        &self,
+        req: &Request,
+        auth: ApiSecurityScheme,
+        request: Json<RoleRequest>,
+    ) -> TheApiResponse<RoleResponse> {
+        self.controller
+            .handle_usecase::<AssignPermissions<D>>(
+                request.0,
+                self.context(req, Some(auth.0.token)),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "6b2d1c4e-0d4f-4a57-9c36-0f4c8c2a9d11";
+
+    #[test]
+    fn test_with_id_plain() {
+        assert_eq!(with_id("/welcome", ID), format!("/welcome?id={ID}"));
+    }
+
+    #[test]
+    fn test_with_id_query() {
+        assert_eq!(
+            with_id("/welcome?from=email", ID),
+            format!("/welcome?from=email&id={ID}")
+        );
+        assert_eq!(with_id("/welcome?", ID), format!("/welcome?id={ID}"));
+    }
+
+    #[test]
+    fn test_with_id_fragment() {
+        assert_eq!(
+            with_id("/failed#retry", ID),
+            format!("/failed?id={ID}#retry")
+        );
+        assert_eq!(
+            with_id("/welcome?from=email#done", ID),
+            format!("/welcome?from=email&id={ID}#done")
+        );
+    }
+
+    #[test]
+    fn test_with_id_strips_id() {
+        assert_eq!(with_id("/", "a&b=c#d"), "/?id=abcd");
+    }
 }
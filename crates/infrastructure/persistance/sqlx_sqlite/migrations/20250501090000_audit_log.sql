@@ -0,0 +1,21 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    actor_id TEXT,
+    actor_role TEXT,
+    usecase TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    error TEXT,
+    request_id TEXT,
+    client_ip TEXT,
+    occurred_at DATETIME NOT NULL
+);
+CREATE INDEX IF NOT EXISTS audit_log_actor_id ON audit_log (actor_id);
+CREATE INDEX IF NOT EXISTS audit_log_occurred_at ON audit_log (occurred_at);
+
+CREATE TABLE IF NOT EXISTS audit_log_targets (
+    entry_id INTEGER NOT NULL REFERENCES audit_log (id) ON DELETE CASCADE,
+    target_id TEXT NOT NULL,
+    PRIMARY KEY (entry_id, target_id)
+);
+CREATE INDEX IF NOT EXISTS audit_log_targets_target_id ON audit_log_targets (target_id);
@@ -0,0 +1,24 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS roles (
+    name TEXT NOT NULL PRIMARY KEY
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role TEXT NOT NULL REFERENCES roles (name) ON DELETE CASCADE,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (role, permission)
+);
+
+INSERT OR IGNORE INTO roles (name) VALUES ('Admin'), ('User'), ('Support');
+
+INSERT OR IGNORE INTO role_permissions (role, permission) VALUES
+    ('Admin', 'users:read'),
+    ('Admin', 'users:update'),
+    ('Admin', 'users:delete'),
+    ('Admin', 'signup:read'),
+    ('Admin', 'signup:manage'),
+    ('Admin', 'audit_log:read'),
+    ('Admin', 'roles:manage'),
+    ('Support', 'users:read'),
+    ('Support', 'signup:read'),
+    ('Support', 'audit_log:read');
@@ -0,0 +1,4 @@
+-- Add migration script here
+-- Full serialized state, the legacy columns can not represent failures
+ALTER TABLE signup_process_states ADD COLUMN payload TEXT;
+ALTER TABLE signup_process_states ADD COLUMN payload_version INTEGER;
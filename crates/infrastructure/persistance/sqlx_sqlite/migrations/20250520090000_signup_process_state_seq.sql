@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE signup_process_states ADD COLUMN seq INTEGER NOT NULL DEFAULT 0;
+-- number existing states of each process in insertion order
+UPDATE signup_process_states SET seq = (
+    SELECT COUNT(*) FROM signup_process_states AS earlier
+    WHERE earlier.id = signup_process_states.id
+        AND earlier.rowid < signup_process_states.rowid
+);
+CREATE UNIQUE INDEX IF NOT EXISTS signup_process_states_id_seq ON signup_process_states (id, seq);
@@ -0,0 +1,6 @@
+-- Add migration script here
+-- purged signup processes leave only their id behind
+CREATE TABLE IF NOT EXISTS signup_process_tombstones (
+    id TEXT PRIMARY KEY NOT NULL,
+    purged_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- looked up when a signup is initialized for an email
+CREATE INDEX IF NOT EXISTS signup_process_states_email ON signup_process_states (email);
@@ -0,0 +1,9 @@
+-- Add migration script here
+-- names are made unique through their canonical form, which is filled in on
+-- startup. A name clashing with an older row gets part of the id appended
+-- until it is free, the renamed accounts are kept so their owners can be told.
+CREATE TABLE IF NOT EXISTS users_renamed (
+    user_id TEXT NOT NULL PRIMARY KEY,
+    old_name TEXT NOT NULL,
+    new_name TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Add migration script here
+-- canonical forms emails and names are compared by, written on save and
+-- filled in for older rows on startup since they are computed in Rust
+ALTER TABLE users ADD COLUMN email_canonical TEXT;
+ALTER TABLE users ADD COLUMN name_canonical TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_canonical ON users (email_canonical);
+CREATE UNIQUE INDEX IF NOT EXISTS users_name_canonical ON users (name_canonical);
+ALTER TABLE signup_process_states ADD COLUMN email_canonical TEXT;
+CREATE INDEX IF NOT EXISTS signup_process_states_email_canonical ON signup_process_states (email_canonical);
@@ -0,0 +1,7 @@
+-- Add migration script here
+-- the signup history no longer keeps passwords, they live in users only
+UPDATE signup_process_states SET password = NULL WHERE password IS NOT NULL;
+-- a password serializes as a [secret, marker] pair, only the secret goes
+UPDATE signup_process_states
+SET payload = json_set(payload, '$.Completed.password[0]', '[REDACTED]')
+WHERE json_type(payload, '$.Completed.password') = 'array';
@@ -0,0 +1,12 @@
+-- Add migration script here
+-- tokens are kept as hashes bound to what they were issued for, the plain
+-- ones are hashed and scoped on startup and the old table dropped after
+ALTER TABLE tokens RENAME TO tokens_plain;
+CREATE TABLE tokens (
+    token_hash TEXT NOT NULL PRIMARY KEY,
+    purpose TEXT NOT NULL,
+    process_id TEXT NOT NULL,
+    email TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS tokens_scope ON tokens (purpose, process_id);
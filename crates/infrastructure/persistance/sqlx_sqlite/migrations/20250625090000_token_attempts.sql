@@ -0,0 +1,9 @@
+-- Add migration script here
+-- failed verifications per scope, they lock a process once there are too many
+CREATE TABLE IF NOT EXISTS token_attempts (
+    purpose TEXT NOT NULL,
+    process_id TEXT NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (purpose, process_id)
+);
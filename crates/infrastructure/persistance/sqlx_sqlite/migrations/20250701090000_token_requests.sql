@@ -0,0 +1,9 @@
+-- Add migration script here
+-- tokens requested per scope within the current window, they rate limit login links
+CREATE TABLE IF NOT EXISTS token_requests (
+    purpose TEXT NOT NULL,
+    process_id TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    window_started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (purpose, process_id)
+);
@@ -0,0 +1,14 @@
+-- Add migration script here
+-- TOTP second factor of a user, the secret is encrypted by the repo
+CREATE TABLE IF NOT EXISTS user_mfa (
+    user_id TEXT NOT NULL PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    secret_ciphertext BLOB NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+    last_used_step INTEGER
+);
+-- single use recovery codes, stored as hashes only
+CREATE TABLE IF NOT EXISTS user_mfa_recovery_codes (
+    user_id TEXT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    PRIMARY KEY (user_id, code_hash)
+);
@@ -0,0 +1,19 @@
+-- Add migration script here
+-- accounts of users at OpenID Connect providers, the subject is unique per provider
+CREATE TABLE IF NOT EXISTS user_identities (
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id TEXT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (provider, subject)
+);
+CREATE INDEX IF NOT EXISTS user_identities_user_id ON user_identities (user_id);
+-- logins started at a provider, kept by a hash of their state until completed
+CREATE TABLE IF NOT EXISTS external_logins (
+    state_hash TEXT NOT NULL PRIMARY KEY,
+    provider TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    code_verifier_ciphertext BLOB NOT NULL,
+    link_user_id TEXT REFERENCES users (id) ON DELETE CASCADE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,29 @@
+-- Add migration script here
+-- the verification email state remembers when its email went out, rows
+-- written so far entered the state when it was sent
+UPDATE signup_process_states
+SET payload = json_set(
+    payload,
+    '$.VerificationEmailSent.sent_at',
+    strftime('%Y-%m-%dT%H:%M:%SZ', entered_at)
+)
+WHERE json_type(payload, '$.VerificationEmailSent') = 'object';
+-- failed states take the time of the state they failed in
+UPDATE signup_process_states
+SET payload = json_set(
+    payload,
+    '$.Failed.previous_state.VerificationEmailSent.sent_at',
+    COALESCE(
+        (
+            SELECT strftime('%Y-%m-%dT%H:%M:%SZ', sent.entered_at)
+            FROM signup_process_states AS sent
+            WHERE sent.id = signup_process_states.id
+                AND sent.seq < signup_process_states.seq
+                AND sent.state = 'VerificationEmailSent'
+            ORDER BY sent.seq DESC
+            LIMIT 1
+        ),
+        strftime('%Y-%m-%dT%H:%M:%SZ', entered_at)
+    )
+)
+WHERE json_type(payload, '$.Failed.previous_state.VerificationEmailSent') = 'object';
@@ -0,0 +1,244 @@
+use ca_application::gateway::database::{
+    signup_process::StateKind,
+    token::{Purpose, Scope},
+};
+use ca_domain::entity::user::{Email, UserName};
+use sqlx::{Pool, Sqlite};
+
+use crate::{models::signup_process_state::state_name, repositories::token::token_hash};
+
+/// Brings rows written before a migration in line with it where the new
+/// columns are computed in Rust
+pub(crate) async fn backfill(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    canonical_forms(pool).await?;
+    hashed_tokens(pool).await
+}
+
+/// Fills in the canonical columns of rows written before they existed. Users
+/// are filled in oldest first, so of two clashing names the older one is
+/// kept and the other user is renamed, see [`rename_user`], once every other
+/// name has its canonical form. A clash on the email keeps a `NULL` column
+/// and stays matched by its exact value.
+async fn canonical_forms(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    let users: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT id, email, name FROM users \
+        WHERE email_canonical IS NULL OR name_canonical IS NULL ORDER BY rowid",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut clashing_names = Vec::new();
+    for (id, email, name) in users {
+        let columns = [
+            ("email_canonical", Email::new_unchecked(email).canonical()),
+            (
+                "name_canonical",
+                UserName::new_unchecked(name.as_str()).canonical(),
+            ),
+        ];
+        // one column at a time so a clash on one still fills the other
+        for (column, canonical) in columns {
+            let sql = format!("UPDATE users SET {column} = ? WHERE id = ? AND {column} IS NULL");
+            match sqlx::query(&sql)
+                .bind(canonical)
+                .bind(&id)
+                .execute(pool)
+                .await
+            {
+                Ok(_) => {}
+                Err(sqlx::Error::Database(err))
+                    if err.is_unique_violation() && column == "name_canonical" =>
+                {
+                    clashing_names.push((id.clone(), name.clone()));
+                }
+                Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                    log::warn!("User {id} clashes with another user on {column}");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+    for (id, name) in clashing_names {
+        rename_user(pool, &id, &name).await?;
+    }
+
+    let emails: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT email FROM signup_process_states \
+        WHERE email IS NOT NULL AND email_canonical IS NULL",
+    )
+    .fetch_all(pool)
+    .await?;
+    for email in emails {
+        sqlx::query(
+            "UPDATE signup_process_states SET email_canonical = ? \
+            WHERE email = ? AND email_canonical IS NULL",
+        )
+        .bind(Email::new_unchecked(email.as_str()).canonical())
+        .bind(email)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Gives a user whose name is taken the name `<name>_<id>` and records the
+/// rename in `users_renamed` so its owner can be told. The part of the id
+/// grows until the name is free, the name is cut to keep the result within
+/// the maximum length.
+async fn rename_user(pool: &Pool<Sqlite>, id: &str, name: &str) -> Result<(), sqlx::Error> {
+    let max_len = UserName::max_len();
+    let mut tx = pool.begin().await?;
+    let mut renamed = None;
+    for id_len in 8..=id.len().min(max_len - 2) {
+        let kept: String = name.chars().take(max_len - 1 - id_len).collect();
+        let new_name = format!("{kept}_{}", &id[..id_len]);
+        let res = sqlx::query("UPDATE users SET name = ?, name_canonical = ? WHERE id = ?")
+            .bind(&new_name)
+            .bind(UserName::new_unchecked(new_name.as_str()).canonical())
+            .bind(id)
+            .execute(&mut *tx)
+            .await;
+        match res {
+            Ok(_) => {
+                renamed = Some(new_name);
+                break;
+            }
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {}
+            Err(err) => return Err(err),
+        }
+    }
+    let Some(new_name) = renamed else {
+        log::warn!("User {id} clashes with another user on its name and could not be renamed");
+        return Ok(());
+    };
+    sqlx::query("INSERT INTO users_renamed (user_id, old_name, new_name) VALUES (?, ?, ?)")
+        .bind(id)
+        .bind(name)
+        .bind(&new_name)
+        .execute(&mut *tx)
+        .await?;
+    log::warn!("User {id} clashes with another user on its name and was renamed to {new_name}");
+    tx.commit().await
+}
+
+/// Hashes the plain tokens left from before tokens were scoped and drops
+/// their table. A token is scoped to the signup process of its email that
+/// still waits for verification, tokens without one could not verify
+/// anything anymore and are dropped as well.
+async fn hashed_tokens(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    let exists: Option<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'tokens_plain'",
+    )
+    .fetch_optional(pool)
+    .await?;
+    if exists.is_none() {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    let tokens: Vec<(String, String, String)> =
+        sqlx::query_as("SELECT token, email, created_at FROM tokens_plain")
+            .fetch_all(&mut *tx)
+            .await?;
+    for (token, email, created_at) in tokens {
+        let canonical = Email::new_unchecked(email).canonical();
+        let process_id: Option<String> = sqlx::query_scalar(
+            // a failed state carries no email, it is found through the
+            // earlier states of the same process
+            "SELECT s.id FROM signup_process_states s \
+            JOIN signup_process_states e ON e.id = s.id \
+            WHERE e.email_canonical = ?1 AND s.state IN (?2, ?3) \
+            AND s.seq = (SELECT MAX(seq) FROM signup_process_states WHERE id = s.id) \
+            ORDER BY s.entered_at DESC LIMIT 1",
+        )
+        .bind(&canonical)
+        .bind(state_name(StateKind::VerificationEmailSent))
+        .bind(state_name(StateKind::Failed))
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(process_id) = process_id else {
+            log::warn!("Dropping a token without a signup process waiting for it");
+            continue;
+        };
+        let scope = Scope::new(Purpose::Signup, process_id);
+        sqlx::query(
+            "INSERT OR REPLACE INTO tokens (token_hash, purpose, process_id, email, created_at) \
+            VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(token_hash(&scope, &token))
+        .bind(scope.purpose.to_string())
+        .bind(&scope.process_id)
+        .bind(canonical)
+        .bind(created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query("DROP TABLE tokens_plain")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> Pool<Sqlite> {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        for sql in [
+            "CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT NOT NULL, email TEXT NOT NULL, \
+            name_canonical TEXT, email_canonical TEXT)",
+            "CREATE UNIQUE INDEX users_name_canonical ON users (name_canonical)",
+            "CREATE UNIQUE INDEX users_email_canonical ON users (email_canonical)",
+            "CREATE TABLE users_renamed (user_id TEXT NOT NULL PRIMARY KEY, \
+            old_name TEXT NOT NULL, new_name TEXT NOT NULL)",
+            "CREATE TABLE signup_process_states (email TEXT, email_canonical TEXT)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn clashing_names_are_renamed_and_recorded() {
+        let pool = pool().await;
+        let ids = [
+            "aaaaaaaa-0000-0000-0000-000000000001",
+            "aaaaaaaa-0000-0000-0000-000000000002",
+            "aaaaaaaa-0000-0000-0000-000000000003",
+        ];
+        // the name the first rename would pick is taken already
+        let names = ["Test_User", "test_user", "test_user_aaaaaaaa"];
+        for (i, (id, name)) in ids.iter().zip(names).enumerate() {
+            sqlx::query("INSERT INTO users (id, name, email) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(name)
+                .bind(format!("user{i}@test.com"))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        canonical_forms(&pool).await.unwrap();
+        let users: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT name, name_canonical FROM users ORDER BY rowid")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(users[0].0, "Test_User");
+        assert_eq!(users[1].0, "test_user_aaaaaaaa-");
+        assert_eq!(users[2].0, "test_user_aaaaaaaa");
+        assert!(users.iter().all(|(_, canonical)| canonical.is_some()));
+        let renamed: Vec<(String, String, String)> =
+            sqlx::query_as("SELECT user_id, old_name, new_name FROM users_renamed")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            renamed,
+            [(
+                ids[1].to_string(),
+                "test_user".to_string(),
+                "test_user_aaaaaaaa-".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::{fmt, fs, io, path::Path};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+/// Key the repos encrypt secrets at rest with, it never shows up in logs
+#[derive(Clone)]
+pub struct SecretKey([u8; KEY_LEN]);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSecretKeyError;
+
+impl fmt::Display for ParseSecretKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "secret key must be {} hex encoded bytes", KEY_LEN)
+    }
+}
+
+impl std::error::Error for ParseSecretKeyError {}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+impl SecretKey {
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Self, ParseSecretKeyError> {
+        let hex = hex.trim();
+        if hex.len() != KEY_LEN * 2 || !hex.is_ascii() {
+            return Err(ParseSecretKeyError);
+        }
+        let mut key = [0u8; KEY_LEN];
+        for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| ParseSecretKeyError)?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| ParseSecretKeyError)?;
+        }
+        Ok(Self(key))
+    }
+
+    fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Reads the key stored at `path`, a new key is generated and stored
+    /// there on first start, so restarts can still decrypt
+    pub fn load_or_create(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(hex) => {
+                Self::from_hex(&hex).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let key = Self::generate();
+                let mut options = fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+                io::Write::write_all(&mut options.open(path)?, key.to_hex().as_bytes())?;
+                Ok(key)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Random nonce followed by the ciphertext, `context` binds it to its
+    /// row so it can not be moved to another one
+    pub(crate) fn encrypt(&self, plaintext: &[u8], context: &[u8]) -> Vec<u8> {
+        let cipher = XChaCha20Poly1305::new(&self.0.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: context,
+                },
+            )
+            .expect("encrypting in memory does not fail");
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        sealed
+    }
+
+    /// Fails on a wrong key, `context` or tampered data
+    pub(crate) fn decrypt(&self, sealed: &[u8], context: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        XChaCha20Poly1305::new(&self.0.into())
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: context,
+                },
+            )
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = SecretKey::generate();
+        let sealed = key.encrypt(b"JBSWY3DPEHPK3PXP", b"user-1");
+        assert_eq!(
+            key.decrypt(&sealed, b"user-1").as_deref(),
+            Some(&b"JBSWY3DPEHPK3PXP"[..])
+        );
+        // bound to its row and key
+        assert_eq!(key.decrypt(&sealed, b"user-2"), None);
+        assert_eq!(SecretKey::generate().decrypt(&sealed, b"user-1"), None);
+        // the nonce makes equal secrets look different
+        assert_ne!(sealed, key.encrypt(b"JBSWY3DPEHPK3PXP", b"user-1"));
+    }
+
+    #[test]
+    fn test_hex() {
+        let key = SecretKey::generate();
+        assert_eq!(SecretKey::from_hex(&key.to_hex()).unwrap().0, key.0);
+        assert!(SecretKey::from_hex("abcd").is_err());
+        assert!(SecretKey::from_hex(&"zz".repeat(KEY_LEN)).is_err());
+        assert_eq!(format!("{key:?}"), "SecretKey(..)");
+    }
+}
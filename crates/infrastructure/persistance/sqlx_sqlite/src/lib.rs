@@ -6,18 +6,28 @@ use ca_application::gateway::database::{
 use ca_domain::{entity::signup_process::SignupProcessValue, value_object::Id};
 use sqlx::{migrate::MigrateDatabase, Pool, Sqlite, SqlitePool};
 
+pub use cipher::{ParseSecretKeyError, SecretKey};
+
+mod backfill;
+mod cipher;
 mod models;
 mod repositories;
 
 #[derive(Debug, Clone)]
 pub struct SqlxSqlite {
     pool: Pool<Sqlite>,
+    secret_key: SecretKey,
 }
 
 pub type SqlxSqliteTransaction = sqlx::Transaction<'static, Sqlite>;
 
 impl SqlxSqlite {
-    pub async fn try_new(folder: &str) -> Result<Self, sqlx::Error> {
+    /// Opens the database in `folder`. Secrets at rest are encrypted with
+    /// `secret_key`, without one a key stored next to the database is used
+    /// and created on first start. That fallback is meant for development
+    /// and tests only: whoever can read the database can read the key too,
+    /// so it protects nothing and a warning is printed on every start.
+    pub async fn try_new(folder: &str, secret_key: Option<SecretKey>) -> Result<Self, sqlx::Error> {
         let db_url = format!("sqlite://{}/sqlite.db", folder);
         if !Sqlite::database_exists(&db_url).await.unwrap_or(false) {
             println!("Creating database {}", &db_url);
@@ -46,12 +56,30 @@ impl SqlxSqlite {
                 panic!("error: {}", error);
             }
         }
+        backfill::backfill(&pool).await?;
+        let secret_key = match secret_key {
+            Some(secret_key) => secret_key,
+            None => {
+                let path = std::path::Path::new(folder).join("secret.key");
+                log::warn!("No secret key given, using {}", path.display());
+                eprintln!(
+                    "WARNING: secrets at rest are encrypted with the key stored next to the \
+                    database at {}, which does not protect them. Set the secret key through \
+                    the environment outside of development.",
+                    path.display()
+                );
+                SecretKey::load_or_create(&path)?
+            }
+        };
 
-        Ok(Self { pool })
+        Ok(Self { pool, secret_key })
     }
     pub fn pool(&self) -> &Pool<Sqlite> {
         &self.pool
     }
+    pub(crate) fn secret_key(&self) -> &SecretKey {
+        &self.secret_key
+    }
     pub fn new_id_inner(&self) -> Result<uuid::Uuid, NewIdError> {
         Ok(uuid::Uuid::new_v4())
     }
@@ -100,4 +128,28 @@ impl Database for &SqlxSqlite {
     fn token_repo(&self) -> impl database::token::Repo<Transaction = Self::Transaction> {
         *self
     }
+
+    fn audit_log_repo(&self) -> impl database::audit_log::Repo<Transaction = Self::Transaction> {
+        *self
+    }
+
+    fn role_repo(&self) -> impl database::role::Repo<Transaction = Self::Transaction> {
+        *self
+    }
+
+    fn mfa_repo(&self) -> impl database::mfa::Repo<Transaction = Self::Transaction> {
+        *self
+    }
+
+    fn user_identity_repo(
+        &self,
+    ) -> impl database::user_identity::Repo<Transaction = Self::Transaction> {
+        *self
+    }
+
+    fn external_login_repo(
+        &self,
+    ) -> impl database::external_login::Repo<Transaction = Self::Transaction> {
+        *self
+    }
 }
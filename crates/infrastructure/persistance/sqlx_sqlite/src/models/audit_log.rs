@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use ca_application::gateway::database::audit_log::{Outcome, Record};
+use ca_domain::entity::user::Id as UserId;
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditLogEntry {
+    pub actor_id: Option<String>,
+    pub actor_role: Option<String>,
+    pub usecase: String,
+    // comma separated, aggregated from audit_log_targets
+    pub target_ids: Option<String>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub request_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<&Record> for AuditLogEntry {
+    fn from(record: &Record) -> Self {
+        let (outcome, error) = match &record.outcome {
+            Outcome::Success => ("Success", None),
+            Outcome::Failure(error) => ("Failure", Some(error.clone())),
+            Outcome::Denied => ("Denied", None),
+        };
+        Self {
+            actor_id: record.actor_id.map(|id| id.to_string()),
+            actor_role: record.actor_role.as_ref().map(|role| role.to_string()),
+            usecase: record.usecase.clone(),
+            target_ids: None,
+            outcome: outcome.to_string(),
+            error,
+            request_id: record.request_id.clone(),
+            client_ip: record.client_ip.clone(),
+            occurred_at: record.occurred_at,
+        }
+    }
+}
+
+impl From<AuditLogEntry> for Record {
+    fn from(entry: AuditLogEntry) -> Self {
+        let outcome = match entry.outcome.as_str() {
+            "Success" => Outcome::Success,
+            "Denied" => Outcome::Denied,
+            _ => Outcome::Failure(entry.error.unwrap_or_default()),
+        };
+        Record {
+            actor_id: entry
+                .actor_id
+                .and_then(|id| Uuid::from_str(&id).ok())
+                .map(UserId::from),
+            actor_role: entry.actor_role.and_then(|role| role.parse().ok()),
+            usecase: entry.usecase,
+            target_ids: entry
+                .target_ids
+                .map(|ids| ids.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            outcome,
+            request_id: entry.request_id,
+            client_ip: entry.client_ip,
+            occurred_at: entry.occurred_at,
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use chrono::NaiveDateTime;
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ExternalLogin {
+    pub provider: String,
+    pub nonce: String,
+    // nonce and ciphertext, see `SecretKey::encrypt`
+    pub code_verifier_ciphertext: Vec<u8>,
+    pub link_user_id: Option<String>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,10 @@
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserMfa {
+    pub user_id: String,
+    // nonce and ciphertext, see `SecretKey::encrypt`
+    pub secret_ciphertext: Vec<u8>,
+    pub confirmed: bool,
+    pub last_used_step: Option<i64>,
+}
@@ -1,2 +1,7 @@
+pub mod audit_log;
+pub mod external_login;
+pub mod mfa;
+pub mod role;
 pub mod signup_process_state;
 pub mod user;
+pub mod user_identity;
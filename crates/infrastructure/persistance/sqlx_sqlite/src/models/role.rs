@@ -0,0 +1,28 @@
+use ca_application::gateway::database::role::Record;
+use ca_domain::value_object::{Permission, Role as DomainRole};
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Role {
+    pub name: String,
+    // comma separated, aggregated from role_permissions
+    pub permissions: Option<String>,
+}
+
+impl From<Role> for Record {
+    fn from(role: Role) -> Self {
+        Record {
+            role: DomainRole::new(role.name),
+            permissions: role
+                .permissions
+                .map(|permissions| {
+                    permissions
+                        .split(',')
+                        .filter(|permission| !permission.is_empty())
+                        .map(Permission::new)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
@@ -2,139 +2,406 @@ use std::{str::FromStr, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use sqlx::prelude::FromRow;
+use thiserror::Error;
 
-use ca_application::gateway::database::signup_process::Record;
+use ca_application::gateway::database::signup_process::{Record, StateKind};
 use ca_domain::{
     entity::{
-        signup_process::{Error as SignupError, Id, SignupStateEnum},
+        signup_process::{Error as SignupError, Id, SignupStateEnum, UnknownError},
         user::{Email, Password},
     },
-    value_object::UserName,
+    value_object::{UserName, REDACTED},
 };
 
-// NOTE:
+/// Version of the serialized `payload` column written by this build,
+/// version 2 added `sent_at` to the verification email state
+pub const CURRENT_PAYLOAD_VERSION: i64 = 2;
+
+// NOTE: the legacy columns are still written so older rows and ad-hoc
+// queries keep working, but `payload` is the source of truth when present.
+// The password of a completed signup lives in `users` only, the history
+// keeps it redacted.
 
 #[derive(Debug, Clone, FromRow)]
 pub struct SignupProcessState {
     #[sqlx(rename = "id")]
     pub signup_id: String, // non null not unique
+    pub seq: i64, // unique together with id
     pub username: Option<String>,
     pub email: Option<String>,
     pub password: Option<String>,
     pub entered_at: DateTime<Utc>,
     pub state: String,
     pub error: Option<String>,
+    pub payload: Option<String>,
+    pub payload_version: Option<i64>,
+}
+
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("Invalid signup process id {0:?}")]
+    InvalidId(String),
+    #[error("Invalid signup process state sequence number {0}")]
+    InvalidSeq(i64),
+    #[error("Unknown signup process state {0:?}")]
+    UnknownState(String),
+    #[error("Signup process state {state} is missing {field}")]
+    MissingField { state: String, field: &'static str },
+    #[error(transparent)]
+    UnknownError(#[from] UnknownError),
+    #[error("Failed signup process state has no previous state")]
+    MissingPreviousState,
+    #[error("Unsupported signup process payload version {0}")]
+    UnsupportedVersion(i64),
+    #[error("Invalid signup process payload: {0}")]
+    Payload(#[from] serde_json::Error),
+}
+
+/// Value of the `state` column for processes in the given state
+pub fn state_name(kind: StateKind) -> &'static str {
+    match kind {
+        StateKind::Initialized => "Initialized",
+        StateKind::VerificationEmailSent => "VerificationEmailSent",
+        StateKind::EmailVerified => "EmailVerified",
+        StateKind::Completed => "Completed",
+        StateKind::ForDeletion => "ForDeletion",
+        StateKind::Failed => "Failed",
+    }
 }
 
 impl From<Record> for SignupProcessState {
     fn from(record: Record) -> Self {
-        match record.state {
-            SignupStateEnum::Initialized { email } => SignupProcessState {
-                signup_id: record.id.to_string(),
-                username: None,
-                email: Some(email.to_string()),
-                password: None,
-                entered_at: record.entered_at,
-                state: "Initialized".to_string(),
-                error: None,
-            },
-            SignupStateEnum::VerificationEmailSent { email } => SignupProcessState {
-                signup_id: record.id.to_string(),
-                username: None,
-                email: Some(email.to_string()),
-                password: None,
-                entered_at: record.entered_at,
-                state: "VerificationEmailSent".to_string(),
-                error: None,
+        let (state, email, username, password, error) = match &record.state {
+            SignupStateEnum::Initialized { email } => {
+                ("Initialized", Some(email.to_string()), None, None, None)
+            }
+            SignupStateEnum::VerificationEmailSent { email, .. } => (
+                "VerificationEmailSent",
+                Some(email.to_string()),
+                None,
+                None,
+                None,
+            ),
+            SignupStateEnum::EmailVerified { email, .. } => {
+                ("EmailVerified", Some(email.to_string()), None, None, None)
+            }
+            SignupStateEnum::Completed {
+                email, username, ..
+            } => (
+                "Completed",
+                Some(email.to_string()),
+                Some(username.to_string()),
+                None,
+                None,
+            ),
+            SignupStateEnum::ForDeletion => ("ForDeletion", None, None, None, None),
+            SignupStateEnum::Failed { error, .. } => {
+                ("Failed", None, None, None, Some(error.to_string()))
+            }
+        };
+        SignupProcessState {
+            signup_id: record.id.to_string(),
+            seq: record.seq as i64,
+            username,
+            email,
+            password,
+            entered_at: record.entered_at,
+            state: state.to_string(),
+            error,
+            // plain data with string keys, serializing it can not fail
+            payload: Some(
+                serde_json::to_string(&record.state).expect("signup state is serializable"),
+            ),
+            payload_version: Some(CURRENT_PAYLOAD_VERSION),
+        }
+    }
+}
+
+impl SignupProcessState {
+    fn field<'a>(
+        &self,
+        value: &'a Option<String>,
+        field: &'static str,
+    ) -> Result<&'a String, DecodeError> {
+        value.as_ref().ok_or_else(|| DecodeError::MissingField {
+            state: self.state.clone(),
+            field,
+        })
+    }
+
+    /// Decodes the state, `prev_state` is only used for legacy `Failed`
+    /// rows written before the payload column existed.
+    fn decode(&self, prev_state: Option<&SignupStateEnum>) -> Result<SignupStateEnum, DecodeError> {
+        match (self.payload_version, &self.payload) {
+            (Some(CURRENT_PAYLOAD_VERSION), Some(payload)) => {
+                return Ok(serde_json::from_str(payload)?)
+            }
+            (Some(1), Some(payload)) => {
+                let mut payload = serde_json::from_str(payload)?;
+                self.fill_sent_at(&mut payload, prev_state);
+                return Ok(serde_json::from_value(payload)?);
+            }
+            (Some(version), _) if version != CURRENT_PAYLOAD_VERSION => {
+                return Err(DecodeError::UnsupportedVersion(version))
+            }
+            _ => {}
+        }
+        Ok(match self.state.as_str() {
+            "Initialized" => SignupStateEnum::Initialized {
+                email: Email::new_unchecked(self.field(&self.email, "email")?),
             },
-            SignupStateEnum::EmailVerified { email } => SignupProcessState {
-                signup_id: record.id.to_string(),
-                username: None,
-                email: Some(email.to_string()),
-                password: None,
-                entered_at: record.entered_at,
-                state: "EmailVerified".to_string(),
-                error: None,
+            // legacy rows were written when the email went out
+            "VerificationEmailSent" => SignupStateEnum::VerificationEmailSent {
+                email: Email::new_unchecked(self.field(&self.email, "email")?),
+                sent_at: self.entered_at,
+                resends: 0,
+                extensions: 0,
             },
-            SignupStateEnum::Completed {
-                email,
-                username,
-                password,
-            } => SignupProcessState {
-                signup_id: record.id.to_string(),
-                username: Some(username.to_string()),
-                email: Some(email.to_string()),
-                password: Some(password.to_string()),
-                entered_at: record.entered_at,
-                state: "Completed".to_string(),
-                error: None,
+            "EmailVerified" => SignupStateEnum::EmailVerified {
+                email: Email::new_unchecked(self.field(&self.email, "email")?),
+                extensions: 0,
             },
-            SignupStateEnum::ForDeletion => SignupProcessState {
-                signup_id: record.id.to_string(),
-                username: None,
-                email: None,
-                password: None,
-                entered_at: record.entered_at,
-                state: "ForDeletion".to_string(),
-                error: None,
+            "Completed" => SignupStateEnum::Completed {
+                email: Email::new_unchecked(self.field(&self.email, "email")?),
+                username: UserName::new_unchecked(self.field(&self.username, "username")?),
+                password: Password::new_unchecked(REDACTED),
             },
-            SignupStateEnum::Failed {
-                #[allow(unused_variables)]
-                previous_state,
-                error,
-            } => SignupProcessState {
-                signup_id: record.id.to_string(),
-                username: None,
-                email: None,
-                password: None,
-                entered_at: record.entered_at,
-                state: "Failed".to_string(),
-                error: Some(error.to_string()),
+            "ForDeletion" => SignupStateEnum::ForDeletion,
+            "Failed" => SignupStateEnum::Failed {
+                previous_state: Arc::new(
+                    prev_state
+                        .cloned()
+                        .ok_or(DecodeError::MissingPreviousState)?,
+                ),
+                error: SignupError::from_str(self.field(&self.error, "error")?)?,
             },
-        }
+            state => return Err(DecodeError::UnknownState(state.to_string())),
+        })
     }
 }
 
-fn from_proces_and_prev(
-    (value, prev_state): (&SignupProcessState, &Option<SignupStateEnum>),
-) -> SignupStateEnum {
-    match value.state.as_str() {
-        "Initialized" => SignupStateEnum::Initialized {
-            email: Email::new(value.email.as_ref().unwrap()),
-        },
-        "VerificationEmailSent" => SignupStateEnum::VerificationEmailSent {
-            email: Email::new(value.email.as_ref().unwrap()),
-        },
-        "EmailVerified" => SignupStateEnum::EmailVerified {
-            email: Email::new(value.email.as_ref().unwrap()),
-        },
-        "Completed" => SignupStateEnum::Completed {
-            email: Email::new(value.email.as_ref().unwrap()),
-            username: UserName::new(value.username.as_ref().unwrap()),
-            password: Password::new(value.password.as_ref().unwrap()),
-        },
-        "ForDeletion" => SignupStateEnum::ForDeletion,
-        "Failed" => SignupStateEnum::Failed {
-            // temp previous state
-            previous_state: Arc::new(prev_state.clone().unwrap()),
-            error: SignupError::from_str(value.error.as_ref().unwrap()).unwrap(),
-        },
-        _ => panic!("Invalid state"),
+impl SignupProcessState {
+    /// Version 1 payloads may lack `sent_at`, the email went out when the
+    /// state was entered, the same as for legacy rows. A failed state takes
+    /// the time of the earlier row it failed in.
+    fn fill_sent_at(&self, payload: &mut serde_json::Value, prev_state: Option<&SignupStateEnum>) {
+        let earlier_sent_at = match prev_state {
+            Some(SignupStateEnum::VerificationEmailSent { sent_at, .. }) => *sent_at,
+            _ => self.entered_at,
+        };
+        let mut sent_at = self.entered_at;
+        let mut state = Some(payload);
+        while let Some(value) = state {
+            if let Some(sent) = value
+                .get_mut("VerificationEmailSent")
+                .and_then(serde_json::Value::as_object_mut)
+            {
+                sent.entry("sent_at")
+                    .or_insert_with(|| serde_json::json!(sent_at));
+            }
+            sent_at = earlier_sent_at;
+            state = value.pointer_mut("/Failed/previous_state");
+        }
     }
 }
 
-pub fn from_chain(chain: Vec<SignupProcessState>) -> Vec<Record> {
+pub fn from_chain(chain: Vec<SignupProcessState>) -> Result<Vec<Record>, DecodeError> {
     let mut previous: Option<SignupStateEnum> = None;
     chain
         .into_iter()
         .map(|process| {
-            let state = from_proces_and_prev((&process, &previous));
+            let state = process.decode(previous.as_ref())?;
             previous = Some(state.clone());
-            Record {
-                id: Id::from(uuid::Uuid::from_str(&process.signup_id).unwrap()),
-                state: state.clone(),
+            Ok(Record {
+                id: Id::from(
+                    uuid::Uuid::from_str(&process.signup_id)
+                        .map_err(|_| DecodeError::InvalidId(process.signup_id.clone()))?,
+                ),
+                seq: u64::try_from(process.seq)
+                    .map_err(|_| DecodeError::InvalidSeq(process.seq))?,
+                state,
                 entered_at: process.entered_at,
-            }
+            })
         })
-        .collect::<Vec<_>>()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(state: SignupStateEnum) -> Record {
+        Record {
+            id: Id::from(uuid::Uuid::new_v4()),
+            seq: 0,
+            state,
+            entered_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn nested_failure_round_trips() {
+        let email = Email::new_unchecked("test@test.com");
+        let state = SignupStateEnum::Failed {
+            previous_state: Arc::new(SignupStateEnum::Failed {
+                previous_state: Arc::new(SignupStateEnum::VerificationEmailSent {
+                    email: email.clone(),
+                    sent_at: Utc::now(),
+                    resends: 0,
+                    extensions: 0,
+                }),
+                error: SignupError::VerificationEmailSendError,
+            }),
+            error: SignupError::TokenExpired,
+        };
+        let record = record(state);
+        // no earlier rows, the previous state has to come from the payload
+        let decoded = from_chain(vec![SignupProcessState::from(record.clone())]).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded[0].state).unwrap(),
+            serde_json::to_value(&record.state).unwrap()
+        );
+    }
+
+    #[test]
+    fn completed_keeps_no_password() {
+        let row = SignupProcessState::from(record(SignupStateEnum::Completed {
+            email: Email::new_unchecked("test@test.com"),
+            username: UserName::new_unchecked("test_user"),
+            password: Password::new_unchecked("hunter22"),
+        }));
+        assert_eq!(row.password, None);
+        assert!(!row.payload.as_ref().unwrap().contains("hunter22"));
+        for row in [
+            row.clone(),
+            SignupProcessState {
+                payload: None,
+                payload_version: None,
+                ..row
+            },
+        ] {
+            match &from_chain(vec![row]).unwrap()[0].state {
+                SignupStateEnum::Completed { password, .. } => {
+                    assert_eq!(password.expose_secret(), REDACTED)
+                }
+                state => panic!("unexpected state {state:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_failed_uses_previous_row() {
+        let email = Email::new_unchecked("test@test.com");
+        let first = record(SignupStateEnum::VerificationEmailSent {
+            email,
+            sent_at: Utc::now(),
+            resends: 0,
+            extensions: 0,
+        });
+        let mut rows = vec![SignupProcessState::from(first.clone())];
+        let mut failed = rows[0].clone();
+        failed.seq = 1;
+        failed.state = "Failed".to_string();
+        failed.email = None;
+        failed.error = Some("Token Expired".to_string());
+        rows.push(failed);
+        for row in rows.iter_mut() {
+            row.payload = None;
+            row.payload_version = None;
+        }
+        let decoded = from_chain(rows).unwrap();
+        match &decoded[1].state {
+            SignupStateEnum::Failed {
+                previous_state,
+                error,
+            } => {
+                assert_eq!(error, &SignupError::TokenExpired);
+                assert!(matches!(
+                    **previous_state,
+                    SignupStateEnum::VerificationEmailSent { .. }
+                ));
+            }
+            state => panic!("unexpected state {state:?}"),
+        }
+    }
+
+    #[test]
+    fn version_1_payload_without_sent_at() {
+        let sent_state = SignupStateEnum::VerificationEmailSent {
+            email: Email::new_unchecked("test@test.com"),
+            sent_at: Utc::now(),
+            resends: 0,
+            extensions: 0,
+        };
+        let failed_state = SignupStateEnum::Failed {
+            previous_state: Arc::new(sent_state.clone()),
+            error: SignupError::TooManyAttempts,
+        };
+        // rows as written before the verification email state had `sent_at`
+        let version_1 = |state: SignupStateEnum, seq: u64, pointer: &str| {
+            let mut row = SignupProcessState::from(Record {
+                seq,
+                ..record(state.clone())
+            });
+            let mut payload = serde_json::to_value(&state).unwrap();
+            payload
+                .pointer_mut(pointer)
+                .and_then(serde_json::Value::as_object_mut)
+                .unwrap()
+                .remove("sent_at");
+            row.payload = Some(payload.to_string());
+            row.payload_version = Some(1);
+            row
+        };
+        let sent = version_1(sent_state, 0, "/VerificationEmailSent");
+        let mut failed = version_1(
+            failed_state,
+            1,
+            "/Failed/previous_state/VerificationEmailSent",
+        );
+        failed.entered_at = sent.entered_at + chrono::Duration::minutes(5);
+        let sent_at = sent.entered_at;
+        let decoded = from_chain(vec![sent, failed]).unwrap();
+        let previous_state = match &decoded[1].state {
+            SignupStateEnum::Failed { previous_state, .. } => &**previous_state,
+            state => panic!("unexpected state {state:?}"),
+        };
+        for state in [&decoded[0].state, previous_state] {
+            match state {
+                SignupStateEnum::VerificationEmailSent {
+                    sent_at: actual, ..
+                } => assert_eq!(actual, &sent_at),
+                state => panic!("unexpected state {state:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn bad_rows_are_typed_errors() {
+        let mut row = SignupProcessState::from(record(SignupStateEnum::ForDeletion));
+        row.payload_version = Some(CURRENT_PAYLOAD_VERSION + 1);
+        assert!(matches!(
+            from_chain(vec![row.clone()]),
+            Err(DecodeError::UnsupportedVersion(_))
+        ));
+        row.payload_version = Some(CURRENT_PAYLOAD_VERSION);
+        row.payload = Some("{".to_string());
+        assert!(matches!(
+            from_chain(vec![row.clone()]),
+            Err(DecodeError::Payload(_))
+        ));
+        row.payload = None;
+        row.payload_version = None;
+        row.state = "Failed".to_string();
+        row.error = Some("Out of coffee".to_string());
+        assert!(matches!(
+            from_chain(vec![row.clone()]),
+            Err(DecodeError::MissingPreviousState)
+        ));
+        row.state = "Sleeping".to_string();
+        assert!(matches!(
+            from_chain(vec![row]),
+            Err(DecodeError::UnknownState(_))
+        ));
+    }
 }
@@ -1,9 +1,14 @@
 use ca_application::gateway::database::user::Record;
-use ca_domain::entity::user::{Email, Password, User as DomainUser, UserName};
+use std::fmt;
+
+use ca_domain::{
+    entity::user::{Email, Password, User as DomainUser, UserName},
+    value_object::REDACTED,
+};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Clone, FromRow)]
 pub struct User {
     id: String,
     #[sqlx(rename = "name")]
@@ -14,13 +19,26 @@ pub struct User {
     role: String,
 }
 
+// the stored password is kept out of logs like the domain password is
+impl fmt::Debug for User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("User")
+            .field("id", &self.id)
+            .field("username", &self.username)
+            .field("email", &self.email)
+            .field("password_hash", &REDACTED)
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
 impl From<Record> for User {
     fn from(record: Record) -> Self {
         Self {
             id: record.user.id().to_string(),
             username: record.user.username().to_string(),
             email: record.user.email().to_string(),
-            password_hash: record.user.password().to_string(),
+            password_hash: record.user.password().expose_secret().to_string(),
             role: record.user.role().to_string(),
         }
     }
@@ -30,12 +48,31 @@ impl From<User> for Record {
     fn from(user: User) -> Self {
         let id = Uuid::parse_str(&user.id).unwrap();
         let role = user.role.parse().unwrap();
-        let email = Email::new(user.email);
-        let username = UserName::new(user.username);
-        let password_hash = Password::new(user.password_hash);
+        let email = Email::new_unchecked(user.email);
+        let username = UserName::new_unchecked(user.username);
+        let password_hash = Password::new_unchecked(user.password_hash);
 
         Record {
             user: DomainUser::new(id.into(), role, email, username, password_hash),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_password() {
+        let user = User {
+            id: Uuid::nil().to_string(),
+            username: "test_user".to_string(),
+            email: "test@test.com".to_string(),
+            password_hash: "hunter22".to_string(),
+            role: "user".to_string(),
+        };
+        let debug = format!("{user:?}");
+        assert!(!debug.contains("hunter22"));
+        assert!(debug.contains(REDACTED));
+    }
+}
@@ -0,0 +1,8 @@
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub user_id: String,
+}
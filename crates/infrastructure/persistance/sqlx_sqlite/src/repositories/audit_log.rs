@@ -0,0 +1,92 @@
+use ca_application::gateway::database::audit_log::{Filter, QueryError, Record, Repo, SaveError};
+
+use crate::{models::audit_log::AuditLogEntry, SqlxSqlite, SqlxSqliteTransaction};
+
+#[async_trait::async_trait]
+impl Repo for &SqlxSqlite {
+    type Transaction = SqlxSqliteTransaction;
+    async fn append<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        // entry and its targets are written together, use the callers
+        // transaction or open a dedicated one
+        let mut own_transaction = None;
+        let tx = match transaction {
+            Some(tx) => tx,
+            None => own_transaction.insert(
+                self.pool()
+                    .begin()
+                    .await
+                    .map_err(|_| SaveError::Connection)?,
+            ),
+        };
+        let entry = AuditLogEntry::from(&record);
+        let entry_id = sqlx::query(
+            "INSERT INTO audit_log (actor_id, actor_role, usecase, outcome, error, request_id, client_ip, occurred_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.actor_id)
+        .bind(entry.actor_role)
+        .bind(entry.usecase)
+        .bind(entry.outcome)
+        .bind(entry.error)
+        .bind(entry.request_id)
+        .bind(entry.client_ip)
+        .bind(entry.occurred_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(|_| SaveError::Connection)?
+        .last_insert_rowid();
+        for target_id in record.target_ids {
+            sqlx::query(
+                "INSERT OR IGNORE INTO audit_log_targets (entry_id, target_id) VALUES (?, ?)",
+            )
+            .bind(entry_id)
+            .bind(target_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        }
+        if let Some(tx) = own_transaction {
+            tx.commit().await.map_err(|_| SaveError::Connection)?;
+        }
+        Ok(())
+    }
+
+    async fn query<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        filter: Filter,
+    ) -> Result<Vec<Record>, QueryError> {
+        let query = sqlx::query_as::<_, AuditLogEntry>(
+            "SELECT a.actor_id, a.actor_role, a.usecase, \
+                (SELECT GROUP_CONCAT(t.target_id, ',') FROM audit_log_targets t WHERE t.entry_id = a.id) AS target_ids, \
+                a.outcome, a.error, a.request_id, a.client_ip, a.occurred_at \
+            FROM audit_log a \
+            WHERE (?1 IS NULL OR a.actor_id = ?1) \
+                AND (?2 IS NULL OR EXISTS (SELECT 1 FROM audit_log_targets t WHERE t.entry_id = a.id AND t.target_id = ?2)) \
+                AND (?3 IS NULL OR a.occurred_at >= ?3) \
+                AND (?4 IS NULL OR a.occurred_at <= ?4) \
+            ORDER BY a.occurred_at, a.id \
+            LIMIT ?5 OFFSET ?6",
+        )
+        .bind(filter.actor_id.map(|id| id.to_string()))
+        .bind(filter.target_id)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.limit)
+        .bind(filter.offset);
+        let entries = match transaction {
+            Some(tx) => query
+                .fetch_all(&mut **tx)
+                .await
+                .map_err(|_| QueryError::Connection)?,
+            None => query
+                .fetch_all(self.pool())
+                .await
+                .map_err(|_| QueryError::Connection)?,
+        };
+        Ok(entries.into_iter().map(Record::from).collect())
+    }
+}
@@ -0,0 +1,98 @@
+use ca_application::gateway::database::external_login::*;
+use ca_domain::entity::user::Id;
+use sha2::{Digest, Sha256};
+
+use super::token::timestamp;
+use crate::{models::external_login::ExternalLogin, SqlxSqlite, SqlxSqliteTransaction};
+
+/// Hex SHA-256 of the state, it is random enough that a plain hash holds up
+fn state_hash(state: &str) -> String {
+    Sha256::digest(state.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[async_trait::async_trait]
+impl Repo for &SqlxSqlite {
+    type Transaction = SqlxSqliteTransaction;
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        let state_hash = state_hash(record.state.expose_secret());
+        let code_verifier_ciphertext = self.secret_key().encrypt(
+            record.code_verifier.expose_secret().as_bytes(),
+            state_hash.as_bytes(),
+        );
+        let query = sqlx::query(
+            "INSERT INTO external_logins \
+            (state_hash, provider, nonce, code_verifier_ciphertext, link_user_id, created_at) \
+            VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(state_hash)
+        .bind(record.provider)
+        .bind(record.nonce)
+        .bind(code_verifier_ciphertext)
+        .bind(record.link_user_id.map(|id| id.to_string()))
+        .bind(timestamp(record.created_at));
+        match transaction {
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| SaveError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| SaveError::Connection)?,
+        };
+        Ok(())
+    }
+
+    async fn take<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        state: &str,
+    ) -> Result<Record, TakeError> {
+        let state_hash = state_hash(state);
+        // deleting is the lookup, so a login is completed only once
+        let query = sqlx::query_as::<_, ExternalLogin>(
+            "DELETE FROM external_logins WHERE state_hash = ? \
+            RETURNING provider, nonce, code_verifier_ciphertext, link_user_id, created_at",
+        )
+        .bind(&state_hash);
+        let row = match transaction {
+            Some(tx) => query
+                .fetch_optional(&mut **tx)
+                .await
+                .map_err(|_| TakeError::Connection)?,
+            None => query
+                .fetch_optional(self.pool())
+                .await
+                .map_err(|_| TakeError::Connection)?,
+        }
+        .ok_or(TakeError::NotFound)?;
+        let code_verifier = self
+            .secret_key()
+            .decrypt(&row.code_verifier_ciphertext, state_hash.as_bytes())
+            .and_then(|verifier| String::from_utf8(verifier).ok())
+            .ok_or_else(|| {
+                log::error!("Code verifier of an external login can not be decrypted");
+                TakeError::Connection
+            })?;
+        let link_user_id = row
+            .link_user_id
+            .map(|id| uuid::Uuid::parse_str(&id).map(Id::new))
+            .transpose()
+            .map_err(|_| TakeError::Connection)?;
+        Ok(Record {
+            state: state.into(),
+            provider: row.provider,
+            nonce: row.nonce,
+            code_verifier: code_verifier.into(),
+            link_user_id,
+            created_at: row.created_at.and_utc(),
+        })
+    }
+}
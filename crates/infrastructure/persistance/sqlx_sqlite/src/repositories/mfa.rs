@@ -0,0 +1,232 @@
+use ca_application::gateway::database::mfa::*;
+use ca_domain::entity::user::Id;
+use sha2::{Digest, Sha256};
+
+use crate::{models::mfa::UserMfa, SqlxSqlite, SqlxSqliteTransaction};
+
+/// Hex SHA-256 of the recovery code within the user, codes are random
+/// enough that a plain hash holds up
+fn code_hash(user_id: Id, code: &str) -> String {
+    // typed in by hand, so case and spacing do not matter
+    let code: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    let digest = Sha256::new()
+        .chain_update(user_id.to_string())
+        .chain_update([0])
+        .chain_update(code)
+        .finalize();
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl SqlxSqlite {
+    fn open_mfa(&self, row: UserMfa) -> Result<Record, GetError> {
+        let user_id = uuid::Uuid::parse_str(&row.user_id)
+            .map(Id::new)
+            .map_err(|_| GetError::Connection)?;
+        let secret = self
+            .secret_key()
+            .decrypt(&row.secret_ciphertext, row.user_id.as_bytes())
+            .and_then(|secret| String::from_utf8(secret).ok())
+            .ok_or_else(|| {
+                log::error!(
+                    "Second factor secret of user {} can not be decrypted",
+                    row.user_id
+                );
+                GetError::Connection
+            })?;
+        Ok(Record {
+            user_id,
+            secret: secret.into(),
+            confirmed: row.confirmed,
+            last_used_step: row.last_used_step.map(|step| step as u64),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Repo for &SqlxSqlite {
+    type Transaction = SqlxSqliteTransaction;
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+    ) -> Result<Record, GetError> {
+        let query = sqlx::query_as::<_, UserMfa>(
+            "SELECT user_id, secret_ciphertext, confirmed, last_used_step \
+            FROM user_mfa WHERE user_id = ?",
+        )
+        .bind(user_id.to_string());
+        let row = match transaction {
+            Some(tx) => query
+                .fetch_optional(&mut **tx)
+                .await
+                .map_err(|_| GetError::Connection)?,
+            None => query
+                .fetch_optional(self.pool())
+                .await
+                .map_err(|_| GetError::Connection)?,
+        }
+        .ok_or(GetError::NotFound)?;
+        self.open_mfa(row)
+    }
+
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        let user_id = record.user_id.to_string();
+        let secret_ciphertext = self
+            .secret_key()
+            .encrypt(record.secret.expose_secret().as_bytes(), user_id.as_bytes());
+        let query = sqlx::query(
+            "INSERT INTO user_mfa (user_id, secret_ciphertext, confirmed, last_used_step) \
+            VALUES (?, ?, ?, ?) \
+            ON CONFLICT (user_id) DO UPDATE SET \
+            secret_ciphertext = excluded.secret_ciphertext, confirmed = excluded.confirmed",
+        )
+        .bind(user_id)
+        .bind(secret_ciphertext)
+        .bind(record.confirmed)
+        .bind(record.last_used_step.map(|step| step as i64));
+        match transaction {
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| SaveError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| SaveError::Connection)?,
+        };
+        Ok(())
+    }
+
+    async fn delete<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+    ) -> Result<(), DeleteError> {
+        let mut own_transaction = None;
+        let tx = match transaction {
+            Some(tx) => tx,
+            None => own_transaction.insert(
+                self.pool()
+                    .begin()
+                    .await
+                    .map_err(|_| DeleteError::Connection)?,
+            ),
+        };
+        for sql in [
+            "DELETE FROM user_mfa_recovery_codes WHERE user_id = ?",
+            "DELETE FROM user_mfa WHERE user_id = ?",
+        ] {
+            sqlx::query(sql)
+                .bind(user_id.to_string())
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| DeleteError::Connection)?;
+        }
+        if let Some(tx) = own_transaction {
+            tx.commit().await.map_err(|_| DeleteError::Connection)?;
+        }
+        Ok(())
+    }
+
+    async fn replace_recovery_codes<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+        codes: &[String],
+    ) -> Result<(), SaveError> {
+        let mut own_transaction = None;
+        let tx = match transaction {
+            Some(tx) => tx,
+            None => own_transaction.insert(
+                self.pool()
+                    .begin()
+                    .await
+                    .map_err(|_| SaveError::Connection)?,
+            ),
+        };
+        sqlx::query("DELETE FROM user_mfa_recovery_codes WHERE user_id = ?")
+            .bind(user_id.to_string())
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        for code in codes {
+            sqlx::query(
+                "INSERT OR IGNORE INTO user_mfa_recovery_codes (user_id, code_hash) VALUES (?, ?)",
+            )
+            .bind(user_id.to_string())
+            .bind(code_hash(user_id, code))
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        }
+        if let Some(tx) = own_transaction {
+            tx.commit().await.map_err(|_| SaveError::Connection)?;
+        }
+        Ok(())
+    }
+
+    async fn use_recovery_code<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+        code: &str,
+    ) -> Result<(), RecoveryCodeError> {
+        // deleting is the check, so a code works only once
+        let query =
+            sqlx::query("DELETE FROM user_mfa_recovery_codes WHERE user_id = ? AND code_hash = ?")
+                .bind(user_id.to_string())
+                .bind(code_hash(user_id, code));
+        let result = match transaction {
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| RecoveryCodeError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| RecoveryCodeError::Connection)?,
+        };
+        if result.rows_affected() == 0 {
+            return Err(RecoveryCodeError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn use_step<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        user_id: Id,
+        step: u64,
+    ) -> Result<(), StepError> {
+        // moving the step is the check, so two requests can not both use it
+        let query = sqlx::query(
+            "UPDATE user_mfa SET last_used_step = ? \
+            WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)",
+        )
+        .bind(step as i64)
+        .bind(user_id.to_string())
+        .bind(step as i64);
+        let result = match transaction {
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| StepError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| StepError::Connection)?,
+        };
+        if result.rows_affected() == 0 {
+            return Err(StepError::Replayed);
+        }
+        Ok(())
+    }
+}
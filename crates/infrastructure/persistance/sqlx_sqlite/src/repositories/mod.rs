@@ -1,3 +1,8 @@
+pub mod audit_log;
+pub mod external_login;
+pub mod mfa;
+pub mod role;
 pub mod signup_process;
 pub mod token;
 pub mod user;
+pub mod user_identity;
@@ -0,0 +1,95 @@
+use ca_application::gateway::database::role::{GetAllError, GetError, Record, Repo, SaveError};
+use ca_domain::value_object::Role as DomainRole;
+
+use crate::{models::role::Role, SqlxSqlite, SqlxSqliteTransaction};
+
+const SELECT_ROLES: &str = "SELECT r.name, \
+        (SELECT GROUP_CONCAT(p.permission, ',') FROM role_permissions p WHERE p.role = r.name) AS permissions \
+    FROM roles r";
+
+#[async_trait::async_trait]
+impl Repo for &SqlxSqlite {
+    type Transaction = SqlxSqliteTransaction;
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        // the role and its permissions are written together, use the callers
+        // transaction or open a dedicated one
+        let mut own_transaction = None;
+        let tx = match transaction {
+            Some(tx) => tx,
+            None => own_transaction.insert(
+                self.pool()
+                    .begin()
+                    .await
+                    .map_err(|_| SaveError::Connection)?,
+            ),
+        };
+        let role = record.role.to_string();
+        sqlx::query("INSERT OR IGNORE INTO roles (name) VALUES (?)")
+            .bind(&role)
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        sqlx::query("DELETE FROM role_permissions WHERE role = ?")
+            .bind(&role)
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| SaveError::Connection)?;
+        for permission in record.permissions {
+            sqlx::query("INSERT INTO role_permissions (role, permission) VALUES (?, ?)")
+                .bind(&role)
+                .bind(permission.to_string())
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| SaveError::Connection)?;
+        }
+        if let Some(tx) = own_transaction {
+            tx.commit().await.map_err(|_| SaveError::Connection)?;
+        }
+        Ok(())
+    }
+
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        role: DomainRole,
+    ) -> Result<Record, GetError> {
+        let sql = format!("{SELECT_ROLES} WHERE r.name = ?");
+        let query = sqlx::query_as::<_, Role>(&sql).bind(role.to_string());
+        let role_result = match transaction {
+            Some(tx) => query
+                .fetch_optional(&mut **tx)
+                .await
+                .map_err(|_| GetError::Connection)?
+                .ok_or(GetError::NotFound)?,
+            None => query
+                .fetch_optional(self.pool())
+                .await
+                .map_err(|_| GetError::Connection)?
+                .ok_or(GetError::NotFound)?,
+        };
+        Ok(Record::from(role_result))
+    }
+
+    async fn get_all<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+    ) -> Result<Vec<Record>, GetAllError> {
+        let sql = format!("{SELECT_ROLES} ORDER BY r.name");
+        let query = sqlx::query_as::<_, Role>(&sql);
+        let role_results = match transaction {
+            Some(tx) => query
+                .fetch_all(&mut **tx)
+                .await
+                .map_err(|_| GetAllError::Connection)?,
+            None => query
+                .fetch_all(self.pool())
+                .await
+                .map_err(|_| GetAllError::Connection)?,
+        };
+        Ok(role_results.into_iter().map(Record::from).collect())
+    }
+}
@@ -1,11 +1,11 @@
 use ca_application::gateway::database::{
     identifier::{NewId, NewIdError},
-    signup_process::{DeleteError, GetError, Record, Repo, SaveError},
+    signup_process::{DeleteError, GetError, Record, Repo, SaveError, StateFilter, StateKind},
 };
-use ca_domain::entity::signup_process::Id;
+use ca_domain::entity::{signup_process::Id, user::Email};
 
 use crate::{
-    models::signup_process_state::{from_chain, SignupProcessState},
+    models::signup_process_state::{from_chain, state_name, SignupProcessState},
     SqlxSqlite, SqlxSqliteTransaction,
 };
 use sqlx;
@@ -16,30 +16,47 @@ impl Repo for &SqlxSqlite {
         &self,
         transaction: Option<&'a mut Self::Transaction>,
         record: Record,
+        expected_seq: Option<u64>,
     ) -> Result<(), SaveError> {
-        println!("Save Latest State: {:?}", record);
+        log::debug!("Save Latest State: {:?}", record);
         let sps = SignupProcessState::from(record);
-        let query = sqlx::query("INSERT INTO signup_process_states (id, username, email, password, error, state) VALUES (?, ?, ?, ?, ?, ?)")
-            .bind(sps.signup_id)
-            .bind(sps.username)
-            .bind(sps.email)
-            .bind(sps.password)
-            .bind(sps.error)
-            .bind(sps.state);
+        let email_canonical = sps
+            .email
+            .as_deref()
+            .map(|email| Email::new_unchecked(email).canonical());
+        let seq = expected_seq.map_or(0, |seq| seq + 1);
+        // only append if nobody else moved the chain since it was loaded,
+        // the unique (id, seq) index catches inserts racing past the check
+        let query = sqlx::query(
+            "INSERT INTO signup_process_states (id, seq, username, email, password, error, state, payload, payload_version, entered_at, email_canonical) \
+            SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?11, ?12 \
+            WHERE (SELECT MAX(seq) FROM signup_process_states WHERE id = ?1) IS ?10",
+        )
+        .bind(sps.signup_id)
+        .bind(seq as i64)
+        .bind(sps.username)
+        .bind(sps.email)
+        .bind(sps.password)
+        .bind(sps.error)
+        .bind(sps.state)
+        .bind(sps.payload)
+        .bind(sps.payload_version)
+        .bind(expected_seq.map(|seq| seq as i64))
+        // same text format as the CURRENT_TIMESTAMP default
+        .bind(sps.entered_at.format("%Y-%m-%d %H:%M:%S").to_string())
+        .bind(email_canonical);
         let res = match transaction {
-            Some(tx) => query
-                .execute(&mut **tx)
-                .await
-                .map_err(|_| SaveError::Connection),
-            None => query
-                .execute(self.pool())
-                .await
-                .map_err(|_| SaveError::Connection),
+            Some(tx) => query.execute(&mut **tx).await,
+            None => query.execute(self.pool()).await,
         };
         match res {
+            Ok(res) if res.rows_affected() == 0 => Err(SaveError::Conflict),
             Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                Err(SaveError::Conflict)
+            }
             Err(err) => {
-                println!("Error saving signup process state: {:?}", err);
+                log::error!("Error saving signup process state: {:?}", err);
                 Err(SaveError::Connection)
             }
         }
@@ -50,9 +67,9 @@ impl Repo for &SqlxSqlite {
         transaction: Option<&'a mut Self::Transaction>,
         id: Id,
     ) -> Result<Record, GetError> {
-        // TODO: Handle empty state chain/None
-        let records = self.get_state_chain(transaction, id).await?;
-        Ok(records.last().unwrap().clone())
+        // the whole chain is needed to decode legacy failed states
+        let mut records = self.get_state_chain(transaction, id).await?;
+        records.pop().ok_or(GetError::NotFound)
     }
 
     async fn get_state_chain<'a>(
@@ -61,7 +78,7 @@ impl Repo for &SqlxSqlite {
         id: Id,
     ) -> Result<Vec<Record>, GetError> {
         let query =
-            sqlx::query_as::<_, SignupProcessState>("SELECT id, username, email, password, error, state, entered_at FROM signup_process_states WHERE id = ?")
+            sqlx::query_as::<_, SignupProcessState>("SELECT id, seq, username, email, password, error, state, entered_at, payload, payload_version FROM signup_process_states WHERE id = ? ORDER BY seq")
                 .bind(id.to_string());
         let sps_results = match transaction {
             Some(tx) => query
@@ -74,28 +91,146 @@ impl Repo for &SqlxSqlite {
                 .map_err(|_| GetError::Connection)?,
         };
 
-        Ok(from_chain(sps_results))
+        if sps_results.is_empty() {
+            return Err(GetError::NotFound);
+        }
+        from_chain(sps_results).map_err(|err| {
+            log::error!(
+                "Failed to decode signup process {} state chain: {}",
+                id,
+                err
+            );
+            GetError::Corrupted
+        })
     }
 
-    async fn delete<'a>(
+    async fn get_by_state<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
-        id: Id,
-    ) -> Result<(), DeleteError> {
-        let query =
-            sqlx::query("DELETE FROM signup_process_states WHERE id = ?").bind(id.to_string());
-        match transaction {
+        filter: StateFilter,
+    ) -> Result<Vec<Record>, GetError> {
+        // entered_at is stored in the CURRENT_TIMESTAMP format, which
+        // compares correctly as text
+        let query = sqlx::query_as::<_, SignupProcessState>(
+            "SELECT id, seq, username, email, password, error, state, entered_at, payload, payload_version \
+            FROM signup_process_states s \
+            WHERE s.state = ?1 AND s.entered_at < ?2 \
+            AND s.seq = (SELECT MAX(seq) FROM signup_process_states WHERE id = s.id) \
+            ORDER BY s.entered_at LIMIT ?3",
+        )
+        .bind(state_name(filter.state))
+        .bind(
+            filter
+                .entered_before
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        )
+        .bind(filter.limit as i64);
+        let sps_results = match transaction {
             Some(tx) => query
-                .execute(&mut **tx)
+                .fetch_all(&mut **tx)
                 .await
-                .map_err(|_| DeleteError::Connection)?,
+                .map_err(|_| GetError::Connection)?,
             None => query
-                .execute(self.pool())
+                .fetch_all(self.pool())
                 .await
-                .map_err(|_| DeleteError::Connection)?,
+                .map_err(|_| GetError::Connection)?,
         };
+        sps_results
+            .into_iter()
+            .map(|sps| {
+                let id = sps.signup_id.clone();
+                from_chain(vec![sps])
+                    .map(|mut records| records.remove(0))
+                    .map_err(|err| {
+                        log::error!("Failed to decode signup process {} state: {}", id, err);
+                        GetError::Corrupted
+                    })
+            })
+            .collect()
+    }
+
+    async fn get_active_by_email<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        email: &Email,
+    ) -> Result<Option<Record>, GetError> {
+        let query = sqlx::query_as::<_, SignupProcessState>(
+            "SELECT id, seq, username, email, password, error, state, entered_at, payload, payload_version \
+            FROM signup_process_states s \
+            WHERE s.email_canonical = ?1 AND s.state IN (?2, ?3, ?4) \
+            AND s.seq = (SELECT MAX(seq) FROM signup_process_states WHERE id = s.id) \
+            ORDER BY s.entered_at DESC LIMIT 1",
+        )
+        .bind(email.canonical())
+        .bind(state_name(StateKind::Initialized))
+        .bind(state_name(StateKind::VerificationEmailSent))
+        .bind(state_name(StateKind::EmailVerified));
+        let sps = match transaction {
+            Some(tx) => query.fetch_optional(&mut **tx).await,
+            None => query.fetch_optional(self.pool()).await,
+        }
+        .map_err(|_| GetError::Connection)?;
+        let Some(sps) = sps else {
+            return Ok(None);
+        };
+        let id = sps.signup_id.clone();
+        from_chain(vec![sps])
+            .map(|mut records| records.pop())
+            .map_err(|err| {
+                log::error!("Failed to decode signup process {} state: {}", id, err);
+                GetError::Corrupted
+            })
+    }
+
+    async fn delete<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        id: Id,
+    ) -> Result<(), DeleteError> {
+        // the chain and its tombstone have to go together, use a
+        // transaction of our own if the caller did not pass one
+        let mut own_transaction = None;
+        let tx = match transaction {
+            Some(tx) => tx,
+            None => own_transaction.insert(
+                self.pool()
+                    .begin()
+                    .await
+                    .map_err(|_| DeleteError::Connection)?,
+            ),
+        };
+        let res = sqlx::query("DELETE FROM signup_process_states WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| DeleteError::Connection)?;
+        if res.rows_affected() == 0 {
+            return Err(DeleteError::NotFound);
+        }
+        sqlx::query("INSERT OR IGNORE INTO signup_process_tombstones (id) VALUES (?)")
+            .bind(id.to_string())
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| DeleteError::Connection)?;
+        if let Some(tx) = own_transaction {
+            tx.commit().await.map_err(|_| DeleteError::Connection)?;
+        }
         Ok(())
     }
+
+    async fn count_tombstones<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+    ) -> Result<u64, GetError> {
+        let query = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM signup_process_tombstones");
+        let count = match transaction {
+            Some(tx) => query.fetch_one(&mut **tx).await,
+            None => query.fetch_one(self.pool()).await,
+        }
+        .map_err(|_| GetError::Connection)?;
+        Ok(count as u64)
+    }
 }
 
 #[async_trait::async_trait]
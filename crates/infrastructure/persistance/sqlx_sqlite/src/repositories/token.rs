@@ -1,75 +1,126 @@
 use ca_application::gateway::database::token::*;
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
 
 use crate::{SqlxSqlite, SqlxSqliteTransaction};
 
+/// Hex SHA-256 of the token within its scope, the scope keeps equal short
+/// codes of different processes apart. A plain hash is enough for random
+/// UUIDs, numeric codes are guessable offline and rely on their short
+/// lifetime and a limit on verification attempts instead
+pub(crate) fn token_hash(scope: &Scope, token: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(scope.purpose.to_string())
+        .chain_update([0])
+        .chain_update(&scope.process_id)
+        .chain_update([0])
+        .chain_update(token)
+        .finalize();
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Same text format as the CURRENT_TIMESTAMP default
+pub(crate) fn timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
 #[async_trait::async_trait]
 impl Repo for &SqlxSqlite {
     type Transaction = SqlxSqliteTransaction;
-    async fn gen<'a>(
+    async fn issue<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
         email: &str,
-    ) -> Result<Record, GenError> {
-        // log::debug!("Generate token for email: {}", email);
-        let token = uuid::Uuid::new_v4();
-        let query = sqlx::query("INSERT INTO tokens (token, email) VALUES (?, ?)")
-            .bind(token.to_string())
-            .bind(email.to_string());
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), IssueError> {
+        let query = sqlx::query(
+            "INSERT OR REPLACE INTO tokens (token_hash, purpose, process_id, email, created_at) \
+            VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(token_hash(scope, token))
+        .bind(scope.purpose.to_string())
+        .bind(&scope.process_id)
+        .bind(email)
+        .bind(timestamp(now));
         match transaction {
-            Some(tx) => {
-                query
-                    .execute(&mut **tx)
-                    .await
-                    .map_err(|_| GenError::Connection)?;
-            }
-            None => {
-                query
-                    .execute(self.pool())
-                    .await
-                    .map_err(|_| GenError::Connection)?;
-            }
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| IssueError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| IssueError::Connection)?,
         };
-        Ok(Record {
-            token: token.to_string(),
-        })
+        Ok(())
     }
 
     async fn verify<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
         email: &str,
         token: &str,
+        max_age: Duration,
+        now: DateTime<Utc>,
     ) -> Result<(), VerifyError> {
-        log::debug!("Verify token for email: {} and token: {}", email, token);
-        let query = sqlx::query_as("SELECT token, email, created_at FROM tokens WHERE token = ?")
-            .bind(token.to_string());
-        let maybe_row: Option<(String, String, String)> = match transaction {
-            Some(tx) => query
-                .fetch_optional(&mut **tx)
-                .await
-                .map_err(|_| VerifyError::Connection)?,
-            None => query
-                .fetch_optional(self.pool())
-                .await
-                .map_err(|_| VerifyError::Connection)?,
+        log::debug!("Verify token for {:?}", scope);
+        let mut own_transaction = None;
+        let tx = match transaction {
+            Some(tx) => tx,
+            None => own_transaction.insert(
+                self.pool()
+                    .begin()
+                    .await
+                    .map_err(|_| VerifyError::Connection)?,
+            ),
         };
-        if maybe_row.is_none() {
-            log::warn!("Token not found!");
-            return Err(VerifyError::NotFound);
-        }
-        let (_, db_email, db_created_at) = maybe_row.unwrap();
-
-        if db_email != email {
-            log::warn!("Email mismatch!");
-            return Err(VerifyError::Mismatch);
+        let token_hash = token_hash(scope, token);
+        // consuming the token is the check, so it can only be used once
+        let res = sqlx::query(
+            "DELETE FROM tokens \
+            WHERE token_hash = ? AND purpose = ? AND process_id = ? AND email = ? AND created_at >= ?",
+        )
+        .bind(&token_hash)
+        .bind(scope.purpose.to_string())
+        .bind(&scope.process_id)
+        .bind(email)
+        .bind(timestamp(now - max_age))
+        .execute(&mut **tx)
+        .await
+        .map_err(|_| VerifyError::Connection)?;
+        if res.rows_affected() == 0 {
+            let row: Option<(String,)> =
+                sqlx::query_as("SELECT email FROM tokens WHERE token_hash = ?")
+                    .bind(&token_hash)
+                    .fetch_optional(&mut **tx)
+                    .await
+                    .map_err(|_| VerifyError::Connection)?;
+            return Err(match row {
+                None => {
+                    log::warn!("Token not found!");
+                    VerifyError::NotFound
+                }
+                Some((db_email,)) if db_email != email => {
+                    log::warn!("Email mismatch!");
+                    VerifyError::Mismatch
+                }
+                Some(_) => {
+                    log::warn!("Token expired!");
+                    VerifyError::TokenExpired
+                }
+            });
         }
-        let created_at = NaiveDateTime::parse_from_str(&db_created_at, "%Y-%m-%d %H:%M:%S")
-            .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
-            .unwrap();
-        if Utc::now() - created_at > Duration::days(1) {
-            log::warn!("Token expired!");
-            return Err(VerifyError::TokenExpired);
+        sqlx::query("DELETE FROM token_attempts WHERE purpose = ? AND process_id = ?")
+            .bind(scope.purpose.to_string())
+            .bind(&scope.process_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|_| VerifyError::Connection)?;
+        if let Some(tx) = own_transaction {
+            tx.commit().await.map_err(|_| VerifyError::Connection)?;
         }
         Ok(())
     }
@@ -77,12 +128,14 @@ impl Repo for &SqlxSqlite {
     async fn extend<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
-        email: &str,
+        scope: &Scope,
+        now: DateTime<Utc>,
     ) -> Result<(), ExtendError> {
-        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        let query = sqlx::query("UPDATE tokens SET created_at = ? WHERE email = ?")
-            .bind(now)
-            .bind(email.to_string());
+        let query =
+            sqlx::query("UPDATE tokens SET created_at = ? WHERE purpose = ? AND process_id = ?")
+                .bind(timestamp(now))
+                .bind(scope.purpose.to_string())
+                .bind(&scope.process_id);
         match transaction {
             Some(tx) => query
                 .execute(&mut **tx)
@@ -95,4 +148,105 @@ impl Repo for &SqlxSqlite {
         };
         Ok(())
     }
+    async fn invalidate<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+    ) -> Result<(), InvalidateError> {
+        let query = sqlx::query("DELETE FROM tokens WHERE purpose = ? AND process_id = ?")
+            .bind(scope.purpose.to_string())
+            .bind(&scope.process_id);
+        match transaction {
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| InvalidateError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| InvalidateError::Connection)?,
+        };
+        Ok(())
+    }
+    async fn record_failed_attempt<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+        now: DateTime<Utc>,
+    ) -> Result<u32, AttemptError> {
+        let query = sqlx::query_scalar(
+            "INSERT INTO token_attempts (purpose, process_id, failures, last_failed_at) \
+            VALUES (?1, ?2, 1, ?3) \
+            ON CONFLICT (purpose, process_id) \
+            DO UPDATE SET failures = failures + 1, last_failed_at = ?3 \
+            RETURNING failures",
+        )
+        .bind(scope.purpose.to_string())
+        .bind(&scope.process_id)
+        .bind(timestamp(now));
+        let failures: i64 = match transaction {
+            Some(tx) => query
+                .fetch_one(&mut **tx)
+                .await
+                .map_err(|_| AttemptError::Connection)?,
+            None => query
+                .fetch_one(self.pool())
+                .await
+                .map_err(|_| AttemptError::Connection)?,
+        };
+        Ok(failures as u32)
+    }
+    async fn clear_attempts<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+    ) -> Result<(), AttemptError> {
+        let query = sqlx::query("DELETE FROM token_attempts WHERE purpose = ? AND process_id = ?")
+            .bind(scope.purpose.to_string())
+            .bind(&scope.process_id);
+        match transaction {
+            Some(tx) => query
+                .execute(&mut **tx)
+                .await
+                .map_err(|_| AttemptError::Connection)?,
+            None => query
+                .execute(self.pool())
+                .await
+                .map_err(|_| AttemptError::Connection)?,
+        };
+        Ok(())
+    }
+    async fn record_request<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        scope: &Scope,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<u32, AttemptError> {
+        // the right hand sides all see the row before the update
+        let query = sqlx::query_scalar(
+            "INSERT INTO token_requests (purpose, process_id, requests, window_started_at) \
+            VALUES (?1, ?2, 1, ?3) \
+            ON CONFLICT (purpose, process_id) \
+            DO UPDATE SET \
+            requests = CASE WHEN window_started_at < ?4 THEN 1 ELSE requests + 1 END, \
+            window_started_at = CASE WHEN window_started_at < ?4 THEN ?3 ELSE window_started_at END \
+            RETURNING requests",
+        )
+        .bind(scope.purpose.to_string())
+        .bind(&scope.process_id)
+        .bind(timestamp(now))
+        .bind(timestamp(now - window));
+        let requests: i64 = match transaction {
+            Some(tx) => query
+                .fetch_one(&mut **tx)
+                .await
+                .map_err(|_| AttemptError::Connection)?,
+            None => query
+                .fetch_one(self.pool())
+                .await
+                .map_err(|_| AttemptError::Connection)?,
+        };
+        Ok(requests as u32)
+    }
 }
@@ -1,7 +1,7 @@
 use ca_application::gateway::database::user::{
     DeleteError, GetAllError, GetError, Record, Repo, SaveError,
 };
-use ca_domain::entity::user::{Id, UserName};
+use ca_domain::entity::user::{Email, Id, UserName};
 
 use crate::{models::user::User, SqlxSqlite, SqlxSqliteTransaction};
 #[async_trait::async_trait]
@@ -12,29 +12,39 @@ impl Repo for &SqlxSqlite {
         transaction: Option<&'a mut Self::Transaction>,
         record: Record,
     ) -> Result<(), SaveError> {
+        // saving an existing user updates it in place
         let query = sqlx::query(
-            "INSERT INTO users (id, name, email, password, role) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO users (id, name, email, password, role, name_canonical, email_canonical) \
+            VALUES (?, ?, ?, ?, ?, ?, ?) \
+            ON CONFLICT (id) DO UPDATE SET name = excluded.name, email = excluded.email, \
+            password = excluded.password, role = excluded.role, \
+            name_canonical = excluded.name_canonical, email_canonical = excluded.email_canonical",
         )
         .bind(record.user.id().to_string())
         .bind(record.user.username().to_string())
         .bind(record.user.email().to_string())
-        .bind(record.user.password().to_string())
-        .bind(record.user.role().to_string());
-        match transaction {
-            Some(tx) => {
-                query
-                    .execute(&mut **tx)
-                    .await
-                    .map_err(|_| SaveError::Connection)?;
-            }
-            None => {
-                query
-                    .execute(self.pool())
-                    .await
-                    .map_err(|_| SaveError::Connection)?;
-            }
+        .bind(record.user.password().expose_secret().to_string())
+        .bind(record.user.role().to_string())
+        .bind(record.user.username().canonical())
+        .bind(record.user.email().canonical());
+        let res = match transaction {
+            Some(tx) => query.execute(&mut **tx).await,
+            None => query.execute(self.pool()).await,
         };
-        Ok(())
+        match res {
+            Ok(_) => Ok(()),
+            // the id conflict is handled by the upsert, the message names
+            // the column of the violated unique index, `users.name` or
+            // `users.name_canonical` for the name
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                if err.message().contains("users.name") {
+                    Err(SaveError::UsernameTaken)
+                } else {
+                    Err(SaveError::EmailTaken)
+                }
+            }
+            Err(_) => Err(SaveError::Connection),
+        }
     }
 
     async fn get<'a>(
@@ -66,9 +76,14 @@ impl Repo for &SqlxSqlite {
         transaction: Option<&'a mut Self::Transaction>,
         username: UserName,
     ) -> Result<Record, GetError> {
+        // the canonical match comes first, a clashing legacy name left
+        // without a canonical form only matches its exact value
         let query = sqlx::query_as::<_, User>(
-            "SELECT id, name, email, password, role FROM users WHERE name = ?",
+            "SELECT id, name, email, password, role FROM users \
+            WHERE name_canonical = ?1 OR (name_canonical IS NULL AND name = ?2) \
+            ORDER BY name_canonical IS NULL LIMIT 1",
         )
+        .bind(username.canonical())
         .bind(username.to_string());
         let user_result = match transaction {
             Some(tx) => query
@@ -85,6 +100,42 @@ impl Repo for &SqlxSqlite {
         Ok(Record::from(user_result))
     }
 
+    async fn exists_by_email<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        email: &Email,
+    ) -> Result<bool, GetError> {
+        let query = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM users \
+            WHERE email_canonical = ?1 OR (email_canonical IS NULL AND email = ?2))",
+        )
+        .bind(email.canonical())
+        .bind(email.to_string());
+        match transaction {
+            Some(tx) => query.fetch_one(&mut **tx).await,
+            None => query.fetch_one(self.pool()).await,
+        }
+        .map_err(|_| GetError::Connection)
+    }
+
+    async fn exists_by_username<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        username: &UserName,
+    ) -> Result<bool, GetError> {
+        let query = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM users \
+            WHERE name_canonical = ?1 OR (name_canonical IS NULL AND name = ?2))",
+        )
+        .bind(username.canonical())
+        .bind(username.to_string());
+        match transaction {
+            Some(tx) => query.fetch_one(&mut **tx).await,
+            None => query.fetch_one(self.pool()).await,
+        }
+        .map_err(|_| GetError::Connection)
+    }
+
     async fn get_all<'a>(
         &self,
         transaction: Option<&'a mut Self::Transaction>,
@@ -0,0 +1,62 @@
+use ca_application::gateway::database::user_identity::*;
+use ca_domain::entity::{user::Id, user_identity::UserIdentity as DomainUserIdentity};
+
+use crate::{models::user_identity::UserIdentity, SqlxSqlite, SqlxSqliteTransaction};
+
+#[async_trait::async_trait]
+impl Repo for &SqlxSqlite {
+    type Transaction = SqlxSqliteTransaction;
+    async fn get<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Record, GetError> {
+        let query = sqlx::query_as::<_, UserIdentity>(
+            "SELECT provider, subject, user_id FROM user_identities \
+            WHERE provider = ? AND subject = ?",
+        )
+        .bind(provider)
+        .bind(subject);
+        let row = match transaction {
+            Some(tx) => query
+                .fetch_optional(&mut **tx)
+                .await
+                .map_err(|_| GetError::Connection)?,
+            None => query
+                .fetch_optional(self.pool())
+                .await
+                .map_err(|_| GetError::Connection)?,
+        }
+        .ok_or(GetError::NotFound)?;
+        let user_id = uuid::Uuid::parse_str(&row.user_id)
+            .map(Id::new)
+            .map_err(|_| GetError::Connection)?;
+        Ok(DomainUserIdentity::new(row.provider, row.subject, user_id).into())
+    }
+
+    async fn save<'a>(
+        &self,
+        transaction: Option<&'a mut Self::Transaction>,
+        record: Record,
+    ) -> Result<(), SaveError> {
+        let identity = record.identity;
+        let query = sqlx::query(
+            "INSERT INTO user_identities (provider, subject, user_id) VALUES (?, ?, ?)",
+        )
+        .bind(identity.provider())
+        .bind(identity.subject())
+        .bind(identity.user_id().to_string());
+        let res = match transaction {
+            Some(tx) => query.execute(&mut **tx).await,
+            None => query.execute(self.pool()).await,
+        };
+        match res {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(err)) if err.is_unique_violation() => {
+                Err(SaveError::AlreadyLinked)
+            }
+            Err(_) => Err(SaveError::Connection),
+        }
+    }
+}
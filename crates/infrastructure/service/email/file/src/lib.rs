@@ -1,6 +1,7 @@
 use ca_application::gateway::service::email::{
-    EmailAddress, EmailService, EmailServiceError, EmailVerificationService,
+    encode_query_value, EmailAddress, EmailService, EmailServiceError, EmailVerificationService,
 };
+use ca_domain::entity::signup_process::Id;
 use directories::UserDirs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -8,15 +9,36 @@ use std::path::{Path, PathBuf};
 #[derive(Debug, Clone)]
 pub struct FileEmailService {
     folder_path: PathBuf,
+    public_base_url: Option<String>,
 }
 
 impl FileEmailService {
     pub fn try_new(folder_path: PathBuf) -> Result<Self, std::io::Error> {
         std::fs::create_dir_all(folder_path.clone())?;
 
-        Ok(Self { folder_path })
+        Ok(Self {
+            folder_path,
+            public_base_url: None,
+        })
+    }
+
+    /// Verification emails link to the api served at `url`, e.g.
+    /// `https://example.com`, instead of only containing the code
+    pub fn with_public_base_url(mut self, url: impl Into<String>) -> Self {
+        self.public_base_url = Some(url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    fn verification_link(&self, id: Id, token: &str) -> Option<String> {
+        self.public_base_url.as_ref().map(|base_url| {
+            format!(
+                "{base_url}/signup_processes/{id}/verify?token={}",
+                encode_query_value(token)
+            )
+        })
     }
 }
+
 // TODO:use async file system
 #[async_trait::async_trait]
 impl EmailService for &FileEmailService {
@@ -47,10 +69,17 @@ impl EmailVerificationService for &FileEmailService {
     async fn send_verification_email(
         &self,
         to: EmailAddress,
+        id: Id,
         verification_code: &str,
     ) -> Result<(), EmailServiceError> {
         let subject = "Please verify your email address";
-        let body = format!("Your verification code is: `{}`", verification_code);
+        let body = match self.verification_link(id, verification_code) {
+            Some(link) => format!(
+                "Open {link} to verify your email address or enter the verification code: `{}`",
+                verification_code
+            ),
+            None => format!("Your verification code is: `{}`", verification_code),
+        };
 
         self.send_email(to, subject, &body).await
     }
@@ -78,3 +107,27 @@ pub fn data_storage_directory(data_dir: Option<PathBuf>) -> PathBuf {
         base_path.join(DEFAULT_STORAGE_DIR_NAME)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_link() {
+        let id = Id::new(uuid::Uuid::nil());
+        let service = FileEmailService {
+            folder_path: PathBuf::new(),
+            public_base_url: None,
+        };
+        assert_eq!(service.verification_link(id, "token"), None);
+
+        let service = service.with_public_base_url("https://example.com/");
+        assert_eq!(
+            service.verification_link(id, "a b/c"),
+            Some(
+                "https://example.com/signup_processes/00000000-0000-0000-0000-000000000000/verify?token=a%20b%2Fc"
+                    .to_string()
+            )
+        );
+    }
+}
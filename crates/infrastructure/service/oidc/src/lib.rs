@@ -0,0 +1,589 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use ca_application::gateway::service::oidc::{
+    Authorization, ExternalClaims, OidcError, OidcService,
+};
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::{
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+const SCOPES: &str = "openid email profile";
+/// 256 bits for state, nonce and PKCE verifier, the verifier ends up at 43
+/// characters, the least RFC 7636 allows
+const RANDOM_BYTES: usize = 32;
+/// Seconds the clocks of the provider and ours may differ by
+const LEEWAY_SECONDS: u64 = 60;
+/// Asymmetric algorithms only, a token signed with a shared secret could be
+/// forged with the client secret
+const ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// Client registration at a provider
+#[derive(Debug, Clone)]
+pub struct ProviderConfig {
+    /// Issuer URL the provider metadata is discovered at
+    pub issuer: String,
+    pub client_id: String,
+    /// Left out for public clients, which rely on PKCE alone
+    pub client_secret: Option<String>,
+    /// Where the provider sends the user back with a code
+    pub redirect_uri: String,
+}
+
+/// Provider metadata of OpenID Connect Discovery
+#[derive(Debug, Clone, Deserialize)]
+struct Metadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default, deserialize_with = "lenient_bool")]
+    email_verified: bool,
+    preferred_username: Option<String>,
+}
+
+// Some providers send `email_verified` as a string
+fn lenient_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Lenient {
+        Bool(bool),
+        String(String),
+    }
+    Ok(match Lenient::deserialize(deserializer)? {
+        Lenient::Bool(value) => value,
+        Lenient::String(value) => value.eq_ignore_ascii_case("true"),
+    })
+}
+
+struct Provider {
+    config: ProviderConfig,
+    metadata: RwLock<Option<Metadata>>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+/// Authorization code flow with PKCE against OpenID Connect providers,
+/// their metadata and keys are discovered on first use and kept, clones
+/// share them
+#[derive(Default, Clone)]
+pub struct OidcClient {
+    http: reqwest::Client,
+    providers: HashMap<String, Arc<Provider>>,
+}
+
+impl OidcClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Registers the provider under `name`, the name users pick it by
+    pub fn with_provider(mut self, name: impl Into<String>, config: ProviderConfig) -> Self {
+        self.providers.insert(
+            name.into(),
+            Arc::new(Provider {
+                config,
+                metadata: RwLock::new(None),
+                jwks: RwLock::new(None),
+            }),
+        );
+        self
+    }
+    pub fn provider_names(&self) -> impl Iterator<Item = &str> {
+        self.providers.keys().map(String::as_str)
+    }
+
+    fn provider(&self, name: &str) -> Result<&Provider, OidcError> {
+        self.providers
+            .get(name)
+            .map(Arc::as_ref)
+            .ok_or_else(|| OidcError::UnknownProvider(name.to_string()))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, OidcError> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| {
+                log::error!("Identity provider request to {url} failed: {err}");
+                OidcError::Provider
+            })?;
+        response.json().await.map_err(|err| {
+            log::error!("Identity provider answered {url} unexpectedly: {err}");
+            OidcError::Provider
+        })
+    }
+
+    async fn metadata(&self, provider: &Provider) -> Result<Metadata, OidcError> {
+        if let Some(metadata) = provider.metadata.read().await.as_ref() {
+            return Ok(metadata.clone());
+        }
+        let issuer = &provider.config.issuer;
+        let metadata: Metadata = self
+            .get_json(&format!(
+                "{}/.well-known/openid-configuration",
+                issuer.trim_end_matches('/')
+            ))
+            .await?;
+        // a mismatch means the tokens would be rejected anyway
+        if &metadata.issuer != issuer {
+            log::error!(
+                "Identity provider {issuer} claims to be {}",
+                metadata.issuer
+            );
+            return Err(OidcError::Provider);
+        }
+        *provider.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Key `kid` of the provider, the keys are fetched again once when it is
+    /// missing, so rotated keys are picked up
+    async fn signing_key(
+        &self,
+        provider: &Provider,
+        jwks_uri: &str,
+        kid: Option<&str>,
+    ) -> Result<Jwk, OidcError> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+        if let Some(key) = provider.jwks.read().await.as_ref().and_then(find) {
+            return Ok(key);
+        }
+        let jwks: JwkSet = self.get_json(jwks_uri).await?;
+        let key = find(&jwks);
+        *provider.jwks.write().await = Some(jwks);
+        key.ok_or_else(|| OidcError::InvalidIdToken("unknown signing key".to_string()))
+    }
+
+    async fn validate_id_token(
+        &self,
+        provider: &Provider,
+        metadata: &Metadata,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let invalid = |err: jsonwebtoken::errors::Error| OidcError::InvalidIdToken(err.to_string());
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid)?;
+        if !ALGORITHMS.contains(&header.alg) {
+            return Err(OidcError::InvalidIdToken(format!(
+                "algorithm {:?} is not accepted",
+                header.alg
+            )));
+        }
+        let jwk = self
+            .signing_key(provider, &metadata.jwks_uri, header.kid.as_deref())
+            .await?;
+        let key = DecodingKey::from_jwk(&jwk).map_err(invalid)?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.config.issuer]);
+        validation.set_audience(&[&provider.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = LEEWAY_SECONDS;
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(invalid)?
+            .claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(OidcError::InvalidIdToken("nonce mismatch".to_string()));
+        }
+        Ok(claims)
+    }
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; RANDOM_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+/// `S256` challenge of RFC 7636 for `verifier`
+fn code_challenge(verifier: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(verifier.as_bytes()))
+}
+
+#[async_trait]
+impl OidcService for &OidcClient {
+    async fn authorize(&self, provider_name: &str) -> Result<Authorization, OidcError> {
+        let provider = self.provider(provider_name)?;
+        let metadata = self.metadata(provider).await?;
+        let state = random_token();
+        let nonce = random_token();
+        let code_verifier = random_token();
+        let url = url::Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", provider.config.client_id.as_str()),
+                ("redirect_uri", provider.config.redirect_uri.as_str()),
+                ("scope", SCOPES),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge(&code_verifier).as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|err| {
+            log::error!("Authorization endpoint of {provider_name} is invalid: {err}");
+            OidcError::Provider
+        })?;
+        Ok(Authorization {
+            url: url.into(),
+            state: state.into(),
+            nonce,
+            code_verifier: code_verifier.into(),
+        })
+    }
+
+    async fn exchange_code(
+        &self,
+        provider_name: &str,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<ExternalClaims, OidcError> {
+        let provider = self.provider(provider_name)?;
+        let metadata = self.metadata(provider).await?;
+        let config = &provider.config;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(client_secret) = &config.client_secret {
+            form.push(("client_secret", client_secret));
+        }
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|err| {
+                log::error!("Token request to {provider_name} failed: {err}");
+                OidcError::Provider
+            })?;
+        let status = response.status();
+        // RFC 6749 answers a bad code or verifier with 400 invalid_grant
+        if status == reqwest::StatusCode::BAD_REQUEST {
+            return Err(OidcError::InvalidCode);
+        }
+        if !status.is_success() {
+            log::error!("Token request to {provider_name} answered {status}");
+            return Err(OidcError::Provider);
+        }
+        let tokens: TokenResponse = response.json().await.map_err(|err| {
+            log::error!("Token response of {provider_name} is unexpected: {err}");
+            OidcError::Provider
+        })?;
+        let claims = self
+            .validate_id_token(provider, &metadata, &tokens.id_token, nonce)
+            .await?;
+        Ok(ExternalClaims {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+            preferred_username: claims.preferred_username,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use jsonwebtoken::{EncodingKey, Header};
+    use poem::{
+        get, handler,
+        http::StatusCode,
+        listener::{Acceptor, Listener, TcpListener},
+        post,
+        web::{Data, Form, Json},
+        EndpointExt, IntoResponse, Response, Route, Server,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    // Signing key of the mock provider and the JWKS publishing it
+    const KEY_PEM: &[u8] = include_bytes!("testdata/provider_key.pem");
+    const JWKS: &str = include_str!("testdata/provider_jwks.json");
+    const KID: &str = "test-key";
+    const PROVIDER: &str = "mock";
+    const CLIENT_ID: &str = "test-client";
+    const REDIRECT_URI: &str = "http://localhost:3000/login/callback";
+    const CODE: &str = "test-code";
+
+    /// Local OpenID Connect provider, it hands out an ID token for `CODE`
+    /// when the verifier matches the challenge of the login
+    #[derive(Clone)]
+    struct MockProvider {
+        issuer: String,
+        challenge: Arc<Mutex<Option<String>>>,
+        /// Claims of the next ID token, filled in with the nonce
+        claims: Arc<Mutex<Value>>,
+    }
+
+    #[handler]
+    fn discovery(Data(provider): Data<&MockProvider>) -> Json<Value> {
+        let issuer = &provider.issuer;
+        Json(json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{issuer}/authorize"),
+            "token_endpoint": format!("{issuer}/token"),
+            "jwks_uri": format!("{issuer}/jwks"),
+        }))
+    }
+
+    #[handler]
+    fn jwks() -> Json<Value> {
+        Json(serde_json::from_str(JWKS).unwrap())
+    }
+
+    #[derive(Deserialize)]
+    struct TokenForm {
+        grant_type: String,
+        code: String,
+        client_id: String,
+        code_verifier: String,
+    }
+
+    #[handler]
+    fn token(Data(provider): Data<&MockProvider>, Form(form): Form<TokenForm>) -> Response {
+        let challenge = provider.challenge.lock().unwrap().clone();
+        if form.grant_type != "authorization_code"
+            || form.code != CODE
+            || form.client_id != CLIENT_ID
+            || challenge != Some(code_challenge(&form.code_verifier))
+        {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            )
+                .into_response();
+        }
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(KID.to_string());
+        let id_token = jsonwebtoken::encode(
+            &header,
+            &*provider.claims.lock().unwrap(),
+            &EncodingKey::from_rsa_pem(KEY_PEM).unwrap(),
+        )
+        .unwrap();
+        Json(json!({
+            "access_token": "test-access-token",
+            "token_type": "Bearer",
+            "id_token": id_token,
+        }))
+        .into_response()
+    }
+
+    async fn start_provider() -> MockProvider {
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+        let issuer = format!("http://{addr}");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let provider = MockProvider {
+            claims: Arc::new(Mutex::new(json!({
+                "iss": issuer,
+                "aud": CLIENT_ID,
+                "sub": "248289761001",
+                "iat": now,
+                "exp": now + 300,
+                "email": "jane@example.com",
+                "email_verified": true,
+                "preferred_username": "jane",
+            }))),
+            issuer,
+            challenge: Arc::new(Mutex::new(None)),
+        };
+        let app = Route::new()
+            .at("/.well-known/openid-configuration", get(discovery))
+            .at("/jwks", get(jwks))
+            .at("/token", post(token))
+            .data(provider.clone());
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+        provider
+    }
+
+    fn client(provider: &MockProvider) -> OidcClient {
+        OidcClient::new().with_provider(
+            PROVIDER,
+            ProviderConfig {
+                issuer: provider.issuer.clone(),
+                client_id: CLIENT_ID.to_string(),
+                client_secret: None,
+                redirect_uri: REDIRECT_URI.to_string(),
+            },
+        )
+    }
+
+    /// Starts a login and plays the part of the user at the provider, the
+    /// provider learns the challenge and puts the nonce into its ID token
+    async fn log_in_at_provider(client: &OidcClient, provider: &MockProvider) -> Authorization {
+        let authorization = client.authorize(PROVIDER).await.unwrap();
+        let url = url::Url::parse(&authorization.url).unwrap();
+        assert_eq!(url.path(), "/authorize");
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["client_id"], CLIENT_ID);
+        assert_eq!(params["redirect_uri"], REDIRECT_URI);
+        assert_eq!(params["state"], authorization.state.expose_secret());
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert!(params["scope"].split(' ').any(|scope| scope == "openid"));
+        *provider.challenge.lock().unwrap() = Some(params["code_challenge"].clone());
+        provider.claims.lock().unwrap()["nonce"] = params["nonce"].clone().into();
+        authorization
+    }
+
+    #[test]
+    fn test_code_challenge() {
+        // example of appendix B of RFC 7636
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(random_token().len(), 43);
+        assert_ne!(random_token(), random_token());
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        let provider = start_provider().await;
+        let client = client(&provider);
+        let authorization = log_in_at_provider(&client, &provider).await;
+        let claims = (&client)
+            .exchange_code(
+                PROVIDER,
+                CODE,
+                authorization.code_verifier.expose_secret(),
+                &authorization.nonce,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            claims,
+            ExternalClaims {
+                subject: "248289761001".to_string(),
+                email: Some("jane@example.com".to_string()),
+                email_verified: true,
+                preferred_username: Some("jane".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wrong_code_verifier() {
+        let provider = start_provider().await;
+        let client = client(&provider);
+        let authorization = log_in_at_provider(&client, &provider).await;
+        let result = (&client)
+            .exchange_code(PROVIDER, CODE, &random_token(), &authorization.nonce)
+            .await;
+        assert_eq!(result, Err(OidcError::InvalidCode));
+    }
+
+    #[tokio::test]
+    async fn test_nonce_mismatch() {
+        let provider = start_provider().await;
+        let client = client(&provider);
+        let authorization = log_in_at_provider(&client, &provider).await;
+        let result = (&client)
+            .exchange_code(
+                PROVIDER,
+                CODE,
+                authorization.code_verifier.expose_secret(),
+                "replayed-nonce",
+            )
+            .await;
+        assert_eq!(
+            result,
+            Err(OidcError::InvalidIdToken("nonce mismatch".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_id_token() {
+        for (claim, value) in [
+            ("aud", json!("other-client")),
+            ("iss", json!("https://evil.example.com")),
+            ("exp", json!(1_000_000_000)),
+        ] {
+            let provider = start_provider().await;
+            let client = client(&provider);
+            let authorization = log_in_at_provider(&client, &provider).await;
+            provider.claims.lock().unwrap()[claim] = value;
+            let result = (&client)
+                .exchange_code(
+                    PROVIDER,
+                    CODE,
+                    authorization.code_verifier.expose_secret(),
+                    &authorization.nonce,
+                )
+                .await;
+            assert!(
+                matches!(result, Err(OidcError::InvalidIdToken(_))),
+                "{claim}: {result:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_provider() {
+        let client = OidcClient::new();
+        assert_eq!(
+            (&client).authorize("google").await.unwrap_err(),
+            OidcError::UnknownProvider("google".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lenient_email_verified() {
+        let claims: IdTokenClaims =
+            serde_json::from_value(json!({ "sub": "1", "email_verified": "true" })).unwrap();
+        assert!(claims.email_verified);
+        let claims: IdTokenClaims = serde_json::from_value(json!({ "sub": "1" })).unwrap();
+        assert!(!claims.email_verified);
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt, str::FromStr};
+
+use ca_application::gateway::service::token::TokenGenerator;
+use rand::{distributions::Alphanumeric, rngs::OsRng, Rng};
+
+const DEFAULT_OTP_DIGITS: u8 = 6;
+const DEFAULT_URL_SAFE_LEN: u8 = 32;
+
+/// Shape of the generated tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenFormat {
+    /// Random UUID, the default, suits links
+    #[default]
+    Uuid,
+    /// Code of `digits` digits that is easy to type in on a phone
+    NumericOtp { digits: u8 },
+    /// `len` random letters and digits
+    UrlSafe { len: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTokenFormatError(String);
+
+impl fmt::Display for ParseTokenFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid token format {:?}, expected uuid, otp[:digits] or url-safe[:len]",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseTokenFormatError {}
+
+/// Parses `uuid`, `otp`, `otp:8`, `url-safe` or `url-safe:48`
+impl FromStr for TokenFormat {
+    type Err = ParseTokenFormatError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseTokenFormatError(s.to_string());
+        let (name, size) = match s.split_once(':') {
+            Some((name, size)) => (name, Some(size.parse::<u8>().map_err(|_| err())?)),
+            None => (s, None),
+        };
+        match (name, size) {
+            ("uuid", None) => Ok(Self::Uuid),
+            ("otp", None) => Ok(Self::NumericOtp {
+                digits: DEFAULT_OTP_DIGITS,
+            }),
+            ("otp", Some(digits @ 4..=10)) => Ok(Self::NumericOtp { digits }),
+            ("url-safe", None) => Ok(Self::UrlSafe {
+                len: DEFAULT_URL_SAFE_LEN,
+            }),
+            ("url-safe", Some(len @ 16..)) => Ok(Self::UrlSafe { len }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Generates tokens of a [`TokenFormat`] from the operating system's
+/// random source
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomTokenGenerator {
+    format: TokenFormat,
+}
+
+impl RandomTokenGenerator {
+    pub fn new(format: TokenFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl TokenGenerator for RandomTokenGenerator {
+    fn generate(&self) -> String {
+        match self.format {
+            TokenFormat::Uuid => uuid::Uuid::new_v4().to_string(),
+            TokenFormat::NumericOtp { digits } => (0..digits)
+                .map(|_| char::from(b'0' + OsRng.gen_range(0..10)))
+                .collect(),
+            TokenFormat::UrlSafe { len } => OsRng
+                .sample_iter(&Alphanumeric)
+                .take(len.into())
+                .map(char::from)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("uuid".parse(), Ok(TokenFormat::Uuid));
+        assert_eq!("otp".parse(), Ok(TokenFormat::NumericOtp { digits: 6 }));
+        assert_eq!("otp:8".parse(), Ok(TokenFormat::NumericOtp { digits: 8 }));
+        assert_eq!("url-safe".parse(), Ok(TokenFormat::UrlSafe { len: 32 }));
+        for format in ["otp:2", "otp:x", "url-safe:8", "uuid:4", "hex"] {
+            assert!(format.parse::<TokenFormat>().is_err(), "{format}");
+        }
+    }
+
+    #[test]
+    fn generate() {
+        let otp = RandomTokenGenerator::new(TokenFormat::NumericOtp { digits: 6 }).generate();
+        assert_eq!(otp.len(), 6);
+        assert!(otp.chars().all(|c| c.is_ascii_digit()));
+        let url_safe = RandomTokenGenerator::new(TokenFormat::UrlSafe { len: 40 }).generate();
+        assert_eq!(url_safe.len(), 40);
+        assert!(url_safe.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(uuid::Uuid::parse_str(&RandomTokenGenerator::default().generate()).is_ok());
+    }
+}
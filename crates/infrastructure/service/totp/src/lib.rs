@@ -0,0 +1,184 @@
+use ca_application::gateway::service::totp::TotpService;
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, Rng, RngCore};
+use sha1::Sha1;
+
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Steps a code is still accepted before or after the current one, covers
+/// clock drift of the device
+const SKEW_STEPS: u64 = 1;
+/// 160 bits, the size RFC 4226 recommends for HMAC-SHA1
+const SECRET_BYTES: usize = 20;
+/// Letters and digits that can not be mixed up when typed in
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_CODE_GROUP_LEN: usize = 4;
+
+/// RFC 6238 codes of 6 digits over 30 second steps with HMAC-SHA1, the
+/// parameters every authenticator app supports
+#[derive(Debug, Clone)]
+pub struct TotpGenerator {
+    issuer: String,
+}
+
+impl TotpGenerator {
+    /// `issuer` names the service in authenticator apps
+    pub fn new(issuer: impl Into<String>) -> Self {
+        Self {
+            issuer: issuer.into(),
+        }
+    }
+}
+
+// HOTP value of RFC 4226 for `counter`
+fn hotp(key: &[u8], counter: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(digits),
+        width = digits as usize
+    )
+}
+
+// Compares without returning early, so timing does not leak matching digits
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// Percent-encodes everything except the unreserved characters of RFC 3986
+fn encode_uri_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+impl TotpService for TotpGenerator {
+    fn generate_secret(&self) -> String {
+        let mut secret = [0u8; SECRET_BYTES];
+        OsRng.fill_bytes(&mut secret);
+        BASE32_NOPAD.encode(&secret)
+    }
+
+    fn provisioning_uri(&self, secret: &str, account: &str) -> String {
+        let issuer = encode_uri_component(&self.issuer);
+        format!(
+            "otpauth://totp/{issuer}:{}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+            encode_uri_component(account)
+        )
+    }
+
+    fn verify(&self, secret: &str, code: &str, now: DateTime<Utc>) -> Option<u64> {
+        let key = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+        // authenticator apps often show the code in two groups
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        let current = u64::try_from(now.timestamp() / STEP_SECONDS).ok()?;
+        (current.saturating_sub(SKEW_STEPS)..=current + SKEW_STEPS)
+            .find(|step| constant_time_eq(hotp(&key, *step, DIGITS).as_bytes(), code.as_bytes()))
+    }
+
+    fn generate_recovery_code(&self) -> String {
+        let group = || -> String {
+            (0..RECOVERY_CODE_GROUP_LEN)
+                .map(|_| {
+                    char::from(
+                        RECOVERY_CODE_ALPHABET[OsRng.gen_range(0..RECOVERY_CODE_ALPHABET.len())],
+                    )
+                })
+                .collect()
+        };
+        format!("{}-{}", group(), group())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Secret of the SHA1 test vectors in appendix B of RFC 6238
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_rfc_6238_vectors() {
+        for (time, code) in [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ] {
+            let step = (time / STEP_SECONDS) as u64;
+            assert_eq!(hotp(RFC_SECRET, step, 8), code, "{time}");
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let generator = TotpGenerator::new("Example");
+        let secret = BASE32_NOPAD.encode(RFC_SECRET);
+        let now = DateTime::from_timestamp(1111111111, 0).unwrap();
+        // the last six digits of the eight digit vector
+        assert_eq!(
+            generator.verify(&secret, "050471", now),
+            Some(1111111111 / 30)
+        );
+        assert_eq!(
+            generator.verify(&secret, "050 471", now),
+            Some(1111111111 / 30)
+        );
+        // a code of the previous step is still accepted
+        let later = DateTime::from_timestamp(1111111111 + 30, 0).unwrap();
+        assert_eq!(
+            generator.verify(&secret, "050471", later),
+            Some(1111111111 / 30)
+        );
+        let much_later = DateTime::from_timestamp(1111111111 + 90, 0).unwrap();
+        assert_eq!(generator.verify(&secret, "050471", much_later), None);
+        assert_eq!(generator.verify(&secret, "050472", now), None);
+        assert_eq!(generator.verify("not base32!", "050471", now), None);
+    }
+
+    #[test]
+    fn test_generate() {
+        let generator = TotpGenerator::new("Example");
+        let secret = generator.generate_secret();
+        assert_eq!(
+            BASE32_NOPAD.decode(secret.as_bytes()).unwrap().len(),
+            SECRET_BYTES
+        );
+        assert_ne!(secret, generator.generate_secret());
+        let code = generator.generate_recovery_code();
+        assert_eq!(code.len(), 9);
+        assert_eq!(code.as_bytes()[4], b'-');
+        assert!(code
+            .bytes()
+            .filter(|byte| *byte != b'-')
+            .all(|byte| RECOVERY_CODE_ALPHABET.contains(&byte)));
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        let generator = TotpGenerator::new("Clean Arch");
+        assert_eq!(
+            generator.provisioning_uri("JBSWY3DPEHPK3PXP", "jane@example.com"),
+            "otpauth://totp/Clean%20Arch:jane%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=Clean%20Arch&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
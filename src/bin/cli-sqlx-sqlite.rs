@@ -1,14 +1,31 @@
 use ca_application::gateway::service::auth::{AuthExtractor, AuthPacker};
-use ca_application::gateway::service::email::EmailVerificationService;
+use ca_application::gateway::service::clock::{Clock, SystemClock};
+use ca_application::gateway::service::email::{EmailService, EmailVerificationService};
+use ca_application::gateway::service::job_metrics::MetricsHandle;
+use ca_application::gateway::service::oidc::OidcService;
+use ca_application::gateway::service::token::TokenGenerator;
+use ca_application::gateway::service::totp::TotpService;
 use ca_application::gateway::{
-    AuthExtractorProvider, AuthPackerProvider, DatabaseProvider, EmailVerificationServiceProvider,
+    AuthExtractorProvider, AuthPackerProvider, ClockProvider, DatabaseProvider,
+    EmailServiceProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+    LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider, SignupPolicyProvider,
+    TokenGeneratorProvider, TotpServiceProvider,
 };
+use ca_domain::value_object::{LoginLinkPolicy, PasswordPolicy, SignupPolicy};
 
 use ca_infrastructure_auth_jwt::JwtAuth;
 use ca_infrastructure_interface_cli as cli;
 use ca_infrastructure_persistance_sqlx_sqlite::SqlxSqlite;
 use ca_infrastructure_service_email_file::{data_storage_directory, FileEmailService};
+use ca_infrastructure_service_oidc::OidcClient;
+use ca_infrastructure_service_token_random::RandomTokenGenerator;
+use ca_infrastructure_service_totp::TotpGenerator;
 use clap::Parser;
+use clean_arch::config::{
+    login_link_policy_from_env, oidc_client_from_env, password_policy_from_env,
+    public_base_url_from_env, secret_key_from_env, signup_policy_from_env, token_format_from_env,
+    totp_issuer_from_env,
+};
 use std::{path::PathBuf, sync::Arc};
 
 #[derive(Parser)]
@@ -23,18 +40,39 @@ struct DependancyProvider {
     db: SqlxSqlite,
     email_verification_servuce: FileEmailService,
     jwt_auth: JwtAuth,
+    signup_policy: SignupPolicy,
+    password_policy: PasswordPolicy,
+    login_link_policy: LoginLinkPolicy,
+    token_generator: RandomTokenGenerator,
+    totp_generator: TotpGenerator,
+    oidc_client: OidcClient,
+    expire_signup_metrics: MetricsHandle,
 }
 
 impl DependancyProvider {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         db: SqlxSqlite,
         email_verification_servuce: FileEmailService,
         jwt_auth: JwtAuth,
+        signup_policy: SignupPolicy,
+        password_policy: PasswordPolicy,
+        login_link_policy: LoginLinkPolicy,
+        token_generator: RandomTokenGenerator,
+        totp_generator: TotpGenerator,
+        oidc_client: OidcClient,
     ) -> Self {
         Self {
             db,
             email_verification_servuce,
             jwt_auth,
+            signup_policy,
+            password_policy,
+            login_link_policy,
+            token_generator,
+            totp_generator,
+            oidc_client,
+            expire_signup_metrics: MetricsHandle::default(),
         }
     }
 }
@@ -51,6 +89,13 @@ impl Clone for DependancyProvider {
             db: self.db.clone(),
             email_verification_servuce: self.email_verification_servuce.clone(),
             jwt_auth: self.jwt_auth.clone(),
+            signup_policy: self.signup_policy.clone(),
+            password_policy: self.password_policy.clone(),
+            login_link_policy: self.login_link_policy.clone(),
+            token_generator: self.token_generator,
+            totp_generator: self.totp_generator.clone(),
+            oidc_client: self.oidc_client.clone(),
+            expire_signup_metrics: self.expire_signup_metrics.clone(),
         }
     }
 }
@@ -61,6 +106,12 @@ impl EmailVerificationServiceProvider for DependancyProvider {
     }
 }
 
+impl EmailServiceProvider for DependancyProvider {
+    fn email_service(&self) -> impl EmailService {
+        &self.email_verification_servuce
+    }
+}
+
 impl AuthExtractorProvider for DependancyProvider {
     fn auth_extractor(&self) -> impl AuthExtractor {
         &self.jwt_auth
@@ -73,18 +124,78 @@ impl AuthPackerProvider for DependancyProvider {
     }
 }
 
+impl ClockProvider for DependancyProvider {
+    fn clock(&self) -> impl Clock {
+        SystemClock
+    }
+}
+
+impl SignupPolicyProvider for DependancyProvider {
+    fn signup_policy(&self) -> SignupPolicy {
+        self.signup_policy.clone()
+    }
+}
+
+impl PasswordPolicyProvider for DependancyProvider {
+    fn password_policy(&self) -> PasswordPolicy {
+        self.password_policy.clone()
+    }
+}
+
+impl LoginLinkPolicyProvider for DependancyProvider {
+    fn login_link_policy(&self) -> LoginLinkPolicy {
+        self.login_link_policy.clone()
+    }
+}
+
+impl TokenGeneratorProvider for DependancyProvider {
+    fn token_generator(&self) -> impl TokenGenerator {
+        &self.token_generator
+    }
+}
+
+impl TotpServiceProvider for DependancyProvider {
+    fn totp_service(&self) -> impl TotpService {
+        &self.totp_generator
+    }
+}
+
+impl OidcServiceProvider for DependancyProvider {
+    fn oidc_service(&self) -> impl OidcService {
+        &self.oidc_client
+    }
+}
+
+impl JobMetricsProvider for DependancyProvider {
+    fn expire_signup_metrics(&self) -> MetricsHandle {
+        self.expire_signup_metrics.clone()
+    }
+}
+
 #[tokio::main]
 pub async fn main() -> Result<(), std::io::Error> {
     let args = Args::parse();
     let data_folder_path = data_storage_directory(None);
     let data_folder_str = data_folder_path.to_str().unwrap();
-    let email_verification_service = FileEmailService::try_new(data_folder_path.clone())?;
+    let mut email_verification_service = FileEmailService::try_new(data_folder_path.clone())?;
+    // links only make sense where the api is served, so there is no default
+    if let Some(url) = public_base_url_from_env() {
+        email_verification_service = email_verification_service.with_public_base_url(url);
+    }
     let jwt_auth = JwtAuth::new("secret".to_string());
-    let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str).await.unwrap();
+    let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str, secret_key_from_env())
+        .await
+        .unwrap();
     let dep_provider = Arc::new(DependancyProvider::new(
         sqlx_sqlite,
         email_verification_service,
         jwt_auth,
+        signup_policy_from_env(),
+        password_policy_from_env(),
+        login_link_policy_from_env(),
+        RandomTokenGenerator::new(token_format_from_env()),
+        TotpGenerator::new(totp_issuer_from_env()),
+        oidc_client_from_env(),
     ));
     cli::run(dep_provider, args.command).await;
     Ok(())
@@ -98,6 +209,7 @@ mod tests {
         entity::{auth_context::AuthContext, user},
         value_object::Role,
     };
+    use clean_arch::config::DEFAULT_TOTP_ISSUER;
 
     #[tokio::test]
     async fn test_login() {
@@ -109,7 +221,7 @@ mod tests {
         // let token = (&jwt_auth)
         //     .pack_auth(AuthContext {
         //         user_id: user::Id::new(uuid::Uuid::from_u128(0)),
-        //         role: Role::Admin,
+        //         role: Role::admin(),
         //     })
         //     .await;
         let args = Args {
@@ -119,11 +231,17 @@ mod tests {
             },
             data_dir: None,
         };
-        let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str).await.unwrap();
+        let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str, None).await.unwrap();
         let dep_provider = Arc::new(DependancyProvider::new(
             sqlx_sqlite,
             email_verification_service,
             jwt_auth,
+            SignupPolicy::default(),
+            PasswordPolicy::default(),
+            LoginLinkPolicy::default(),
+            RandomTokenGenerator::default(),
+            TotpGenerator::new(DEFAULT_TOTP_ISSUER),
+            OidcClient::new(),
         ));
         cli::run(dep_provider, args.command).await;
     }
@@ -136,20 +254,26 @@ mod tests {
             FileEmailService::try_new(data_folder_path.clone()).unwrap();
         let jwt_auth = JwtAuth::new("secret".to_string());
         let token = (&jwt_auth)
-            .pack_auth(AuthContext {
-                user_id: user::Id::new(uuid::Uuid::from_u128(0)),
-                role: Role::Admin,
-            })
+            .pack_auth(AuthContext::new(
+                user::Id::new(uuid::Uuid::from_u128(0)),
+                Role::admin(),
+            ))
             .await;
         let args = Args {
             command: cli::Command::ListUsers { token: Some(token) },
             data_dir: None,
         };
-        let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str).await.unwrap();
+        let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str, None).await.unwrap();
         let dep_provider = Arc::new(DependancyProvider::new(
             sqlx_sqlite,
             email_verification_service,
             jwt_auth,
+            SignupPolicy::default(),
+            PasswordPolicy::default(),
+            LoginLinkPolicy::default(),
+            RandomTokenGenerator::default(),
+            TotpGenerator::new(DEFAULT_TOTP_ISSUER),
+            OidcClient::new(),
         ));
         cli::run(dep_provider, args.command).await;
     }
@@ -1,35 +1,82 @@
 use std::sync::Arc;
 
-use ca_application::gateway::{
-    service::{
-        auth::{AuthExtractor, AuthPacker},
-        email::EmailVerificationService,
+use ca_application::{
+    gateway::{
+        service::{
+            auth::{AuthExtractor, AuthPacker},
+            clock::{Clock, SystemClock},
+            email::{EmailService, EmailVerificationService},
+            job_metrics::MetricsHandle,
+            oidc::OidcService,
+            token::TokenGenerator,
+            totp::TotpService,
+        },
+        AuthExtractorProvider, AuthPackerProvider, ClockProvider, DatabaseProvider,
+        EmailServiceProvider, EmailVerificationServiceProvider, JobMetricsProvider,
+        LoginLinkPolicyProvider, OidcServiceProvider, PasswordPolicyProvider, SignupPolicyProvider,
+        TokenGeneratorProvider, TotpServiceProvider,
+    },
+    job::{
+        expire_signup_processes::ExpireSignupProcesses,
+        purge_signup_processes::PurgeSignupProcesses, JobRunner,
     },
-    AuthExtractorProvider, AuthPackerProvider, DatabaseProvider, EmailVerificationServiceProvider,
 };
+use ca_domain::value_object::{LoginLinkPolicy, PasswordPolicy, SignupPolicy};
 use ca_infrastructure_auth_jwt::JwtAuth;
 use ca_infrastructure_interface_poem_openapi::Api;
 use ca_infrastructure_persistance_sqlx_sqlite::SqlxSqlite;
 use ca_infrastructure_service_email_file::{data_storage_directory, FileEmailService};
+use ca_infrastructure_service_oidc::OidcClient;
+use ca_infrastructure_service_token_random::RandomTokenGenerator;
+use ca_infrastructure_service_totp::TotpGenerator;
+use clean_arch::config::{
+    login_link_policy_from_env, oidc_client_from_env, password_policy_from_env,
+    public_base_url_from_env, purge_grace_period_from_env, secret_key_from_env,
+    signup_policy_from_env, token_format_from_env, totp_issuer_from_env, trusted_proxies_from_env,
+    verification_redirects_from_env,
+};
 use poem::{listener::TcpListener, Route, Server};
 use poem_openapi::OpenApiService;
 
+const SERVER_URL: &str = "http://localhost:3000";
+
 struct DependancyProvider {
     db: SqlxSqlite,
     email_verification_servuce: FileEmailService,
     jwt_auth: JwtAuth,
+    signup_policy: SignupPolicy,
+    password_policy: PasswordPolicy,
+    login_link_policy: LoginLinkPolicy,
+    token_generator: RandomTokenGenerator,
+    totp_generator: TotpGenerator,
+    oidc_client: OidcClient,
+    expire_signup_metrics: MetricsHandle,
 }
 
 impl DependancyProvider {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         db: SqlxSqlite,
         email_verification_servuce: FileEmailService,
         jwt_auth: JwtAuth,
+        signup_policy: SignupPolicy,
+        password_policy: PasswordPolicy,
+        login_link_policy: LoginLinkPolicy,
+        token_generator: RandomTokenGenerator,
+        totp_generator: TotpGenerator,
+        oidc_client: OidcClient,
     ) -> Self {
         Self {
             db,
             email_verification_servuce,
             jwt_auth,
+            signup_policy,
+            password_policy,
+            login_link_policy,
+            token_generator,
+            totp_generator,
+            oidc_client,
+            expire_signup_metrics: MetricsHandle::default(),
         }
     }
 }
@@ -46,6 +93,13 @@ impl Clone for DependancyProvider {
             db: self.db.clone(),
             email_verification_servuce: self.email_verification_servuce.clone(),
             jwt_auth: self.jwt_auth.clone(),
+            signup_policy: self.signup_policy.clone(),
+            password_policy: self.password_policy.clone(),
+            login_link_policy: self.login_link_policy.clone(),
+            token_generator: self.token_generator,
+            totp_generator: self.totp_generator.clone(),
+            oidc_client: self.oidc_client.clone(),
+            expire_signup_metrics: self.expire_signup_metrics.clone(),
         }
     }
 }
@@ -56,6 +110,12 @@ impl EmailVerificationServiceProvider for DependancyProvider {
     }
 }
 
+impl EmailServiceProvider for DependancyProvider {
+    fn email_service(&self) -> impl EmailService {
+        &self.email_verification_servuce
+    }
+}
+
 impl AuthExtractorProvider for DependancyProvider {
     fn auth_extractor(&self) -> impl AuthExtractor {
         &self.jwt_auth
@@ -68,20 +128,97 @@ impl AuthPackerProvider for DependancyProvider {
     }
 }
 
+impl ClockProvider for DependancyProvider {
+    fn clock(&self) -> impl Clock {
+        SystemClock
+    }
+}
+
+impl SignupPolicyProvider for DependancyProvider {
+    fn signup_policy(&self) -> SignupPolicy {
+        self.signup_policy.clone()
+    }
+}
+
+impl PasswordPolicyProvider for DependancyProvider {
+    fn password_policy(&self) -> PasswordPolicy {
+        self.password_policy.clone()
+    }
+}
+
+impl LoginLinkPolicyProvider for DependancyProvider {
+    fn login_link_policy(&self) -> LoginLinkPolicy {
+        self.login_link_policy.clone()
+    }
+}
+
+impl TokenGeneratorProvider for DependancyProvider {
+    fn token_generator(&self) -> impl TokenGenerator {
+        &self.token_generator
+    }
+}
+
+impl TotpServiceProvider for DependancyProvider {
+    fn totp_service(&self) -> impl TotpService {
+        &self.totp_generator
+    }
+}
+
+impl OidcServiceProvider for DependancyProvider {
+    fn oidc_service(&self) -> impl OidcService {
+        &self.oidc_client
+    }
+}
+
+impl JobMetricsProvider for DependancyProvider {
+    fn expire_signup_metrics(&self) -> MetricsHandle {
+        self.expire_signup_metrics.clone()
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let data_folder_path = data_storage_directory(None);
     let data_folder_str = data_folder_path.to_str().unwrap();
-    let email_verification_service = FileEmailService::try_new(data_folder_path.clone()).unwrap();
+    let public_base_url = public_base_url_from_env().unwrap_or_else(|| SERVER_URL.to_string());
+    let email_verification_service = FileEmailService::try_new(data_folder_path.clone())
+        .unwrap()
+        .with_public_base_url(public_base_url);
     let jwt_auth = JwtAuth::new("secret".to_string());
-    let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str).await.unwrap();
+    let sqlx_sqlite = SqlxSqlite::try_new(data_folder_str, secret_key_from_env())
+        .await
+        .unwrap();
     let dep_provider = Arc::new(DependancyProvider::new(
         sqlx_sqlite,
         email_verification_service,
         jwt_auth,
+        signup_policy_from_env(),
+        password_policy_from_env(),
+        login_link_policy_from_env(),
+        RandomTokenGenerator::new(token_format_from_env()),
+        TotpGenerator::new(totp_issuer_from_env()),
+        oidc_client_from_env(),
     ));
-    let api_service = OpenApiService::new(Api::new(dep_provider), "Hello World", "1.0")
-        .server("http://localhost:3000");
+    let mut job_runner = JobRunner::new()
+        .with_job(Arc::new(ExpireSignupProcesses::new(dep_provider.clone())))
+        .with_job(Arc::new(
+            PurgeSignupProcesses::new(dep_provider.clone())
+                .with_grace_period(purge_grace_period_from_env()),
+        ));
+    tokio::spawn(async move {
+        loop {
+            job_runner.run_due(SystemClock.now()).await;
+            let wait = job_runner
+                .next_due()
+                .and_then(|next| (next - SystemClock.now()).to_std().ok())
+                .unwrap_or_default();
+            tokio::time::sleep(wait).await;
+        }
+    });
+    let api = Api::new(dep_provider)
+        .with_verification_redirects(verification_redirects_from_env())
+        .with_trusted_proxies(trusted_proxies_from_env());
+    let api_service = OpenApiService::new(api, "Hello World", "1.0").server(SERVER_URL);
     let ui = api_service.swagger_ui();
     let app = Route::new().nest("/", api_service).nest("/docs", ui);
 
@@ -90,3 +227,142 @@ async fn main() {
         .await
         .unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ca_application::gateway::database::{
+        signup_process::Repo as _,
+        token::{Purpose, Repo as _, Scope},
+        Database,
+    };
+    use ca_domain::entity::{
+        signup_process::{Id, SignupProcess},
+        user::Email,
+    };
+    use ca_infrastructure_interface_poem_openapi::VerificationRedirects;
+    use ca_infrastructure_persistance_sqlx_sqlite::SecretKey;
+    use poem::{http::StatusCode, Endpoint, Request};
+    use tempfile::TempDir;
+
+    const TOKEN: &str = "test-token";
+
+    /// Api on a fresh database holding one process waiting for `TOKEN`, the
+    /// database is removed along with the returned directory
+    async fn setup(redirects: VerificationRedirects) -> (impl Endpoint, Id, TempDir) {
+        let dir = tempfile::Builder::new()
+            .prefix("ca-test-")
+            .tempdir()
+            .unwrap();
+        let folder = dir.path().to_path_buf();
+        let email_service = FileEmailService::try_new(folder.clone()).unwrap();
+        let db = SqlxSqlite::try_new(folder.to_str().unwrap(), Some(SecretKey::generate()))
+            .await
+            .unwrap();
+        let id = Id::new(uuid::Uuid::new_v4());
+        let email = Email::new_unchecked("verify@test.com");
+        let now = SystemClock.now();
+        let process = SignupProcess::new(id, email.clone(), now);
+        {
+            let db_ref = &db;
+            let repo = db_ref.signup_process_repo();
+            repo.save_latest_state(None, process.clone().into(), None)
+                .await
+                .unwrap();
+            repo.save_latest_state(None, process.send_verification_email(now).into(), Some(0))
+                .await
+                .unwrap();
+            db_ref
+                .token_repo()
+                .issue(
+                    None,
+                    &Scope::new(Purpose::Signup, id),
+                    &email.canonical(),
+                    TOKEN,
+                    now,
+                )
+                .await
+                .unwrap();
+        }
+        let dep_provider = Arc::new(DependancyProvider::new(
+            db,
+            email_service,
+            JwtAuth::new("secret".to_string()),
+            SignupPolicy::default(),
+            PasswordPolicy::default(),
+            LoginLinkPolicy::default(),
+            RandomTokenGenerator::default(),
+            TotpGenerator::new("test"),
+            OidcClient::new(),
+        ));
+        let api = Api::new(dep_provider).with_verification_redirects(redirects);
+        let app = Route::new().nest("/", OpenApiService::new(api, "Test", "1.0"));
+        (app, id, dir)
+    }
+
+    async fn verify(app: &impl Endpoint, id: Id, token: &str) -> (StatusCode, String) {
+        let request = Request::builder()
+            .uri(
+                format!("/signup_processes/{id}/verify?token={token}")
+                    .parse()
+                    .unwrap(),
+            )
+            .finish();
+        let response = app.get_response(request).await;
+        let location = response
+            .headers()
+            .get("location")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        (response.status(), location)
+    }
+
+    #[tokio::test]
+    async fn test_verify_link_success_redirect() {
+        let (app, id, _dir) = setup(VerificationRedirects {
+            success_url: "https://app.test/welcome?from=email#done".to_string(),
+            failure_url: "https://app.test/failed".to_string(),
+        })
+        .await;
+        let (status, location) = verify(&app, id, TOKEN).await;
+        assert_eq!(status, StatusCode::SEE_OTHER);
+        assert_eq!(
+            location,
+            format!("https://app.test/welcome?from=email&id={id}#done")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_link_failure_redirect() {
+        let (app, id, _dir) = setup(VerificationRedirects {
+            success_url: "https://app.test/welcome".to_string(),
+            failure_url: "https://app.test/failed#retry".to_string(),
+        })
+        .await;
+        let (status, location) = verify(&app, id, "wrong-token").await;
+        assert_eq!(status, StatusCode::SEE_OTHER);
+        assert_eq!(location, format!("https://app.test/failed?id={id}#retry"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_link_default_redirect() {
+        let (app, id, _dir) = setup(VerificationRedirects::default()).await;
+        let (status, location) = verify(&app, id, TOKEN).await;
+        assert_eq!(status, StatusCode::SEE_OTHER);
+        assert_eq!(location, format!("/?id={id}"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_link_opened_twice_redirects_to_success() {
+        let (app, id, _dir) = setup(VerificationRedirects {
+            success_url: "https://app.test/welcome".to_string(),
+            failure_url: "https://app.test/failed".to_string(),
+        })
+        .await;
+        verify(&app, id, TOKEN).await;
+        let (status, location) = verify(&app, id, TOKEN).await;
+        assert_eq!(status, StatusCode::SEE_OTHER);
+        assert_eq!(location, format!("https://app.test/welcome?id={id}"));
+    }
+}
@@ -0,0 +1,273 @@
+//! Settings both binaries read from the environment. Unset variables keep
+//! the defaults, set ones have to parse or the binary refuses to start.
+
+use std::{net::IpAddr, str::FromStr};
+
+use ca_application::job::purge_signup_processes::DEFAULT_GRACE_PERIOD_DAYS;
+use ca_domain::value_object::{LoginLinkPolicy, PasswordPolicy, SignupPolicy};
+use ca_infrastructure_interface_poem_openapi::VerificationRedirects;
+use ca_infrastructure_persistance_sqlx_sqlite::SecretKey;
+use ca_infrastructure_service_oidc::{OidcClient, ProviderConfig};
+use ca_infrastructure_service_token_random::TokenFormat;
+
+pub const SIGNUP_VERIFICATION_TTL_ENV: &str = "CA_SIGNUP_VERIFICATION_TTL_SECONDS";
+pub const SIGNUP_COMPLETION_TTL_ENV: &str = "CA_SIGNUP_COMPLETION_TTL_SECONDS";
+pub const SIGNUP_MAX_EXTENSIONS_ENV: &str = "CA_SIGNUP_MAX_EXTENSIONS";
+pub const SIGNUP_MAX_RESENDS_ENV: &str = "CA_SIGNUP_MAX_RESENDS";
+pub const SIGNUP_RESEND_COOLDOWN_ENV: &str = "CA_SIGNUP_RESEND_COOLDOWN_SECONDS";
+pub const SIGNUP_MAX_VERIFICATION_ATTEMPTS_ENV: &str = "CA_SIGNUP_MAX_VERIFICATION_ATTEMPTS";
+pub const SIGNUP_ATTEMPT_LOCKOUT_ENV: &str = "CA_SIGNUP_ATTEMPT_LOCKOUT_SECONDS";
+pub const PASSWORD_MIN_CHARACTER_CLASSES_ENV: &str = "CA_PASSWORD_MIN_CHARACTER_CLASSES";
+pub const PASSWORD_MIN_ENTROPY_BITS_ENV: &str = "CA_PASSWORD_MIN_ENTROPY_BITS";
+pub const PASSWORD_BREACHED_LIST_ENV: &str = "CA_PASSWORD_BREACHED_LIST";
+pub const TOKEN_FORMAT_ENV: &str = "CA_TOKEN_FORMAT";
+pub const LOGIN_LINK_TTL_ENV: &str = "CA_LOGIN_LINK_TTL_SECONDS";
+pub const LOGIN_LINK_MAX_REQUESTS_ENV: &str = "CA_LOGIN_LINK_MAX_REQUESTS";
+pub const LOGIN_LINK_MAX_CLIENT_REQUESTS_ENV: &str = "CA_LOGIN_LINK_MAX_CLIENT_REQUESTS";
+pub const LOGIN_LINK_REQUEST_WINDOW_ENV: &str = "CA_LOGIN_LINK_REQUEST_WINDOW_SECONDS";
+pub const LOGIN_LINK_MAX_ATTEMPTS_ENV: &str = "CA_LOGIN_LINK_MAX_ATTEMPTS";
+pub const LOGIN_LINK_URL_ENV: &str = "CA_LOGIN_LINK_URL";
+pub const PUBLIC_BASE_URL_ENV: &str = "CA_PUBLIC_BASE_URL";
+pub const TOTP_ISSUER_ENV: &str = "CA_TOTP_ISSUER";
+pub const SECRET_KEY_ENV: &str = "CA_SECRET_KEY";
+pub const DEFAULT_TOTP_ISSUER: &str = "clean-architecture-with-rust";
+pub const OIDC_PROVIDERS_ENV: &str = "CA_OIDC_PROVIDERS";
+pub const VERIFY_SUCCESS_URL_ENV: &str = "CA_VERIFY_SUCCESS_URL";
+pub const VERIFY_FAILURE_URL_ENV: &str = "CA_VERIFY_FAILURE_URL";
+pub const TRUSTED_PROXIES_ENV: &str = "CA_TRUSTED_PROXIES";
+pub const PURGE_GRACE_PERIOD_ENV: &str = "CA_PURGE_GRACE_PERIOD_DAYS";
+
+/// Value of an environment variable, `None` when unset
+fn var<T>(name: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    parse(name, std::env::var(name).ok())
+}
+
+fn parse<T>(name: &str, value: Option<String>) -> Option<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = value?;
+    Some(
+        value
+            .parse()
+            .unwrap_or_else(|err| panic!("Failed to read {name}={value:?}: {err}")),
+    )
+}
+
+/// Duration of an environment variable in whole seconds, `None` when unset
+fn seconds(name: &str) -> Option<chrono::Duration> {
+    parse_seconds(name, std::env::var(name).ok())
+}
+
+/// Seconds have to be positive, a window or lockout of no time would let
+/// nothing through or hold nothing back
+fn parse_seconds(name: &str, value: Option<String>) -> Option<chrono::Duration> {
+    let seconds: i64 = parse(name, value)?;
+    if seconds <= 0 {
+        panic!("Failed to read {name}={seconds:?}: has to be a positive number of seconds");
+    }
+    Some(chrono::Duration::seconds(seconds))
+}
+
+/// Signup policy with the defaults overridden by the environment
+pub fn signup_policy_from_env() -> SignupPolicy {
+    let mut policy = SignupPolicy::default();
+    if let Some(ttl) = seconds(SIGNUP_VERIFICATION_TTL_ENV) {
+        policy = policy.with_verification_ttl(ttl);
+    }
+    if let Some(ttl) = seconds(SIGNUP_COMPLETION_TTL_ENV) {
+        policy = policy.with_completion_ttl(ttl);
+    }
+    if let Some(max_extensions) = var(SIGNUP_MAX_EXTENSIONS_ENV) {
+        policy = policy.with_max_extensions(max_extensions);
+    }
+    if let Some(max_resends) = var(SIGNUP_MAX_RESENDS_ENV) {
+        policy = policy.with_max_resends(max_resends);
+    }
+    if let Some(cooldown) = seconds(SIGNUP_RESEND_COOLDOWN_ENV) {
+        policy = policy.with_resend_cooldown(cooldown);
+    }
+    if let Some(max_attempts) = var(SIGNUP_MAX_VERIFICATION_ATTEMPTS_ENV) {
+        policy = policy.with_max_verification_attempts(max_attempts);
+    }
+    if let Some(lockout) = seconds(SIGNUP_ATTEMPT_LOCKOUT_ENV) {
+        policy = policy.with_attempt_lockout(lockout);
+    }
+    policy
+}
+
+/// Password policy with the defaults overridden by the environment, the
+/// breached list is a file with one password per line, `#` starts a comment
+pub fn password_policy_from_env() -> PasswordPolicy {
+    let mut policy = PasswordPolicy::default();
+    if let Some(min) = var(PASSWORD_MIN_CHARACTER_CLASSES_ENV) {
+        policy = policy.with_min_character_classes(min);
+    }
+    if let Some(min) = var(PASSWORD_MIN_ENTROPY_BITS_ENV) {
+        policy = policy.with_min_entropy_bits(min);
+    }
+    if let Ok(path) = std::env::var(PASSWORD_BREACHED_LIST_ENV) {
+        let list = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("Failed to read breached password list {path}: {err}"));
+        policy = policy.with_breached(list.lines().filter(|line| !line.starts_with('#')));
+    }
+    policy
+}
+
+/// Login link policy with the defaults overridden by the environment
+pub fn login_link_policy_from_env() -> LoginLinkPolicy {
+    let mut policy = LoginLinkPolicy::default();
+    if let Some(ttl) = seconds(LOGIN_LINK_TTL_ENV) {
+        policy = policy.with_ttl(ttl);
+    }
+    if let Some(max_requests) = var(LOGIN_LINK_MAX_REQUESTS_ENV) {
+        policy = policy.with_max_requests(max_requests);
+    }
+    if let Some(max_requests) = var(LOGIN_LINK_MAX_CLIENT_REQUESTS_ENV) {
+        policy = policy.with_max_client_requests(max_requests);
+    }
+    if let Some(window) = seconds(LOGIN_LINK_REQUEST_WINDOW_ENV) {
+        policy = policy.with_request_window(window);
+    }
+    if let Some(max_attempts) = var(LOGIN_LINK_MAX_ATTEMPTS_ENV) {
+        policy = policy.with_max_attempts(max_attempts);
+    }
+    if let Ok(url) = std::env::var(LOGIN_LINK_URL_ENV) {
+        policy = policy.with_link_url(url);
+    }
+    policy
+}
+
+/// Format of verification tokens, `uuid` unless the environment asks for
+/// `otp[:digits]` or `url-safe[:len]`
+pub fn token_format_from_env() -> TokenFormat {
+    var(TOKEN_FORMAT_ENV).unwrap_or_default()
+}
+
+/// Pages the verification link redirects to, overridden by the environment
+pub fn verification_redirects_from_env() -> VerificationRedirects {
+    let mut redirects = VerificationRedirects::default();
+    if let Ok(url) = std::env::var(VERIFY_SUCCESS_URL_ENV) {
+        redirects.success_url = url;
+    }
+    if let Ok(url) = std::env::var(VERIFY_FAILURE_URL_ENV) {
+        redirects.failure_url = url;
+    }
+    redirects
+}
+
+/// Addresses of the proxies in front of the api, comma separated, only they
+/// are believed about the client address
+pub fn trusted_proxies_from_env() -> Vec<IpAddr> {
+    std::env::var(TRUSTED_PROXIES_ENV)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .filter_map(|ip| parse(TRUSTED_PROXIES_ENV, Some(ip.to_string())))
+        .collect()
+}
+
+/// Time deleted signup processes are kept before the background job purges
+/// them
+pub fn purge_grace_period_from_env() -> chrono::Duration {
+    let days: u32 = var(PURGE_GRACE_PERIOD_ENV).unwrap_or(DEFAULT_GRACE_PERIOD_DAYS);
+    chrono::Duration::days(days.into())
+}
+
+/// Base url links in emails point at, `None` when unset
+pub fn public_base_url_from_env() -> Option<String> {
+    std::env::var(PUBLIC_BASE_URL_ENV).ok()
+}
+
+/// Name authenticator apps list the second factor under
+pub fn totp_issuer_from_env() -> String {
+    std::env::var(TOTP_ISSUER_ENV).unwrap_or_else(|_| DEFAULT_TOTP_ISSUER.to_string())
+}
+
+/// Hex encoded key to encrypt secrets at rest with. When unset the key
+/// stored next to the database is used, which only suits development since
+/// a copy of the data folder then holds both the secrets and their key.
+pub fn secret_key_from_env() -> Option<SecretKey> {
+    let hex = std::env::var(SECRET_KEY_ENV).ok()?;
+    Some(
+        SecretKey::from_hex(&hex)
+            .unwrap_or_else(|err| panic!("Failed to read {SECRET_KEY_ENV}: {err}")),
+    )
+}
+
+/// OpenID Connect providers listed comma separated by name, each set up
+/// with `CA_OIDC_<NAME>_ISSUER`, `_CLIENT_ID`, `_CLIENT_SECRET` (optional)
+/// and `_REDIRECT_URI`
+pub fn oidc_client_from_env() -> OidcClient {
+    let names = std::env::var(OIDC_PROVIDERS_ENV).unwrap_or_default();
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .fold(OidcClient::new(), |client, name| {
+            let prefix = format!("CA_OIDC_{}", name.to_uppercase());
+            let var = |suffix: &str| std::env::var(format!("{prefix}_{suffix}")).ok();
+            let required = |suffix: &str| {
+                var(suffix).unwrap_or_else(|| panic!("{prefix}_{suffix} is not set"))
+            };
+            client.with_provider(
+                name,
+                ProviderConfig {
+                    issuer: required("ISSUER"),
+                    client_id: required("CLIENT_ID"),
+                    client_secret: var("CLIENT_SECRET"),
+                    redirect_uri: required("REDIRECT_URI"),
+                },
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unset() {
+        assert_eq!(parse::<u32>(SIGNUP_MAX_RESENDS_ENV, None), None);
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(
+            parse::<i64>(SIGNUP_VERIFICATION_TTL_ENV, Some("600".to_string())),
+            Some(600)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CA_SIGNUP_MAX_RESENDS")]
+    fn test_parse_rejects_bad_value() {
+        parse::<u32>(SIGNUP_MAX_RESENDS_ENV, Some("three".to_string()));
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(
+            parse_seconds(SIGNUP_RESEND_COOLDOWN_ENV, Some("30".to_string())),
+            Some(chrono::Duration::seconds(30))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CA_SIGNUP_VERIFICATION_TTL_SECONDS")]
+    fn test_parse_seconds_rejects_negative() {
+        parse_seconds(SIGNUP_VERIFICATION_TTL_ENV, Some("-600".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "CA_SIGNUP_ATTEMPT_LOCKOUT_SECONDS")]
+    fn test_parse_seconds_rejects_zero() {
+        parse_seconds(SIGNUP_ATTEMPT_LOCKOUT_ENV, Some("0".to_string()));
+    }
+}
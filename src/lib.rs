@@ -0,0 +1,3 @@
+//! Wiring shared by the binaries
+
+pub mod config;